@@ -0,0 +1,12 @@
+//! The outcome of looking up the balance of a purse in global state.
+
+use casper_types::U512;
+
+/// The result of `EngineState::get_purse_balance`.
+#[derive(Debug)]
+pub enum BalanceResult {
+    /// The given state root hash is not in global state.
+    RootNotFound,
+    /// The purse's balance, in motes.
+    Success(U512),
+}
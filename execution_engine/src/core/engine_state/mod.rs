@@ -1,3 +1,4 @@
+pub mod balance;
 pub mod deploy_item;
 pub mod engine_config;
 mod error;
@@ -35,6 +36,7 @@ use casper_types::{
 };
 
 pub use self::{
+    balance::BalanceResult,
     deploy_item::DeployItem,
     engine_config::EngineConfig,
     error::{Error, RootNotFound},
@@ -754,6 +756,25 @@ where
             .into())
     }
 
+    pub fn get_purse_balance(
+        &self,
+        correlation_id: CorrelationId,
+        state_hash: Blake2bHash,
+        purse_uref: URef,
+    ) -> Result<BalanceResult, Error> {
+        let mut tracking_copy = match self.tracking_copy(state_hash)? {
+            Some(tracking_copy) => tracking_copy,
+            None => return Ok(BalanceResult::RootNotFound),
+        };
+        let purse_balance_key = tracking_copy
+            .get_purse_balance_key(correlation_id, purse_uref.into())
+            .map_err(Error::Exec)?;
+        let balance = tracking_copy
+            .get_purse_balance(correlation_id, purse_balance_key)
+            .map_err(Error::Exec)?;
+        Ok(BalanceResult::Success(balance.value()))
+    }
+
     pub fn run_execute(
         &self,
         correlation_id: CorrelationId,
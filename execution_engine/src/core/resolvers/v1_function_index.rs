@@ -49,6 +49,7 @@ pub enum FunctionIndex {
     RemoveContractUserGroupIndex,
     ExtendContractUserGroupURefsIndex,
     RemoveContractUserGroupURefsIndex,
+    TryCallContractFuncIndex,
 }
 
 impl Into<usize> for FunctionIndex {
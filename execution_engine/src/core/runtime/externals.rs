@@ -668,6 +668,43 @@ where
                 )?;
                 Ok(Some(RuntimeValue::I32(api_error::i32_from(ret))))
             }
+
+            FunctionIndex::TryCallContractFuncIndex => {
+                // args(0) = pointer to contract hash where contract is at in global state
+                // args(1) = size of contract hash
+                // args(2) = pointer to entry point
+                // args(3) = size of entry point
+                // args(4) = pointer to function arguments in Wasm memory
+                // args(5) = size of arguments
+                // args(6) = pointer to result size (output)
+                let (
+                    contract_hash_ptr,
+                    contract_hash_size,
+                    entry_point_name_ptr,
+                    entry_point_name_size,
+                    args_ptr,
+                    args_size,
+                    result_size_ptr,
+                ): (_, _, _, u32, _, u32, _) = Args::parse(args)?;
+                scoped_instrumenter
+                    .add_property("entry_point_name_size", entry_point_name_size.to_string());
+                scoped_instrumenter.add_property("args_size", args_size.to_string());
+
+                let contract_hash: ContractHash =
+                    self.t_from_mem(contract_hash_ptr, contract_hash_size)?;
+                let entry_point_name: String =
+                    self.t_from_mem(entry_point_name_ptr, entry_point_name_size)?;
+                let args_bytes: Vec<u8> = self.bytes_from_mem(args_ptr, args_size as usize)?;
+
+                let ret = self.try_call_contract_host_buffer(
+                    contract_hash,
+                    &entry_point_name,
+                    args_bytes,
+                    result_size_ptr,
+                    &mut scoped_instrumenter,
+                )?;
+                Ok(Some(RuntimeValue::I32(api_error::i32_from(ret))))
+            }
         }
     }
 }
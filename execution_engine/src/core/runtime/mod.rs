@@ -2410,6 +2410,37 @@ where
         self.manage_call_contract_host_buffer(result_size_ptr, result)
     }
 
+    /// Like `call_contract_host_buffer`, but a revert of the called contract is returned to the
+    /// caller as an `ApiError` rather than aborting its execution.  Any changes to global state
+    /// made by the reverted call are discarded; the gas it used is still charged.
+    fn try_call_contract_host_buffer(
+        &mut self,
+        contract_hash: ContractHash,
+        entry_point_name: &str,
+        args_bytes: Vec<u8>,
+        result_size_ptr: u32,
+        scoped_instrumenter: &mut ScopedInstrumenter,
+    ) -> Result<Result<(), ApiError>, Error> {
+        // Exit early if the host buffer is already occupied
+        if let Err(err) = self.check_host_buffer() {
+            return Ok(Err(err));
+        }
+        let args: RuntimeArgs = bytesrepr::deserialize(args_bytes)?;
+        let checkpoint = self.context.state().borrow().checkpoint();
+        scoped_instrumenter.pause();
+        let call_result = self.call_contract(contract_hash, entry_point_name, args);
+        scoped_instrumenter.unpause();
+        let result = match call_result {
+            Ok(result) => result,
+            Err(Error::Revert(api_error)) => {
+                self.context.state().borrow_mut().restore(checkpoint);
+                return Ok(Err(api_error));
+            }
+            Err(error) => return Err(error),
+        };
+        self.manage_call_contract_host_buffer(result_size_ptr, result)
+    }
+
     fn call_versioned_contract_host_buffer(
         &mut self,
         contract_package_hash: ContractPackageHash,
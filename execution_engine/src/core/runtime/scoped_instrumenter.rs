@@ -139,6 +139,7 @@ impl Drop for ScopedInstrumenter {
             FunctionIndex::RemoveContractUserGroupURefsIndex => {
                 "host_remove_contract_user_group_urefs"
             }
+            FunctionIndex::TryCallContractFuncIndex => "host_function_try_call_contract",
         };
 
         let mut properties = mem::take(&mut self.properties);
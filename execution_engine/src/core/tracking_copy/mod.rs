@@ -157,6 +157,14 @@ pub struct TrackingCopy<R> {
     fns: AdditiveMap<Key, Transform>,
 }
 
+/// The mutations held by a `TrackingCopy` at some point in time, as captured by
+/// [`TrackingCopy::checkpoint`].
+pub struct TrackingCopyCheckpoint {
+    muts_cached: HashMap<Key, StoredValue>,
+    ops: AdditiveMap<Key, Op>,
+    fns: AdditiveMap<Key, Transform>,
+}
+
 #[derive(Debug)]
 pub enum AddResult {
     Success,
@@ -326,6 +334,24 @@ impl<R: StateReader<Key, StoredValue>> TrackingCopy<R> {
         }
     }
 
+    /// Captures the mutations made so far, so they can later be reinstated via `restore()`.
+    pub fn checkpoint(&self) -> TrackingCopyCheckpoint {
+        TrackingCopyCheckpoint {
+            muts_cached: self.cache.muts_cached.clone(),
+            ops: self.ops.clone(),
+            fns: self.fns.clone(),
+        }
+    }
+
+    /// Discards all mutations made since `checkpoint` was taken.
+    ///
+    /// Cached reads are kept, as they reflect the unmodified underlying state.
+    pub fn restore(&mut self, checkpoint: TrackingCopyCheckpoint) {
+        self.cache.muts_cached = checkpoint.muts_cached;
+        self.ops = checkpoint.ops;
+        self.fns = checkpoint.fns;
+    }
+
     pub fn effect(&self) -> ExecutionEffect {
         ExecutionEffect::new(self.ops.clone(), self.fns.clone())
     }
@@ -287,6 +287,32 @@ fn tracking_copy_aw() {
     assert_eq!(tc.ops.get(&k), Some(&Op::Write));
 }
 
+#[test]
+fn tracking_copy_restore_checkpoint() {
+    let correlation_id = CorrelationId::new();
+    let counter = Rc::new(Cell::new(0));
+    let db = CountingDb::new(counter);
+    let mut tc = TrackingCopy::new(db);
+    let k1 = Key::Hash([1u8; 32]);
+    let k2 = Key::Hash([2u8; 32]);
+
+    let one = StoredValue::CLValue(CLValue::from_t(1_i32).unwrap());
+    let two = StoredValue::CLValue(CLValue::from_t(2_i32).unwrap());
+    tc.write(k1, one.clone());
+
+    let checkpoint = tc.checkpoint();
+    tc.write(k1, two.clone());
+    tc.write(k2, two);
+    tc.restore(checkpoint);
+
+    // only the write made before the checkpoint remains
+    assert_eq!(tc.fns.len(), 1);
+    assert_eq!(tc.fns.get(&k1), Some(&Transform::Write(one.clone())));
+    assert_eq!(tc.ops.len(), 1);
+    assert_eq!(tc.ops.get(&k1), Some(&Op::Write));
+    assert_eq!(tc.read(correlation_id, &k1).unwrap(), Some(one));
+}
+
 proptest! {
     #[test]
     fn query_empty_path(k in key_arb(), missing_key in key_arb(), v in stored_value_arb()) {
@@ -9,8 +9,11 @@ mod main_purse;
 mod mint_purse;
 mod revert;
 mod subcall;
+mod subcall_out_of_gas;
+mod subcall_revert_at_depth;
 mod transfer;
 mod transfer_purse_to_account;
 mod transfer_purse_to_purse;
 mod transfer_stored;
 mod transfer_u512_stored;
+mod try_subcall;
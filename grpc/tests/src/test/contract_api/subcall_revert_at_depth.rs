@@ -0,0 +1,49 @@
+use assert_matches::assert_matches;
+
+use casper_engine_test_support::{
+    internal::{
+        utils, ExecuteRequestBuilder, InMemoryWasmTestBuilder, DEFAULT_RUN_GENESIS_REQUEST,
+    },
+    DEFAULT_ACCOUNT_ADDR,
+};
+use casper_execution_engine::core::{engine_state::Error, execution};
+use casper_types::{runtime_args, ApiError, RuntimeArgs};
+
+const CONTRACT_SUBCALL_REVERT_AT_DEPTH: &str = "subcall_revert_at_depth.wasm";
+const ARG_DEPTH: &str = "depth";
+const DEPTH_REACHED_ERROR: u16 = 4;
+
+#[ignore]
+#[test]
+fn should_revert_only_at_requested_subcall_depth() {
+    for &depth in &[1u32, 5] {
+        let mut builder = InMemoryWasmTestBuilder::default();
+
+        let exec_request = ExecuteRequestBuilder::standard(
+            *DEFAULT_ACCOUNT_ADDR,
+            CONTRACT_SUBCALL_REVERT_AT_DEPTH,
+            runtime_args! { ARG_DEPTH => depth },
+        )
+        .build();
+
+        builder
+            .run_genesis(&DEFAULT_RUN_GENESIS_REQUEST)
+            .exec(exec_request)
+            .commit();
+
+        // The revert in the innermost frame should unwind through every frame above it unchanged.
+        let response = builder
+            .get_exec_response(0)
+            .expect("there should be a response");
+        let execution_result = utils::get_success_result(response);
+        let error = execution_result.as_error().expect("should have error");
+        assert_matches!(
+            error,
+            Error::Exec(execution::Error::Revert(ApiError::User(
+                DEPTH_REACHED_ERROR
+            ))),
+            "unexpected error at depth {}",
+            depth
+        );
+    }
+}
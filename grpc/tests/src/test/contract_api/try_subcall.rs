@@ -0,0 +1,95 @@
+use casper_engine_test_support::{
+    internal::{ExecuteRequestBuilder, InMemoryWasmTestBuilder, DEFAULT_RUN_GENESIS_REQUEST},
+    DEFAULT_ACCOUNT_ADDR,
+};
+use casper_types::{runtime_args, Key, RuntimeArgs};
+
+const CONTRACT_TRY_SUBCALL_DEFINE: &str = "try_subcall_define.wasm";
+const CONTRACT_TRY_SUBCALL_CALL: &str = "try_subcall_call.wasm";
+const TRY_SUBCALL_KEY: &str = "try_subcall";
+const COUNTER_KEY: &str = "counter";
+const PATH_KEY: &str = "path";
+const ARG_ENTRY_POINT: &str = "entry_point";
+const ARG_PROCEED: &str = "proceed";
+const ENTRY_POINT_CHECK: &str = "check";
+const ENTRY_POINT_CHECK_AND_REVERT: &str = "check_and_revert";
+
+/// Installs the contract, runs the calling session with the given args and returns the path taken
+/// along with the resulting counter value.
+fn run_try_subcall(entry_point: &str, proceed: bool) -> (String, u32) {
+    let mut builder = InMemoryWasmTestBuilder::default();
+
+    let define_request = ExecuteRequestBuilder::standard(
+        *DEFAULT_ACCOUNT_ADDR,
+        CONTRACT_TRY_SUBCALL_DEFINE,
+        RuntimeArgs::default(),
+    )
+    .build();
+
+    let call_request = ExecuteRequestBuilder::standard(
+        *DEFAULT_ACCOUNT_ADDR,
+        CONTRACT_TRY_SUBCALL_CALL,
+        runtime_args! { ARG_ENTRY_POINT => entry_point, ARG_PROCEED => proceed },
+    )
+    .build();
+
+    builder
+        .run_genesis(&DEFAULT_RUN_GENESIS_REQUEST)
+        .exec(define_request)
+        .expect_success()
+        .commit()
+        .exec(call_request)
+        .expect_success()
+        .commit();
+
+    let path: String = builder
+        .query(None, Key::Account(*DEFAULT_ACCOUNT_ADDR), &[PATH_KEY])
+        .expect("should have path")
+        .as_cl_value()
+        .expect("should be CLValue")
+        .clone()
+        .into_t()
+        .expect("should cast CLValue to string");
+
+    let counter: u32 = builder
+        .query(
+            None,
+            Key::Account(*DEFAULT_ACCOUNT_ADDR),
+            &[TRY_SUBCALL_KEY, COUNTER_KEY],
+        )
+        .expect("should have counter")
+        .as_cl_value()
+        .expect("should be CLValue")
+        .clone()
+        .into_t()
+        .expect("should cast CLValue to integer");
+
+    (path, counter)
+}
+
+#[ignore]
+#[test]
+fn should_skip_second_subcall_when_first_returns_false() {
+    let (path, counter) = run_try_subcall(ENTRY_POINT_CHECK, false);
+    assert_eq!(path, "skipped");
+    assert_eq!(counter, 0, "second subcall should not have been made");
+}
+
+#[ignore]
+#[test]
+fn should_make_second_subcall_when_first_returns_true() {
+    let (path, counter) = run_try_subcall(ENTRY_POINT_CHECK, true);
+    assert_eq!(path, "proceeded");
+    assert_eq!(counter, 1);
+}
+
+#[ignore]
+#[test]
+fn should_continue_and_discard_changes_when_first_subcall_reverts() {
+    let (path, counter) = run_try_subcall(ENTRY_POINT_CHECK_AND_REVERT, true);
+    assert_eq!(path, "reverted");
+    assert_eq!(
+        counter, 0,
+        "reverted subcall's write should have been discarded"
+    );
+}
@@ -15,17 +15,75 @@
 //!   hex-encoded deploy hash.  The response will be the JSON-serialized deploy, "null"  if the
 //!   deploy doesn't exist or an error message on failure..
 //! * To list all stored deploy hashes, send an HTTP GET request to "/deploys".  The response will
-//!   be the JSON-serialized list of hex-encoded deploy hashes or an error message on failure.
+//!   be the JSON-serialized list of hex-encoded deploy hashes in ascending order or an error
+//!   message on failure.  The optional query parameters `limit` and `cursor` restrict the response
+//!   to at most `limit` hashes following the hex-encoded hash `cursor`.  To fetch the next page,
+//!   pass the last hash of the current one as `cursor`.  A page holding fewer than `limit` hashes
+//!   is the last one.
+//! * To retrieve a block, send an HTTP GET request to "/blocks/<ID>" where <ID> is either the
+//!   hex-encoded block hash or the decimal block height.  The response will be the JSON-serialized
+//!   block, "null" if the block doesn't exist or an error message on failure.
+//! * To retrieve the execution results of a block's deploys, send an HTTP GET request to
+//!   "/execution-results/<HASH>" where <HASH> is the hex-encoded block hash.  The response will be
+//!   the JSON-serialized map of hex-encoded deploy hashes to their headers and execution results,
+//!   "null" if no results are stored for the block or an error message on failure.
+//! * To query global state, send an HTTP GET request to "/state/<ROOT>/<KEY>[/<PATH>]" where <ROOT>
+//!   is the hex-encoded global state root hash, <KEY> is the formatted key at which to start (e.g.
+//!   "account-hash-<HEX>") and the optional <PATH> is a "/"-separated list of named keys to follow
+//!   from it.  The response will be the JSON-serialized hex-encoded `StoredValue`, or why it could
+//!   not be found, or an error message on failure.
+//! * To retrieve the balance of a purse, send an HTTP GET request to "/balances/<ROOT>/<ID>" where
+//!   <ROOT> is the hex-encoded global state root hash and <ID> is either the hex-encoded public key
+//!   of an account, for its main purse, or the formatted purse `URef` (e.g. "uref-<HEX>-007").  The
+//!   response will be the JSON-serialized balance, or why it could not be found, or an error
+//!   message on failure.
+//! * To subscribe to finalized blocks, send an HTTP GET request to "/events/blocks".  The response
+//!   will be a stream of server-sent events, each holding a JSON-serialized block as it gets
+//!   finalized.
+//! * To list the connected peers, send an HTTP GET request to "/peers".  The response will be the
+//!   JSON-serialized map of peer node IDs to their addresses.
+//! * To list the deploys pending in the deploy buffer, i.e. received but not yet proposed in a
+//!   block, send an HTTP GET request to "/pending-deploys".  The response will be the
+//!   JSON-serialized list of hex-encoded deploy hashes in ascending order.
+//! * To retrieve the parameters of the active chainspec relevant to clients, send an HTTP GET
+//!   request to "/chainspec".  The response will be the JSON-serialized chain name, genesis
+//!   timestamp, protocol version and deploy limits, or "null" if the chainspec isn't stored.
+//! * To retrieve the next upgrade scheduled in the active chainspec, send an HTTP GET request to
+//!   "/next-upgrade".  The response will be the JSON-serialized activation point and protocol
+//!   version of the upgrade, or "null" if no upgrade is scheduled.
+//! * To retrieve the node's metrics, send an HTTP GET request to "/metrics".  The response will be
+//!   the metrics in the Prometheus text exposition format, or, if the request has an "Accept:
+//!   application/json" header, a JSON-serialized list of metric names and values.
+//! * To retrieve the node's status, send an HTTP GET request to "/status".  The response will be
+//!   the JSON-serialized status, including the node version, current era, last finalized block
+//!   height and hash, connected peers and uptime.
+//!
+//! Responses to GET requests are JSON-serialized by default.  Clients can request MessagePack
+//! instead by sending an "Accept: application/msgpack" header, which may weigh it against JSON
+//! via quality factors as usual.
 
 mod config;
 mod event;
+mod metrics_format;
+mod response_format;
+#[cfg(test)]
+mod tests;
 
-use std::{borrow::Cow, error::Error as StdError, fmt::Debug, net::SocketAddr, str};
+use std::{
+    collections::{BTreeMap, HashMap},
+    convert::Infallible,
+    fmt::Debug,
+    mem,
+    net::SocketAddr,
+    str,
+};
 
 use bytes::Bytes;
-use futures::{join, FutureExt};
+use futures::{channel::mpsc, join, StreamExt};
 use http::Response;
 use rand::{CryptoRng, Rng};
+use semver::Version;
+use serde::{Deserialize, Serialize};
 use smallvec::smallvec;
 use tracing::{debug, error, info, warn};
 use warp::{
@@ -33,43 +91,97 @@ use warp::{
     filters::path::Tail,
     http::StatusCode,
     reject::Rejection,
-    reply::{self, Json, WithStatus},
-    Filter,
+    reply::{self, Json, Reply, WithStatus},
+    sse, Filter,
 };
 
+use casper_execution_engine::core::engine_state::{self, QueryRequest, QueryResult};
+use casper_types::{bytesrepr::ToBytes, Key, URef, U512};
+
 use super::Component;
 use crate::{
-    components::storage::Storage,
-    crypto::hash::Digest,
+    components::{
+        chainspec_loader::Chainspec,
+        contract_runtime::{BalanceError, BalanceIdentifier},
+        storage::Storage,
+    },
+    crypto::{asymmetric_key::PublicKey, hash::Digest},
     effect::{
         announcements::ApiServerAnnouncement,
         requests::{
-            ApiRequest, ContractRuntimeRequest, LinearChainRequest, MetricsRequest,
-            NetworkInfoRequest, StorageRequest,
+            ApiRequest, ConsensusRequest, ContractRuntimeRequest, DeployBufferRequest,
+            LinearChainRequest, MetricsRequest, NetworkInfoRequest, StorageRequest,
         },
-        EffectBuilder, EffectExt, Effects,
+        EffectBuilder, EffectExt, Effects, Responder,
     },
     reactor::QueueKind,
     small_network::NodeId,
-    types::{Deploy, DeployHash, StatusFeed},
+    types::{
+        BlockExecutionResults, BlockHash, BlockIdentifier, Deploy, DeployHash, DeployHeader,
+        ExecutionResult, FinalizedBlock, StatusFeed, TimeDiff, Timestamp,
+    },
 };
 pub use config::Config;
 pub(crate) use event::Event;
+pub use metrics_format::{MetricSample, MetricsFormat};
+pub use response_format::{ResponseFormat, SerializationError};
 
 const DEPLOYS_API_PATH: &str = "deploys";
+const BLOCKS_API_PATH: &str = "blocks";
+const EXECUTION_RESULTS_API_PATH: &str = "execution-results";
 const METRICS_API_PATH: &str = "metrics";
 const STATUS_API_PATH: &str = "status";
+const PEERS_API_PATH: &str = "peers";
+const CHAINSPEC_API_PATH: &str = "chainspec";
+const NEXT_UPGRADE_API_PATH: &str = "next-upgrade";
+const PENDING_DEPLOYS_API_PATH: &str = "pending-deploys";
+const STATE_API_PATH: &str = "state";
+const BALANCES_API_PATH: &str = "balances";
+const EVENTS_API_PATH: &str = "events";
+const FINALIZED_BLOCKS_API_PATH: &str = "blocks";
+const ACCEPT_HEADER: &str = "accept";
+/// The maximum number of concurrent subscriptions to finalized blocks.
+const MAX_FINALIZED_BLOCK_SUBSCRIBERS: usize = 100;
+/// The number of finalized blocks buffered for a subscriber.  A subscriber falling further behind
+/// is dropped.
+const FINALIZED_BLOCK_BUFFER_SIZE: usize = 64;
 
 #[derive(Debug)]
-pub(crate) struct ApiServer {}
+pub(crate) struct ApiServer {
+    /// The time at which the API server, and hence the node, was started.
+    started: Timestamp,
+    /// The protocol version of the active chainspec.
+    protocol_version: Version,
+    /// The senders of the subscriptions to finalized blocks.
+    finalized_block_subscribers: Vec<mpsc::Sender<FinalizedBlock>>,
+    /// The requests waiting on an in-flight storage lookup of a deploy, keyed by deploy hash.
+    ///
+    /// Concurrent requests for the same deploy share a single lookup.
+    pending_deploy_requests: HashMap<DeployHash, Vec<PendingDeployRequest>>,
+}
+
+/// A request for a deploy, waiting on the result of the storage lookup.
+type PendingDeployRequest = (
+    ResponseFormat,
+    Responder<Result<Vec<u8>, SerializationError>>,
+);
 
 impl ApiServer {
-    pub(crate) fn new<REv>(config: Config, effect_builder: EffectBuilder<REv>) -> Self
+    pub(crate) fn new<REv>(
+        config: Config,
+        protocol_version: Version,
+        effect_builder: EffectBuilder<REv>,
+    ) -> Self
     where
         REv: From<Event> + From<ApiRequest> + From<StorageRequest<Storage>> + Send,
     {
         tokio::spawn(run_server(config, effect_builder));
-        ApiServer {}
+        ApiServer {
+            started: Timestamp::now(),
+            protocol_version,
+            finalized_block_subscribers: Vec::new(),
+            pending_deploy_requests: HashMap::new(),
+        }
     }
 }
 
@@ -86,34 +198,95 @@ where
     let get_deploy = warp::get()
         .and(warp::path(DEPLOYS_API_PATH))
         .and(warp::path::tail())
-        .and_then(move |hex_digest| parse_get_deploy_request(effect_builder, hex_digest));
+        .and(warp::query::<ListDeploysQuery>())
+        .and(warp::header::optional::<String>(ACCEPT_HEADER))
+        .and_then(move |hex_digest, query, maybe_accept| {
+            parse_get_deploy_request(effect_builder, hex_digest, query, maybe_accept)
+        });
 
     let get_metrics = warp::get()
         .and(warp::path(METRICS_API_PATH))
-        .and_then(move || {
-            effect_builder
-                .make_request(
-                    |responder| ApiRequest::GetMetrics { responder },
-                    QueueKind::Api,
-                )
-                .map(|text_opt| match text_opt {
-                    Some(text) => {
-                        Ok::<_, Rejection>(reply::with_status(Cow::from(text), StatusCode::OK))
-                    }
-                    None => Ok(reply::with_status(
-                        Cow::from("failed to collect metrics. sorry!"),
-                        StatusCode::INTERNAL_SERVER_ERROR,
-                    )),
-                })
+        .and(warp::header::optional::<String>(ACCEPT_HEADER))
+        .and_then(move |maybe_accept| handle_get_metrics(effect_builder, maybe_accept));
+
+    let get_block = warp::get()
+        .and(warp::path(BLOCKS_API_PATH))
+        .and(warp::path::tail())
+        .and(warp::header::optional::<String>(ACCEPT_HEADER))
+        .and_then(move |identifier, maybe_accept| {
+            handle_get_block_request(effect_builder, identifier, maybe_accept)
+        });
+
+    let get_block_execution_results = warp::get()
+        .and(warp::path(EXECUTION_RESULTS_API_PATH))
+        .and(warp::path::tail())
+        .and(warp::header::optional::<String>(ACCEPT_HEADER))
+        .and_then(move |hex_digest, maybe_accept| {
+            handle_get_block_execution_results_request(effect_builder, hex_digest, maybe_accept)
+        });
+
+    let query_global_state = warp::get()
+        .and(warp::path(STATE_API_PATH))
+        .and(warp::path::tail())
+        .and(warp::header::optional::<String>(ACCEPT_HEADER))
+        .and_then(move |tail, maybe_accept| {
+            handle_query_global_state_request(effect_builder, tail, maybe_accept)
+        });
+
+    let get_balance = warp::get()
+        .and(warp::path(BALANCES_API_PATH))
+        .and(warp::path::tail())
+        .and(warp::header::optional::<String>(ACCEPT_HEADER))
+        .and_then(move |tail, maybe_accept| {
+            handle_get_balance_request(effect_builder, tail, maybe_accept)
         });
 
+    let subscribe_finalized_blocks = warp::get()
+        .and(warp::path(EVENTS_API_PATH))
+        .and(warp::path(FINALIZED_BLOCKS_API_PATH))
+        .and(warp::path::end())
+        .and_then(move || handle_subscribe_finalized_blocks(effect_builder));
+
     let get_status = warp::get()
         .and(warp::path(STATUS_API_PATH))
-        .and_then(move || handle_get_status(effect_builder));
+        .and(warp::header::optional::<String>(ACCEPT_HEADER))
+        .and_then(move |maybe_accept| handle_get_status(effect_builder, maybe_accept));
+
+    let get_peers = warp::get()
+        .and(warp::path(PEERS_API_PATH))
+        .and(warp::header::optional::<String>(ACCEPT_HEADER))
+        .and_then(move |maybe_accept| handle_get_peers(effect_builder, maybe_accept));
+
+    let get_chainspec = warp::get()
+        .and(warp::path(CHAINSPEC_API_PATH))
+        .and(warp::header::optional::<String>(ACCEPT_HEADER))
+        .and_then(move |maybe_accept| handle_get_chainspec(effect_builder, maybe_accept));
+
+    let get_next_upgrade = warp::get()
+        .and(warp::path(NEXT_UPGRADE_API_PATH))
+        .and(warp::header::optional::<String>(ACCEPT_HEADER))
+        .and_then(move |maybe_accept| handle_get_next_upgrade(effect_builder, maybe_accept));
+
+    let get_pending_deploys = warp::get()
+        .and(warp::path(PENDING_DEPLOYS_API_PATH))
+        .and(warp::header::optional::<String>(ACCEPT_HEADER))
+        .and_then(move |maybe_accept| handle_get_pending_deploys(effect_builder, maybe_accept));
 
     let mut server_addr = SocketAddr::from((config.bind_interface, config.bind_port));
 
-    let filter = post_deploy.or(get_deploy).or(get_metrics).or(get_status);
+    let filter = post_deploy
+        .or(get_deploy)
+        .or(get_block)
+        .or(get_block_execution_results)
+        .or(query_global_state)
+        .or(get_balance)
+        .or(subscribe_finalized_blocks)
+        .or(get_metrics)
+        .or(get_status)
+        .or(get_peers)
+        .or(get_chainspec)
+        .or(get_next_upgrade)
+        .or(get_pending_deploys);
 
     debug!(%server_addr, "starting HTTP server");
     loop {
@@ -156,7 +329,7 @@ where
         }
     };
 
-    effect_builder
+    let deploy_hash = effect_builder
         .make_request(
             |responder| ApiRequest::SubmitDeploy {
                 deploy: Box::new(deploy),
@@ -166,64 +339,67 @@ where
         )
         .await;
 
-    let json = reply::json(&"");
+    let json = reply::json(&hex::encode(deploy_hash.inner()));
     Ok(reply::with_status(json, StatusCode::OK))
 }
 
 async fn parse_get_deploy_request<REv>(
     effect_builder: EffectBuilder<REv>,
     tail: Tail,
-) -> Result<Response<String>, Rejection>
+    query: ListDeploysQuery,
+    maybe_accept: Option<String>,
+) -> Result<Response<Vec<u8>>, Rejection>
 where
     REv: From<Event> + From<ApiRequest> + From<StorageRequest<Storage>> + Send,
 {
+    let format = ResponseFormat::from_accept_header(maybe_accept.as_deref());
     if tail.as_str().is_empty() {
-        handle_list_deploys_request(effect_builder).await
+        handle_list_deploys_request(effect_builder, query, format).await
     } else {
-        handle_get_deploy_request(effect_builder, tail).await
+        handle_get_deploy_request(effect_builder, tail, format).await
     }
 }
 
 async fn handle_list_deploys_request<REv>(
     effect_builder: EffectBuilder<REv>,
-) -> Result<Response<String>, Rejection>
+    query: ListDeploysQuery,
+    format: ResponseFormat,
+) -> Result<Response<Vec<u8>>, Rejection>
 where
     REv: From<Event> + From<ApiRequest> + From<StorageRequest<Storage>> + Send,
 {
-    let deploy_hashes = effect_builder
+    let cursor = match query.cursor.as_deref().map(Digest::from_hex).transpose() {
+        Ok(maybe_digest) => maybe_digest.map(DeployHash::new),
+        Err(error) => {
+            info!(%error, "failed to list deploys");
+            let error_reply = format!(
+                "Failed to parse cursor as hex-encoded DeployHash.  Error: {}",
+                error
+            );
+            return Ok(bad_request(error_reply));
+        }
+    };
+
+    let result = effect_builder
         .make_request(
-            |responder| ApiRequest::ListDeploys { responder },
+            |responder| ApiRequest::ListDeploys {
+                limit: query.limit,
+                cursor,
+                format,
+                responder,
+            },
             QueueKind::Api,
         )
         .await;
-    let error_body = |error: &dyn std::error::Error| -> String {
-        format!(
-            r#""Internal server error listing deploys.  Error: {}""#,
-            error
-        )
-    };
-
-    let hex_hashes = deploy_hashes
-        .into_iter()
-        .map(|deploy_hash| hex::encode(deploy_hash.inner()))
-        .collect::<Vec<_>>();
-    // TODO - paginate these?
-    let (body, status) = match serde_json::to_string(&hex_hashes) {
-        Ok(body) => (body, StatusCode::OK),
-        Err(error) => (error_body(&error), StatusCode::INTERNAL_SERVER_ERROR),
-    };
 
-    Ok(Response::builder()
-        .header("content-type", "application/json")
-        .status(status)
-        .body(body)
-        .unwrap())
+    Ok(serialized_response(format, result, "listing deploys"))
 }
 
 async fn handle_get_deploy_request<REv>(
     effect_builder: EffectBuilder<REv>,
     hex_digest: Tail,
-) -> Result<Response<String>, Rejection>
+    format: ResponseFormat,
+) -> Result<Response<Vec<u8>>, Rejection>
 where
     REv: From<Event> + From<ApiRequest> + From<StorageRequest<Storage>> + Send,
 {
@@ -236,74 +412,547 @@ where
                 hex_digest.as_str(),
                 error
             );
-            let response = Response::builder()
-                .header("content-type", "application/json")
-                .status(StatusCode::BAD_REQUEST)
-                .body(error_reply)
-                .unwrap();
-            return Ok(response);
+            return Ok(bad_request(error_reply));
         }
     };
 
-    let maybe_deploy = effect_builder
+    let result = effect_builder
         .make_request(
             |responder| ApiRequest::GetDeploy {
                 hash: DeployHash::new(digest),
+                format,
                 responder,
             },
             QueueKind::Api,
         )
         .await;
 
-    let error_body = |error: &dyn StdError| -> String {
-        format!(
-            r#""Internal server error retrieving {}.  Error: {}""#,
-            hex_digest.as_str(),
-            error
+    let context = format!("retrieving {}", hex_digest.as_str());
+    Ok(serialized_response(format, result, &context))
+}
+
+/// Parses `identifier` as a decimal block height or, failing that, as a hex-encoded block hash.
+fn parse_block_identifier(identifier: &str) -> Result<BlockIdentifier, String> {
+    if let Ok(height) = identifier.parse() {
+        return Ok(BlockIdentifier::Height(height));
+    }
+    Digest::from_hex(identifier)
+        .map(|digest| BlockIdentifier::Hash(BlockHash::new(digest)))
+        .map_err(|error| error.to_string())
+}
+
+async fn handle_get_metrics<REv>(
+    effect_builder: EffectBuilder<REv>,
+    maybe_accept: Option<String>,
+) -> Result<Response<String>, Rejection>
+where
+    REv: From<Event> + From<ApiRequest> + Send,
+{
+    let format = MetricsFormat::from_accept_header(maybe_accept.as_deref());
+    let maybe_metrics = effect_builder
+        .make_request(
+            |responder| ApiRequest::GetMetrics { format, responder },
+            QueueKind::Api,
         )
+        .await;
+
+    let response = match maybe_metrics {
+        Some(metrics) => Response::builder()
+            .header("content-type", format.content_type())
+            .status(StatusCode::OK)
+            .body(metrics),
+        None => Response::builder()
+            .status(StatusCode::INTERNAL_SERVER_ERROR)
+            .body("failed to collect metrics. sorry!".to_string()),
     };
+    Ok(response.unwrap())
+}
 
-    let (body, status) = match maybe_deploy {
-        Some(deploy) => match deploy.to_json() {
-            Ok(deploy_as_json) => (deploy_as_json, StatusCode::OK),
-            Err(error) => (error_body(&error), StatusCode::INTERNAL_SERVER_ERROR),
-        },
-        None => ("null".to_string(), StatusCode::OK),
+async fn handle_get_block_request<REv>(
+    effect_builder: EffectBuilder<REv>,
+    identifier: Tail,
+    maybe_accept: Option<String>,
+) -> Result<Response<Vec<u8>>, Rejection>
+where
+    REv: From<Event> + From<ApiRequest> + Send,
+{
+    let format = ResponseFormat::from_accept_header(maybe_accept.as_deref());
+    let block_identifier = match parse_block_identifier(identifier.as_str()) {
+        Ok(block_identifier) => block_identifier,
+        Err(error) => {
+            info!(%error, "failed to get block");
+            let error_reply = format!(
+                "Failed to parse '{}' as block height or hex-encoded BlockHash.  Error: {}",
+                identifier.as_str(),
+                error
+            );
+            return Ok(bad_request(error_reply));
+        }
     };
 
-    Ok(Response::builder()
-        .header("content-type", "application/json")
-        .status(status)
-        .body(body)
-        .unwrap())
+    let result = effect_builder
+        .make_request(
+            |responder| ApiRequest::GetBlock {
+                identifier: block_identifier,
+                format,
+                responder,
+            },
+            QueueKind::Api,
+        )
+        .await;
+
+    let context = format!("retrieving {}", block_identifier);
+    Ok(serialized_response(format, result, &context))
+}
+
+async fn handle_get_block_execution_results_request<REv>(
+    effect_builder: EffectBuilder<REv>,
+    hex_digest: Tail,
+    maybe_accept: Option<String>,
+) -> Result<Response<Vec<u8>>, Rejection>
+where
+    REv: From<Event> + From<ApiRequest> + Send,
+{
+    let format = ResponseFormat::from_accept_header(maybe_accept.as_deref());
+    let digest = match Digest::from_hex(hex_digest.as_str()) {
+        Ok(digest) => digest,
+        Err(error) => {
+            info!(%error, "failed to get block execution results");
+            let error_reply = format!(
+                "Failed to parse '{}' as hex-encoded BlockHash.  Error: {}",
+                hex_digest.as_str(),
+                error
+            );
+            return Ok(bad_request(error_reply));
+        }
+    };
+
+    let result = effect_builder
+        .make_request(
+            |responder| ApiRequest::GetBlockExecutionResults {
+                block_hash: BlockHash::new(digest),
+                format,
+                responder,
+            },
+            QueueKind::Api,
+        )
+        .await;
+
+    let context = format!("retrieving execution results for {}", hex_digest.as_str());
+    Ok(serialized_response(format, result, &context))
+}
+
+/// Parses `tail` as "<ROOT>/<KEY>[/<PATH>]", i.e. a hex-encoded global state root hash and a
+/// formatted key, optionally followed by the names of the named keys to follow from it.
+fn parse_global_state_query(tail: &str) -> Result<(Digest, Key, Vec<String>), String> {
+    let mut segments = tail.split('/');
+    let state_root_hash = Digest::from_hex(segments.next().unwrap_or_default())
+        .map_err(|error| format!("failed to parse state root hash: {}", error))?;
+    let base_key = segments
+        .next()
+        .ok_or_else(|| "missing key".to_string())
+        .and_then(|key| {
+            Key::from_formatted_str(key)
+                .map_err(|error| format!("failed to parse key: {:?}", error))
+        })?;
+    let path = segments.map(ToString::to_string).collect();
+    Ok((state_root_hash, base_key, path))
+}
+
+async fn handle_query_global_state_request<REv>(
+    effect_builder: EffectBuilder<REv>,
+    tail: Tail,
+    maybe_accept: Option<String>,
+) -> Result<Response<Vec<u8>>, Rejection>
+where
+    REv: From<Event> + From<ApiRequest> + Send,
+{
+    let format = ResponseFormat::from_accept_header(maybe_accept.as_deref());
+    let (state_root_hash, base_key, path) = match parse_global_state_query(tail.as_str()) {
+        Ok(query) => query,
+        Err(error) => {
+            info!(%error, "failed to query global state");
+            let error_reply = format!(
+                "Failed to parse '{}' as <ROOT>/<KEY>[/<PATH>].  Error: {}",
+                tail.as_str(),
+                error
+            );
+            return Ok(bad_request(error_reply));
+        }
+    };
+
+    let result = effect_builder
+        .make_request(
+            |responder| ApiRequest::QueryGlobalState {
+                state_root_hash,
+                base_key,
+                path,
+                format,
+                responder,
+            },
+            QueueKind::Api,
+        )
+        .await;
+
+    let context = format!("querying {}", tail.as_str());
+    Ok(serialized_response(format, result, &context))
+}
+
+/// Parses `tail` as "<ROOT>/<ID>", i.e. a hex-encoded global state root hash and either a
+/// hex-encoded public key or a formatted purse `URef`.
+fn parse_balance_query(tail: &str) -> Result<(Digest, BalanceIdentifier), String> {
+    let mut segments = tail.splitn(2, '/');
+    let state_root_hash = Digest::from_hex(segments.next().unwrap_or_default())
+        .map_err(|error| format!("failed to parse state root hash: {}", error))?;
+    let identifier = segments
+        .next()
+        .ok_or_else(|| "missing public key or purse".to_string())?;
+    let purse = match URef::from_formatted_str(identifier) {
+        Ok(purse) => BalanceIdentifier::Purse(purse),
+        Err(_) => PublicKey::from_hex(identifier)
+            .map(BalanceIdentifier::PublicKey)
+            .map_err(|error| format!("failed to parse public key or purse: {}", error))?,
+    };
+    Ok((state_root_hash, purse))
+}
+
+async fn handle_get_balance_request<REv>(
+    effect_builder: EffectBuilder<REv>,
+    tail: Tail,
+    maybe_accept: Option<String>,
+) -> Result<Response<Vec<u8>>, Rejection>
+where
+    REv: From<Event> + From<ApiRequest> + Send,
+{
+    let format = ResponseFormat::from_accept_header(maybe_accept.as_deref());
+    let (state_root_hash, purse) = match parse_balance_query(tail.as_str()) {
+        Ok(query) => query,
+        Err(error) => {
+            info!(%error, "failed to get balance");
+            let error_reply = format!(
+                "Failed to parse '{}' as <ROOT>/<ID>.  Error: {}",
+                tail.as_str(),
+                error
+            );
+            return Ok(bad_request(error_reply));
+        }
+    };
+
+    let result = effect_builder
+        .make_request(
+            |responder| ApiRequest::GetBalance {
+                state_root_hash,
+                purse,
+                format,
+                responder,
+            },
+            QueueKind::Api,
+        )
+        .await;
+
+    let context = format!("retrieving balance for {}", tail.as_str());
+    Ok(serialized_response(format, result, &context))
+}
+
+async fn handle_subscribe_finalized_blocks<REv>(
+    effect_builder: EffectBuilder<REv>,
+) -> Result<impl Reply, Rejection>
+where
+    REv: From<Event> + From<ApiRequest> + Send,
+{
+    let maybe_receiver = effect_builder
+        .make_request(
+            |responder| ApiRequest::SubscribeFinalizedBlocks { responder },
+            QueueKind::Api,
+        )
+        .await;
+    let receiver = match maybe_receiver {
+        Some(receiver) => receiver,
+        None => {
+            let reply = reply::with_status(
+                "too many subscribers to finalized blocks",
+                StatusCode::SERVICE_UNAVAILABLE,
+            );
+            return Ok(reply.into_response());
+        }
+    };
+
+    // Once the client disconnects, the stream and hence the receiver are dropped, which ends the
+    // subscription.
+    let events = receiver.map(|finalized_block| Ok::<_, Infallible>(sse::json(finalized_block)));
+    Ok(sse::reply(sse::keep_alive().stream(events)).into_response())
 }
 
 async fn handle_get_status<REv>(
     effect_builder: EffectBuilder<REv>,
-) -> Result<Response<String>, Rejection>
+    maybe_accept: Option<String>,
+) -> Result<Response<Vec<u8>>, Rejection>
+where
+    REv: From<Event> + From<ApiRequest> + Send,
+{
+    let format = ResponseFormat::from_accept_header(maybe_accept.as_deref());
+    let result = effect_builder
+        .make_request(
+            |responder| ApiRequest::GetStatus { format, responder },
+            QueueKind::Api,
+        )
+        .await;
+
+    Ok(serialized_response(format, result, "retrieving status"))
+}
+
+async fn handle_get_peers<REv>(
+    effect_builder: EffectBuilder<REv>,
+    maybe_accept: Option<String>,
+) -> Result<Response<Vec<u8>>, Rejection>
 where
     REv: From<Event> + From<ApiRequest> + Send,
 {
-    let status_feed_json = effect_builder
+    let format = ResponseFormat::from_accept_header(maybe_accept.as_deref());
+    let result = effect_builder
         .make_request(
-            |responder| ApiRequest::GetStatus { responder },
+            |responder| ApiRequest::GetPeers { format, responder },
             QueueKind::Api,
         )
         .await;
 
-    let (body, status) = match status_feed_json {
-        Some(body) => (body, StatusCode::OK),
-        None => (
-            String::from("status unavailable"),
-            StatusCode::INTERNAL_SERVER_ERROR,
-        ),
+    Ok(serialized_response(format, result, "retrieving peers"))
+}
+
+async fn handle_get_chainspec<REv>(
+    effect_builder: EffectBuilder<REv>,
+    maybe_accept: Option<String>,
+) -> Result<Response<Vec<u8>>, Rejection>
+where
+    REv: From<Event> + From<ApiRequest> + Send,
+{
+    let format = ResponseFormat::from_accept_header(maybe_accept.as_deref());
+    let result = effect_builder
+        .make_request(
+            |responder| ApiRequest::GetChainspec { format, responder },
+            QueueKind::Api,
+        )
+        .await;
+
+    Ok(serialized_response(format, result, "retrieving chainspec"))
+}
+
+async fn handle_get_next_upgrade<REv>(
+    effect_builder: EffectBuilder<REv>,
+    maybe_accept: Option<String>,
+) -> Result<Response<Vec<u8>>, Rejection>
+where
+    REv: From<Event> + From<ApiRequest> + Send,
+{
+    let format = ResponseFormat::from_accept_header(maybe_accept.as_deref());
+    let result = effect_builder
+        .make_request(
+            |responder| ApiRequest::GetNextUpgrade { format, responder },
+            QueueKind::Api,
+        )
+        .await;
+
+    Ok(serialized_response(
+        format,
+        result,
+        "retrieving next upgrade",
+    ))
+}
+
+async fn handle_get_pending_deploys<REv>(
+    effect_builder: EffectBuilder<REv>,
+    maybe_accept: Option<String>,
+) -> Result<Response<Vec<u8>>, Rejection>
+where
+    REv: From<Event> + From<ApiRequest> + Send,
+{
+    let format = ResponseFormat::from_accept_header(maybe_accept.as_deref());
+    let result = effect_builder
+        .make_request(
+            |responder| ApiRequest::GetPendingDeploys { format, responder },
+            QueueKind::Api,
+        )
+        .await;
+
+    Ok(serialized_response(
+        format,
+        result,
+        "retrieving pending deploys",
+    ))
+}
+
+/// Query parameters for listing deploys.
+#[derive(Debug, Default, Deserialize)]
+struct ListDeploysQuery {
+    /// The maximum number of deploy hashes to return.
+    limit: Option<usize>,
+    /// The hex-encoded last deploy hash of the previous page.
+    cursor: Option<String>,
+}
+
+/// Returns the hex-encoded page of at most `limit` of `deploy_hashes` following `cursor`.
+///
+/// The hashes are sorted so that pages are deterministic regardless of the storage order.
+fn page_deploy_hashes(
+    mut deploy_hashes: Vec<DeployHash>,
+    cursor: Option<DeployHash>,
+    limit: Option<usize>,
+) -> Vec<String> {
+    deploy_hashes.sort();
+    let start = match cursor {
+        Some(cursor) => match deploy_hashes.binary_search(&cursor) {
+            Ok(index) => index + 1,
+            Err(index) => index,
+        },
+        None => 0,
+    };
+    let end = match limit {
+        Some(limit) => deploy_hashes.len().min(start.saturating_add(limit)),
+        None => deploy_hashes.len(),
     };
+    deploy_hashes[start..end]
+        .iter()
+        .map(|deploy_hash| hex::encode(deploy_hash.inner()))
+        .collect()
+}
+
+/// The execution results of a block's deploys, keyed by hex-encoded deploy hash.
+///
+/// Deploy hashes are not strings, so they can't be used as JSON object keys directly.
+pub type GetBlockExecutionResultsResponse = BTreeMap<String, (DeployHeader, ExecutionResult)>;
+
+/// Returns the response to a request for the given execution results of a block's deploys.
+fn block_execution_results_response(
+    execution_results: BlockExecutionResults,
+) -> GetBlockExecutionResultsResponse {
+    execution_results
+        .into_iter()
+        .map(|(deploy_hash, result)| (hex::encode(deploy_hash.inner()), result))
+        .collect()
+}
+
+/// The response to a global state query.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct QueryGlobalStateResponse {
+    /// The hex-encoded `ToBytes` serialization of the `StoredValue`, if it was found.
+    pub stored_value: Option<String>,
+    /// Why the value could not be found, if it wasn't.
+    pub error: Option<String>,
+}
+
+impl QueryGlobalStateResponse {
+    fn new(result: Result<QueryResult, engine_state::Error>) -> Result<Self, SerializationError> {
+        let error = match result {
+            Ok(QueryResult::Success(stored_value)) => {
+                let bytes = stored_value
+                    .to_bytes()
+                    .map_err(SerializationError::StoredValue)?;
+                return Ok(QueryGlobalStateResponse {
+                    stored_value: Some(hex::encode(bytes)),
+                    error: None,
+                });
+            }
+            Ok(QueryResult::RootNotFound) => "state root hash not found".to_string(),
+            Ok(QueryResult::ValueNotFound(message)) => message,
+            Ok(QueryResult::CircularReference(message)) => message,
+            Err(error) => error.to_string(),
+        };
+        Ok(QueryGlobalStateResponse {
+            stored_value: None,
+            error: Some(error),
+        })
+    }
+}
+
+/// The response to a balance query.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct GetBalanceResponse {
+    /// The balance in motes, as a decimal string, if it was found.
+    pub balance: Option<String>,
+    /// Why the balance could not be found, if it wasn't.
+    pub error: Option<String>,
+}
+
+impl From<Result<U512, BalanceError>> for GetBalanceResponse {
+    fn from(result: Result<U512, BalanceError>) -> Self {
+        match result {
+            Ok(balance) => GetBalanceResponse {
+                balance: Some(balance.to_string()),
+                error: None,
+            },
+            Err(error) => GetBalanceResponse {
+                balance: None,
+                error: Some(error.to_string()),
+            },
+        }
+    }
+}
+
+/// The parameters of the active chainspec which clients need to build valid deploys.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct GetChainspecResponse {
+    /// The name of the chain.
+    pub chain_name: String,
+    /// The genesis timestamp.
+    pub genesis_timestamp: Timestamp,
+    /// The protocol version.
+    pub protocol_version: String,
+    /// The maximum payment cost of a deploy in motes, as a decimal string.
+    pub max_payment_cost: String,
+    /// The maximum time to live of a deploy.
+    pub max_ttl: TimeDiff,
+    /// The maximum number of dependencies of a deploy.
+    pub max_dependencies: u8,
+    /// The maximum size of a block in bytes.
+    pub max_block_size: u32,
+    /// The maximum amount of gas all deploys of a block may consume.
+    pub block_gas_limit: u64,
+}
+
+impl From<&Chainspec> for GetChainspecResponse {
+    fn from(chainspec: &Chainspec) -> Self {
+        let deploy_config = &chainspec.genesis.deploy_config;
+        GetChainspecResponse {
+            chain_name: chainspec.genesis.name.clone(),
+            genesis_timestamp: chainspec.genesis.timestamp,
+            protocol_version: chainspec.genesis.protocol_version.to_string(),
+            max_payment_cost: deploy_config.max_payment_cost.value().to_string(),
+            max_ttl: deploy_config.max_ttl,
+            max_dependencies: deploy_config.max_dependencies,
+            max_block_size: deploy_config.max_block_size,
+            block_gas_limit: deploy_config.block_gas_limit,
+        }
+    }
+}
 
-    Ok(Response::builder()
+/// Builds a "bad request" response with the given error message.
+fn bad_request(error_reply: String) -> Response<Vec<u8>> {
+    Response::builder()
         .header("content-type", "application/json")
-        .status(status)
-        .body(body)
-        .unwrap())
+        .status(StatusCode::BAD_REQUEST)
+        .body(error_reply.into_bytes())
+        .unwrap()
+}
+
+/// Builds the response for a result serialized by the API server component, or an internal
+/// server error response if serialization failed.
+fn serialized_response(
+    format: ResponseFormat,
+    result: Result<Vec<u8>, SerializationError>,
+    context: &str,
+) -> Response<Vec<u8>> {
+    match result {
+        Ok(body) => Response::builder()
+            .header("content-type", format.content_type())
+            .status(StatusCode::OK)
+            .body(body)
+            .unwrap(),
+        Err(error) => Response::builder()
+            .header("content-type", "application/json")
+            .status(StatusCode::INTERNAL_SERVER_ERROR)
+            .body(format!(r#""Internal server error {}.  Error: {}""#, context, error).into_bytes())
+            .unwrap(),
+    }
 }
 
 impl<REv, R> Component<REv, R> for ApiServer
@@ -311,7 +960,9 @@ where
     REv: From<ApiServerAnnouncement>
         + From<NetworkInfoRequest<NodeId>>
         + From<LinearChainRequest<NodeId>>
+        + From<ConsensusRequest>
         + From<ContractRuntimeRequest>
+        + From<DeployBufferRequest>
         + From<MetricsRequest>
         + From<StorageRequest<Storage>>
         + Send,
@@ -327,62 +978,296 @@ where
     ) -> Effects<Self::Event> {
         match event {
             Event::ApiRequest(ApiRequest::SubmitDeploy { deploy, responder }) => {
+                let deploy_hash = *deploy.id();
                 let mut effects = effect_builder.announce_deploy_received(deploy).ignore();
-                effects.extend(responder.respond(()).ignore());
+                effects.extend(responder.respond(deploy_hash).ignore());
                 effects
             }
-            Event::ApiRequest(ApiRequest::GetDeploy { hash, responder }) => effect_builder
-                .get_deploys_from_storage(smallvec![hash])
-                .event(move |mut result| Event::GetDeployResult {
-                    hash,
-                    result: Box::new(result.pop().expect("can only contain one result")),
-                    main_responder: responder,
-                }),
-            Event::ApiRequest(ApiRequest::ListDeploys { responder }) => effect_builder
+            Event::ApiRequest(ApiRequest::GetDeploy {
+                hash,
+                format,
+                responder,
+            }) => {
+                let pending_requests = self.pending_deploy_requests.entry(hash).or_default();
+                pending_requests.push((format, responder));
+                if pending_requests.len() > 1 {
+                    // A lookup of this deploy is already in flight.
+                    return Effects::new();
+                }
+                effect_builder
+                    .get_deploys_from_storage(smallvec![hash])
+                    .event(move |mut result| Event::GetDeployResult {
+                        hash,
+                        result: Box::new(result.pop().expect("can only contain one result")),
+                    })
+            }
+            Event::ApiRequest(ApiRequest::ListDeploys {
+                limit,
+                cursor,
+                format,
+                responder,
+            }) => effect_builder
                 .list_deploys()
                 .event(move |result| Event::ListDeploysResult {
                     result,
+                    limit,
+                    cursor,
+                    format,
                     main_responder: responder,
                 }),
-            Event::ApiRequest(ApiRequest::GetMetrics { responder }) => effect_builder
+            Event::ApiRequest(ApiRequest::GetMetrics { format, responder }) => effect_builder
                 .get_metrics()
                 .event(move |text| Event::GetMetricsResult {
                     text,
+                    format,
+                    main_responder: responder,
+                }),
+            Event::ApiRequest(ApiRequest::GetBlock {
+                identifier,
+                format,
+                responder,
+            }) => effect_builder
+                .get_block::<NodeId>(identifier)
+                .event(move |result| Event::GetBlockResult {
+                    maybe_identifier: identifier,
+                    result: Box::new(result),
+                    format,
+                    main_responder: responder,
+                }),
+            Event::ApiRequest(ApiRequest::GetBlockExecutionResults {
+                block_hash,
+                format,
+                responder,
+            }) => effect_builder
+                .get_execution_results_from_storage::<Storage>(block_hash)
+                .event(move |result| Event::GetBlockExecutionResultsResult {
+                    block_hash,
+                    result: Box::new(result),
+                    format,
+                    main_responder: responder,
+                }),
+            Event::ApiRequest(ApiRequest::GetPeers { format, responder }) => effect_builder
+                .network_peers::<NodeId>()
+                .event(move |peers| Event::GetPeersResult {
+                    peers: peers.into_iter().collect(),
+                    format,
+                    main_responder: responder,
+                }),
+            Event::ApiRequest(ApiRequest::GetChainspec { format, responder }) => effect_builder
+                .get_chainspec::<Storage>(self.protocol_version.clone())
+                .event(move |result| Event::GetChainspecResult {
+                    result: Box::new(result),
+                    format,
                     main_responder: responder,
                 }),
-            Event::ApiRequest(ApiRequest::GetStatus { responder }) => async move {
-                let (last_finalized_block, peers) = join!(
-                    effect_builder.get_last_finalized_block(),
-                    effect_builder.network_peers()
-                );
-                let status_feed = StatusFeed::new(last_finalized_block, peers);
-                debug!("GetStatus --status_feed: {:?}", status_feed);
-                let json = {
-                    match serde_json::to_string(&status_feed) {
-                        Ok(json) => json,
-                        Err(error) => {
-                            error!("GetStatus --error: {:?}", error);
-                            serde_json::to_string(&StatusFeed::default())
-                                .unwrap_or_else(|_| String::default())
-                        }
+            Event::ApiRequest(ApiRequest::GetNextUpgrade { format, responder }) => {
+                let protocol_version = self.protocol_version.clone();
+                effect_builder
+                    .get_chainspec::<Storage>(protocol_version.clone())
+                    .event(move |maybe_chainspec| Event::GetNextUpgradeResult {
+                        result: maybe_chainspec
+                            .and_then(|chainspec| chainspec.next_upgrade(&protocol_version)),
+                        format,
+                        main_responder: responder,
+                    })
+            }
+            Event::ApiRequest(ApiRequest::GetPendingDeploys { format, responder }) => {
+                effect_builder.get_pending_deploys().event(move |hashes| {
+                    Event::GetPendingDeploysResult {
+                        hashes,
+                        format,
+                        main_responder: responder,
                     }
-                };
-                responder.respond(Some(json)).await;
+                })
+            }
+            Event::ApiRequest(ApiRequest::QueryGlobalState {
+                state_root_hash,
+                base_key,
+                path,
+                format,
+                responder,
+            }) => {
+                let query_request = QueryRequest::new(state_root_hash.into(), base_key, path);
+                effect_builder
+                    .query_global_state(query_request)
+                    .event(move |result| Event::QueryGlobalStateResult {
+                        result: Box::new(result),
+                        format,
+                        main_responder: responder,
+                    })
+            }
+            Event::ApiRequest(ApiRequest::GetBalance {
+                state_root_hash,
+                purse,
+                format,
+                responder,
+            }) => effect_builder
+                .get_balance(state_root_hash, purse)
+                .event(move |result| Event::GetBalanceResult {
+                    result: Box::new(result),
+                    format,
+                    main_responder: responder,
+                }),
+            Event::ApiRequest(ApiRequest::SubscribeFinalizedBlocks { responder }) => {
+                if self.finalized_block_subscribers.len() >= MAX_FINALIZED_BLOCK_SUBSCRIBERS {
+                    warn!("rejecting subscription to finalized blocks: too many subscribers");
+                    return responder.respond(None).ignore();
+                }
+                let (sender, receiver) = mpsc::channel(FINALIZED_BLOCK_BUFFER_SIZE);
+                self.finalized_block_subscribers.push(sender);
+                responder.respond(Some(receiver)).ignore()
             }
-            .ignore(),
-            Event::GetDeployResult {
-                hash: _,
+            Event::GetBalanceResult {
                 result,
+                format,
                 main_responder,
-            } => main_responder.respond(*result).ignore(),
+            } => {
+                let response = GetBalanceResponse::from(*result);
+                main_responder.respond(format.serialize(&response)).ignore()
+            }
+            Event::FinalizedBlock(finalized_block) => {
+                // Drop the subscriptions whose receivers are gone or have fallen too far behind.
+                let subscribers = mem::take(&mut self.finalized_block_subscribers);
+                self.finalized_block_subscribers = subscribers
+                    .into_iter()
+                    .filter_map(
+                        |mut sender| match sender.try_send((*finalized_block).clone()) {
+                            Ok(()) => Some(sender),
+                            Err(error) if error.is_full() => {
+                                info!("dropping subscriber lagging behind on finalized blocks");
+                                None
+                            }
+                            Err(_) => None,
+                        },
+                    )
+                    .collect();
+                Effects::new()
+            }
+            Event::ApiRequest(ApiRequest::GetStatus { format, responder }) => {
+                let uptime = Timestamp::now().saturating_sub(self.started);
+                async move {
+                    join!(
+                        effect_builder.get_last_finalized_block(),
+                        effect_builder.network_peers(),
+                        effect_builder.get_current_era()
+                    )
+                }
+                .event(move |(last_finalized_block, peers, era_id)| {
+                    Event::GetStatusResult {
+                        status: Box::new(StatusFeed::new(
+                            last_finalized_block,
+                            peers,
+                            Some(era_id),
+                            uptime,
+                        )),
+                        format,
+                        main_responder: responder,
+                    }
+                })
+            }
+            Event::GetDeployResult { hash, result } => self
+                .pending_deploy_requests
+                .remove(&hash)
+                .unwrap_or_default()
+                .into_iter()
+                .flat_map(|(format, main_responder)| {
+                    main_responder
+                        .respond(format.serialize_deploy((*result).as_ref()))
+                        .ignore()
+                })
+                .collect(),
             Event::ListDeploysResult {
                 result,
+                limit,
+                cursor,
+                format,
                 main_responder,
-            } => main_responder.respond(result).ignore(),
+            } => {
+                let hex_hashes = page_deploy_hashes(result, cursor, limit);
+                main_responder
+                    .respond(format.serialize(&hex_hashes))
+                    .ignore()
+            }
             Event::GetMetricsResult {
                 text,
+                format,
+                main_responder,
+            } => main_responder
+                .respond(text.and_then(|text| format.render(text)))
+                .ignore(),
+            Event::GetBlockResult {
+                maybe_identifier: _,
+                result,
+                format,
                 main_responder,
-            } => main_responder.respond(text).ignore(),
+            } => main_responder.respond(format.serialize(&result)).ignore(),
+            Event::GetBlockExecutionResultsResult {
+                block_hash: _,
+                result,
+                format,
+                main_responder,
+            } => {
+                let response = (*result).map(block_execution_results_response);
+                main_responder.respond(format.serialize(&response)).ignore()
+            }
+            Event::GetStatusResult {
+                status,
+                format,
+                main_responder,
+            } => {
+                debug!("GetStatus --status_feed: {:?}", status);
+                let result = format.serialize(&status);
+                if let Err(error) = &result {
+                    error!("GetStatus --error: {:?}", error);
+                }
+                main_responder.respond(result).ignore()
+            }
+            Event::GetPeersResult {
+                peers,
+                format,
+                main_responder,
+            } => {
+                // Node IDs are not strings, so they can't be used as JSON object keys directly.
+                let peers: BTreeMap<String, String> = peers
+                    .into_iter()
+                    .map(|(node_id, address)| (node_id.to_string(), address.to_string()))
+                    .collect();
+                main_responder.respond(format.serialize(&peers)).ignore()
+            }
+            Event::GetChainspecResult {
+                result,
+                format,
+                main_responder,
+            } => {
+                let response = (*result).as_ref().map(GetChainspecResponse::from);
+                main_responder.respond(format.serialize(&response)).ignore()
+            }
+            Event::GetNextUpgradeResult {
+                result,
+                format,
+                main_responder,
+            } => main_responder.respond(format.serialize(&result)).ignore(),
+            Event::GetPendingDeploysResult {
+                mut hashes,
+                format,
+                main_responder,
+            } => {
+                hashes.sort();
+                let hashes: Vec<String> = hashes
+                    .iter()
+                    .map(|deploy_hash| hex::encode(deploy_hash.inner()))
+                    .collect();
+                main_responder.respond(format.serialize(&hashes)).ignore()
+            }
+            Event::QueryGlobalStateResult {
+                result,
+                format,
+                main_responder,
+            } => {
+                let response = QueryGlobalStateResponse::new(*result)
+                    .and_then(|response| format.serialize(&response));
+                main_responder.respond(response).ignore()
+            }
         }
     }
 }
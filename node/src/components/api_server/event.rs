@@ -1,36 +1,109 @@
-use std::fmt::{self, Display, Formatter};
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Display, Formatter},
+    net::SocketAddr,
+};
 
 use derive_more::From;
 
+use casper_execution_engine::core::engine_state::{self, QueryResult};
+use casper_types::U512;
+
+use super::{MetricsFormat, ResponseFormat, SerializationError};
 use crate::{
+    components::{
+        chainspec_loader::{Chainspec, NextUpgrade},
+        contract_runtime::BalanceError,
+    },
     effect::{requests::ApiRequest, Responder},
-    types::{Deploy, DeployHash},
+    small_network::NodeId,
+    types::{
+        Block, BlockExecutionResults, BlockHash, BlockIdentifier, Deploy, DeployHash,
+        FinalizedBlock, StatusFeed,
+    },
 };
 
 #[derive(Debug, From)]
 pub enum Event {
     #[from]
     ApiRequest(ApiRequest),
+    /// The result of looking up a deploy, to be sent to all requests waiting on it.
     GetDeployResult {
         hash: DeployHash,
         result: Box<Option<Deploy>>,
-        main_responder: Responder<Option<Deploy>>,
     },
     ListDeploysResult {
         result: Vec<DeployHash>,
-        main_responder: Responder<Vec<DeployHash>>,
+        limit: Option<usize>,
+        cursor: Option<DeployHash>,
+        format: ResponseFormat,
+        main_responder: Responder<Result<Vec<u8>, SerializationError>>,
     },
     GetMetricsResult {
         text: Option<String>,
+        format: MetricsFormat,
         main_responder: Responder<Option<String>>,
     },
+    GetBlockResult {
+        maybe_identifier: BlockIdentifier,
+        result: Box<Option<Block>>,
+        format: ResponseFormat,
+        main_responder: Responder<Result<Vec<u8>, SerializationError>>,
+    },
+    /// The result of looking up the execution results of a block's deploys.
+    GetBlockExecutionResultsResult {
+        block_hash: BlockHash,
+        result: Box<Option<BlockExecutionResults>>,
+        format: ResponseFormat,
+        main_responder: Responder<Result<Vec<u8>, SerializationError>>,
+    },
+    GetStatusResult {
+        status: Box<StatusFeed>,
+        format: ResponseFormat,
+        main_responder: Responder<Result<Vec<u8>, SerializationError>>,
+    },
+    /// The result of looking up the active chainspec.
+    GetChainspecResult {
+        result: Box<Option<Chainspec>>,
+        format: ResponseFormat,
+        main_responder: Responder<Result<Vec<u8>, SerializationError>>,
+    },
+    /// The next upgrade scheduled in the active chainspec, if any.
+    GetNextUpgradeResult {
+        result: Option<NextUpgrade>,
+        format: ResponseFormat,
+        main_responder: Responder<Result<Vec<u8>, SerializationError>>,
+    },
+    GetPeersResult {
+        peers: BTreeMap<NodeId, SocketAddr>,
+        format: ResponseFormat,
+        main_responder: Responder<Result<Vec<u8>, SerializationError>>,
+    },
+    /// The hashes of the deploys pending in the deploy buffer.
+    GetPendingDeploysResult {
+        hashes: Vec<DeployHash>,
+        format: ResponseFormat,
+        main_responder: Responder<Result<Vec<u8>, SerializationError>>,
+    },
+    GetBalanceResult {
+        result: Box<Result<U512, BalanceError>>,
+        format: ResponseFormat,
+        main_responder: Responder<Result<Vec<u8>, SerializationError>>,
+    },
+    /// A block was finalized, to be sent to the subscribers.
+    FinalizedBlock(Box<FinalizedBlock>),
+    QueryGlobalStateResult {
+        result: Box<Result<QueryResult, engine_state::Error>>,
+        format: ResponseFormat,
+        main_responder: Responder<Result<Vec<u8>, SerializationError>>,
+    },
 }
 
 impl Display for Event {
     fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
         match self {
             Event::ApiRequest(request) => write!(formatter, "{}", request),
-            Event::GetDeployResult { hash, result, .. } => {
+            Event::GetDeployResult { hash, result } => {
                 write!(formatter, "GetDeployResult for {}: {:?}", hash, result)
             }
             Event::ListDeploysResult { result, .. } => {
@@ -40,6 +113,82 @@ impl Display for Event {
                 Some(tx) => write!(formatter, "GetMetricsResult ({} bytes)", tx.len()),
                 None => write!(formatter, "GetMetricsResult (failed)"),
             },
+            Event::GetBlockResult {
+                maybe_identifier,
+                result,
+                ..
+            } => match result.as_ref() {
+                Some(block) => write!(
+                    formatter,
+                    "GetBlockResult for {}: {}",
+                    maybe_identifier,
+                    block.hash()
+                ),
+                None => write!(
+                    formatter,
+                    "GetBlockResult for {}: not found",
+                    maybe_identifier
+                ),
+            },
+            Event::GetBlockExecutionResultsResult {
+                block_hash, result, ..
+            } => match result.as_ref() {
+                Some(execution_results) => write!(
+                    formatter,
+                    "GetBlockExecutionResultsResult for {}: {} deploys",
+                    block_hash,
+                    execution_results.len()
+                ),
+                None => write!(
+                    formatter,
+                    "GetBlockExecutionResultsResult for {}: not found",
+                    block_hash
+                ),
+            },
+            Event::GetStatusResult { status, .. } => {
+                write!(formatter, "GetStatusResult: era ")?;
+                match status.era_id() {
+                    Some(era_id) => write!(formatter, "{}", era_id.0)?,
+                    None => write!(formatter, "unknown")?,
+                }
+                write!(formatter, ", height ")?;
+                match status.last_linear_block_height() {
+                    Some(height) => write!(formatter, "{}", height),
+                    None => write!(formatter, "unknown"),
+                }
+            }
+            Event::GetChainspecResult { result, .. } => match result.as_ref() {
+                Some(chainspec) => write!(
+                    formatter,
+                    "GetChainspecResult: {}",
+                    chainspec.genesis.protocol_version
+                ),
+                None => write!(formatter, "GetChainspecResult: not found"),
+            },
+            Event::GetNextUpgradeResult { result, .. } => match result {
+                Some(upgrade) => write!(formatter, "GetNextUpgradeResult: {}", upgrade),
+                None => write!(formatter, "GetNextUpgradeResult: none scheduled"),
+            },
+            Event::GetPeersResult { peers, .. } => {
+                write!(formatter, "GetPeersResult: {} peers", peers.len())
+            }
+            Event::GetPendingDeploysResult { hashes, .. } => {
+                write!(
+                    formatter,
+                    "GetPendingDeploysResult: {} deploys",
+                    hashes.len()
+                )
+            }
+            Event::GetBalanceResult { result, .. } => match result.as_ref() {
+                Ok(balance) => write!(formatter, "GetBalanceResult: {}", balance),
+                Err(error) => write!(formatter, "GetBalanceResult: {}", error),
+            },
+            Event::FinalizedBlock(block) => write!(formatter, "{}", block),
+            Event::QueryGlobalStateResult { result, .. } => match result.as_ref() {
+                Ok(QueryResult::Success(_)) => write!(formatter, "QueryGlobalStateResult: found"),
+                Ok(query_result) => write!(formatter, "QueryGlobalStateResult: {:?}", query_result),
+                Err(error) => write!(formatter, "QueryGlobalStateResult: {}", error),
+            },
         }
     }
 }
@@ -0,0 +1,98 @@
+use std::fmt::{self, Display, Formatter};
+
+use serde::{Deserialize, Serialize};
+use tracing::error;
+
+use super::response_format::accept_quality;
+
+const PROMETHEUS_CONTENT_TYPE: &str = "text/plain; version=0.0.4";
+const JSON_CONTENT_TYPE: &str = "application/json";
+
+/// The format in which the API server returns the node's metrics.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MetricsFormat {
+    /// The Prometheus text exposition format, the default.
+    Prometheus,
+    /// A JSON-serialized list of `MetricSample`s.
+    Json,
+}
+
+/// A single sample of a metric, parsed from the Prometheus text exposition format.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct MetricSample {
+    /// The name of the metric, including its labels if it has any, e.g. `foo{bar="baz"}`.
+    pub name: String,
+    /// The sampled value.
+    pub value: f64,
+}
+
+impl MetricsFormat {
+    /// Returns the format requested via the given `Accept` header value, defaulting to Prometheus.
+    pub(crate) fn from_accept_header(maybe_accept: Option<&str>) -> Self {
+        match maybe_accept {
+            Some(accept)
+                if accept_quality(accept, JSON_CONTENT_TYPE)
+                    > accept_quality(accept, PROMETHEUS_CONTENT_TYPE) =>
+            {
+                MetricsFormat::Json
+            }
+            _ => MetricsFormat::Prometheus,
+        }
+    }
+
+    /// Returns the value of the `Content-Type` header for responses in this format.
+    pub(crate) fn content_type(self) -> &'static str {
+        match self {
+            MetricsFormat::Prometheus => PROMETHEUS_CONTENT_TYPE,
+            MetricsFormat::Json => JSON_CONTENT_TYPE,
+        }
+    }
+
+    /// Converts metrics rendered in the Prometheus text exposition format to this format.
+    ///
+    /// Returns `None` if the conversion failed.
+    pub(crate) fn render(self, text: String) -> Option<String> {
+        match self {
+            MetricsFormat::Prometheus => Some(text),
+            MetricsFormat::Json => match serde_json::to_string(&parse_samples(&text)) {
+                Ok(json) => Some(json),
+                Err(error) => {
+                    error!(%error, "JSON encoding of metrics failed");
+                    None
+                }
+            },
+        }
+    }
+}
+
+impl Display for MetricsFormat {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        match self {
+            MetricsFormat::Prometheus => write!(formatter, "Prometheus"),
+            MetricsFormat::Json => write!(formatter, "JSON"),
+        }
+    }
+}
+
+/// Parses the samples out of the Prometheus text exposition format, skipping comments and any
+/// malformed lines.
+fn parse_samples(text: &str) -> Vec<MetricSample> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| {
+            // Label values may contain whitespace, so the name ends after the closing brace.
+            let name_end = match line.find('{') {
+                Some(_) => line.rfind('}')? + 1,
+                None => line.find(char::is_whitespace)?,
+            };
+            let (name, rest) = line.split_at(name_end);
+            // The value may be followed by a timestamp, which we ignore.
+            let value = rest.split_whitespace().next()?.parse().ok()?;
+            Some(MetricSample {
+                name: name.to_string(),
+                value,
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,136 @@
+use std::fmt::{self, Display, Formatter};
+
+use serde::Serialize;
+use thiserror::Error;
+
+use casper_types::bytesrepr;
+
+use crate::types::{Deploy, DeployError};
+
+const JSON_CONTENT_TYPE: &str = "application/json";
+const MESSAGE_PACK_CONTENT_TYPE: &str = "application/msgpack";
+
+/// The format in which the API server serializes its responses.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ResponseFormat {
+    /// JSON, the default.
+    Json,
+    /// MessagePack, a more compact binary format for bandwidth-sensitive clients.
+    MessagePack,
+}
+
+/// Error serializing an API server response.
+#[derive(Debug, Error)]
+pub enum SerializationError {
+    /// Error serializing as JSON.
+    #[error("failed to serialize as JSON: {0}")]
+    Json(#[from] serde_json::Error),
+    /// Error serializing as MessagePack.
+    #[error("failed to serialize as MessagePack: {0}")]
+    MessagePack(#[from] rmp_serde::encode::Error),
+    /// Error converting a deploy to its JSON representation.
+    #[error("failed to serialize deploy: {0}")]
+    Deploy(#[from] DeployError),
+    /// Error serializing a global state value.
+    #[error("failed to serialize stored value: {0}")]
+    StoredValue(bytesrepr::Error),
+}
+
+impl ResponseFormat {
+    /// Returns the format requested via the given `Accept` header value, defaulting to JSON.
+    pub(crate) fn from_accept_header(maybe_accept: Option<&str>) -> Self {
+        match maybe_accept {
+            Some(accept)
+                if accept_quality(accept, MESSAGE_PACK_CONTENT_TYPE)
+                    > accept_quality(accept, JSON_CONTENT_TYPE) =>
+            {
+                ResponseFormat::MessagePack
+            }
+            _ => ResponseFormat::Json,
+        }
+    }
+
+    /// Returns the value of the `Content-Type` header for responses in this format.
+    pub(crate) fn content_type(self) -> &'static str {
+        match self {
+            ResponseFormat::Json => JSON_CONTENT_TYPE,
+            ResponseFormat::MessagePack => MESSAGE_PACK_CONTENT_TYPE,
+        }
+    }
+
+    /// Serializes `value` in this format.
+    pub(crate) fn serialize<T: Serialize>(self, value: &T) -> Result<Vec<u8>, SerializationError> {
+        match self {
+            ResponseFormat::Json => Ok(serde_json::to_vec(value)?),
+            ResponseFormat::MessagePack => Ok(rmp_serde::to_vec(value)?),
+        }
+    }
+
+    /// Serializes `maybe_deploy` in this format.
+    ///
+    /// Deploys have a dedicated JSON representation, so they can't be passed to `serialize`.
+    pub(crate) fn serialize_deploy(
+        self,
+        maybe_deploy: Option<&Deploy>,
+    ) -> Result<Vec<u8>, SerializationError> {
+        match (self, maybe_deploy) {
+            (ResponseFormat::Json, Some(deploy)) => Ok(deploy.to_json()?.into_bytes()),
+            _ => self.serialize(&maybe_deploy),
+        }
+    }
+}
+
+impl Display for ResponseFormat {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        match self {
+            ResponseFormat::Json => write!(formatter, "JSON"),
+            ResponseFormat::MessagePack => write!(formatter, "MessagePack"),
+        }
+    }
+}
+
+/// Returns the quality factor with which the `Accept` header value `accept` accepts
+/// `content_type`, or 0 if it doesn't accept it at all.
+///
+/// The most specific media range matching `content_type` determines the quality, which defaults to
+/// 1 if the range has no `q` parameter. Any other parameters are ignored.
+pub(super) fn accept_quality(accept: &str, content_type: &str) -> f32 {
+    let content_type = content_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase();
+    let any_subtype = format!("{}/*", content_type.split('/').next().unwrap_or_default());
+    accept
+        .split(',')
+        .filter_map(|media_range| {
+            let mut params = media_range.split(';');
+            let range = params.next()?.trim().to_ascii_lowercase();
+            let specificity = if range == content_type {
+                2
+            } else if range == any_subtype {
+                1
+            } else if range == "*/*" {
+                0
+            } else {
+                return None;
+            };
+            let quality = params
+                .filter_map(|param| {
+                    let mut name_and_value = param.splitn(2, '=');
+                    let name = name_and_value.next()?.trim();
+                    let value = name_and_value.next()?.trim();
+                    if name.eq_ignore_ascii_case("q") {
+                        value.parse().ok()
+                    } else {
+                        None
+                    }
+                })
+                .next()
+                .unwrap_or(1.0);
+            Some((specificity, quality))
+        })
+        .max_by_key(|&(specificity, _)| specificity)
+        .map_or(0.0, |(_, quality)| quality)
+}
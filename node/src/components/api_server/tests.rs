@@ -0,0 +1,772 @@
+use casper_execution_engine::shared::stored_value::StoredValue;
+use casper_types::{account::AccountHash, bytesrepr::FromBytes, CLValue};
+use prometheus::{Encoder, IntCounter, Registry, TextEncoder};
+use tokio::task::JoinHandle;
+
+use super::*;
+use crate::{
+    components::{chainspec_loader::NextUpgrade, contract_runtime, storage},
+    reactor::{validator, EventQueueHandle, Scheduler},
+    testing::TestRng,
+    types::{FinalizedBlock, Timestamp},
+    utils,
+};
+
+struct TestContext {
+    rng: TestRng,
+    scheduler: &'static Scheduler<validator::Event>,
+    effect_builder: EffectBuilder<validator::Event>,
+    api_server: ApiServer,
+}
+
+impl TestContext {
+    fn new() -> Self {
+        let scheduler = utils::leak(Scheduler::<validator::Event>::new(QueueKind::weights()));
+        TestContext {
+            rng: TestRng::new(),
+            scheduler,
+            effect_builder: EffectBuilder::new(EventQueueHandle::new(scheduler)),
+            api_server: ApiServer {
+                started: Timestamp::now(),
+                protocol_version: Version::from((1, 0, 0)),
+                finalized_block_subscribers: Vec::new(),
+                pending_deploy_requests: HashMap::new(),
+            },
+        }
+    }
+
+    /// Pops the next event off the scheduler, expecting it to be an API request.
+    async fn pop_api_request(&self) -> ApiRequest {
+        match self.scheduler.pop().await {
+            (validator::Event::ApiServer(Event::ApiRequest(request)), _) => request,
+            (event, _) => panic!("unexpected event: {}", event),
+        }
+    }
+
+    /// Subscribes to finalized blocks, returning the receiving end of the subscription, if any.
+    async fn subscribe_finalized_blocks(&mut self) -> Option<mpsc::Receiver<FinalizedBlock>> {
+        let request = tokio::spawn(self.effect_builder.make_request(
+            |responder| ApiRequest::SubscribeFinalizedBlocks { responder },
+            QueueKind::Api,
+        ));
+        let api_request = self.pop_api_request().await;
+        self.handle_event(Event::ApiRequest(api_request)).await;
+        request.await.unwrap()
+    }
+
+    /// Has the API server handle `event` and runs the resulting effects to completion.
+    async fn handle_event(&mut self, event: Event) {
+        let effects = <ApiServer as Component<validator::Event, TestRng>>::handle_event(
+            &mut self.api_server,
+            self.effect_builder,
+            &mut self.rng,
+            event,
+        );
+        for effect in effects {
+            let _ = effect.await;
+        }
+    }
+
+    /// Has the API server handle `event`, running the resulting effects in the background.
+    fn spawn_event(&mut self, event: Event) -> Vec<JoinHandle<Vec<Event>>> {
+        <ApiServer as Component<validator::Event, TestRng>>::handle_event(
+            &mut self.api_server,
+            self.effect_builder,
+            &mut self.rng,
+            event,
+        )
+        .into_iter()
+        .map(|effect| tokio::spawn(async move { effect.await.into_iter().collect() }))
+        .collect()
+    }
+
+    /// Waits for effects spawned by `spawn_event` and has the API server handle their events.
+    async fn complete_effects(&mut self, effects: Vec<JoinHandle<Vec<Event>>>) {
+        for effect in effects {
+            for event in effect.await.unwrap() {
+                self.handle_event(event).await;
+            }
+        }
+    }
+
+    /// Pops the next event off the scheduler, expecting it to be a storage lookup of `deploy`, and
+    /// answers it.
+    async fn answer_deploy_lookup(&self, deploy: &Deploy) {
+        match self.scheduler.pop().await {
+            (
+                validator::Event::Storage(storage::Event::Request(StorageRequest::GetDeploys {
+                    deploy_hashes,
+                    responder,
+                })),
+                _,
+            ) => {
+                assert_eq!(deploy_hashes.as_slice(), &[*deploy.id()]);
+                responder.respond(smallvec![Some(deploy.clone())]).await;
+            }
+            (event, _) => panic!("unexpected event: {}", event),
+        }
+    }
+}
+
+#[tokio::test]
+async fn should_respond_with_peers_intact() {
+    let mut context = TestContext::new();
+
+    let mut peers = BTreeMap::new();
+    let _ = peers.insert(
+        NodeId::from([1; 64]),
+        "127.0.0.1:34553".parse::<SocketAddr>().unwrap(),
+    );
+    let _ = peers.insert(
+        NodeId::from([2; 64]),
+        "10.0.0.2:34553".parse::<SocketAddr>().unwrap(),
+    );
+
+    let request = tokio::spawn(context.effect_builder.make_request(
+        |responder| ApiRequest::GetPeers {
+            format: ResponseFormat::Json,
+            responder,
+        },
+        QueueKind::Api,
+    ));
+
+    let main_responder = match context.pop_api_request().await {
+        ApiRequest::GetPeers { responder, .. } => responder,
+        request => panic!("unexpected request: {}", request),
+    };
+
+    // Inject the mock peer map as if it had been returned by the network component.
+    context
+        .handle_event(Event::GetPeersResult {
+            peers: peers.clone(),
+            format: ResponseFormat::Json,
+            main_responder,
+        })
+        .await;
+
+    let body = request.await.unwrap().unwrap();
+    let received: BTreeMap<String, String> = serde_json::from_slice(&body).unwrap();
+    let expected: BTreeMap<String, String> = peers
+        .iter()
+        .map(|(node_id, address)| (node_id.to_string(), address.to_string()))
+        .collect();
+    assert_eq!(received, expected);
+}
+
+#[tokio::test]
+async fn should_serialize_deploy_as_json_and_message_pack() {
+    let mut context = TestContext::new();
+    let deploy = Deploy::random(&mut context.rng);
+
+    for &format in &[ResponseFormat::Json, ResponseFormat::MessagePack] {
+        let hash = *deploy.id();
+        let request = tokio::spawn(context.effect_builder.make_request(
+            move |responder| ApiRequest::GetDeploy {
+                hash,
+                format,
+                responder,
+            },
+            QueueKind::Api,
+        ));
+
+        let api_request = context.pop_api_request().await;
+        let effects = context.spawn_event(Event::ApiRequest(api_request));
+        context.answer_deploy_lookup(&deploy).await;
+        context.complete_effects(effects).await;
+
+        let body = request.await.unwrap().unwrap();
+        let decoded = match format {
+            ResponseFormat::Json => Deploy::from_json(str::from_utf8(&body).unwrap()).unwrap(),
+            ResponseFormat::MessagePack => rmp_serde::from_read_ref(&body).unwrap(),
+        };
+        assert_eq!(decoded, deploy, "round trip through {} failed", format);
+    }
+}
+
+#[test]
+fn should_pick_response_format_from_accept_header() {
+    let format = |accept| ResponseFormat::from_accept_header(Some(accept));
+    assert_eq!(format("application/msgpack"), ResponseFormat::MessagePack);
+    assert_eq!(
+        format("application/json;q=0.5, Application/MsgPack; charset=utf-8"),
+        ResponseFormat::MessagePack
+    );
+    assert_eq!(
+        format("application/msgpack;q=0.5, application/*"),
+        ResponseFormat::Json
+    );
+    assert_eq!(format("application/msgpack; q=0"), ResponseFormat::Json);
+    assert_eq!(format("*/*"), ResponseFormat::Json);
+    assert_eq!(
+        ResponseFormat::from_accept_header(None),
+        ResponseFormat::Json
+    );
+}
+
+#[tokio::test]
+async fn should_coalesce_concurrent_requests_for_same_deploy() {
+    const REQUEST_COUNT: usize = 5;
+
+    let mut context = TestContext::new();
+    let deploy = Deploy::random(&mut context.rng);
+    let hash = *deploy.id();
+
+    let requests: Vec<_> = (0..REQUEST_COUNT)
+        .map(|_| {
+            tokio::spawn(context.effect_builder.make_request(
+                move |responder| ApiRequest::GetDeploy {
+                    hash,
+                    format: ResponseFormat::Json,
+                    responder,
+                },
+                QueueKind::Api,
+            ))
+        })
+        .collect();
+
+    let mut effects = Vec::new();
+    for _ in 0..REQUEST_COUNT {
+        let api_request = context.pop_api_request().await;
+        effects.extend(context.spawn_event(Event::ApiRequest(api_request)));
+    }
+
+    // Only a single storage lookup should have been made, for all the requests.
+    context.answer_deploy_lookup(&deploy).await;
+    context.complete_effects(effects).await;
+    assert_eq!(context.scheduler.item_count(), 0);
+    assert!(context.api_server.pending_deploy_requests.is_empty());
+
+    for request in requests {
+        let body = request.await.unwrap().unwrap();
+        let decoded = Deploy::from_json(str::from_utf8(&body).unwrap()).unwrap();
+        assert_eq!(decoded, deploy);
+    }
+}
+
+/// Requests the execution results of the block with `block_hash` via the API server, as if
+/// `stored_results` were held in storage.
+async fn get_block_execution_results(
+    context: &mut TestContext,
+    stored_results: &HashMap<BlockHash, BlockExecutionResults>,
+    block_hash: BlockHash,
+) -> Option<GetBlockExecutionResultsResponse> {
+    let request = tokio::spawn(context.effect_builder.make_request(
+        move |responder| ApiRequest::GetBlockExecutionResults {
+            block_hash,
+            format: ResponseFormat::Json,
+            responder,
+        },
+        QueueKind::Api,
+    ));
+
+    let api_request = context.pop_api_request().await;
+    let effects = context.spawn_event(Event::ApiRequest(api_request));
+    match context.scheduler.pop().await {
+        (
+            validator::Event::Storage(storage::Event::Request(
+                StorageRequest::GetExecutionResults {
+                    block_hash: requested_hash,
+                    responder,
+                },
+            )),
+            _,
+        ) => {
+            assert_eq!(requested_hash, block_hash);
+            responder
+                .respond(stored_results.get(&block_hash).cloned())
+                .await;
+        }
+        (event, _) => panic!("unexpected event: {}", event),
+    }
+    context.complete_effects(effects).await;
+
+    let body = request.await.unwrap().unwrap();
+    serde_json::from_slice(&body).unwrap()
+}
+
+#[tokio::test]
+async fn should_return_stored_block_execution_results() {
+    let mut context = TestContext::new();
+    let block_hash = BlockHash::new(Digest::random(&mut context.rng));
+    let execution_results: BlockExecutionResults = (0..3)
+        .map(|_| {
+            let deploy = Deploy::random(&mut context.rng);
+            let execution_result = ExecutionResult::random(&mut context.rng);
+            (*deploy.id(), (deploy.header().clone(), execution_result))
+        })
+        .collect();
+    let mut stored_results = HashMap::new();
+    let _ = stored_results.insert(block_hash, execution_results.clone());
+
+    let response = get_block_execution_results(&mut context, &stored_results, block_hash)
+        .await
+        .expect("should find execution results");
+    assert_eq!(
+        response,
+        block_execution_results_response(execution_results)
+    );
+
+    let unknown_block_hash = BlockHash::new(Digest::random(&mut context.rng));
+    let response =
+        get_block_execution_results(&mut context, &stored_results, unknown_block_hash).await;
+    assert!(response.is_none());
+}
+
+/// Lists a page of `deploy_hashes` via the API server, as if they were held in storage.
+async fn list_deploys(
+    context: &mut TestContext,
+    deploy_hashes: &[DeployHash],
+    cursor: Option<&str>,
+    limit: Option<usize>,
+) -> Vec<String> {
+    let cursor = cursor.map(|cursor| DeployHash::new(Digest::from_hex(cursor).unwrap()));
+    let request = tokio::spawn(context.effect_builder.make_request(
+        move |responder| ApiRequest::ListDeploys {
+            limit,
+            cursor,
+            format: ResponseFormat::Json,
+            responder,
+        },
+        QueueKind::Api,
+    ));
+
+    let main_responder = match context.pop_api_request().await {
+        ApiRequest::ListDeploys { responder, .. } => responder,
+        request => panic!("unexpected request: {}", request),
+    };
+
+    context
+        .handle_event(Event::ListDeploysResult {
+            result: deploy_hashes.to_vec(),
+            limit,
+            cursor,
+            format: ResponseFormat::Json,
+            main_responder,
+        })
+        .await;
+
+    let body = request.await.unwrap().unwrap();
+    serde_json::from_slice(&body).unwrap()
+}
+
+#[tokio::test]
+async fn should_walk_deploy_hashes_in_pages() {
+    let mut context = TestContext::new();
+    let deploy_hashes: Vec<_> = (0..5)
+        .map(|_| DeployHash::new(Digest::random(&mut context.rng)))
+        .collect();
+    let mut expected: Vec<_> = deploy_hashes
+        .iter()
+        .map(|deploy_hash| hex::encode(deploy_hash.inner()))
+        .collect();
+    expected.sort();
+
+    let first_page = list_deploys(&mut context, &deploy_hashes, None, Some(3)).await;
+    assert_eq!(first_page, expected[..3].to_vec());
+
+    let second_page = list_deploys(
+        &mut context,
+        &deploy_hashes,
+        first_page.last().map(String::as_str),
+        Some(3),
+    )
+    .await;
+    assert_eq!(second_page, expected[3..].to_vec());
+
+    // Listing without a limit returns everything in the same order.
+    let all = list_deploys(&mut context, &deploy_hashes, None, None).await;
+    assert_eq!(all, expected);
+}
+
+#[tokio::test]
+async fn should_return_empty_page_after_last_deploy_hash() {
+    let mut context = TestContext::new();
+    let deploy_hashes: Vec<_> = (0..4)
+        .map(|_| DeployHash::new(Digest::random(&mut context.rng)))
+        .collect();
+    let last = deploy_hashes.iter().max().unwrap();
+
+    let page = list_deploys(
+        &mut context,
+        &deploy_hashes,
+        Some(&hex::encode(last.inner())),
+        Some(2),
+    )
+    .await;
+    assert!(page.is_empty());
+}
+
+/// Queries global state via the API server, answering the contract runtime's query with `result`.
+async fn query_global_state(
+    context: &mut TestContext,
+    base_key: Key,
+    path: Vec<String>,
+    result: Result<QueryResult, engine_state::Error>,
+) -> QueryGlobalStateResponse {
+    let state_root_hash = Digest::random(&mut context.rng);
+    let expected_query_request = QueryRequest::new(state_root_hash.into(), base_key, path.clone());
+    let request = tokio::spawn(context.effect_builder.make_request(
+        move |responder| ApiRequest::QueryGlobalState {
+            state_root_hash,
+            base_key,
+            path,
+            format: ResponseFormat::Json,
+            responder,
+        },
+        QueueKind::Api,
+    ));
+
+    let api_request = context.pop_api_request().await;
+    let effects = context.spawn_event(Event::ApiRequest(api_request));
+
+    // The query should be routed to the contract runtime unchanged.
+    match context.scheduler.pop().await {
+        (
+            validator::Event::ContractRuntime(contract_runtime::Event::Request(
+                ContractRuntimeRequest::Query {
+                    query_request,
+                    responder,
+                },
+            )),
+            _,
+        ) => {
+            assert_eq!(query_request, expected_query_request);
+            responder.respond(result).await;
+        }
+        (event, _) => panic!("unexpected event: {}", event),
+    }
+
+    context.complete_effects(effects).await;
+
+    let body = request.await.unwrap().unwrap();
+    serde_json::from_slice(&body).unwrap()
+}
+
+#[tokio::test]
+async fn should_query_existing_key() {
+    let mut context = TestContext::new();
+    let base_key = Key::Account(AccountHash::new([1; 32]));
+    let path = vec!["counter".to_string()];
+    let stored_value = StoredValue::CLValue(CLValue::from_t(1u64).unwrap());
+
+    let response = query_global_state(
+        &mut context,
+        base_key,
+        path,
+        Ok(QueryResult::Success(stored_value.clone())),
+    )
+    .await;
+
+    assert!(response.error.is_none());
+    let bytes = hex::decode(response.stored_value.unwrap()).unwrap();
+    let (decoded, remainder) = StoredValue::from_bytes(&bytes).unwrap();
+    assert!(remainder.is_empty());
+    assert_eq!(decoded, stored_value);
+}
+
+#[tokio::test]
+async fn should_report_missing_path() {
+    let mut context = TestContext::new();
+    let base_key = Key::Account(AccountHash::new([1; 32]));
+    let path = vec!["missing".to_string()];
+    let message = "Name missing not found in Account at path:".to_string();
+
+    let response = query_global_state(
+        &mut context,
+        base_key,
+        path,
+        Ok(QueryResult::ValueNotFound(message.clone())),
+    )
+    .await;
+
+    assert!(response.stored_value.is_none());
+    assert_eq!(response.error, Some(message));
+}
+
+#[tokio::test]
+async fn should_stream_finalized_blocks_to_subscriber_in_order() {
+    let mut context = TestContext::new();
+    let mut receiver = context.subscribe_finalized_blocks().await.unwrap();
+
+    let first = FinalizedBlock::random(&mut context.rng);
+    let second = FinalizedBlock::random(&mut context.rng);
+    context
+        .handle_event(Event::FinalizedBlock(Box::new(first.clone())))
+        .await;
+    context
+        .handle_event(Event::FinalizedBlock(Box::new(second.clone())))
+        .await;
+
+    assert_eq!(receiver.next().await, Some(first));
+    assert_eq!(receiver.next().await, Some(second));
+
+    // Once the subscriber disconnects, its subscription is dropped.
+    drop(receiver);
+    let third = FinalizedBlock::random(&mut context.rng);
+    context
+        .handle_event(Event::FinalizedBlock(Box::new(third)))
+        .await;
+    assert!(context.api_server.finalized_block_subscribers.is_empty());
+}
+
+#[tokio::test]
+async fn should_drop_subscriber_lagging_behind_on_finalized_blocks() {
+    let mut context = TestContext::new();
+    let mut receiver = context.subscribe_finalized_blocks().await.unwrap();
+
+    // The channel holds one block more than its buffer size, since there is one sender.
+    for _ in 0..=FINALIZED_BLOCK_BUFFER_SIZE {
+        let finalized_block = FinalizedBlock::random(&mut context.rng);
+        context
+            .handle_event(Event::FinalizedBlock(Box::new(finalized_block)))
+            .await;
+    }
+    assert_eq!(context.api_server.finalized_block_subscribers.len(), 1);
+
+    // The next block doesn't fit, so the subscription is dropped.  The buffered blocks can still
+    // be read, after which the stream ends.
+    let finalized_block = FinalizedBlock::random(&mut context.rng);
+    context
+        .handle_event(Event::FinalizedBlock(Box::new(finalized_block)))
+        .await;
+    assert!(context.api_server.finalized_block_subscribers.is_empty());
+    let mut received = 0;
+    while receiver.next().await.is_some() {
+        received += 1;
+    }
+    assert_eq!(received, FINALIZED_BLOCK_BUFFER_SIZE + 1);
+}
+
+#[tokio::test]
+async fn should_reject_subscribers_to_finalized_blocks_beyond_limit() {
+    let mut context = TestContext::new();
+    let mut receivers = vec![];
+    for _ in 0..MAX_FINALIZED_BLOCK_SUBSCRIBERS {
+        receivers.push(context.subscribe_finalized_blocks().await.unwrap());
+    }
+    assert!(context.subscribe_finalized_blocks().await.is_none());
+
+    // Once a subscriber disconnects, there is room for a new one.
+    drop(receivers.pop());
+    let finalized_block = FinalizedBlock::random(&mut context.rng);
+    context
+        .handle_event(Event::FinalizedBlock(Box::new(finalized_block)))
+        .await;
+    assert!(context.subscribe_finalized_blocks().await.is_some());
+}
+
+#[tokio::test]
+async fn should_return_pending_deploys_from_deploy_buffer() {
+    let mut context = TestContext::new();
+    let mut pending_deploys: Vec<DeployHash> = (0..3)
+        .map(|_| DeployHash::new(Digest::random(&mut context.rng)))
+        .collect();
+
+    let request = tokio::spawn(context.effect_builder.make_request(
+        |responder| ApiRequest::GetPendingDeploys {
+            format: ResponseFormat::Json,
+            responder,
+        },
+        QueueKind::Api,
+    ));
+
+    let api_request = context.pop_api_request().await;
+    let effects = context.spawn_event(Event::ApiRequest(api_request));
+    match context.scheduler.pop().await {
+        (
+            validator::Event::DeployBufferRequest(DeployBufferRequest::ListPending { responder }),
+            _,
+        ) => responder.respond(pending_deploys.clone()).await,
+        (event, _) => panic!("unexpected event: {}", event),
+    }
+    context.complete_effects(effects).await;
+
+    let body = request.await.unwrap().unwrap();
+    let response: Vec<String> = serde_json::from_slice(&body).unwrap();
+    pending_deploys.sort();
+    let expected: Vec<String> = pending_deploys
+        .iter()
+        .map(|deploy_hash| hex::encode(deploy_hash.inner()))
+        .collect();
+    assert_eq!(response, expected);
+}
+
+#[tokio::test]
+async fn should_return_chainspec_summary() {
+    let mut context = TestContext::new();
+    let chainspec = Chainspec::random(&mut context.rng);
+    context.api_server.protocol_version = chainspec.genesis.protocol_version.clone();
+
+    let request = tokio::spawn(context.effect_builder.make_request(
+        |responder| ApiRequest::GetChainspec {
+            format: ResponseFormat::Json,
+            responder,
+        },
+        QueueKind::Api,
+    ));
+
+    let api_request = context.pop_api_request().await;
+    let effects = context.spawn_event(Event::ApiRequest(api_request));
+    match context.scheduler.pop().await {
+        (
+            validator::Event::Storage(storage::Event::Request(StorageRequest::GetChainspec {
+                version,
+                responder,
+            })),
+            _,
+        ) => {
+            assert_eq!(version, chainspec.genesis.protocol_version);
+            responder.respond(Some(chainspec.clone())).await;
+        }
+        (event, _) => panic!("unexpected event: {}", event),
+    }
+    context.complete_effects(effects).await;
+
+    let body = request.await.unwrap().unwrap();
+    let response: Option<GetChainspecResponse> = serde_json::from_slice(&body).unwrap();
+    let response = response.expect("should return chainspec");
+    assert_eq!(
+        response.protocol_version,
+        chainspec.genesis.protocol_version.to_string()
+    );
+    assert_eq!(response, GetChainspecResponse::from(&chainspec));
+}
+
+/// Requests the metrics in the given format via the API server, as if `registry` held the node's
+/// metrics.
+async fn get_metrics(
+    context: &mut TestContext,
+    registry: &Registry,
+    format: MetricsFormat,
+) -> Option<String> {
+    let request = tokio::spawn(context.effect_builder.make_request(
+        move |responder| ApiRequest::GetMetrics { format, responder },
+        QueueKind::Api,
+    ));
+
+    let api_request = context.pop_api_request().await;
+    let effects = context.spawn_event(Event::ApiRequest(api_request));
+    match context.scheduler.pop().await {
+        (
+            validator::Event::MetricsRequest(MetricsRequest::RenderNodeMetricsText { responder }),
+            _,
+        ) => {
+            let mut buf = Vec::new();
+            TextEncoder::new()
+                .encode(&registry.gather(), &mut buf)
+                .unwrap();
+            responder
+                .respond(Some(String::from_utf8(buf).unwrap()))
+                .await;
+        }
+        (event, _) => panic!("unexpected event: {}", event),
+    }
+    context.complete_effects(effects).await;
+
+    request.await.unwrap()
+}
+
+#[tokio::test]
+async fn should_return_metrics_in_requested_format() {
+    let mut context = TestContext::new();
+    let registry = Registry::new();
+    let counter = IntCounter::new("test_counter", "a counter for testing").unwrap();
+    registry.register(Box::new(counter.clone())).unwrap();
+    counter.inc_by(3);
+
+    let text = get_metrics(&mut context, &registry, MetricsFormat::Prometheus)
+        .await
+        .expect("should return metrics");
+    assert!(text.contains("test_counter 3"));
+
+    let json = get_metrics(&mut context, &registry, MetricsFormat::Json)
+        .await
+        .expect("should return metrics");
+    let samples: Vec<MetricSample> = serde_json::from_str(&json).expect("should be valid JSON");
+    assert_eq!(
+        samples,
+        vec![MetricSample {
+            name: "test_counter".to_string(),
+            value: 3.0,
+        }]
+    );
+}
+
+#[tokio::test]
+async fn should_return_hash_of_submitted_deploy() {
+    let mut context = TestContext::new();
+    let deploy = Deploy::random(&mut context.rng);
+
+    let submitted = deploy.clone();
+    let request = tokio::spawn(context.effect_builder.make_request(
+        move |responder| ApiRequest::SubmitDeploy {
+            deploy: Box::new(submitted),
+            responder,
+        },
+        QueueKind::Api,
+    ));
+
+    let api_request = context.pop_api_request().await;
+    let effects = context.spawn_event(Event::ApiRequest(api_request));
+    match context.scheduler.pop().await {
+        (
+            validator::Event::ApiServerAnnouncement(ApiServerAnnouncement::DeployReceived {
+                deploy: announced,
+            }),
+            _,
+        ) => assert_eq!(*announced, deploy),
+        (event, _) => panic!("unexpected event: {}", event),
+    }
+    context.complete_effects(effects).await;
+
+    assert_eq!(request.await.unwrap(), *deploy.id());
+}
+
+/// Requests the next scheduled upgrade via the API server, as if `chainspec` were the active
+/// chainspec held in storage.
+async fn get_next_upgrade(context: &mut TestContext, chainspec: &Chainspec) -> Option<NextUpgrade> {
+    context.api_server.protocol_version = chainspec.genesis.protocol_version.clone();
+    let request = tokio::spawn(context.effect_builder.make_request(
+        |responder| ApiRequest::GetNextUpgrade {
+            format: ResponseFormat::Json,
+            responder,
+        },
+        QueueKind::Api,
+    ));
+
+    let api_request = context.pop_api_request().await;
+    let effects = context.spawn_event(Event::ApiRequest(api_request));
+    match context.scheduler.pop().await {
+        (
+            validator::Event::Storage(storage::Event::Request(StorageRequest::GetChainspec {
+                version,
+                responder,
+            })),
+            _,
+        ) => {
+            assert_eq!(version, chainspec.genesis.protocol_version);
+            responder.respond(Some(chainspec.clone())).await;
+        }
+        (event, _) => panic!("unexpected event: {}", event),
+    }
+    context.complete_effects(effects).await;
+
+    let body = request.await.unwrap().unwrap();
+    serde_json::from_slice(&body).unwrap()
+}
+
+#[tokio::test]
+async fn should_return_next_upgrade_if_scheduled() {
+    let mut context = TestContext::new();
+    let mut chainspec = Chainspec::random(&mut context.rng);
+
+    let upgrade = get_next_upgrade(&mut context, &chainspec).await;
+    assert_eq!(
+        upgrade,
+        chainspec.next_upgrade(&chainspec.genesis.protocol_version)
+    );
+    assert!(upgrade.is_some());
+
+    chainspec.upgrades.clear();
+    assert!(get_next_upgrade(&mut context, &chainspec).await.is_none());
+}
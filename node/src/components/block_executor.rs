@@ -1,37 +1,40 @@
 //! Block executor component.
 
 use std::{
-    collections::{HashMap, VecDeque},
-    fmt::{Debug, Display},
+    collections::{BTreeMap, HashMap, VecDeque},
+    fmt::{self, Debug, Display, Formatter},
+    iter,
 };
 
 use derive_more::From;
 use itertools::Itertools;
 use rand::{CryptoRng, Rng};
 use smallvec::SmallVec;
+use thiserror::Error;
 use tracing::{debug, error, trace};
 
 use casper_execution_engine::{
     core::engine_state::{
-        self,
         deploy_item::DeployItem,
         execute_request::ExecuteRequest,
         execution_result::{ExecutionResult, ExecutionResults},
-        RootNotFound,
     },
     storage::global_state::CommitResult,
 };
 use casper_types::ProtocolVersion;
 
 use crate::{
-    components::{storage::Storage, Component},
-    crypto::hash::Digest,
+    components::{contract_runtime::ExecuteError, storage::Storage, Component},
+    crypto::{asymmetric_key::PublicKey, hash::Digest},
     effect::{
         announcements::BlockExecutorAnnouncement,
         requests::{BlockExecutorRequest, ContractRuntimeRequest, StorageRequest},
         EffectBuilder, EffectExt, Effects,
     },
-    types::{Block, BlockHash, Deploy, FinalizedBlock},
+    types::{
+        Block, BlockExecutionResults, BlockHash, Deploy, DeployHash, DeployHeader,
+        ExecutionResult as DeployExecutionResult, FinalizedBlock, SystemTransaction,
+    },
 };
 
 /// A helper trait whose bounds represent the requirements for a reactor event that `BlockExecutor`
@@ -72,17 +75,84 @@ pub enum Event {
         /// State of this request.
         state: State,
         /// Result of deploy execution.
-        result: Result<ExecutionResults, RootNotFound>,
+        result: Result<ExecutionResults, ExecuteError>,
     },
     /// The result of committing a single set of transforms after executing a single deploy.
     CommitExecutionEffects {
         /// State of this request.
         state: State,
-        /// Commit result for execution request.
-        commit_result: Result<CommitResult, engine_state::Error>,
+        /// Commit result for execution request: the post-state hash if the commit succeeded.
+        commit_result: Result<Digest, CommitError>,
     },
 }
 
+/// Error committing the execution effects of a single deploy.
+#[derive(Debug, Error)]
+pub enum CommitError {
+    /// The contract runtime failed to commit the execution effects.
+    #[error(transparent)]
+    Execute(#[from] ExecuteError),
+    /// The pre-state hash the execution effects were committed on top of is not in the global
+    /// state, e.g. because it was pruned.
+    #[error("root not found: {0}")]
+    RootNotFound(Digest),
+    /// Committing the execution effects did not succeed.
+    #[error("unsuccessful commit: {0}")]
+    Unsuccessful(CommitResult),
+}
+
+/// A summary of the step run at the end of an era, i.e. of the system transactions of its switch
+/// block.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct StepEffect {
+    /// The validators slashed for equivocating.
+    pub slashed: Vec<PublicKey>,
+    /// The block rewards distributed, in trillionths (10^-12) of the total reward for one block.
+    pub rewards: BTreeMap<PublicKey, u64>,
+}
+
+impl StepEffect {
+    /// Summarizes the system transactions of the given block, adding up the rewards of each
+    /// validator.
+    pub(crate) fn new(finalized_block: &FinalizedBlock) -> Self {
+        let mut step_effect = StepEffect {
+            slashed: finalized_block.equivocators().copied().collect(),
+            rewards: BTreeMap::new(),
+        };
+        for system_transaction in finalized_block.system_transactions() {
+            if let SystemTransaction::Rewards(rewards) = system_transaction {
+                for (public_key, amount) in rewards {
+                    *step_effect.rewards.entry(*public_key).or_default() += amount;
+                }
+            }
+        }
+        step_effect
+    }
+}
+
+impl Display for StepEffect {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            formatter,
+            "{} validators slashed, {} validators rewarded",
+            self.slashed.len(),
+            self.rewards.len()
+        )
+    }
+}
+
+/// Extracts the post-state hash from the result of committing execution effects.
+fn post_state_hash(
+    pre_state_hash: Digest,
+    commit_result: Result<CommitResult, ExecuteError>,
+) -> Result<Digest, CommitError> {
+    match commit_result? {
+        CommitResult::Success { state_root } => Ok(state_root.into()),
+        CommitResult::RootNotFound => Err(CommitError::RootNotFound(pre_state_hash)),
+        commit_result => Err(CommitError::Unsuccessful(commit_result)),
+    }
+}
+
 impl Display for Event {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -108,35 +178,36 @@ impl Display for Event {
             ),
             Event::DeployExecutionResult {
                 state,
-                result: Err(_),
+                result: Err(error),
             } => write!(
                 f,
                 "deploys execution result for finalized block with height {} with \
-                pre-state hash {}: root not found",
+                pre-state hash {}: {}",
                 state.finalized_block.height(),
-                state.pre_state_hash
+                state.pre_state_hash,
+                error
             ),
             Event::CommitExecutionEffects {
                 state,
-                commit_result: Ok(CommitResult::Success { state_root, .. }),
+                commit_result: Ok(post_state_hash),
             } => write!(
                 f,
                 "commit execution effects for finalized block with height {} with \
                 pre-state hash {}: success with post-state hash {}",
                 state.finalized_block.height(),
                 state.pre_state_hash,
-                state_root,
+                post_state_hash,
             ),
             Event::CommitExecutionEffects {
                 state,
-                commit_result,
+                commit_result: Err(error),
             } => write!(
                 f,
                 "commit execution effects for finalized block with height {} with \
-                pre-state hash {}: failed {:?}",
+                pre-state hash {}: failed {}",
                 state.finalized_block.height(),
                 state.pre_state_hash,
-                commit_result,
+                error,
             ),
         }
     }
@@ -148,6 +219,10 @@ pub struct State {
     finalized_block: FinalizedBlock,
     /// Deploys which have still to be executed.
     remaining_deploys: VecDeque<Deploy>,
+    /// The hash and header of the deploy currently being executed.
+    executing_deploy: Option<(DeployHash, DeployHeader)>,
+    /// The results of the deploys executed so far.
+    execution_results: BlockExecutionResults,
     /// Current pre-state hash of global storage.  Is initialized with the parent block's
     /// post-state hash, and is updated after each commit.
     pre_state_hash: Digest,
@@ -221,8 +296,33 @@ impl BlockExecutor {
                 // The state hash of the last execute-commit cycle is used as the block's post state
                 // hash.
                 let next_height = state.finalized_block.height() + 1;
+                let opt_step = if state.finalized_block.switch_block() {
+                    let era_id = state.finalized_block.era_id();
+                    let step_effect = StepEffect::new(&state.finalized_block);
+                    Some((era_id, step_effect))
+                } else {
+                    None
+                };
                 let block = self.create_block(state.finalized_block, state.pre_state_hash);
-                let mut effects = effect_builder.announce_linear_chain_block(block).ignore();
+                let execution_results = state.execution_results;
+                // Store the execution results before announcing the block, so that they can be
+                // looked up as soon as the block is known. The step is only announced after the
+                // switch block it belongs to.
+                let mut effects = async move {
+                    effect_builder
+                        .put_execution_results_to_storage::<Storage>(
+                            *block.hash(),
+                            execution_results,
+                        )
+                        .await;
+                    effect_builder.announce_linear_chain_block(block).await;
+                    if let Some((era_id, step_effect)) = opt_step {
+                        effect_builder
+                            .announce_step_executed(era_id, step_effect)
+                            .await
+                    }
+                }
+                .ignore();
                 // If the child is already finalized, start execution.
                 if let Some((finalized_block, deploys)) = self.exec_queue.remove(&next_height) {
                     effects.extend(self.handle_get_deploys_result(
@@ -234,14 +334,14 @@ impl BlockExecutor {
                 return effects;
             }
         };
-        let deploy_item = DeployItem::from(next_deploy);
-
-        let execute_request = ExecuteRequest::new(
-            state.pre_state_hash.into(),
-            state.finalized_block.timestamp().millis(),
-            vec![Ok(deploy_item)],
-            ProtocolVersion::V1_0_0,
-        );
+        state.executing_deploy = Some((*next_deploy.id(), next_deploy.header().clone()));
+        let execute_request = build_execute_requests(
+            &state.finalized_block,
+            iter::once(next_deploy),
+            state.pre_state_hash,
+        )
+        .pop()
+        .expect("should create one execute request per deploy");
 
         effect_builder
             .request_execute(execute_request)
@@ -258,6 +358,8 @@ impl BlockExecutor {
             let state = State {
                 finalized_block,
                 remaining_deploys: deploys,
+                executing_deploy: None,
+                execution_results: BlockExecutionResults::new(),
                 pre_state_hash,
             };
             self.execute_next_deploy_or_create_block(effect_builder, state)
@@ -273,14 +375,23 @@ impl BlockExecutor {
     fn commit_execution_effects<REv: ReactorEventT>(
         &mut self,
         effect_builder: EffectBuilder<REv>,
-        state: State,
+        mut state: State,
         execution_results: ExecutionResults,
     ) -> Effects<Event> {
-        let execution_effect = match execution_results
+        let execution_result = execution_results
             .into_iter()
             .exactly_one()
-            .expect("should only be one exec result")
-        {
+            .expect("should only be one exec result");
+        if let Some((deploy_hash, deploy_header)) = state.executing_deploy.take() {
+            let _ = state.execution_results.insert(
+                deploy_hash,
+                (
+                    deploy_header,
+                    DeployExecutionResult::from(&execution_result),
+                ),
+            );
+        }
+        let execution_effect = match execution_result {
             ExecutionResult::Success { effect, cost } => {
                 debug!(?effect, %cost, "execution succeeded");
                 effect
@@ -294,11 +405,12 @@ impl BlockExecutor {
                 effect
             }
         };
+        let pre_state_hash = state.pre_state_hash;
         effect_builder
-            .request_commit(state.pre_state_hash, execution_effect.transforms)
-            .event(|commit_result| Event::CommitExecutionEffects {
+            .request_commit(pre_state_hash, execution_effect.transforms)
+            .event(move |commit_result| Event::CommitExecutionEffects {
                 state,
-                commit_result,
+                commit_result: post_state_hash(pre_state_hash, commit_result),
             })
     }
 
@@ -337,6 +449,32 @@ impl BlockExecutor {
     }
 }
 
+/// Creates one `ExecuteRequest` for each of the given deploys of `finalized_block`, to be executed
+/// on top of the global state identified by `state_root`.
+///
+/// The block time of each request is the finalized block's timestamp.
+fn build_execute_requests<I>(
+    finalized_block: &FinalizedBlock,
+    deploys: I,
+    state_root: Digest,
+) -> Vec<ExecuteRequest>
+where
+    I: IntoIterator<Item = Deploy>,
+{
+    let block_time = finalized_block.timestamp().millis();
+    deploys
+        .into_iter()
+        .map(|deploy| {
+            ExecuteRequest::new(
+                state_root.into(),
+                block_time,
+                vec![Ok(DeployItem::from(deploy))],
+                ProtocolVersion::V1_0_0,
+            )
+        })
+        .collect()
+}
+
 impl<REv: ReactorEventT, R: Rng + CryptoRng + ?Sized> Component<REv, R> for BlockExecutor {
     type Event = Event;
 
@@ -362,9 +500,24 @@ impl<REv: ReactorEventT, R: Rng + CryptoRng + ?Sized> Component<REv, R> for Bloc
 
             Event::DeployExecutionResult { state, result } => {
                 trace!(?state, ?result, "deploy execution result");
-                // As for now a given state is expected to exist.
-                let execution_results = result.unwrap();
-                self.commit_execution_effects(effect_builder, state, execution_results)
+                match result {
+                    Ok(execution_results) => {
+                        self.commit_execution_effects(effect_builder, state, execution_results)
+                    }
+                    Err(ExecuteError::Cancelled) => {
+                        debug!(?state, "deploy execution cancelled due to shutdown");
+                        Effects::new()
+                    }
+                    Err(error) => {
+                        // We can't skip a deploy without diverging from the other validators,
+                        // so the whole block is abandoned. Its parent's post-state hash is
+                        // unchanged, so the block can be executed again from scratch.
+                        error!(%error, ?state, "deploy execution failed");
+                        effect_builder
+                            .announce_block_execution_failed(state.finalized_block, error)
+                            .ignore()
+                    }
+                }
             }
 
             Event::CommitExecutionEffects {
@@ -373,24 +526,157 @@ impl<REv: ReactorEventT, R: Rng + CryptoRng + ?Sized> Component<REv, R> for Bloc
             } => {
                 trace!(?state, ?commit_result, "commit result");
                 match commit_result {
-                    Ok(CommitResult::Success {
-                        state_root: post_state_hash,
-                    }) => {
+                    Ok(post_state_hash) => {
                         debug!(?post_state_hash, "commit succeeded");
-                        state.pre_state_hash = post_state_hash.into();
+                        state.pre_state_hash = post_state_hash;
                         self.execute_next_deploy_or_create_block(effect_builder, state)
                     }
-                    _ => {
+                    Err(CommitError::Execute(ExecuteError::Cancelled)) => {
+                        debug!(?state, "commit cancelled due to shutdown");
+                        Effects::new()
+                    }
+                    Err(CommitError::Execute(error)) if error.is_transient() => {
+                        // The block is abandoned, as if its execution had failed. The parent's
+                        // post-state hash is unchanged, so the block can be executed again.
+                        error!(%error, ?state, "commit failed");
+                        effect_builder
+                            .announce_block_execution_failed(state.finalized_block, error)
+                            .ignore()
+                    }
+                    Err(error) => {
                         // When commit fails we panic as we'll not be able to execute the next
                         // block.
                         error!(
-                            ?commit_result,
+                            %error,
+                            ?state,
                             "commit failed - internal contract runtime error"
                         );
-                        panic!("unable to commit");
+                        panic!("unable to commit: {}", error);
                     }
                 }
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use casper_execution_engine::{core::engine_state, shared::newtypes::Blake2bHash};
+    use casper_types::{account::AccountHash, Key};
+
+    use super::*;
+    use crate::{
+        components::consensus::EraId,
+        crypto::asymmetric_key::PublicKey,
+        testing::TestRng,
+        types::{ProtoBlock, Timestamp},
+    };
+
+    #[test]
+    fn should_build_one_execute_request_per_deploy() {
+        let mut rng = TestRng::new();
+
+        let deploys = vec![Deploy::random(&mut rng), Deploy::random(&mut rng)];
+        let deploy_hashes = deploys.iter().map(|deploy| *deploy.id()).collect();
+        let timestamp = Timestamp::now();
+        let finalized_block = FinalizedBlock::new(
+            ProtoBlock::new(deploy_hashes, true),
+            timestamp,
+            vec![],
+            false,
+            EraId(0),
+            0,
+            PublicKey::random(&mut rng),
+        );
+        let state_root = Digest::random(&mut rng);
+
+        let execute_requests =
+            build_execute_requests(&finalized_block, deploys.clone(), state_root);
+
+        assert_eq!(execute_requests.len(), 2);
+        for (execute_request, deploy) in execute_requests.iter().zip(deploys) {
+            assert_eq!(
+                execute_request.parent_state_hash,
+                Blake2bHash::from(state_root)
+            );
+            assert_eq!(execute_request.block_time, timestamp.millis());
+            assert_eq!(execute_request.protocol_version, ProtocolVersion::V1_0_0);
+            match execute_request.deploys.as_slice() {
+                [Ok(deploy_item)] => assert_eq!(*deploy_item, DeployItem::from(deploy)),
+                _ => panic!("expected exactly one deploy item"),
+            }
+        }
+    }
+
+    #[test]
+    fn should_report_structured_commit_errors() {
+        let mut rng = TestRng::new();
+        let pre_state_hash = Digest::random(&mut rng);
+        let state_root = Digest::random(&mut rng);
+
+        let success = post_state_hash(
+            pre_state_hash,
+            Ok(CommitResult::Success {
+                state_root: state_root.into(),
+            }),
+        );
+        assert_eq!(success.unwrap(), state_root);
+
+        match post_state_hash(pre_state_hash, Ok(CommitResult::RootNotFound)) {
+            Err(CommitError::RootNotFound(root)) => assert_eq!(root, pre_state_hash),
+            result => panic!("unexpected result: {:?}", result),
+        }
+
+        let key = Key::Account(AccountHash::new([1; 32]));
+        match post_state_hash(pre_state_hash, Ok(CommitResult::KeyNotFound(key))) {
+            Err(CommitError::Unsuccessful(CommitResult::KeyNotFound(missing))) => {
+                assert_eq!(missing, key)
+            }
+            result => panic!("unexpected result: {:?}", result),
+        }
+
+        match post_state_hash(
+            pre_state_hash,
+            Err(ExecuteError::Engine(
+                engine_state::Error::InsufficientPayment,
+            )),
+        ) {
+            Err(CommitError::Execute(ExecuteError::Engine(
+                engine_state::Error::InsufficientPayment,
+            ))) => (),
+            result => panic!("unexpected result: {:?}", result),
+        }
+    }
+
+    #[test]
+    fn should_summarize_slashings_and_add_up_rewards_of_step() {
+        let mut rng = TestRng::new();
+        let alice = PublicKey::random(&mut rng);
+        let bob = PublicKey::random(&mut rng);
+
+        let rewards = |amounts: &[(PublicKey, u64)]| {
+            SystemTransaction::Rewards(amounts.iter().cloned().collect())
+        };
+        let system_transactions = vec![
+            SystemTransaction::Slash(bob),
+            rewards(&[(alice, 10), (bob, 5)]),
+            rewards(&[(alice, 20)]),
+        ];
+
+        let finalized_block = FinalizedBlock::new_switch_block(
+            ProtoBlock::new(vec![], true),
+            Timestamp::now(),
+            system_transactions,
+            EraId(1),
+            11,
+            alice,
+        );
+
+        let step_effect = StepEffect::new(&finalized_block);
+        assert_eq!(step_effect.slashed, vec![bob]);
+        assert_eq!(
+            step_effect.rewards,
+            vec![(alice, 30), (bob, 5)].into_iter().collect()
+        );
+    }
+}
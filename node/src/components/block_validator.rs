@@ -17,6 +17,7 @@ use std::{
 use derive_more::{Display, From};
 use rand::{CryptoRng, Rng};
 use smallvec::{smallvec, SmallVec};
+use tracing::info;
 
 use crate::{
     components::Component,
@@ -102,6 +103,11 @@ where
                 sender,
                 responder,
             }) => {
+                if let Some(deploy_hash) = block.duplicate_deploy() {
+                    // Executing the block would apply the deploy twice.
+                    info!(%deploy_hash, "block contains a deploy more than once");
+                    return responder.respond((false, block)).ignore();
+                }
                 if block.deploys().is_empty() {
                     // If there are no deploys, return early.
                     let mut effects = Effects::new();
@@ -208,3 +214,43 @@ where
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        crypto::hash::Digest,
+        reactor::{validator, EventQueueHandle, QueueKind, Scheduler},
+        small_network::NodeId,
+        testing::TestRng,
+        types::ProtoBlock,
+        utils,
+    };
+
+    #[tokio::test]
+    async fn should_reject_duplicate_deploys() {
+        let mut rng = TestRng::new();
+        let scheduler = utils::leak(Scheduler::<validator::Event>::new(QueueKind::weights()));
+        let effect_builder = EffectBuilder::new(EventQueueHandle::new(scheduler));
+        let mut block_validator = BlockValidator::<ProtoBlock, NodeId>::new();
+
+        let deploy_hash = DeployHash::new(Digest::random(&mut rng));
+        let proto_block = ProtoBlock::new(vec![deploy_hash, deploy_hash], false);
+        let sender = NodeId::from([1; 64]);
+        let validation =
+            tokio::spawn(effect_builder.validate_proto_block(sender, proto_block.clone()));
+        let request = match scheduler.pop().await {
+            (validator::Event::ProtoBlockValidatorRequest(request), _) => request,
+            (event, _) => panic!("unexpected event: {}", event),
+        };
+
+        // The block is rejected without fetching any deploys.
+        let effects =
+            block_validator.handle_event(effect_builder, &mut rng, Event::Request(request));
+        for effect in effects {
+            assert!(effect.await.is_empty());
+        }
+        assert_eq!(validation.await.unwrap(), (false, proto_block));
+        assert_eq!(scheduler.item_count(), 0);
+    }
+}
@@ -21,14 +21,15 @@ use tracing::{debug, error, info, trace};
 use casper_execution_engine::core::engine_state::{self, genesis::GenesisResult};
 
 use crate::{
-    components::{storage::Storage, Component},
+    components::{consensus::EraId, storage::Storage, Component},
     crypto::hash::Digest,
     effect::{
+        announcements::ChainspecLoaderAnnouncement,
         requests::{ContractRuntimeRequest, StorageRequest},
         EffectBuilder, EffectExt, Effects,
     },
 };
-pub use chainspec::Chainspec;
+pub use chainspec::{Chainspec, NextUpgrade};
 pub(crate) use chainspec::{DeployConfig, HighwayConfig};
 pub use error::Error;
 
@@ -39,6 +40,8 @@ pub enum Event {
     PutToStorage { version: Version },
     /// The result of contract runtime running the genesis process.
     CommitGenesisResult(Result<GenesisResult, engine_state::Error>),
+    /// The node has entered a new era.
+    NewEra(EraId),
 }
 
 impl Display for Event {
@@ -53,6 +56,7 @@ impl Display for Event {
                 }
                 Err(error) => write!(formatter, "failed to commit genesis: {}", error),
             },
+            Event::NewEra(era_id) => write!(formatter, "entered era {}", era_id.0),
         }
     }
 }
@@ -60,6 +64,8 @@ impl Display for Event {
 #[derive(Debug)]
 pub(crate) struct ChainspecLoader {
     chainspec: Chainspec,
+    // The protocol version the node is running.
+    protocol_version: Version,
     // If `Some`, we're finished.  The value of the bool indicates success (true) or not.
     completed_successfully: Option<bool>,
     // If `Some` then genesis process returned a valid post state hash.
@@ -80,6 +86,7 @@ impl ChainspecLoader {
             .event(|_| Event::PutToStorage { version });
         Ok((
             ChainspecLoader {
+                protocol_version: chainspec.genesis.protocol_version.clone(),
                 chainspec,
                 completed_successfully: None,
                 genesis_post_state_hash: None,
@@ -96,7 +103,7 @@ impl ChainspecLoader {
         self.completed_successfully.unwrap_or_default()
     }
 
-    pub(crate) fn genesis_post_state_hash(self) -> Option<Digest> {
+    pub(crate) fn genesis_post_state_hash(&self) -> Option<Digest> {
         self.genesis_post_state_hash
     }
 
@@ -107,7 +114,11 @@ impl ChainspecLoader {
 
 impl<REv, R> Component<REv, R> for ChainspecLoader
 where
-    REv: From<Event> + From<StorageRequest<Storage>> + From<ContractRuntimeRequest> + Send,
+    REv: From<Event>
+        + From<StorageRequest<Storage>>
+        + From<ContractRuntimeRequest>
+        + From<ChainspecLoaderAnnouncement>
+        + Send,
     R: Rng + CryptoRng + ?Sized,
 {
     type Event = Event;
@@ -152,6 +163,67 @@ where
                 }
                 Effects::new()
             }
+            Event::NewEra(current_era) => {
+                match self
+                    .chainspec
+                    .overdue_upgrade(&self.protocol_version, current_era)
+                {
+                    Some(upgrade) => effect_builder
+                        .announce_upgrade_overdue(current_era, upgrade)
+                        .ignore(),
+                    None => Effects::new(),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        reactor::{validator, EventQueueHandle, QueueKind, Scheduler},
+        testing::TestRng,
+        utils,
+    };
+
+    #[tokio::test]
+    async fn should_announce_overdue_upgrade_in_new_era() {
+        let mut rng = TestRng::new();
+        let scheduler = utils::leak(Scheduler::<validator::Event>::new(QueueKind::weights()));
+        let effect_builder = EffectBuilder::new(EventQueueHandle::new(scheduler));
+        let chainspec = Chainspec::from_resources("test/valid/chainspec.toml");
+        let mut chainspec_loader = ChainspecLoader {
+            protocol_version: chainspec.genesis.protocol_version.clone(),
+            chainspec,
+            completed_successfully: Some(true),
+            genesis_post_state_hash: None,
+        };
+
+        // The first upgrade activates in era 23.
+        let effects =
+            chainspec_loader.handle_event(effect_builder, &mut rng, Event::NewEra(EraId(22)));
+        assert!(effects.is_empty());
+
+        let effects =
+            chainspec_loader.handle_event(effect_builder, &mut rng, Event::NewEra(EraId(24)));
+        for effect in effects {
+            assert!(effect.await.is_empty());
+        }
+        match scheduler.pop().await {
+            (
+                validator::Event::ChainspecLoaderAnnouncement(
+                    ChainspecLoaderAnnouncement::UpgradeOverdue {
+                        current_era,
+                        upgrade,
+                    },
+                ),
+                _,
+            ) => {
+                assert_eq!(current_era, EraId(24));
+                assert_eq!(upgrade.activation_era(), EraId(23));
+            }
+            (event, _) => panic!("unexpected event: {}", event),
         }
     }
 }
@@ -1,5 +1,5 @@
 use std::{
-    fmt::{self, Debug, Formatter},
+    fmt::{self, Debug, Display, Formatter},
     path::Path,
 };
 
@@ -20,6 +20,7 @@ use super::{config, error::GenesisLoadError, Error};
 #[cfg(test)]
 use crate::testing::TestRng;
 use crate::{
+    components::consensus::EraId,
     crypto::asymmetric_key::PublicKey,
     types::{TimeDiff, Timestamp},
     utils::Loadable,
@@ -293,6 +294,44 @@ impl UpgradePoint {
     }
 }
 
+/// An upgrade the node is expected to apply once the era given by its activation point is reached.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NextUpgrade {
+    activation_point: ActivationPoint,
+    protocol_version: Version,
+}
+
+impl NextUpgrade {
+    /// Returns the ID of the era from which on the upgrade is to be applied.
+    pub fn activation_era(&self) -> EraId {
+        EraId(self.activation_point.rank)
+    }
+
+    /// Returns the protocol version to upgrade to.
+    pub fn protocol_version(&self) -> &Version {
+        &self.protocol_version
+    }
+}
+
+impl From<&UpgradePoint> for NextUpgrade {
+    fn from(upgrade_point: &UpgradePoint) -> Self {
+        NextUpgrade {
+            activation_point: upgrade_point.activation_point,
+            protocol_version: upgrade_point.protocol_version.clone(),
+        }
+    }
+}
+
+impl Display for NextUpgrade {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            formatter,
+            "upgrade to {} at era {}",
+            self.protocol_version, self.activation_point.rank
+        )
+    }
+}
+
 /// A collection of configuration settings describing the state of the system at genesis and
 /// upgrades to basic system functionality (including system contracts and gas costs) occurring
 /// after genesis.
@@ -302,6 +341,35 @@ pub struct Chainspec {
     pub(crate) upgrades: Vec<UpgradePoint>,
 }
 
+impl Chainspec {
+    /// Returns the earliest upgrade to a newer protocol version than `current_version`, the one
+    /// this node runs, if any is scheduled.
+    pub(crate) fn next_upgrade(&self, current_version: &Version) -> Option<NextUpgrade> {
+        self.upgrades
+            .iter()
+            .filter(|upgrade_point| upgrade_point.protocol_version > *current_version)
+            .min_by_key(|upgrade_point| upgrade_point.activation_point.rank)
+            .map(NextUpgrade::from)
+    }
+
+    /// Returns the earliest upgrade to a newer protocol version than `current_version`, the one
+    /// this node runs, whose activation point has been reached by `current_era`, if any.
+    pub(crate) fn overdue_upgrade(
+        &self,
+        current_version: &Version,
+        current_era: EraId,
+    ) -> Option<NextUpgrade> {
+        self.upgrades
+            .iter()
+            .filter(|upgrade_point| {
+                upgrade_point.protocol_version > *current_version
+                    && upgrade_point.activation_point.rank <= current_era.0
+            })
+            .min_by_key(|upgrade_point| upgrade_point.activation_point.rank)
+            .map(NextUpgrade::from)
+    }
+}
+
 impl Loadable for Chainspec {
     type Error = Error;
     fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, Self::Error> {
@@ -454,6 +522,44 @@ mod tests {
         check_spec(spec);
     }
 
+    #[test]
+    fn should_find_earliest_overdue_upgrade() {
+        let spec = Chainspec::from_resources("test/valid/chainspec.toml");
+        let genesis_version = spec.genesis.protocol_version.clone();
+
+        assert!(spec.overdue_upgrade(&genesis_version, EraId(22)).is_none());
+
+        let upgrade = spec.overdue_upgrade(&genesis_version, EraId(23)).unwrap();
+        assert_eq!(upgrade.activation_era(), EraId(23));
+        assert_eq!(*upgrade.protocol_version(), Version::from((0, 2, 0)));
+
+        let upgrade = spec.overdue_upgrade(&genesis_version, EraId(40)).unwrap();
+        assert_eq!(upgrade.activation_era(), EraId(23));
+
+        // Once the node runs the first upgrade, only the second one can be overdue.
+        let upgraded_version = Version::from((0, 2, 0));
+        assert!(spec.overdue_upgrade(&upgraded_version, EraId(38)).is_none());
+        let upgrade = spec.overdue_upgrade(&upgraded_version, EraId(40)).unwrap();
+        assert_eq!(upgrade.activation_era(), EraId(39));
+    }
+
+    #[test]
+    fn should_find_next_upgrade() {
+        let mut spec = Chainspec::from_resources("test/valid/chainspec.toml");
+        let genesis_version = spec.genesis.protocol_version.clone();
+
+        let upgrade = spec.next_upgrade(&genesis_version).unwrap();
+        assert_eq!(upgrade.activation_era(), EraId(23));
+        assert_eq!(*upgrade.protocol_version(), Version::from((0, 2, 0)));
+
+        let upgrade = spec.next_upgrade(&Version::from((0, 2, 0))).unwrap();
+        assert_eq!(upgrade.activation_era(), EraId(39));
+        assert!(spec.next_upgrade(&Version::from((0, 3, 0))).is_none());
+
+        spec.upgrades.clear();
+        assert!(spec.next_upgrade(&genesis_version).is_none());
+    }
+
     #[test]
     fn rmp_serde_roundtrip() {
         let mut rng = TestRng::new();
@@ -11,7 +11,7 @@ mod traits;
 use std::fmt::{self, Debug, Display, Formatter};
 
 use crate::{
-    components::{storage::Storage, Component},
+    components::{contract_runtime::ExecuteError, storage::Storage, Component},
     effect::{
         announcements::ConsensusAnnouncement,
         requests::{
@@ -21,10 +21,10 @@ use crate::{
         EffectBuilder, Effects,
     },
     protocol::Message,
-    types::{ProtoBlock, Timestamp},
+    types::{FinalizedBlock, ProtoBlock, Timestamp},
 };
 pub use config::Config;
-pub(crate) use consensus_protocol::BlockContext;
+pub(crate) use consensus_protocol::{BlockContext, LeaderCount};
 use derive_more::From;
 pub(crate) use era_supervisor::{EraId, EraSupervisor};
 use hex_fmt::HexFmt;
@@ -70,6 +70,11 @@ pub enum Event<I> {
         sender: I,
         proto_block: ProtoBlock,
     },
+    /// Executing a finalized block failed; it may be retried.
+    BlockExecutionFailed {
+        finalized_block: FinalizedBlock,
+        error: ExecuteError,
+    },
 }
 
 impl Display for ConsensusMessage {
@@ -134,6 +139,10 @@ impl<I: Debug> Display for Event<I> {
                 "A proto-block received from {:?} turned out to be invalid for era {:?}: {:?}",
                 sender, era_id, proto_block
             ),
+            Event::BlockExecutionFailed {
+                finalized_block,
+                error,
+            } => write!(f, "Execution of {} failed: {}", finalized_block, error),
         }
     }
 }
@@ -191,6 +200,25 @@ where
                 block_header,
                 responder,
             )) => handling_es.handle_linear_chain_block(*block_header, responder),
+            Event::ConsensusRequest(requests::ConsensusRequest::LeaderStats(era_id, responder)) => {
+                handling_es.handle_leader_stats(era_id, responder)
+            }
+            Event::ConsensusRequest(requests::ConsensusRequest::ValidatorStakes(
+                era_id,
+                responder,
+            )) => handling_es.handle_validator_stakes(era_id, responder),
+            Event::ConsensusRequest(requests::ConsensusRequest::ForceNextEra {
+                validators,
+                responder,
+            }) => handling_es.handle_force_next_era(validators, responder),
+            Event::ConsensusRequest(requests::ConsensusRequest::CurrentEra(responder)) => {
+                handling_es.handle_current_era(responder)
+            }
+            Event::ConsensusRequest(requests::ConsensusRequest::GetEvidence {
+                era_id,
+                validator,
+                responder,
+            }) => handling_es.handle_get_evidence(era_id, validator, responder),
             Event::AcceptProtoBlock {
                 era_id,
                 proto_block,
@@ -200,6 +228,10 @@ where
                 sender,
                 proto_block,
             } => handling_es.handle_invalid_proto_block(era_id, sender, proto_block),
+            Event::BlockExecutionFailed {
+                finalized_block,
+                error,
+            } => handling_es.handle_block_execution_failed(finalized_block, error),
         }
     }
 }
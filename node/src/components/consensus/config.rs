@@ -1,6 +1,6 @@
 use serde::{Deserialize, Serialize};
 
-use crate::{crypto::asymmetric_key::SecretKey, utils::External};
+use crate::{crypto::asymmetric_key::SecretKey, types::TimeDiff, utils::External};
 
 /// Consensus configuration.
 #[derive(Debug, Deserialize, Serialize, Default)]
@@ -9,4 +9,16 @@ use crate::{crypto::asymmetric_key::SecretKey, utils::External};
 pub struct Config {
     /// Path to secret key file.
     pub secret_key_path: External<SecretKey>,
+    /// Whether to accept requests to start the next era before the current one has ended.
+    ///
+    /// This is intended for testing and for recovering from a stalled era only, and must not be
+    /// enabled in normal operation.
+    #[serde(default)]
+    pub allow_forced_era_transitions: bool,
+    /// How long, in milliseconds, a proto block found valid is remembered, so that repeated
+    /// proposals of the same block aren't validated again.
+    ///
+    /// If zero, validated proto blocks are not cached.
+    #[serde(default)]
+    pub block_validation_cache_ttl_millis: TimeDiff,
 }
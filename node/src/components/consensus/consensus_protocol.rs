@@ -2,6 +2,7 @@ use std::{collections::BTreeMap, fmt::Debug};
 
 use anyhow::Error;
 use rand::{CryptoRng, Rng};
+use serde::Serialize;
 
 use crate::{components::consensus::traits::ConsensusValueT, types::Timestamp};
 
@@ -60,6 +61,17 @@ pub(crate) struct FinalizedBlock<C: ConsensusValueT, VID> {
     pub(crate) proposer: VID,
 }
 
+/// The number of rounds a validator was the leader of, compared to the number it was expected to
+/// lead according to its weight.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+pub struct LeaderCount {
+    /// The number of rounds the validator actually led.
+    pub(crate) actual: u64,
+    /// The expected number of rounds, i.e. the number of rounds times the validator's share of the
+    /// total weight.
+    pub(crate) expected: f64,
+}
+
 #[derive(Debug)]
 pub(crate) enum ConsensusProtocolResult<I, C: ConsensusValueT, VID> {
     CreatedGossipMessage(Vec<u8>),
@@ -118,4 +130,15 @@ pub(crate) trait ConsensusProtocol<I, C: ConsensusValueT, VID, R: Rng + CryptoRn
 
     /// Turns this instance into a passive observer, that does not create any new vertices.
     fn deactivate_validator(&mut self);
+
+    /// Returns how many of the rounds beginning between `start` (inclusive) and `end` (exclusive)
+    /// each validator was the leader of.
+    fn leader_counts(&self, start: Timestamp, end: Timestamp) -> Vec<(VID, LeaderCount)>;
+
+    /// Returns the serialized evidence against the given validator, if any is known.
+    fn evidence(&self, validator: &VID) -> Option<Vec<u8>>;
+
+    /// Returns the correct validators that haven't produced any vertex in the last `rounds` rounds
+    /// before `now`.
+    fn inactive_validators(&self, now: Timestamp, rounds: u64) -> Vec<VID>;
 }
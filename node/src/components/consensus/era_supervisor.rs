@@ -6,17 +6,22 @@
 //! Most importantly, it doesn't care about what messages it's forwarding.
 
 use std::{
-    collections::HashMap,
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
     fmt::{self, Debug, Formatter},
+    iter,
+    ops::{Add, Sub},
     rc::Rc,
+    time::Duration,
 };
 
 use anyhow::Error;
 use casper_types::U512;
+use linked_hash_map::LinkedHashMap;
 use num_traits::AsPrimitive;
+use prometheus::{IntCounter, IntGauge, Registry};
 use rand::{CryptoRng, Rng};
 use serde::{Deserialize, Serialize};
-use tracing::{error, info};
+use tracing::{error, info, warn};
 
 use casper_execution_engine::shared::motes::Motes;
 
@@ -26,20 +31,25 @@ use crate::{
         consensus::{
             consensus_protocol::{
                 BlockContext, ConsensusProtocol, ConsensusProtocolResult,
-                FinalizedBlock as CpFinalizedBlock,
+                FinalizedBlock as CpFinalizedBlock, LeaderCount,
             },
             highway_core::{highway::Params, validators::Validators},
             protocols::highway::{HighwayContext, HighwayProtocol, HighwaySecret},
             traits::NodeIdT,
             Config, ConsensusMessage, Event, ReactorEventT,
         },
+        contract_runtime::ExecuteError,
     },
     crypto::{
         asymmetric_key::{self, PublicKey, SecretKey, Signature},
         hash,
     },
     effect::{EffectBuilder, EffectExt, Effects, Responder},
-    types::{BlockHeader, FinalizedBlock, ProtoBlock, SystemTransaction, Timestamp},
+    fatal,
+    types::{
+        BlockHeader, BlockLike, FinalizedBlock, ProtoBlock, ProtoBlockHash, SystemTransaction,
+        TimeDiff, Timestamp,
+    },
     utils::WithDir,
 };
 
@@ -49,6 +59,14 @@ const BLOCK_REWARD: u64 = 1_000_000_000_000;
 /// The number of recent eras to retain. Eras older than this are dropped from memory.
 // TODO: This needs to be in sync with AUCTION_DELAY/booking_duration_millis. (Already duplicated!)
 const RETAIN_ERAS: u64 = 4;
+/// The number of times executing a finalized block may fail before the node gives up.
+const MAX_BLOCK_EXECUTION_ATTEMPTS: u32 = 3;
+/// The delay before executing a failed block again. It doubles with every further attempt.
+const BLOCK_EXECUTION_RETRY_DELAY: Duration = Duration::from_secs(1);
+/// The maximum number of valid proto block hashes cached at a time.
+const MAX_CACHED_VALID_PROTO_BLOCKS: usize = 1000;
+/// The number of rounds without a new vote after which a validator is reported as inactive.
+const INACTIVE_VALIDATOR_ROUNDS: u64 = 10;
 
 #[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct EraId(pub(crate) u64);
@@ -61,16 +79,113 @@ impl EraId {
         }
     }
 
-    fn successor(self) -> EraId {
-        EraId(self.0 + 1)
+    /// Returns the era following this one.
+    pub fn successor(self) -> EraId {
+        self + 1
+    }
+
+    /// Returns the era preceding this one, or `None` if this is era 0.
+    pub fn predecessor(self) -> Option<EraId> {
+        self.0.checked_sub(1).map(EraId)
+    }
+
+    /// Returns the era `n` eras after this one, or `None` on overflow.
+    pub fn checked_add(self, n: u64) -> Option<EraId> {
+        self.0.checked_add(n).map(EraId)
+    }
+
+    /// Returns the era `n` eras before this one, or era 0 if there are fewer than `n` eras before.
+    pub fn saturating_sub(self, n: u64) -> EraId {
+        EraId(self.0.saturating_sub(n))
+    }
+}
+
+impl Add<u64> for EraId {
+    type Output = EraId;
+
+    fn add(self, n: u64) -> EraId {
+        EraId(self.0 + n)
     }
 }
 
+impl Sub<u64> for EraId {
+    type Output = EraId;
+
+    fn sub(self, n: u64) -> EraId {
+        EraId(self.0 - n)
+    }
+}
+
+/// A consensus protocol instance, together with the results of its initialization.
+pub(crate) type ProtocolInstance<I, R> = (
+    Box<dyn ConsensusProtocol<I, ProtoBlock, PublicKey, R>>,
+    Vec<ConsensusProtocolResult<I, ProtoBlock, PublicKey>>,
+);
+
+/// A function creating the consensus protocol instance for a new era.
+pub(crate) type ProtocolFactory<I, R> = fn(
+    era_id: EraId,
+    timestamp: Timestamp,
+    validator_stakes: Vec<(PublicKey, Motes)>,
+    start_time: Timestamp,
+    highway_config: &HighwayConfig,
+    secret_signing_key: &Rc<SecretKey>,
+) -> ProtocolInstance<I, R>;
+
 pub(crate) struct Era<I, R: Rng + CryptoRng + ?Sized> {
     /// The consensus protocol instance.
     consensus: Box<dyn ConsensusProtocol<I, ProtoBlock, PublicKey, R>>,
     /// The height of this era's first block.
     start_height: u64,
+    /// The time at which this era started.
+    start_time: Timestamp,
+    /// The validators of this era, with their stakes.
+    validator_stakes: BTreeMap<PublicKey, Motes>,
+    /// The height of the latest block finalized in this era, if any.
+    #[cfg(test)]
+    highest_finalized_height: Option<u64>,
+}
+
+/// A summary of an active era, for diagnostics.
+#[cfg(test)]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct EraSummary {
+    /// The era's ID.
+    pub(crate) era_id: EraId,
+    /// The number of validators in the era.
+    pub(crate) validator_count: usize,
+    /// The height of the latest block finalized in the era, if any.
+    pub(crate) highest_finalized_height: Option<u64>,
+}
+
+/// Metrics for the era supervisor.
+#[derive(Debug)]
+pub(crate) struct EraSupervisorMetrics {
+    /// The number of repeated finalizations of a block which were not acted upon.
+    duplicate_finalizations_suppressed: IntCounter,
+    /// The number of validators in the current era which haven't voted recently.
+    inactive_validators: IntGauge,
+}
+
+impl EraSupervisorMetrics {
+    /// Creates and registers new era supervisor metrics.
+    fn new(registry: &Registry) -> Result<Self, prometheus::Error> {
+        let duplicate_finalizations_suppressed = IntCounter::new(
+            "consensus_duplicate_finalizations_suppressed",
+            "number of repeated finalizations of the same block which were suppressed",
+        )?;
+        registry.register(Box::new(duplicate_finalizations_suppressed.clone()))?;
+        let inactive_validators = IntGauge::new(
+            "consensus_inactive_validators",
+            "number of validators in the current era which haven't voted recently",
+        )?;
+        registry.register(Box::new(inactive_validators.clone()))?;
+
+        Ok(EraSupervisorMetrics {
+            duplicate_finalizations_suppressed,
+            inactive_validators,
+        })
+    }
 }
 
 pub(crate) struct EraSupervisor<I, R: Rng + CryptoRng + ?Sized> {
@@ -82,6 +197,30 @@ pub(crate) struct EraSupervisor<I, R: Rng + CryptoRng + ?Sized> {
     validator_stakes: Vec<(PublicKey, Motes)>,
     current_era: EraId,
     highway_config: HighwayConfig,
+    /// The consensus protocol factories, keyed by the first era in which they are used.
+    protocol_factories: BTreeMap<EraId, ProtocolFactory<I, R>>,
+    /// The height of the next block to be added to the linear chain.
+    next_block_height: u64,
+    /// Whether requests to start the next era before the current one has ended are accepted.
+    allow_forced_era_transitions: bool,
+    /// The heights and proto block hashes of the blocks which have been finalized, but not yet
+    /// added to the linear chain.
+    ///
+    /// Proto block hashes alone are not unique, e.g. all empty proto blocks with the same random
+    /// bit have the same hash.
+    finalized_blocks: HashSet<(u64, ProtoBlockHash)>,
+    /// How long a proto block is remembered as valid.
+    block_validation_cache_ttl: TimeDiff,
+    /// The maximum number of deploys in a proposed proto block.
+    max_block_deploys: usize,
+    /// The hashes of recently validated proto blocks, and when they were validated, oldest first.
+    ///
+    /// Only valid proto blocks are cached: a proposal can fail validation merely because its
+    /// sender withheld a deploy, and that must not get the same block rejected from honest peers.
+    validated_proto_blocks: LinkedHashMap<ProtoBlockHash, Timestamp>,
+    /// The number of failed execution attempts of finalized blocks, keyed by height.
+    failed_executions: HashMap<u64, u32>,
+    metrics: EraSupervisorMetrics,
 }
 
 impl<I, R: Rng + CryptoRng + ?Sized> Debug for EraSupervisor<I, R> {
@@ -103,9 +242,13 @@ where
         effect_builder: EffectBuilder<REv>,
         validator_stakes: Vec<(PublicKey, Motes)>,
         highway_config: &HighwayConfig,
+        max_block_deploys: usize,
+        registry: &Registry,
         rng: &mut R,
     ) -> Result<(Self, Effects<Event<I>>), Error> {
         let (root, config) = config.into_parts();
+        let allow_forced_era_transitions = config.allow_forced_era_transitions;
+        let block_validation_cache_ttl = config.block_validation_cache_ttl_millis;
         let secret_signing_key = Rc::new(config.secret_key_path.load(root)?);
         let public_signing_key = PublicKey::from(secret_signing_key.as_ref());
 
@@ -116,6 +259,16 @@ where
             current_era: EraId(0),
             validator_stakes: validator_stakes.clone(),
             highway_config: *highway_config,
+            protocol_factories: iter::once((EraId(0), highway_protocol as ProtocolFactory<I, R>))
+                .collect(),
+            next_block_height: 0,
+            allow_forced_era_transitions,
+            finalized_blocks: HashSet::new(),
+            block_validation_cache_ttl,
+            max_block_deploys,
+            validated_proto_blocks: LinkedHashMap::new(),
+            failed_executions: HashMap::new(),
+            metrics: EraSupervisorMetrics::new(registry)?,
         };
 
         let results = era_supervisor.new_era(
@@ -132,6 +285,49 @@ where
         Ok((era_supervisor, effects))
     }
 
+    /// Records the finalization of the block at the given absolute height.
+    ///
+    /// Returns `false` if the block has already been finalized, in which case the finalization
+    /// must not be acted upon again.
+    fn record_finalized_block(&mut self, height: u64, proto_block_hash: ProtoBlockHash) -> bool {
+        if height < self.next_block_height
+            || !self.finalized_blocks.insert((height, proto_block_hash))
+        {
+            self.metrics.duplicate_finalizations_suppressed.inc();
+            return false;
+        }
+        true
+    }
+
+    /// Returns whether the proto block with the given hash was found valid less than the cache's
+    /// time-to-live before `now`.
+    ///
+    /// Expired entries are dropped from the cache.
+    fn is_cached_valid(&mut self, proto_block_hash: &ProtoBlockHash, now: Timestamp) -> bool {
+        let ttl = self.block_validation_cache_ttl;
+        while let Some((_, validated_at)) = self.validated_proto_blocks.front() {
+            if now.saturating_sub(*validated_at) < ttl {
+                break;
+            }
+            let _ = self.validated_proto_blocks.pop_front();
+        }
+        self.validated_proto_blocks.contains_key(proto_block_hash)
+    }
+
+    /// Records that the proto block with the given hash is valid, evicting the oldest entry if the
+    /// cache is full.
+    fn cache_valid(&mut self, proto_block_hash: ProtoBlockHash, now: Timestamp) {
+        if self.block_validation_cache_ttl == TimeDiff::from(0) {
+            return;
+        }
+        // Re-insert to move the entry to the back, keeping the map ordered by validation time.
+        let _ = self.validated_proto_blocks.remove(&proto_block_hash);
+        let _ = self.validated_proto_blocks.insert(proto_block_hash, now);
+        if self.validated_proto_blocks.len() > MAX_CACHED_VALID_PROTO_BLOCKS {
+            let _ = self.validated_proto_blocks.pop_front();
+        }
+    }
+
     /// Returns a temporary container with this `EraSupervisor`, `EffectBuilder` and random number
     /// generator, for handling events.
     pub(super) fn handling_wrapper<'a, REv: ReactorEventT<I>>(
@@ -165,67 +361,131 @@ where
             !sum_stakes.value().is_zero(),
             "cannot start era with total weight 0"
         );
-        // For Highway, we need u64 weights. Scale down by  sum / u64::MAX,  rounded up.
-        // If we round up the divisor, the resulting sum is guaranteed to be  <= u64::MAX.
-        let scaling_factor = (sum_stakes.value() + U512::from(u64::MAX) - 1) / U512::from(u64::MAX);
-        let scale_stake = |(key, stake): (PublicKey, Motes)| {
-            (key, AsPrimitive::<u64>::as_(stake.value() / scaling_factor))
-        };
-        let validators: Validators<PublicKey> =
-            validator_stakes.into_iter().map(scale_stake).collect();
-
-        let instance_id = hash::hash(format!("Highway era {}", era_id.0));
-        let ftt = validators.total_weight()
-            * u64::from(self.highway_config.finality_threshold_percent)
-            / 100;
-        // The number of rounds after which a block reward is paid out.
-        // TODO: Make this configurable?
-        let reward_delay = 8;
-        let params = Params::new(
-            0, // TODO: get a proper seed.
-            BLOCK_REWARD,
-            BLOCK_REWARD / 5, // TODO: Make reduced block reward configurable?
-            reward_delay,
-            self.highway_config.minimum_round_exponent,
-            self.highway_config.minimum_era_height,
-            start_time + self.highway_config.era_duration,
-        );
 
-        // Activate the era if it is still ongoing based on its minimum duration, and if we are one
-        // of the validators.
-        let our_id = self.public_signing_key;
-        let min_end_time = start_time
-            + self
-                .highway_config
-                .era_duration
-                .max(params.min_round_len() * params.end_height());
-        let should_activate =
-            min_end_time >= timestamp && validators.iter().any(|v| *v.id() == our_id);
-
-        let mut highway =
-            HighwayProtocol::<I, HighwayContext>::new(instance_id, validators, params, ftt);
-
-        let results = if should_activate {
-            let secret = HighwaySecret::new(Rc::clone(&self.secret_signing_key), our_id);
-            highway.activate_validator(our_id, secret, timestamp)
-        } else {
-            Vec::new()
-        };
+        let stakes_by_validator = validator_stakes.iter().cloned().collect();
+        let protocol_factory = self.protocol_for_era(era_id);
+        let (consensus, results) = protocol_factory(
+            era_id,
+            timestamp,
+            validator_stakes,
+            start_time,
+            &self.highway_config,
+            &self.secret_signing_key,
+        );
 
         let era = Era {
-            consensus: Box::new(highway),
+            consensus,
             start_height,
+            start_time,
+            validator_stakes: stakes_by_validator,
+            #[cfg(test)]
+            highest_finalized_height: None,
         };
         let _ = self.active_eras.insert(era_id, era);
 
         // Remove the era that has become obsolete now.
         if era_id.0 > RETAIN_ERAS {
-            self.active_eras.remove(&EraId(era_id.0 - RETAIN_ERAS - 1));
+            self.active_eras.remove(&(era_id - RETAIN_ERAS - 1));
         }
 
         results
     }
 
+    /// Returns the number of rounds each validator led in the given era up to `now`, or `None` if
+    /// the era is not active.
+    fn leader_stats(&self, era_id: EraId, now: Timestamp) -> Option<Vec<(PublicKey, LeaderCount)>> {
+        self.active_eras
+            .get(&era_id)
+            .map(|era| era.consensus.leader_counts(era.start_time, now))
+    }
+
+    /// Returns the validators of the given era with their stakes, or `None` if the era is not
+    /// active.
+    fn validator_stakes(&self, era_id: EraId) -> Option<BTreeMap<PublicKey, Motes>> {
+        self.active_eras
+            .get(&era_id)
+            .map(|era| era.validator_stakes.clone())
+    }
+
+    /// Returns a summary of each active era, ordered by era ID.
+    #[cfg(test)]
+    pub(crate) fn era_summaries(&self) -> Vec<EraSummary> {
+        let mut summaries: Vec<_> = self
+            .active_eras
+            .iter()
+            .map(|(era_id, era)| EraSummary {
+                era_id: *era_id,
+                validator_count: era.validator_stakes.len(),
+                highest_finalized_height: era.highest_finalized_height,
+            })
+            .collect();
+        summaries.sort_by_key(|summary| summary.era_id);
+        summaries
+    }
+
+    /// Returns the serialized evidence against `validator` in the given era, or `None` if there is
+    /// none or the era is not active.
+    fn evidence(&self, era_id: EraId, validator: &PublicKey) -> Option<Vec<u8>> {
+        self.active_eras.get(&era_id)?.consensus.evidence(validator)
+    }
+
+    /// Deactivates the current era and starts its successor with the given validators, without
+    /// waiting for the current era to end.
+    ///
+    /// Returns `None` if forced era transitions are disabled or the validators' total stake is
+    /// zero.
+    fn force_next_era(
+        &mut self,
+        validators: BTreeMap<PublicKey, Motes>,
+        now: Timestamp,
+    ) -> Option<(
+        EraId,
+        Vec<ConsensusProtocolResult<I, ProtoBlock, PublicKey>>,
+    )> {
+        if !self.allow_forced_era_transitions {
+            warn!("ignoring request to force the next era: forced era transitions are disabled");
+            return None;
+        }
+        if validators.values().all(|stake| stake.value().is_zero()) {
+            warn!("ignoring request to force the next era: total stake of validators is zero");
+            return None;
+        }
+        self.current_era_mut().consensus.deactivate_validator();
+        let new_era_id = self.current_era.successor();
+        let validator_stakes: Vec<_> = validators.into_iter().collect();
+        self.validator_stakes = validator_stakes.clone();
+        let results = self.new_era(
+            new_era_id,
+            now,
+            validator_stakes,
+            now,
+            self.next_block_height,
+        );
+        Some((new_era_id, results))
+    }
+
+    /// Returns the factory for the consensus protocol to be run in the given era.
+    fn protocol_for_era(&self, era_id: EraId) -> ProtocolFactory<I, R> {
+        *self
+            .protocol_factories
+            .range(..=era_id)
+            .next_back()
+            .map(|(_, factory)| factory)
+            .expect("should have a consensus protocol for the genesis era")
+    }
+
+    /// Sets the consensus protocol to be run from `activation_era` onwards.
+    #[cfg(test)]
+    pub(crate) fn set_protocol_from_era(
+        &mut self,
+        activation_era: EraId,
+        protocol_factory: ProtocolFactory<I, R>,
+    ) {
+        let _ = self
+            .protocol_factories
+            .insert(activation_era, protocol_factory);
+    }
+
     /// Returns the current era.
     fn current_era_mut(&mut self) -> &mut Era<I, R> {
         self.active_eras
@@ -240,6 +500,66 @@ where
     }
 }
 
+/// Creates a new Highway protocol instance for the given era.
+fn highway_protocol<I, R>(
+    era_id: EraId,
+    timestamp: Timestamp,
+    validator_stakes: Vec<(PublicKey, Motes)>,
+    start_time: Timestamp,
+    highway_config: &HighwayConfig,
+    secret_signing_key: &Rc<SecretKey>,
+) -> ProtocolInstance<I, R>
+where
+    I: NodeIdT,
+    R: Rng + CryptoRng + ?Sized,
+{
+    let sum_stakes: Motes = validator_stakes.iter().map(|(_, stake)| *stake).sum();
+    // For Highway, we need u64 weights. Scale down by  sum / u64::MAX,  rounded up.
+    // If we round up the divisor, the resulting sum is guaranteed to be  <= u64::MAX.
+    let scaling_factor = (sum_stakes.value() + U512::from(u64::MAX) - 1) / U512::from(u64::MAX);
+    let scale_stake = |(key, stake): (PublicKey, Motes)| {
+        (key, AsPrimitive::<u64>::as_(stake.value() / scaling_factor))
+    };
+    let validators: Validators<PublicKey> = validator_stakes.into_iter().map(scale_stake).collect();
+
+    let instance_id = hash::hash(format!("Highway era {}", era_id.0));
+    let ftt =
+        validators.total_weight() * u64::from(highway_config.finality_threshold_percent) / 100;
+    // The number of rounds after which a block reward is paid out.
+    // TODO: Make this configurable?
+    let reward_delay = 8;
+    let params = Params::new(
+        0, // TODO: get a proper seed.
+        BLOCK_REWARD,
+        BLOCK_REWARD / 5, // TODO: Make reduced block reward configurable?
+        reward_delay,
+        highway_config.minimum_round_exponent,
+        highway_config.minimum_era_height,
+        start_time + highway_config.era_duration,
+    );
+
+    // Activate the era if it is still ongoing based on its minimum duration, and if we are one
+    // of the validators.
+    let our_id = PublicKey::from(secret_signing_key.as_ref());
+    let min_end_time = start_time
+        + highway_config
+            .era_duration
+            .max(params.min_round_len() * params.end_height());
+    let should_activate = min_end_time >= timestamp && validators.iter().any(|v| *v.id() == our_id);
+
+    let mut highway =
+        HighwayProtocol::<I, HighwayContext>::new(instance_id, validators, params, ftt);
+
+    let results = if should_activate {
+        let secret = HighwaySecret::new(Rc::clone(secret_signing_key), our_id);
+        highway.activate_validator(our_id, secret, timestamp)
+    } else {
+        Vec::new()
+    };
+
+    (Box::new(highway), results)
+}
+
 /// A mutable `EraSupervisor` reference, together with an `EffectBuilder`.
 ///
 /// This is a short-lived convenience type to avoid passing the effect builder through lots of
@@ -289,9 +609,22 @@ where
         era_id: EraId,
         timestamp: Timestamp,
     ) -> Effects<Event<I>> {
-        self.delegate_to_era(era_id, move |consensus, rng| {
+        let effects = self.delegate_to_era(era_id, move |consensus, rng| {
             consensus.handle_timer(timestamp, rng)
-        })
+        });
+        if era_id == self.era_supervisor.current_era {
+            if let Some(era) = self.era_supervisor.active_eras.get(&era_id) {
+                let inactive_count = era
+                    .consensus
+                    .inactive_validators(timestamp, INACTIVE_VALIDATOR_ROUNDS)
+                    .len();
+                self.era_supervisor
+                    .metrics
+                    .inactive_validators
+                    .set(inactive_count as i64);
+            }
+        }
+        effects
     }
 
     pub(super) fn handle_message(&mut self, sender: I, msg: ConsensusMessage) -> Effects<Event<I>> {
@@ -317,14 +650,78 @@ where
         effects
     }
 
+    pub(super) fn handle_leader_stats(
+        &mut self,
+        era_id: EraId,
+        responder: Responder<Option<Vec<(PublicKey, LeaderCount)>>>,
+    ) -> Effects<Event<I>> {
+        let leader_stats = self.era_supervisor.leader_stats(era_id, Timestamp::now());
+        responder.respond(leader_stats).ignore()
+    }
+
+    pub(super) fn handle_validator_stakes(
+        &mut self,
+        era_id: EraId,
+        responder: Responder<Option<BTreeMap<PublicKey, Motes>>>,
+    ) -> Effects<Event<I>> {
+        let validator_stakes = self.era_supervisor.validator_stakes(era_id);
+        responder.respond(validator_stakes).ignore()
+    }
+
+    pub(super) fn handle_current_era(&mut self, responder: Responder<EraId>) -> Effects<Event<I>> {
+        responder.respond(self.era_supervisor.current_era).ignore()
+    }
+
+    pub(super) fn handle_get_evidence(
+        &mut self,
+        era_id: EraId,
+        validator: PublicKey,
+        responder: Responder<Option<Vec<u8>>>,
+    ) -> Effects<Event<I>> {
+        let evidence = self.era_supervisor.evidence(era_id, &validator);
+        responder.respond(evidence).ignore()
+    }
+
+    pub(super) fn handle_force_next_era(
+        &mut self,
+        validators: BTreeMap<PublicKey, Motes>,
+        responder: Responder<Option<EraId>>,
+    ) -> Effects<Event<I>> {
+        let old_era_id = self.era_supervisor.current_era;
+        let new_validators = validators.keys().cloned().collect();
+        let (new_era_id, results) = match self
+            .era_supervisor
+            .force_next_era(validators, Timestamp::now())
+        {
+            None => return responder.respond(None).ignore(),
+            Some(new_era) => new_era,
+        };
+        info!(
+            ?old_era_id,
+            ?new_era_id,
+            "forced transition to the next era"
+        );
+        let mut effects = responder.respond(Some(new_era_id)).ignore();
+        effects.extend(
+            self.effect_builder
+                .announce_era_transition(old_era_id, new_era_id, new_validators)
+                .ignore(),
+        );
+        effects.extend(self.handle_consensus_results(new_era_id, results));
+        effects
+    }
+
     pub(super) fn handle_linear_chain_block(
         &mut self,
         block_header: BlockHeader,
-        responder: Responder<Signature>,
+        responder: Responder<(PublicKey, Signature)>,
     ) -> Effects<Event<I>> {
-        assert_eq!(
-            block_header.era_id(),
-            self.era_supervisor.current_era,
+        // After a forced transition, the blocks of the previous era can still come in.
+        let current_era = self.era_supervisor.current_era;
+        assert!(
+            block_header.era_id() == current_era
+                || (self.era_supervisor.allow_forced_era_transitions
+                    && block_header.era_id().successor() == current_era),
             "executed block in unexpected era"
         );
         // TODO - we should only sign if we're a validator for the given era ID.
@@ -334,15 +731,28 @@ where
             &self.era_supervisor.public_signing_key,
             self.rng,
         );
-        let mut effects = responder.respond(signature).ignore();
-        if block_header.switch_block() {
+        let mut effects = Effects::new();
+        self.era_supervisor.next_block_height = block_header.height() + 1;
+        let next_block_height = self.era_supervisor.next_block_height;
+        self.era_supervisor
+            .finalized_blocks
+            .retain(|(height, _)| *height >= next_block_height);
+        self.era_supervisor
+            .failed_executions
+            .retain(|height, _| *height >= next_block_height);
+        let new_era_id = block_header.era_id().successor();
+        let new_era_started = self.era_supervisor.active_eras.contains_key(&new_era_id);
+        if block_header.switch_block() && new_era_started {
+            // The next era was forced with the given validators: don't replace it.
+            info!(?new_era_id, "next era has already been started");
+        } else if block_header.switch_block() {
             // TODO: Learn the new weights from contract (validator rotation).
             let validator_stakes = self.era_supervisor.validator_stakes.clone();
             self.era_supervisor
                 .current_era_mut()
                 .consensus
                 .deactivate_validator();
-            let new_era_id = block_header.era_id().successor();
+            let new_validators = validator_stakes.iter().map(|(key, _)| *key).collect();
             let results = self.era_supervisor.new_era(
                 new_era_id,
                 Timestamp::now(), // TODO: This should be passed in.
@@ -350,8 +760,16 @@ where
                 block_header.timestamp(),
                 block_header.height() + 1,
             );
+            effects.extend(
+                self.effect_builder
+                    .announce_era_transition(block_header.era_id(), new_era_id, new_validators)
+                    .ignore(),
+            );
             effects.extend(self.handle_consensus_results(new_era_id, results));
         }
+        // Only acknowledge the block once it has been fully incorporated.
+        let public_key = self.era_supervisor.public_signing_key;
+        effects.extend(responder.respond((public_key, signature)).ignore());
         effects
     }
 
@@ -360,6 +778,8 @@ where
         era_id: EraId,
         proto_block: ProtoBlock,
     ) -> Effects<Event<I>> {
+        self.era_supervisor
+            .cache_valid(*proto_block.hash(), Timestamp::now());
         let mut effects = self.delegate_to_era(era_id, |consensus, rng| {
             consensus.resolve_validity(&proto_block, true, rng)
         });
@@ -382,6 +802,46 @@ where
         })
     }
 
+    /// Requests the execution of the given block again after a delay, unless the error is not
+    /// transient or the block has already failed `MAX_BLOCK_EXECUTION_ATTEMPTS` times.
+    pub(super) fn handle_block_execution_failed(
+        &mut self,
+        finalized_block: FinalizedBlock,
+        error: ExecuteError,
+    ) -> Effects<Event<I>> {
+        if !error.is_transient() {
+            // Executing the block again would fail the same way.
+            return fatal!(
+                self.effect_builder,
+                format!("failed to execute {}: {}", finalized_block, error)
+            );
+        }
+        let failed_executions = self
+            .era_supervisor
+            .failed_executions
+            .entry(finalized_block.height())
+            .or_default();
+        *failed_executions += 1;
+        let attempts = *failed_executions;
+        if attempts >= MAX_BLOCK_EXECUTION_ATTEMPTS {
+            return fatal!(
+                self.effect_builder,
+                format!(
+                    "failed to execute {} {} times: {}",
+                    finalized_block, attempts, error
+                )
+            );
+        }
+        let delay = BLOCK_EXECUTION_RETRY_DELAY * 2u32.pow(attempts - 1);
+        warn!(%finalized_block, %error, attempts, ?delay, "retrying execution of finalized block");
+        let effect_builder = self.effect_builder;
+        async move {
+            let _ = effect_builder.set_timeout(delay).await;
+            effect_builder.execute_block(finalized_block).await
+        }
+        .ignore()
+    }
+
     fn handle_consensus_results<T>(&mut self, era_id: EraId, results: T) -> Effects<Event<I>>
     where
         T: IntoIterator<Item = ConsensusProtocolResult<I, ProtoBlock, PublicKey>>,
@@ -442,11 +902,33 @@ where
                 terminal,
                 proposer,
             }) => {
-                // Announce the finalized proto block.
-                let mut effects = self
-                    .effect_builder
-                    .announce_finalized_proto_block(proto_block.clone())
-                    .ignore();
+                let height = self.era_supervisor.active_eras[&era_id].start_height + height;
+                if !self
+                    .era_supervisor
+                    .record_finalized_block(height, *proto_block.hash())
+                {
+                    warn!(
+                        %proto_block,
+                        height,
+                        "ignoring repeated finalization of block"
+                    );
+                    return Effects::new();
+                }
+                // The block validator rejects proposals containing a deploy twice, so this can only
+                // be a bug. Rather stop the node than apply the same deploy twice.
+                if let Some(deploy_hash) = proto_block.duplicate_deploy() {
+                    return fatal!(
+                        self.effect_builder,
+                        format!(
+                            "finalized {} contains {} more than once",
+                            proto_block, deploy_hash
+                        )
+                    );
+                }
+                #[cfg(test)]
+                if let Some(era) = self.era_supervisor.active_eras.get_mut(&era_id) {
+                    era.highest_finalized_height = Some(height);
+                }
                 // Create instructions for slashing equivocators.
                 let mut system_transactions: Vec<_> = new_equivocators
                     .into_iter()
@@ -455,36 +937,815 @@ where
                 if !rewards.is_empty() {
                     system_transactions.push(SystemTransaction::Rewards(rewards));
                 };
-                let fb = FinalizedBlock::new(
-                    proto_block,
-                    timestamp,
-                    system_transactions,
-                    terminal,
-                    era_id,
-                    self.era_supervisor.active_eras[&era_id].start_height + height,
-                    proposer,
-                );
+                let fb = if terminal {
+                    FinalizedBlock::new_switch_block(
+                        proto_block,
+                        timestamp,
+                        system_transactions,
+                        era_id,
+                        height,
+                        proposer,
+                    )
+                } else {
+                    FinalizedBlock::new_regular(
+                        proto_block,
+                        timestamp,
+                        system_transactions,
+                        era_id,
+                        height,
+                        proposer,
+                    )
+                };
+                // Highway only reports equivocations by the era's own validators, each of them
+                // once, so an invalid slashing can only be a bug.
+                let validators: BTreeSet<PublicKey> = self.era_supervisor.active_eras[&era_id]
+                    .validator_stakes
+                    .keys()
+                    .copied()
+                    .collect();
+                if !fb.slashings_valid(&validators) {
+                    return fatal!(
+                        self.effect_builder,
+                        format!("finalized {} contains invalid slashings", fb)
+                    );
+                }
+                // Announce the finalized block.
+                let mut effects = self
+                    .effect_builder
+                    .announce_finalized_block(fb.clone())
+                    .ignore();
                 // Request execution of the finalized block.
                 effects.extend(self.effect_builder.execute_block(fb).ignore());
                 effects
             }
-            ConsensusProtocolResult::ValidateConsensusValue(sender, proto_block) => self
-                .effect_builder
-                .validate_proto_block(sender.clone(), proto_block)
-                .event(move |(is_valid, proto_block)| {
-                    if is_valid {
-                        Event::AcceptProtoBlock {
-                            era_id,
-                            proto_block,
-                        }
-                    } else {
-                        Event::InvalidProtoBlock {
+            ConsensusProtocolResult::ValidateConsensusValue(sender, proto_block)
+                if !proto_block.has_valid_hash() =>
+            {
+                info!(%proto_block, "proto block hash does not match its contents");
+                self.effect_builder
+                    .immediately()
+                    .event(move |_| Event::InvalidProtoBlock {
+                        era_id,
+                        sender,
+                        proto_block,
+                    })
+            }
+            ConsensusProtocolResult::ValidateConsensusValue(sender, proto_block)
+                if !proto_block.within_limits(self.era_supervisor.max_block_deploys) =>
+            {
+                info!(%proto_block, "proto block contains too many deploys");
+                self.effect_builder
+                    .immediately()
+                    .event(move |_| Event::InvalidProtoBlock {
+                        era_id,
+                        sender,
+                        proto_block,
+                    })
+            }
+            ConsensusProtocolResult::ValidateConsensusValue(sender, proto_block) => {
+                if self
+                    .era_supervisor
+                    .is_cached_valid(proto_block.hash(), Timestamp::now())
+                {
+                    self.effect_builder
+                        .immediately()
+                        .event(move |_| Event::AcceptProtoBlock {
                             era_id,
-                            sender,
                             proto_block,
-                        }
+                        })
+                } else {
+                    self.validate_proto_block(era_id, sender, proto_block)
+                }
+            }
+        }
+    }
+
+    /// Requests the validation of the given proto block.
+    fn validate_proto_block(
+        &mut self,
+        era_id: EraId,
+        sender: I,
+        proto_block: ProtoBlock,
+    ) -> Effects<Event<I>> {
+        self.effect_builder
+            .validate_proto_block(sender.clone(), proto_block)
+            .event(move |(is_valid, proto_block)| {
+                if is_valid {
+                    Event::AcceptProtoBlock {
+                        era_id,
+                        proto_block,
                     }
-                }),
+                } else {
+                    Event::InvalidProtoBlock {
+                        era_id,
+                        sender,
+                        proto_block,
+                    }
+                }
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use casper_execution_engine::{
+        core::engine_state::{self, RootNotFound},
+        storage::error::lmdb::Error as StorageLmdbError,
+    };
+
+    use super::*;
+    use crate::{
+        components::storage::Value,
+        crypto::hash::Digest,
+        effect::requests::{BlockExecutorRequest, ConsensusRequest},
+        reactor::{validator, EventQueueHandle, QueueKind, Scheduler},
+        small_network::NodeId,
+        testing::TestRng,
+        types::{Block, BlockHash, DeployHash, TimeDiff},
+        utils,
+    };
+
+    thread_local! {
+        /// The number of `MockProtocol` instances created on this thread.
+        static MOCK_PROTOCOL_INSTANCES: Cell<usize> = Cell::new(0);
+    }
+
+    /// The evidence `MockProtocol` returns against any validator.
+    const MOCK_EVIDENCE: &[u8] = b"mock evidence";
+
+    /// A consensus protocol that does nothing.
+    struct MockProtocol;
+
+    impl<I, R: Rng + CryptoRng + ?Sized> ConsensusProtocol<I, ProtoBlock, PublicKey, R>
+        for MockProtocol
+    {
+        fn handle_message(
+            &mut self,
+            _sender: I,
+            _msg: Vec<u8>,
+            _rng: &mut R,
+        ) -> Result<Vec<ConsensusProtocolResult<I, ProtoBlock, PublicKey>>, Error> {
+            Ok(vec![])
+        }
+
+        fn handle_timer(
+            &mut self,
+            _timestamp: Timestamp,
+            _rng: &mut R,
+        ) -> Result<Vec<ConsensusProtocolResult<I, ProtoBlock, PublicKey>>, Error> {
+            Ok(vec![])
+        }
+
+        fn propose(
+            &mut self,
+            _value: ProtoBlock,
+            _block_context: BlockContext,
+            _rng: &mut R,
+        ) -> Result<Vec<ConsensusProtocolResult<I, ProtoBlock, PublicKey>>, Error> {
+            Ok(vec![])
+        }
+
+        fn resolve_validity(
+            &mut self,
+            _value: &ProtoBlock,
+            _valid: bool,
+            _rng: &mut R,
+        ) -> Result<Vec<ConsensusProtocolResult<I, ProtoBlock, PublicKey>>, Error> {
+            Ok(vec![])
+        }
+
+        fn deactivate_validator(&mut self) {}
+
+        fn leader_counts(
+            &self,
+            _start: Timestamp,
+            _end: Timestamp,
+        ) -> Vec<(PublicKey, LeaderCount)> {
+            vec![]
+        }
+
+        fn evidence(&self, _validator: &PublicKey) -> Option<Vec<u8>> {
+            Some(MOCK_EVIDENCE.to_vec())
+        }
+
+        fn inactive_validators(&self, _now: Timestamp, _rounds: u64) -> Vec<PublicKey> {
+            vec![]
+        }
+    }
+
+    fn mock_protocol<I, R: Rng + CryptoRng + ?Sized>(
+        _era_id: EraId,
+        _timestamp: Timestamp,
+        _validator_stakes: Vec<(PublicKey, Motes)>,
+        _start_time: Timestamp,
+        _highway_config: &HighwayConfig,
+        _secret_signing_key: &Rc<SecretKey>,
+    ) -> ProtocolInstance<I, R> {
+        MOCK_PROTOCOL_INSTANCES.with(|instances| instances.set(instances.get() + 1));
+        (Box::new(MockProtocol), vec![])
+    }
+
+    fn mock_protocol_instances() -> usize {
+        MOCK_PROTOCOL_INSTANCES.with(Cell::get)
+    }
+
+    /// Returns an era supervisor running Highway, in which we are the only validator, and the
+    /// validator stakes.
+    fn new_test_era_supervisor(
+        rng: &mut TestRng,
+    ) -> (EraSupervisor<u64, TestRng>, Vec<(PublicKey, Motes)>) {
+        new_era_supervisor_with_node_ids(rng)
+    }
+
+    /// Like `new_test_era_supervisor`, but with node IDs of type `I`, e.g. to use the era
+    /// supervisor with a reactor's effect builder.
+    fn new_era_supervisor_with_node_ids<I: NodeIdT>(
+        rng: &mut TestRng,
+    ) -> (EraSupervisor<I, TestRng>, Vec<(PublicKey, Motes)>) {
+        let secret_signing_key = Rc::new(SecretKey::random(rng));
+        let public_signing_key = PublicKey::from(secret_signing_key.as_ref());
+        let validator_stakes = vec![(public_signing_key, Motes::new(U512::from(100)))];
+        let era_supervisor = EraSupervisor {
+            active_eras: Default::default(),
+            secret_signing_key,
+            public_signing_key,
+            validator_stakes: validator_stakes.clone(),
+            current_era: EraId(0),
+            highway_config: HighwayConfig::default(),
+            protocol_factories: iter::once((
+                EraId(0),
+                highway_protocol as ProtocolFactory<I, TestRng>,
+            ))
+            .collect(),
+            next_block_height: 0,
+            allow_forced_era_transitions: false,
+            finalized_blocks: HashSet::new(),
+            block_validation_cache_ttl: TimeDiff::from(60_000),
+            max_block_deploys: 10,
+            validated_proto_blocks: LinkedHashMap::new(),
+            failed_executions: HashMap::new(),
+            metrics: EraSupervisorMetrics::new(&Registry::new()).unwrap(),
+        };
+        (era_supervisor, validator_stakes)
+    }
+
+    #[test]
+    fn should_create_eras_with_different_protocols() {
+        let mut rng = TestRng::new();
+        let (mut era_supervisor, validator_stakes) = new_test_era_supervisor(&mut rng);
+        era_supervisor.set_protocol_from_era(EraId(1), mock_protocol);
+
+        let start_time = era_supervisor.highway_config.genesis_era_start_timestamp;
+        let _ = era_supervisor.new_era(
+            EraId(0),
+            start_time,
+            validator_stakes.clone(),
+            start_time,
+            0,
+        );
+        assert_eq!(mock_protocol_instances(), 0);
+
+        let _ = era_supervisor.new_era(EraId(1), start_time, validator_stakes, start_time, 10);
+        assert_eq!(mock_protocol_instances(), 1);
+
+        assert!(era_supervisor.active_eras().contains_key(&EraId(0)));
+        assert!(era_supervisor.active_eras().contains_key(&EraId(1)));
+    }
+
+    #[test]
+    fn should_report_leader_stats() {
+        let mut rng = TestRng::new();
+        let (mut era_supervisor, validator_stakes) = new_test_era_supervisor(&mut rng);
+
+        let start_time = era_supervisor.highway_config.genesis_era_start_timestamp;
+        let _ = era_supervisor.new_era(EraId(0), start_time, validator_stakes, start_time, 0);
+
+        // As the only validator, we lead every round.
+        let round_len = TimeDiff::from(1 << era_supervisor.highway_config.minimum_round_exponent);
+        let now = start_time + round_len * 10;
+        let leader_stats = era_supervisor
+            .leader_stats(EraId(0), now)
+            .expect("era 0 should be active");
+        assert_eq!(leader_stats.len(), 1);
+        let (validator, leader_count) = leader_stats[0];
+        assert_eq!(validator, era_supervisor.public_signing_key);
+        assert_eq!(leader_count.actual, 10);
+        assert_eq!(leader_count.expected, 10.0);
+
+        assert!(era_supervisor.leader_stats(EraId(1), now).is_none());
+    }
+
+    #[test]
+    fn should_return_evidence_if_known() {
+        let mut rng = TestRng::new();
+        let (mut era_supervisor, validator_stakes) = new_test_era_supervisor(&mut rng);
+        era_supervisor.set_protocol_from_era(EraId(1), mock_protocol);
+
+        let start_time = era_supervisor.highway_config.genesis_era_start_timestamp;
+        for era_id in 0..2 {
+            let _ = era_supervisor.new_era(
+                EraId(era_id),
+                start_time,
+                validator_stakes.clone(),
+                start_time,
+                0,
+            );
+        }
+
+        // Nobody has equivocated in the Highway era.
+        let validator = era_supervisor.public_signing_key;
+        assert!(era_supervisor.evidence(EraId(0), &validator).is_none());
+        // The mock protocol has evidence against everyone.
+        assert_eq!(
+            era_supervisor.evidence(EraId(1), &validator),
+            Some(MOCK_EVIDENCE.to_vec())
+        );
+        // There is no evidence for eras that aren't active.
+        assert!(era_supervisor.evidence(EraId(2), &validator).is_none());
+    }
+
+    #[test]
+    fn should_force_next_era_with_given_validators() {
+        let mut rng = TestRng::new();
+        let (mut era_supervisor, validator_stakes) = new_test_era_supervisor(&mut rng);
+
+        let start_time = era_supervisor.highway_config.genesis_era_start_timestamp;
+        let _ = era_supervisor.new_era(EraId(0), start_time, validator_stakes, start_time, 0);
+
+        let other_validator = PublicKey::from(&SecretKey::random(&mut rng));
+        let validators: BTreeMap<_, _> = vec![
+            (
+                era_supervisor.public_signing_key,
+                Motes::new(U512::from(100)),
+            ),
+            (other_validator, Motes::new(U512::from(200))),
+        ]
+        .into_iter()
+        .collect();
+
+        // Forcing the next era must not work unless it has been enabled.
+        let now = Timestamp::now();
+        assert!(era_supervisor
+            .force_next_era(validators.clone(), now)
+            .is_none());
+        assert!(!era_supervisor.active_eras().contains_key(&EraId(1)));
+
+        era_supervisor.allow_forced_era_transitions = true;
+        let (new_era_id, _) = era_supervisor
+            .force_next_era(validators.clone(), now)
+            .expect("should force the next era");
+        assert_eq!(new_era_id, EraId(1));
+        assert_eq!(era_supervisor.current_era, EraId(1));
+
+        let era_validators: BTreeSet<_> = era_supervisor.active_eras()[&EraId(1)]
+            .consensus
+            .leader_counts(now, now)
+            .into_iter()
+            .map(|(validator, _)| validator)
+            .collect();
+        assert_eq!(era_validators, validators.keys().cloned().collect());
+    }
+
+    #[tokio::test]
+    async fn should_keep_forced_era_after_switch_block() {
+        let mut rng = TestRng::new();
+        let (mut era_supervisor, validator_stakes) =
+            new_era_supervisor_with_node_ids::<NodeId>(&mut rng);
+        era_supervisor.allow_forced_era_transitions = true;
+
+        let start_time = era_supervisor.highway_config.genesis_era_start_timestamp;
+        let _ = era_supervisor.new_era(EraId(0), start_time, validator_stakes, start_time, 0);
+        let other_validator = PublicKey::from(&SecretKey::random(&mut rng));
+        let validators: BTreeMap<_, _> = vec![(other_validator, Motes::new(U512::from(200)))]
+            .into_iter()
+            .collect();
+        let now = Timestamp::now();
+        let _ = era_supervisor
+            .force_next_era(validators.clone(), now)
+            .expect("should force the next era");
+
+        // The switch block of the era that was forced out comes in afterwards.
+        let switch_block = Block::new(
+            BlockHash::new(Digest::default()),
+            Digest::default(),
+            FinalizedBlock::new_switch_block(
+                ProtoBlock::new(vec![], false),
+                start_time,
+                vec![],
+                EraId(0),
+                0,
+                era_supervisor.public_signing_key,
+            ),
+        );
+        let scheduler = utils::leak(Scheduler::<validator::Event>::new(QueueKind::weights()));
+        let effect_builder = EffectBuilder::new(EventQueueHandle::new(scheduler));
+        let mut handling_wrapper = era_supervisor.handling_wrapper(effect_builder, &mut rng);
+        let signature =
+            tokio::spawn(effect_builder.handle_linear_chain_block(switch_block.take_header()));
+        let effects = match scheduler.pop().await {
+            (
+                validator::Event::Consensus(Event::ConsensusRequest(
+                    ConsensusRequest::HandleLinearBlock(block_header, responder),
+                )),
+                _,
+            ) => handling_wrapper.handle_linear_chain_block(*block_header, responder),
+            (event, _) => panic!("unexpected event: {}", event),
+        };
+        for effect in effects {
+            assert!(effect.await.is_empty());
+        }
+        let _ = signature.await.unwrap();
+
+        // The forced era is still current, and keeps its validators.
+        assert_eq!(era_supervisor.current_era, EraId(1));
+        let era_validators: BTreeSet<_> = era_supervisor.active_eras()[&EraId(1)]
+            .consensus
+            .leader_counts(now, now)
+            .into_iter()
+            .map(|(validator, _)| validator)
+            .collect();
+        assert_eq!(era_validators, validators.keys().cloned().collect());
+    }
+
+    #[test]
+    fn should_suppress_duplicate_finalization() {
+        let mut rng = TestRng::new();
+        let (mut era_supervisor, _) = new_test_era_supervisor(&mut rng);
+        let proto_block_hash = *ProtoBlock::new(vec![], false).hash();
+
+        // Only the first finalization of a block is acted upon.
+        assert!(era_supervisor.record_finalized_block(0, proto_block_hash));
+        assert!(!era_supervisor.record_finalized_block(0, proto_block_hash));
+
+        // Blocks below the next height to be executed have been finalized before.
+        era_supervisor.next_block_height = 2;
+        let other_hash = *ProtoBlock::new(vec![], true).hash();
+        assert!(!era_supervisor.record_finalized_block(1, other_hash));
+        assert_eq!(
+            era_supervisor
+                .metrics
+                .duplicate_finalizations_suppressed
+                .get(),
+            2
+        );
+    }
+
+    #[test]
+    fn should_summarize_finalized_heights_per_era() {
+        let mut rng = TestRng::new();
+        let (mut era_supervisor, validator_stakes) =
+            new_era_supervisor_with_node_ids::<NodeId>(&mut rng);
+        let proposer = era_supervisor.public_signing_key;
+        let finalized_block = |height, random_bit| {
+            ConsensusProtocolResult::FinalizedBlock(CpFinalizedBlock {
+                value: ProtoBlock::new(vec![], random_bit),
+                new_equivocators: vec![],
+                rewards: BTreeMap::new(),
+                timestamp: Timestamp::zero(),
+                height,
+                terminal: false,
+                proposer,
+            })
+        };
+        let scheduler = utils::leak(Scheduler::<validator::Event>::new(QueueKind::weights()));
+        let effect_builder = EffectBuilder::new(EventQueueHandle::new(scheduler));
+
+        // Two blocks are finalized in era 0, the second of which is at height 1.
+        let start_time = era_supervisor.highway_config.genesis_era_start_timestamp;
+        let _ = era_supervisor.new_era(
+            EraId(0),
+            start_time,
+            validator_stakes.clone(),
+            start_time,
+            0,
+        );
+        let mut handling_wrapper = era_supervisor.handling_wrapper(effect_builder, &mut rng);
+        let _ = handling_wrapper.handle_consensus_result(EraId(0), finalized_block(0, false));
+        let _ = handling_wrapper.handle_consensus_result(EraId(0), finalized_block(1, true));
+
+        // Era 1 starts at height 2, and its first finalized block has relative height 0.
+        let _ = era_supervisor.new_era(EraId(1), start_time, validator_stakes, start_time, 2);
+        let expected = vec![
+            EraSummary {
+                era_id: EraId(0),
+                validator_count: 1,
+                highest_finalized_height: Some(1),
+            },
+            EraSummary {
+                era_id: EraId(1),
+                validator_count: 1,
+                highest_finalized_height: None,
+            },
+        ];
+        assert_eq!(era_supervisor.era_summaries(), expected);
+
+        let mut handling_wrapper = era_supervisor.handling_wrapper(effect_builder, &mut rng);
+        let _ = handling_wrapper.handle_consensus_result(EraId(1), finalized_block(0, false));
+        let summaries = era_supervisor.era_summaries();
+        assert_eq!(summaries[0], expected[0]);
+        assert_eq!(summaries[1].highest_finalized_height, Some(2));
+    }
+
+    #[tokio::test]
+    async fn should_stop_on_finalized_block_with_duplicate_deploys() {
+        let mut rng = TestRng::new();
+        let (mut era_supervisor, validator_stakes) =
+            new_era_supervisor_with_node_ids::<NodeId>(&mut rng);
+
+        let start_time = era_supervisor.highway_config.genesis_era_start_timestamp;
+        let _ = era_supervisor.new_era(EraId(0), start_time, validator_stakes, start_time, 0);
+
+        let deploy_hash = DeployHash::new(Digest::random(&mut rng));
+        let result = ConsensusProtocolResult::FinalizedBlock(CpFinalizedBlock {
+            value: ProtoBlock::new(vec![deploy_hash, deploy_hash], false),
+            new_equivocators: vec![],
+            rewards: BTreeMap::new(),
+            timestamp: start_time,
+            height: 0,
+            terminal: false,
+            proposer: era_supervisor.public_signing_key,
+        });
+        let scheduler = utils::leak(Scheduler::<validator::Event>::new(QueueKind::weights()));
+        let effect_builder = EffectBuilder::new(EventQueueHandle::new(scheduler));
+        let mut handling_wrapper = era_supervisor.handling_wrapper(effect_builder, &mut rng);
+
+        // Instead of executing the block, the only effect is a fatal error.
+        let mut effects = handling_wrapper.handle_consensus_result(EraId(0), result);
+        assert_eq!(effects.len(), 1);
+        let join_error = tokio::spawn(effects.pop().unwrap())
+            .await
+            .expect_err("fatal effect should panic");
+        assert!(join_error.is_panic());
+    }
+
+    #[tokio::test]
+    async fn should_stop_on_finalized_block_slashing_a_non_validator() {
+        let mut rng = TestRng::new();
+        let (mut era_supervisor, validator_stakes) =
+            new_era_supervisor_with_node_ids::<NodeId>(&mut rng);
+
+        let start_time = era_supervisor.highway_config.genesis_era_start_timestamp;
+        let _ = era_supervisor.new_era(EraId(0), start_time, validator_stakes, start_time, 0);
+
+        let result = ConsensusProtocolResult::FinalizedBlock(CpFinalizedBlock {
+            value: ProtoBlock::new(vec![], false),
+            new_equivocators: vec![PublicKey::random(&mut rng)],
+            rewards: BTreeMap::new(),
+            timestamp: start_time,
+            height: 0,
+            terminal: false,
+            proposer: era_supervisor.public_signing_key,
+        });
+        let scheduler = utils::leak(Scheduler::<validator::Event>::new(QueueKind::weights()));
+        let effect_builder = EffectBuilder::new(EventQueueHandle::new(scheduler));
+        let mut handling_wrapper = era_supervisor.handling_wrapper(effect_builder, &mut rng);
+
+        // Instead of executing the block, the only effect is a fatal error.
+        let mut effects = handling_wrapper.handle_consensus_result(EraId(0), result);
+        assert_eq!(effects.len(), 1);
+        let join_error = tokio::spawn(effects.pop().unwrap())
+            .await
+            .expect_err("fatal effect should panic");
+        assert!(join_error.is_panic());
+    }
+
+    #[tokio::test]
+    async fn should_retry_failed_block_execution() {
+        let mut rng = TestRng::new();
+        let (mut era_supervisor, validator_stakes) =
+            new_era_supervisor_with_node_ids::<NodeId>(&mut rng);
+
+        let start_time = era_supervisor.highway_config.genesis_era_start_timestamp;
+        let _ = era_supervisor.new_era(EraId(0), start_time, validator_stakes, start_time, 0);
+
+        let finalized_block = FinalizedBlock::new(
+            ProtoBlock::new(vec![], false),
+            start_time,
+            vec![],
+            false,
+            EraId(0),
+            0,
+            era_supervisor.public_signing_key,
+        );
+        let scheduler = utils::leak(Scheduler::<validator::Event>::new(QueueKind::weights()));
+        let effect_builder = EffectBuilder::new(EventQueueHandle::new(scheduler));
+        let mut handling_wrapper = era_supervisor.handling_wrapper(effect_builder, &mut rng);
+
+        // After the first failure, the block's execution is requested again.
+        let error = ExecuteError::Engine(engine_state::Error::Storage(StorageLmdbError::Lmdb(
+            lmdb::Error::ReadersFull,
+        )));
+        let effects =
+            handling_wrapper.handle_block_execution_failed(finalized_block.clone(), error);
+        for effect in effects {
+            assert!(effect.await.is_empty());
+        }
+        match scheduler.pop().await {
+            (
+                validator::Event::BlockExecutorRequest(BlockExecutorRequest::ExecuteBlock(block)),
+                _,
+            ) => {
+                assert_eq!(block, finalized_block)
+            }
+            (event, _) => panic!("unexpected event: {}", event),
         }
+
+        // The second attempt succeeds, and the executed block is added to the linear chain.
+        let block = Block::new(
+            BlockHash::new(Digest::default()),
+            Digest::default(),
+            finalized_block,
+        );
+        let signature = tokio::spawn(effect_builder.handle_linear_chain_block(block.take_header()));
+        let effects = match scheduler.pop().await {
+            (
+                validator::Event::Consensus(Event::ConsensusRequest(
+                    ConsensusRequest::HandleLinearBlock(block_header, responder),
+                )),
+                _,
+            ) => handling_wrapper.handle_linear_chain_block(*block_header, responder),
+            (event, _) => panic!("unexpected event: {}", event),
+        };
+        for effect in effects {
+            assert!(effect.await.is_empty());
+        }
+        let _ = signature.await.unwrap();
+        assert_eq!(era_supervisor.next_block_height, 1);
+        assert!(era_supervisor.failed_executions.is_empty());
+    }
+
+    #[tokio::test]
+    async fn should_not_retry_block_execution_after_deterministic_error() {
+        let mut rng = TestRng::new();
+        let (mut era_supervisor, validator_stakes) =
+            new_era_supervisor_with_node_ids::<NodeId>(&mut rng);
+
+        let start_time = era_supervisor.highway_config.genesis_era_start_timestamp;
+        let _ = era_supervisor.new_era(EraId(0), start_time, validator_stakes, start_time, 0);
+
+        let finalized_block = FinalizedBlock::new(
+            ProtoBlock::new(vec![], false),
+            start_time,
+            vec![],
+            false,
+            EraId(0),
+            0,
+            era_supervisor.public_signing_key,
+        );
+        let scheduler = utils::leak(Scheduler::<validator::Event>::new(QueueKind::weights()));
+        let effect_builder = EffectBuilder::new(EventQueueHandle::new(scheduler));
+        let mut handling_wrapper = era_supervisor.handling_wrapper(effect_builder, &mut rng);
+
+        // A missing pre-state won't appear by executing again, so the node gives up at once.
+        let error = ExecuteError::RootNotFound(RootNotFound::new(Digest::default().into()));
+        let mut effects = handling_wrapper.handle_block_execution_failed(finalized_block, error);
+        assert_eq!(effects.len(), 1);
+        let join_error = tokio::spawn(effects.pop().unwrap())
+            .await
+            .expect_err("fatal effect should panic");
+        assert!(join_error.is_panic());
+        assert!(era_supervisor.failed_executions.is_empty());
+    }
+
+    #[tokio::test]
+    async fn should_reject_oversized_proposal_without_validation() {
+        let mut rng = TestRng::new();
+        let (mut era_supervisor, validator_stakes) =
+            new_era_supervisor_with_node_ids::<NodeId>(&mut rng);
+        era_supervisor.set_protocol_from_era(EraId(0), mock_protocol);
+
+        let start_time = era_supervisor.highway_config.genesis_era_start_timestamp;
+        let _ = era_supervisor.new_era(EraId(0), start_time, validator_stakes, start_time, 0);
+
+        let sender = NodeId::from([1; 64]);
+        let deploy_hashes = iter::repeat_with(|| DeployHash::new(Digest::random(&mut rng)))
+            .take(era_supervisor.max_block_deploys + 1)
+            .collect();
+        let proto_block = ProtoBlock::new(deploy_hashes, false);
+        let scheduler = utils::leak(Scheduler::<validator::Event>::new(QueueKind::weights()));
+        let effect_builder = EffectBuilder::new(EventQueueHandle::new(scheduler));
+        let mut handling_wrapper = era_supervisor.handling_wrapper(effect_builder, &mut rng);
+
+        let effects = handling_wrapper.handle_consensus_result(
+            EraId(0),
+            ConsensusProtocolResult::ValidateConsensusValue(sender, proto_block.clone()),
+        );
+        let mut events = Vec::new();
+        for effect in effects {
+            events.extend(effect.await);
+        }
+        match events.as_slice() {
+            [Event::InvalidProtoBlock {
+                era_id: EraId(0),
+                proto_block: rejected,
+                ..
+            }] => assert_eq!(*rejected, proto_block),
+            _ => panic!("unexpected events: {:?}", events),
+        }
+        // The block validator was never asked.
+        assert_eq!(scheduler.item_count(), 0);
+    }
+
+    #[tokio::test]
+    async fn should_validate_repeated_proposal_only_once() {
+        let mut rng = TestRng::new();
+        let (mut era_supervisor, validator_stakes) =
+            new_era_supervisor_with_node_ids::<NodeId>(&mut rng);
+        era_supervisor.set_protocol_from_era(EraId(0), mock_protocol);
+
+        let start_time = era_supervisor.highway_config.genesis_era_start_timestamp;
+        let _ = era_supervisor.new_era(EraId(0), start_time, validator_stakes, start_time, 0);
+
+        let sender = NodeId::from([1; 64]);
+        let proto_block = ProtoBlock::new(vec![], true);
+        let validate =
+            || ConsensusProtocolResult::ValidateConsensusValue(sender.clone(), proto_block.clone());
+        let scheduler = utils::leak(Scheduler::<validator::Event>::new(QueueKind::weights()));
+        let effect_builder = EffectBuilder::new(EventQueueHandle::new(scheduler));
+        let mut handling_wrapper = era_supervisor.handling_wrapper(effect_builder, &mut rng);
+
+        // The first proposal is validated by the block validator.
+        let effects = handling_wrapper.handle_consensus_result(EraId(0), validate());
+        let _validation = effects.into_iter().map(tokio::spawn).collect::<Vec<_>>();
+        // Keep the request, and hence its responder, alive until the end of the test.
+        let _request = match scheduler.pop().await {
+            (validator::Event::ProtoBlockValidatorRequest(request), _) => {
+                assert_eq!(request.block, proto_block);
+                request
+            }
+            (event, _) => panic!("unexpected event: {}", event),
+        };
+        let _ = handling_wrapper.handle_accept_proto_block(EraId(0), proto_block.clone());
+
+        // The repeated proposal is accepted based on the cached outcome.
+        let effects = handling_wrapper.handle_consensus_result(EraId(0), validate());
+        let mut events = Vec::new();
+        for effect in effects {
+            events.extend(effect.await);
+        }
+        match events.as_slice() {
+            [Event::AcceptProtoBlock {
+                era_id: EraId(0),
+                proto_block: accepted,
+            }] => assert_eq!(*accepted, proto_block),
+            _ => panic!("unexpected events: {:?}", events),
+        }
+        assert_eq!(scheduler.item_count(), 0);
+    }
+
+    #[tokio::test]
+    async fn should_validate_repeated_proposal_again_if_invalid() {
+        let mut rng = TestRng::new();
+        let (mut era_supervisor, validator_stakes) =
+            new_era_supervisor_with_node_ids::<NodeId>(&mut rng);
+        era_supervisor.set_protocol_from_era(EraId(0), mock_protocol);
+
+        let start_time = era_supervisor.highway_config.genesis_era_start_timestamp;
+        let _ = era_supervisor.new_era(EraId(0), start_time, validator_stakes, start_time, 0);
+
+        let sender = NodeId::from([1; 64]);
+        let proto_block = ProtoBlock::new(vec![], true);
+        let scheduler = utils::leak(Scheduler::<validator::Event>::new(QueueKind::weights()));
+        let effect_builder = EffectBuilder::new(EventQueueHandle::new(scheduler));
+        let mut handling_wrapper = era_supervisor.handling_wrapper(effect_builder, &mut rng);
+
+        // A proposal may be invalid only because its sender withheld a deploy: another proposal
+        // of the same block is validated afresh.
+        let _ = handling_wrapper.handle_invalid_proto_block(
+            EraId(0),
+            sender.clone(),
+            proto_block.clone(),
+        );
+        let effects = handling_wrapper.handle_consensus_result(
+            EraId(0),
+            ConsensusProtocolResult::ValidateConsensusValue(sender, proto_block.clone()),
+        );
+        let _validation = effects.into_iter().map(tokio::spawn).collect::<Vec<_>>();
+        // Keep the request, and hence its responder, alive until the end of the test.
+        let _request = match scheduler.pop().await {
+            (validator::Event::ProtoBlockValidatorRequest(request), _) => {
+                assert_eq!(request.block, proto_block);
+                request
+            }
+            (event, _) => panic!("unexpected event: {}", event),
+        };
+    }
+
+    #[test]
+    fn should_step_between_eras() {
+        assert_eq!(EraId(0).successor(), EraId(1));
+        assert_eq!(EraId(1).predecessor(), Some(EraId(0)));
+        assert_eq!(EraId(0).predecessor(), None);
+
+        assert_eq!(EraId(3) + 2, EraId(5));
+        assert_eq!(EraId(5) - 2, EraId(3));
+        assert_eq!(EraId(5).saturating_sub(2), EraId(3));
+        assert_eq!(EraId(1).saturating_sub(2), EraId(0));
+
+        assert_eq!(EraId(3).checked_add(2), Some(EraId(5)));
+        assert_eq!(EraId(u64::MAX - 1).checked_add(1), Some(EraId(u64::MAX)));
+        assert_eq!(EraId(u64::MAX).checked_add(1), None);
     }
 }
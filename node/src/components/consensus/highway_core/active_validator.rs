@@ -5,8 +5,8 @@ use tracing::{error, warn};
 
 use super::{
     evidence::Evidence,
-    highway::{ValidVertex, Vertex, WireVote},
-    state::{self, Observation, Panorama, State, Vote},
+    highway::{Dependency, ValidVertex, Vertex, WireVote},
+    state::{self, Observation, Panorama, State, Vote, Weight},
     validators::ValidatorIndex,
 };
 
@@ -31,6 +31,30 @@ pub(crate) enum Effect<C: Context> {
     ///
     /// When this is returned, the validator automatically deactivates.
     WeEquivocated(Evidence<C>),
+    /// A dependency cited by a peer is missing from the protocol state, and needs to be requested
+    /// from that peer.
+    RequestDependency(Dependency<C>),
+}
+
+/// Decides which of the votes in the protocol state a new vote cites.
+pub(crate) trait CutoffPolicy<C: Context> {
+    /// Returns the panorama to cite in a new vote created at `timestamp`.
+    ///
+    /// It must not see any votes with a later timestamp than that. The entry for our own
+    /// validator is ignored: A new vote always cites our own latest vote, since omitting it would
+    /// make the new vote an equivocation.
+    fn panorama(&self, state: &State<C>, timestamp: Timestamp) -> Panorama<C>;
+}
+
+/// The default cutoff policy: cite every vote in the protocol state, up to the new vote's
+/// timestamp.
+#[derive(Debug, Default)]
+pub(crate) struct LatestCutoff;
+
+impl<C: Context> CutoffPolicy<C> for LatestCutoff {
+    fn panorama(&self, state: &State<C>, timestamp: Timestamp) -> Panorama<C> {
+        state.panorama().cutoff(state, timestamp)
+    }
 }
 
 /// A validator that actively participates in consensus by creating new vertices.
@@ -58,6 +82,10 @@ pub(crate) struct ActiveValidator<C: Context> {
     next_timer: Timestamp,
     /// Panorama and timestamp for a block we are about to propose when we get a consensus value.
     next_proposal: Option<(Timestamp, Panorama<C>)>,
+    /// Decides which votes our witness votes and proposals cite.
+    cutoff_policy: Box<dyn CutoffPolicy<C>>,
+    /// The parent block chosen by the fork choice rule for our pending or latest proposal.
+    last_fork_choice: Option<C::Hash>,
 }
 
 impl<C: Context> Debug for ActiveValidator<C> {
@@ -93,11 +121,31 @@ impl<C: Context> ActiveValidator<C> {
             next_round_exp,
             next_timer: Timestamp::zero(),
             next_proposal: None,
+            cutoff_policy: Box::new(LatestCutoff),
+            last_fork_choice: None,
         };
+        if !av.is_bonded(state) {
+            warn!(vidx = ?av.vidx, "not a bonded validator; won't schedule a timer");
+            return (av, vec![]);
+        }
         let effects = av.schedule_timer(timestamp, state);
         (av, effects)
     }
 
+    /// Replaces the policy deciding which votes our witness votes and proposals cite.
+    ///
+    /// This allows modeling e.g. validators with limited memory of past votes.
+    #[cfg(test)]
+    pub(crate) fn set_cutoff_policy(&mut self, cutoff_policy: Box<dyn CutoffPolicy<C>>) {
+        self.cutoff_policy = cutoff_policy;
+    }
+
+    /// Returns the parent block chosen by the fork choice rule in the most recent call to
+    /// `request_new_block`, or `None` if there was none, or if that proposal was canceled.
+    pub(crate) fn last_fork_choice(&self) -> Option<&C::Hash> {
+        self.last_fork_choice.as_ref()
+    }
+
     /// Returns actions a validator needs to take at the specified `timestamp`, with the given
     /// protocol `state`.
     pub(crate) fn handle_timer<R: Rng + CryptoRng + ?Sized>(
@@ -106,6 +154,10 @@ impl<C: Context> ActiveValidator<C> {
         state: &State<C>,
         rng: &mut R,
     ) -> Vec<Effect<C>> {
+        if !self.is_bonded(state) {
+            warn!(vidx = ?self.vidx, "not a bonded validator; won't create a message");
+            return vec![];
+        }
         if self.is_faulty(state) {
             warn!("Creator knows it's faulty. Won't create a message.");
             return vec![];
@@ -121,10 +173,11 @@ impl<C: Context> ActiveValidator<C> {
         if timestamp == r_id && state.leader(r_id) == self.vidx {
             effects.extend(self.request_new_block(state, timestamp, rng))
         } else if timestamp == r_id + self.witness_offset(r_len) {
-            let panorama = state.panorama().cutoff(state, timestamp);
+            let panorama = self.cutoff_panorama(state, timestamp);
             if panorama.has_correct() {
-                let witness_vote = self.new_vote(panorama, timestamp, None, state, rng);
-                effects.push(Effect::NewVertex(ValidVertex(Vertex::Vote(witness_vote))))
+                if let Some(witness_vote) = self.new_vote(panorama, timestamp, None, state, rng) {
+                    effects.push(Effect::NewVertex(ValidVertex(Vertex::Vote(witness_vote))))
+                }
             }
         }
         effects
@@ -146,9 +199,12 @@ impl<C: Context> ActiveValidator<C> {
         } else if self.should_send_confirmation(vhash, timestamp, state) {
             let panorama = self.confirmation_panorama(vhash, state);
             if panorama.has_correct() {
-                let confirmation_vote = self.new_vote(panorama, timestamp, None, state, rng);
-                let vv = ValidVertex(Vertex::Vote(confirmation_vote));
-                return vec![Effect::NewVertex(vv)];
+                if let Some(confirmation_vote) =
+                    self.new_vote(panorama, timestamp, None, state, rng)
+                {
+                    let vv = ValidVertex(Vertex::Vote(confirmation_vote));
+                    return vec![Effect::NewVertex(vv)];
+                }
             }
         }
         vec![]
@@ -172,11 +228,13 @@ impl<C: Context> ActiveValidator<C> {
             );
             return None;
         }
-        let panorama = state.panorama().cutoff(state, timestamp);
+        let panorama = self.cutoff_panorama(state, timestamp);
         let opt_parent_hash = state.fork_choice(&panorama);
+        self.last_fork_choice = opt_parent_hash.cloned();
         if opt_parent_hash.map_or(false, |hash| state.is_terminal_block(hash)) {
-            let proposal_vote = self.new_vote(panorama, timestamp, None, state, rng);
-            return Some(Effect::NewVertex(ValidVertex(Vertex::Vote(proposal_vote))));
+            return self
+                .new_vote(panorama, timestamp, None, state, rng)
+                .map(|proposal_vote| Effect::NewVertex(ValidVertex(Vertex::Vote(proposal_vote))));
         }
         let opt_parent = opt_parent_hash.map(|bh| state.block(bh));
         let height = opt_parent.map_or(0, |block| block.height);
@@ -215,8 +273,10 @@ impl<C: Context> ActiveValidator<C> {
             warn!("unexpected proposal value");
             return vec![];
         };
-        let proposal_vote = self.new_vote(panorama, timestamp, Some(value), state, rng);
-        vec![Effect::NewVertex(ValidVertex(Vertex::Vote(proposal_vote)))]
+        self.new_vote(panorama, timestamp, Some(value), state, rng)
+            .map(|proposal_vote| Effect::NewVertex(ValidVertex(Vertex::Vote(proposal_vote))))
+            .into_iter()
+            .collect()
     }
 
     /// Returns whether the incoming message is a proposal that we need to send a confirmation for.
@@ -262,7 +322,19 @@ impl<C: Context> ActiveValidator<C> {
         panorama
     }
 
+    /// Returns the panorama chosen by the cutoff policy, with our own latest vote.
+    fn cutoff_panorama(&self, state: &State<C>, timestamp: Timestamp) -> Panorama<C> {
+        let mut panorama = self.cutoff_policy.panorama(state, timestamp);
+        if panorama.len() == state.validator_count() {
+            panorama[self.vidx] = state.panorama()[self.vidx].clone();
+        }
+        panorama
+    }
+
     /// Returns a new vote with the given data, and the correct sequence number.
+    ///
+    /// Returns `None` if the timestamp is earlier than our previous vote's, since the new vote
+    /// would be invalid.
     fn new_vote<R: Rng + CryptoRng + ?Sized>(
         &mut self,
         mut panorama: Panorama<C>,
@@ -270,14 +342,29 @@ impl<C: Context> ActiveValidator<C> {
         value: Option<C::ConsensusValue>,
         state: &State<C>,
         rng: &mut R,
-    ) -> SignedWireVote<C> {
+    ) -> Option<SignedWireVote<C>> {
+        let earliest_vote_time = self.earliest_vote_time(state);
+        if timestamp < earliest_vote_time {
+            error!(
+                %timestamp, %earliest_vote_time,
+                "refusing to create a vote earlier than our previous one"
+            );
+            return None;
+        }
         if let Some((prop_time, _)) = self.next_proposal.take() {
             warn!(
                 ?timestamp,
                 "canceling proposal for {} due to vote", prop_time
             );
+            self.last_fork_choice = None;
         }
-        if panorama[self.vidx] != state.panorama()[self.vidx] {
+        if panorama.len() != state.validator_count() {
+            error!(
+                len = panorama.len(),
+                "replacing vote panorama that doesn't match the number of validators"
+            );
+            panorama = state.panorama().clone();
+        } else if panorama[self.vidx] != state.panorama()[self.vidx] {
             error!("replacing vote panorama to avoid equivocation");
             panorama = state.panorama().clone();
         }
@@ -290,7 +377,7 @@ impl<C: Context> ActiveValidator<C> {
             timestamp,
             round_exp: self.round_exp(state, timestamp),
         };
-        SignedWireVote::new(wvote, &self.secret, rng)
+        Some(SignedWireVote::new(wvote, &self.secret, rng))
     }
 
     /// Returns a `ScheduleTimer` effect for the next time we need to be called.
@@ -335,6 +422,11 @@ impl<C: Context> ActiveValidator<C> {
             .map(|vh| state.vote(vh))
     }
 
+    /// Checks if we are one of the validators, with a nonzero weight.
+    fn is_bonded(&self, state: &State<C>) -> bool {
+        (self.vidx.0 as usize) < state.validator_count() && state.weight(self.vidx) > Weight(0)
+    }
+
     /// Checks if validator knows it's faulty.
     fn is_faulty(&self, state: &State<C>) -> bool {
         state.panorama().get(self.vidx).is_faulty()
@@ -463,4 +555,148 @@ mod tests {
         assert_eq!(Some(&prop_hash), fd.next_finalized(&state, 0.into()));
         Ok(())
     }
+
+    /// A cutoff policy that forgets all votes older than the given timestamp.
+    struct ForgetBefore(Timestamp);
+
+    impl<C: Context> CutoffPolicy<C> for ForgetBefore {
+        fn panorama(&self, state: &State<C>, timestamp: Timestamp) -> Panorama<C> {
+            let forget_old = |obs: &Observation<C>| match obs {
+                Observation::Correct(vhash) if state.vote(vhash).timestamp < self.0 => {
+                    Observation::None
+                }
+                obs => obs.clone(),
+            };
+            let panorama = state.panorama().cutoff(state, timestamp);
+            Panorama::from(panorama.iter().map(forget_old).collect::<Vec<_>>())
+        }
+    }
+
+    /// Returns the panorama of the witness vote the validator sends at the given timestamp.
+    fn witness_panorama(
+        av: &mut ActiveValidator<TestContext>,
+        timestamp: Timestamp,
+        state: &State<TestContext>,
+        rng: &mut TestRng,
+    ) -> Panorama<TestContext> {
+        av.handle_timer(timestamp, state, rng)
+            .into_iter()
+            .find_map(|effect| match effect {
+                Eff::NewVertex(ValidVertex(Vertex::Vote(swvote))) => {
+                    Some(swvote.wire_vote.panorama)
+                }
+                _ => None,
+            })
+            .expect("expected a witness vote")
+    }
+
+    #[test]
+    fn custom_cutoff_policy_truncates_witness_panorama() -> Result<(), AddVoteError<TestContext>> {
+        let mut state = State::new_test(WEIGHTS, 0);
+        let mut rng = TestRng::new();
+
+        // Bob voted long ago, Carol only recently.
+        let b0 = add_vote!(state, rng, BOB, 100, 4u8, None; N, N, N)?;
+        let c0 = add_vote!(state, rng, CAROL, 420, 4u8, None; N, N, N)?;
+
+        // By default, Alice's witness vote at 426 cites both.
+        let (mut alice_av, _) = ActiveValidator::new(ALICE, ALICE_SEC, 4, 410.into(), &state);
+        let panorama = witness_panorama(&mut alice_av, 426.into(), &state, &mut rng);
+        assert_eq!(panorama!(N, b0, c0), panorama);
+
+        // If she forgets votes older than 400, it only cites Carol's.
+        let (mut alice_av, _) = ActiveValidator::new(ALICE, ALICE_SEC, 4, 410.into(), &state);
+        alice_av.set_cutoff_policy(Box::new(ForgetBefore(400.into())));
+        let panorama = witness_panorama(&mut alice_av, 426.into(), &state, &mut rng);
+        assert_eq!(panorama!(N, N, c0), panorama);
+        Ok(())
+    }
+
+    #[test]
+    fn custom_cutoff_policy_keeps_own_latest_vote() -> Result<(), AddVoteError<TestContext>> {
+        let mut state = State::new_test(WEIGHTS, 0);
+        let mut rng = TestRng::new();
+
+        // Alice's and Bob's latest votes are older than 400.
+        let a0 = add_vote!(state, rng, ALICE, 100, 4u8, None; N, N, N)?;
+        let b0 = add_vote!(state, rng, BOB, 100, 4u8, None; N, N, N)?;
+        let c0 = add_vote!(state, rng, CAROL, 420, 4u8, None; a0, b0, N)?;
+
+        // Forgetting votes older than 400 drops Bob's vote, but Alice still cites her own, so
+        // that her witness vote is not an equivocation.
+        let (mut alice_av, _) = ActiveValidator::new(ALICE, ALICE_SEC, 4, 410.into(), &state);
+        alice_av.set_cutoff_policy(Box::new(ForgetBefore(400.into())));
+        let panorama = witness_panorama(&mut alice_av, 426.into(), &state, &mut rng);
+        assert_eq!(panorama!(a0, N, c0), panorama);
+        Ok(())
+    }
+
+    #[test]
+    fn records_fork_choice_until_proposal_is_canceled() -> Result<(), AddVoteError<TestContext>> {
+        let mut state = State::new_test(WEIGHTS, 0);
+        let mut rng = TestRng::new();
+
+        let b0 = add_vote!(state, rng, BOB, 0xB; N, N, N)?;
+        let r_id = state::round_id(state.vote(&b0).timestamp + TimeDiff::from(16), 4);
+
+        let (mut alice_av, _) = ActiveValidator::new(ALICE, ALICE_SEC, 4, r_id, &state);
+        assert_eq!(None, alice_av.last_fork_choice());
+
+        // Alice builds her proposal on top of Bob's block.
+        match alice_av.request_new_block(&state, r_id, &mut rng) {
+            Some(Eff::RequestNewBlock(_)) => (),
+            effect => panic!("unexpected effect: {:?}", effect),
+        }
+        assert_eq!(state.fork_choice(state.panorama()), Some(&b0));
+        assert_eq!(Some(&b0), alice_av.last_fork_choice());
+
+        // Her witness vote cancels the proposal, since she never got a consensus value for it.
+        let witness_time = r_id + alice_av.witness_offset(state::round_len(4));
+        alice_av.handle_timer(witness_time, &state, &mut rng);
+        assert_eq!(None, alice_av.last_fork_choice());
+        Ok(())
+    }
+
+    #[test]
+    fn refuses_to_create_vote_earlier_than_previous() -> Result<(), AddVoteError<TestContext>> {
+        let mut state = State::new_test(WEIGHTS, 0);
+        let mut rng = TestRng::new();
+
+        add_vote!(state, rng, CAROL, 420, 4u8, None; N, N, N)?;
+        let (mut bob_av, _) = ActiveValidator::new(BOB, BOB_SEC, 4, 410.into(), &state);
+        let witness_vote = bob_av
+            .handle_timer(426.into(), &state, &mut rng)
+            .into_iter()
+            .find_map(|effect| match effect {
+                Eff::NewVertex(ValidVertex(Vertex::Vote(swvote))) => Some(swvote),
+                _ => None,
+            })
+            .expect("expected a witness vote");
+        state.add_vote(witness_vote)?;
+
+        // A vote with an earlier timestamp than Bob's witness vote would be invalid.
+        let panorama = state.panorama().clone();
+        assert!(bob_av
+            .new_vote(panorama, 425.into(), None, &state, &mut rng)
+            .is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn unbonded_validator_produces_no_votes() {
+        let state = State::new_test(&[Weight(3), Weight(0)], 0);
+        let mut rng = TestRng::new();
+
+        // Bob has no weight, and Carol isn't one of the validators at all.
+        for vidx in &[BOB, CAROL] {
+            let (mut av, effects) =
+                ActiveValidator::new(*vidx, TestSecret(1), 4, 410.into(), &state);
+            assert!(effects.is_empty());
+            for timestamp in &[416, 426, 432, 442] {
+                assert!(av
+                    .handle_timer((*timestamp).into(), &state, &mut rng)
+                    .is_empty());
+            }
+        }
+    }
 }
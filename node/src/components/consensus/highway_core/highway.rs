@@ -1,6 +1,6 @@
 mod vertex;
 
-pub(crate) use crate::components::consensus::highway_core::state::Params;
+pub(crate) use crate::components::consensus::highway_core::state::{Panorama, Params};
 pub(crate) use vertex::{Dependency, SignedWireVote, Vertex, WireVote};
 
 use rand::{CryptoRng, Rng};
@@ -9,11 +9,11 @@ use tracing::{debug, error};
 
 use crate::{
     components::consensus::{
-        consensus_protocol::BlockContext,
+        consensus_protocol::{BlockContext, LeaderCount},
         highway_core::{
             active_validator::{ActiveValidator, Effect},
             state::{State, VoteError},
-            validators::{Validator, Validators},
+            validators::{Validator, ValidatorIndex, ValidatorMap, Validators},
         },
         traits::Context,
     },
@@ -264,6 +264,41 @@ impl<C: Context> Highway<C> {
         self.state.params()
     }
 
+    /// Returns the latest vote or fault of each validator that we know of.
+    pub(crate) fn panorama(&self) -> &Panorama<C> {
+        self.state.panorama()
+    }
+
+    /// Returns effects requesting the dependencies we need to catch up with a peer's `panorama`,
+    /// or `None` if it doesn't have one entry per validator.
+    pub(crate) fn request_missing_dependencies(
+        &self,
+        panorama: &Panorama<C>,
+    ) -> Option<Vec<Effect<C>>> {
+        if panorama.len() != self.state.validator_count() {
+            return None;
+        }
+        let deps = panorama.missing_dependencies(&self.state);
+        Some(deps.into_iter().map(Effect::RequestDependency).collect())
+    }
+
+    /// Returns, for each validator, the number of rounds with the minimum round length beginning
+    /// between `start` (inclusive) and `end` (exclusive) that it was the leader of.
+    pub(crate) fn leader_counts(
+        &self,
+        start: Timestamp,
+        end: Timestamp,
+    ) -> ValidatorMap<LeaderCount> {
+        self.state
+            .leader_counts(start, end, self.params().min_round_exp())
+    }
+
+    /// Returns the validators whose latest vote is more than `rounds` of their own round lengths
+    /// older than `now`, as well as the ones that haven't produced any vote at all.
+    pub(crate) fn inactive_validators(&self, now: Timestamp, rounds: u64) -> Vec<ValidatorIndex> {
+        self.state.inactive_validators(now, rounds)
+    }
+
     pub(super) fn state(&self) -> &State<C> {
         &self.state
     }
@@ -296,7 +331,16 @@ impl<C: Context> Highway<C> {
             match effect {
                 Effect::NewVertex(vv) => result.extend(self.add_valid_vertex(vv.clone(), rng)),
                 Effect::WeEquivocated(_) => self.deactivate_validator(),
-                Effect::ScheduleTimer(_) | Effect::RequestNewBlock(_) => (),
+                Effect::RequestNewBlock(block_context) => {
+                    if let Some(av) = &self.active_validator {
+                        debug!(
+                            ?block_context,
+                            parent = ?av.last_fork_choice(),
+                            "requesting a value for a new block"
+                        );
+                    }
+                }
+                Effect::ScheduleTimer(_) | Effect::RequestDependency(_) => (),
             }
         }
         result.extend(effects);
@@ -358,7 +402,9 @@ impl<C: Context> Highway<C> {
 
 #[cfg(test)]
 pub(crate) mod tests {
-    use std::iter::FromIterator;
+    use std::{iter::FromIterator, rc::Rc};
+
+    use rand::Rng;
 
     use crate::{
         components::consensus::{
@@ -372,16 +418,16 @@ pub(crate) mod tests {
                 },
                 validators::Validators,
             },
+            protocols::highway::{HighwayContext, HighwaySecret},
             traits::ValidatorSecret,
         },
+        crypto::asymmetric_key::{PublicKey, SecretKey},
         testing::TestRng,
         types::Timestamp,
     };
 
-    #[test]
-    fn invalid_signature_error() {
-        let mut rng = TestRng::new();
-
+    /// Returns a `Highway` instance with the test validators, and no active validator.
+    fn new_test_highway() -> Highway<TestContext> {
         let state: State<TestContext> = State::new_test(WEIGHTS, 0);
         let validators = {
             let vid_weights: Vec<(u32, u64)> =
@@ -394,12 +440,18 @@ pub(crate) mod tests {
                     .collect();
             Validators::from_iter(vid_weights)
         };
-        let mut highway = Highway {
+        Highway {
             instance_id: 1u64,
             validators,
             state,
             active_validator: None,
-        };
+        }
+    }
+
+    #[test]
+    fn invalid_signature_error() {
+        let mut rng = TestRng::new();
+        let mut highway = new_test_highway();
         let wvote = WireVote {
             panorama: Panorama::new(WEIGHTS.len()),
             creator: ALICE,
@@ -431,4 +483,53 @@ pub(crate) mod tests {
         let vv = highway.validate_vertex(pvv).unwrap();
         assert!(highway.add_valid_vertex(vv, &mut rng).is_empty());
     }
+
+    #[test]
+    fn invalid_panorama_length_error() {
+        let mut rng = TestRng::new();
+        let highway = new_test_highway();
+
+        // The panorama has an entry for a fourth validator that doesn't exist.
+        let wvote = WireVote {
+            panorama: Panorama::new(WEIGHTS.len() + 1),
+            creator: ALICE,
+            value: Some(0),
+            seq_number: 0,
+            timestamp: Timestamp::zero() + 1.into(),
+            round_exp: 12,
+        };
+        let signature = ALICE_SEC.sign(&wvote.hash(), &mut rng);
+        let vertex = Vertex::Vote(SignedWireVote {
+            wire_vote: wvote,
+            signature,
+        });
+        let err = VertexError::Vote(VoteError::PanoramaLength(WEIGHTS.len() + 1));
+        let expected = (vertex.clone(), err);
+        assert_eq!(Err(expected), highway.pre_validate_vertex(vertex.clone()));
+        assert!(!highway.has_vertex(&vertex));
+    }
+
+    #[test]
+    fn same_seed_yields_identical_signed_votes() {
+        let mut rng = TestRng::new();
+        let secret_key = SecretKey::random_secp256k1(&mut rng);
+        let public_key = PublicKey::from(&secret_key);
+        let secret = HighwaySecret::new(Rc::new(secret_key), public_key);
+        let wvote: WireVote<HighwayContext> = WireVote {
+            panorama: Panorama::new(WEIGHTS.len()),
+            creator: ALICE,
+            value: None,
+            seq_number: 0,
+            timestamp: Timestamp::zero(),
+            round_exp: 4,
+        };
+
+        // Secp256k1 signing draws randomness from the RNG, so each vote gets an identically seeded
+        // one of its own.
+        let seed = rng.gen();
+        let vote0 = SignedWireVote::new(wvote.clone(), &secret, &mut TestRng::from_seed(seed));
+        let vote1 = SignedWireVote::new(wvote, &secret, &mut TestRng::from_seed(seed));
+        assert_eq!(vote0.hash(), vote1.hash());
+        assert_eq!(vote0, vote1);
+    }
 }
@@ -52,6 +52,7 @@ enum HighwayMessage {
     NewVertex(Vertex<TestContext>),
     RequestBlock(BlockContext),
     WeEquivocated(Evidence<TestContext>),
+    RequestDependency(Dependency<TestContext>),
 }
 
 impl Debug for HighwayMessage {
@@ -66,6 +67,9 @@ impl Debug for HighwayMessage {
                 f.debug_struct("NewVertex").field("vertex", &v).finish()
             }
             HighwayMessage::WeEquivocated(ev) => f.debug_tuple("WeEquivocated").field(&ev).finish(),
+            HighwayMessage::RequestDependency(dep) => {
+                f.debug_tuple("RequestDependency").field(&dep).finish()
+            }
         }
     }
 }
@@ -75,7 +79,7 @@ impl HighwayMessage {
         let create_msg = |hwm: HighwayMessage| Message::new(creator, hwm);
 
         match self {
-            HighwayMessage::NewVertex(_) => {
+            HighwayMessage::NewVertex(_) | HighwayMessage::RequestDependency(_) => {
                 TargetedMessage::new(create_msg(self), Target::AllExcept(creator))
             }
             HighwayMessage::Timer(_)
@@ -103,6 +107,7 @@ impl From<Effect<TestContext>> for HighwayMessage {
             Effect::ScheduleTimer(t) => HighwayMessage::Timer(t),
             Effect::RequestNewBlock(block_context) => HighwayMessage::RequestBlock(block_context),
             Effect::WeEquivocated(evidence) => HighwayMessage::WeEquivocated(evidence),
+            Effect::RequestDependency(dep) => HighwayMessage::RequestDependency(dep),
         }
     }
 }
@@ -140,7 +145,11 @@ impl Ord for HighwayMessage {
             }
             (HighwayMessage::Timer(_), _) => std::cmp::Ordering::Less,
             (HighwayMessage::NewVertex(_), _) => std::cmp::Ordering::Less,
+            (HighwayMessage::RequestDependency(dep1), HighwayMessage::RequestDependency(dep2)) => {
+                dep1.cmp(&dep2)
+            }
             (HighwayMessage::RequestBlock(_), _) => std::cmp::Ordering::Less,
+            (HighwayMessage::RequestDependency(_), _) => std::cmp::Ordering::Less,
             (HighwayMessage::WeEquivocated(_), _) => std::cmp::Ordering::Greater,
         }
     }
@@ -249,7 +258,8 @@ impl HighwayValidator {
                 match &msg {
                     HighwayMessage::NewVertex(_)
                     | HighwayMessage::Timer(_)
-                    | HighwayMessage::RequestBlock(_) => vec![msg],
+                    | HighwayMessage::RequestBlock(_)
+                    | HighwayMessage::RequestDependency(_) => vec![msg],
                     HighwayMessage::WeEquivocated(ev) => {
                         panic!("validator equivocated unexpectedly: {:?}", ev);
                     }
@@ -262,7 +272,9 @@ impl HighwayValidator {
                         warn!("Validator is mute – won't gossip vertices in response");
                         vec![]
                     }
-                    HighwayMessage::Timer(_) | HighwayMessage::RequestBlock(_) => vec![msg],
+                    HighwayMessage::Timer(_)
+                    | HighwayMessage::RequestBlock(_)
+                    | HighwayMessage::RequestDependency(_) => vec![msg],
                     HighwayMessage::WeEquivocated(ev) => {
                         panic!("validator equivocated unexpectedly: {:?}", ev);
                     }
@@ -282,6 +294,7 @@ impl HighwayValidator {
                     HighwayMessage::NewVertex(_)
                     | HighwayMessage::RequestBlock(_)
                     | HighwayMessage::WeEquivocated(_)
+                    | HighwayMessage::RequestDependency(_)
                     | HighwayMessage::Timer(_) => vec![msg],
                 }
             }
@@ -453,6 +466,17 @@ where
                     })?
                 }
                 HighwayMessage::WeEquivocated(_evidence) => vec![],
+                HighwayMessage::RequestDependency(dep) => {
+                    // Respond with the requested vertex, if this validator has it.
+                    self.call_validator(rng, &validator_id, |consensus, _rng| {
+                        consensus
+                            .highway()
+                            .get_dependency(&dep)
+                            .map(Effect::NewVertex)
+                            .into_iter()
+                            .collect()
+                    })?
+                }
             }
         };
 
@@ -705,7 +729,7 @@ impl DeliveryStrategy for InstantDeliveryNoDropping {
             HighwayMessage::NewVertex(_) => {
                 DeliverySchedule::AtInstant(base_delivery_timestamp + 1.into())
             }
-            HighwayMessage::WeEquivocated(_) => {
+            HighwayMessage::WeEquivocated(_) | HighwayMessage::RequestDependency(_) => {
                 DeliverySchedule::AtInstant(base_delivery_timestamp + 1.into())
             }
         }
@@ -8,10 +8,11 @@ mod weight;
 #[cfg(test)]
 pub(crate) mod tests;
 
+pub(crate) use panorama::Panorama;
 pub(crate) use params::Params;
 pub(crate) use weight::Weight;
 
-pub(super) use panorama::{Observation, Panorama};
+pub(super) use panorama::Observation;
 pub(super) use vote::Vote;
 
 use std::{
@@ -31,6 +32,7 @@ use tracing::error;
 
 use crate::{
     components::consensus::{
+        consensus_protocol::LeaderCount,
         highway_core::{
             evidence::Evidence,
             highway::{SignedWireVote, WireVote},
@@ -188,6 +190,26 @@ impl<C: Context> State<C> {
         self.evidence.keys().cloned()
     }
 
+    /// Returns the validators whose latest vote is more than `rounds` of their own round lengths
+    /// older than `now`, as well as the ones that haven't produced any vote at all.
+    ///
+    /// Faulty validators are not included.
+    pub(crate) fn inactive_validators(&self, now: Timestamp, rounds: u64) -> Vec<ValidatorIndex> {
+        let is_inactive = |obs: &Observation<C>| match obs {
+            Observation::None => true,
+            Observation::Faulty => false,
+            Observation::Correct(hash) => {
+                let vote = self.vote(hash);
+                vote.timestamp + vote.round_len() * rounds < now
+            }
+        };
+        self.panorama
+            .enumerate()
+            .filter(|(_, obs)| is_inactive(obs))
+            .map(|(idx, _)| idx)
+            .collect()
+    }
+
     /// Returns the vote with the given hash, if present.
     pub(crate) fn opt_vote(&self, hash: &C::Hash) -> Option<&Vote<C>> {
         self.votes.get(hash)
@@ -241,6 +263,66 @@ impl<C: Context> State<C> {
         self.cumulative_w.binary_search(&r).unwrap_or_else(identity)
     }
 
+    /// Returns, for each validator, the number of rounds with exponent `round_exp` beginning
+    /// between `start` (inclusive) and `end` (exclusive) that it was the leader of, together with
+    /// the number expected according to its weight.
+    pub(crate) fn leader_counts(
+        &self,
+        start: Timestamp,
+        end: Timestamp,
+        round_exp: u8,
+    ) -> ValidatorMap<LeaderCount> {
+        let mut actual: ValidatorMap<u64> = self.weights.iter().map(|_| 0).collect();
+        let leaders = self.leaders_in_range(start, end, |_| round_exp);
+        for (_, leader) in &leaders {
+            actual[*leader] += 1;
+        }
+        let rounds = leaders.len() as u64;
+        let total_weight = self.total_weight().0 as f64;
+        self.weights
+            .iter()
+            .zip(actual)
+            .map(|(weight, actual)| LeaderCount {
+                actual,
+                expected: rounds as f64 * weight.0 as f64 / total_weight,
+            })
+            .collect()
+    }
+
+    /// Returns the beginning and leader of each round beginning between `start` (inclusive) and
+    /// `end` (exclusive), e.g. to show the upcoming proposal schedule.
+    ///
+    /// `round_exp` returns the round exponent in effect at a given timestamp, so the window can
+    /// span changes of the round length: after each round, the next one begins at the following
+    /// boundary for the exponent in effect at that time.
+    pub(crate) fn leaders_in_range<F>(
+        &self,
+        start: Timestamp,
+        end: Timestamp,
+        round_exp: F,
+    ) -> Vec<(Timestamp, ValidatorIndex)>
+    where
+        F: Fn(Timestamp) -> u8,
+    {
+        // Returns the first round boundary at or after `timestamp`.
+        let next_round_start = |timestamp: Timestamp| {
+            let r_exp = round_exp(timestamp);
+            let r_id = round_id(timestamp, r_exp);
+            if r_id < timestamp {
+                r_id + round_len(r_exp)
+            } else {
+                r_id
+            }
+        };
+        let mut leaders = vec![];
+        let mut round_start = next_round_start(start);
+        while round_start < end {
+            leaders.push((round_start, self.leader(round_start)));
+            round_start = next_round_start(round_start + round_len(round_exp(round_start)));
+        }
+        leaders
+    }
+
     /// Adds the vote to the protocol state.
     ///
     /// The vote must be valid, and its dependencies satisfied.
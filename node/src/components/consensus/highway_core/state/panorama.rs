@@ -156,6 +156,13 @@ impl<C: Context> Panorama<C> {
         self.enumerate().filter_map(missing_dep).next()
     }
 
+    /// Returns all missing dependencies, i.e. the latest votes and the evidence seen by `self` that
+    /// are not in `state` yet.
+    pub(crate) fn missing_dependencies(&self, state: &State<C>) -> Vec<Dependency<C>> {
+        let missing_dep = |(idx, obs): (_, &Observation<C>)| obs.missing_dep(state, idx);
+        self.enumerate().filter_map(missing_dep).collect()
+    }
+
     /// Returns whether `self` can possibly come later in time than `other`, i.e. it can see
     /// every honest message and every fault seen by `other`.
     pub(super) fn geq(&self, state: &State<C>, other: &Panorama<C>) -> bool {
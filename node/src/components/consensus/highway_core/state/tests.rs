@@ -197,6 +197,36 @@ fn add_vote() -> Result<(), AddVoteError<TestContext>> {
     Ok(())
 }
 
+#[test]
+fn missing_panorama_dependencies() -> Result<(), AddVoteError<TestContext>> {
+    let mut state = State::new_test(WEIGHTS, 0);
+    let mut rng = TestRng::new();
+    let a0 = add_vote!(state, rng, ALICE, 0xA; N, N, N)?;
+    let b0 = add_vote!(state, rng, BOB, 0xB; N, N, N)?;
+    let c0 = add_vote!(state, rng, CAROL, None; N, b0, N)?;
+    let b1 = add_vote!(state, rng, BOB, None; a0, b0, c0)?;
+
+    // A peer catching up has only received a0 and b0 so far.
+    let mut peer_state = State::new_test(WEIGHTS, 0);
+    for hash in &[a0, b0] {
+        peer_state.add_vote(state.wire_vote(hash).expect("vote should exist"))?;
+    }
+
+    // Our latest panorama is sent in response to the peer's request.
+    let serialized = rmp_serde::to_vec(state.panorama()).expect("should serialize panorama");
+    let panorama: Panorama<TestContext> =
+        rmp_serde::from_read_ref(&serialized).expect("should deserialize panorama");
+    assert_eq!(panorama, panorama!(a0, b1, c0));
+
+    // The peer only needs to request the latest votes it doesn't know yet.
+    assert_eq!(
+        vec![Dependency::Vote(b1), Dependency::Vote(c0)],
+        panorama.missing_dependencies(&peer_state)
+    );
+    assert!(panorama.missing_dependencies(&state).is_empty());
+    Ok(())
+}
+
 #[test]
 fn find_in_swimlane() -> Result<(), AddVoteError<TestContext>> {
     let mut state = State::new_test(WEIGHTS, 0);
@@ -252,6 +282,78 @@ fn fork_choice() -> Result<(), AddVoteError<TestContext>> {
     Ok(())
 }
 
+#[test]
+fn inactive_validators() -> Result<(), AddVoteError<TestContext>> {
+    let mut state = State::new_test(WEIGHTS, 0);
+    let mut rng = TestRng::new();
+
+    // With round exponent 4, a round is 16 ms long. Alice is silent after her first vote at 0,
+    // while Bob keeps voting until 160. Carol hasn't voted at all.
+    let a0 = add_vote!(state, rng, ALICE, 0, 4u8, 0xA; N, N, N)?;
+    let b0 = add_vote!(state, rng, BOB, 16, 4u8, None; a0, N, N)?;
+    let _b1 = add_vote!(state, rng, BOB, 160, 4u8, None; a0, b0, N)?;
+
+    // At 176, Alice's latest vote is 11 rounds old, Bob's only one.
+    let now = Timestamp::from(176);
+    assert_eq!(vec![ALICE, CAROL], state.inactive_validators(now, 10));
+    assert_eq!(vec![CAROL], state.inactive_validators(now, 11));
+    Ok(())
+}
+
+#[test]
+fn leader_counts() {
+    let state = State::<TestContext>::new_test(WEIGHTS, 0);
+
+    // With round exponent 4, a round is 16 ms long: count the leaders of 1000 rounds.
+    let rounds = 1000;
+    let counts = state.leader_counts(Timestamp::from(0), Timestamp::from(16 * rounds), 4);
+
+    assert_eq!(rounds, counts.iter().map(|count| count.actual).sum::<u64>());
+    for (idx, count) in counts.enumerate() {
+        let expected = rounds as f64 * state.weight(idx).0 as f64 / 12.0;
+        assert!((count.expected - expected).abs() < 1e-9);
+        // Each validator should lead within 10% of the total number of rounds of its expectation.
+        assert!(
+            (count.actual as f64 - count.expected).abs() < rounds as f64 / 10.0,
+            "{:?} led {} rounds, expected {}",
+            idx,
+            count.actual,
+            count.expected
+        );
+    }
+
+    // Only rounds that begin in the given range are counted.
+    let counts = state.leader_counts(Timestamp::from(1), Timestamp::from(48), 4);
+    assert_eq!(2, counts.iter().map(|count| count.actual).sum::<u64>());
+}
+
+#[test]
+fn leaders_in_range() {
+    let state = State::<TestContext>::new_test(WEIGHTS, 0);
+
+    // Rounds are 16 ms long before 64, and 32 ms long from then on.
+    let round_exp = |timestamp: Timestamp| if timestamp < 64.into() { 4 } else { 5 };
+    let leaders = state.leaders_in_range(Timestamp::from(1), Timestamp::from(160), round_exp);
+
+    let expected_starts: Vec<Timestamp> = vec![16, 32, 48, 64, 96, 128]
+        .into_iter()
+        .map(Timestamp::from)
+        .collect();
+    let expected: Vec<_> = expected_starts
+        .into_iter()
+        .map(|round_start| (round_start, state.leader(round_start)))
+        .collect();
+    assert_eq!(expected, leaders);
+
+    // With a fixed round exponent, the schedule agrees with the leader counts.
+    let leaders = state.leaders_in_range(Timestamp::from(0), Timestamp::from(1600), |_| 4);
+    let counts = state.leader_counts(Timestamp::from(0), Timestamp::from(1600), 4);
+    for (idx, count) in counts.enumerate() {
+        let led = leaders.iter().filter(|(_, leader)| *leader == idx).count();
+        assert_eq!(count.actual, led as u64);
+    }
+}
+
 #[test]
 fn test_log2() {
     assert_eq!(2, log2(0b100));
@@ -1,20 +1,21 @@
-use std::{fmt::Debug, iter, rc::Rc};
+use std::{collections::HashMap, fmt::Debug, iter, rc::Rc};
 
 use anyhow::Error;
 use rand::{CryptoRng, Rng};
 use serde::{Deserialize, Serialize};
-use tracing::info;
+use tracing::{info, warn};
 
 use crate::{
     components::consensus::{
         consensus_protocol::{
             synchronizer::{DagSynchronizerState, SynchronizerEffect},
-            BlockContext, ConsensusProtocol, ConsensusProtocolResult, ProtocolState, VertexTrait,
+            BlockContext, ConsensusProtocol, ConsensusProtocolResult, LeaderCount, ProtocolState,
+            VertexTrait,
         },
         highway_core::{
             active_validator::Effect as AvEffect,
             finality_detector::FinalityDetector,
-            highway::{Dependency, Highway, Params, PreValidatedVertex, Vertex},
+            highway::{Dependency, Highway, Panorama, Params, PreValidatedVertex, Vertex},
             validators::Validators,
             Weight,
         },
@@ -24,9 +25,13 @@ use crate::{
         asymmetric_key::{self, PublicKey, SecretKey, Signature},
         hash::{self, Digest},
     },
-    types::{ProtoBlock, Timestamp},
+    types::{ProtoBlock, TimeDiff, Timestamp},
 };
 
+/// The time in milliseconds after which an unanswered request for a peer's latest panorama is
+/// considered lost.
+const PANORAMA_REQUEST_TIMEOUT_MILLIS: u64 = 10_000;
+
 impl<C: Context> VertexTrait for PreValidatedVertex<C> {
     type Id = Dependency<C>;
     type Value = C::ConsensusValue;
@@ -60,6 +65,9 @@ pub(crate) struct HighwayProtocol<I, C: Context> {
     synchronizer: DagSynchronizerState<I, Highway<C>>,
     finality_detector: FinalityDetector<C>,
     highway: Highway<C>,
+    /// The peers we requested the latest panorama from, that haven't responded yet, with the time
+    /// of the request.
+    panorama_requests: HashMap<I, Timestamp>,
 }
 
 impl<I: NodeIdT, C: Context> HighwayProtocol<I, C> {
@@ -73,6 +81,7 @@ impl<I: NodeIdT, C: Context> HighwayProtocol<I, C> {
             synchronizer: DagSynchronizerState::new(),
             finality_detector: FinalityDetector::new(ftt),
             highway: Highway::new(instance_id, validators, params),
+            panorama_requests: HashMap::new(),
         }
     }
 
@@ -114,9 +123,32 @@ impl<I: NodeIdT, C: Context> HighwayProtocol<I, C> {
             AvEffect::WeEquivocated(evidence) => {
                 panic!("this validator equivocated: {:?}", evidence);
             }
+            AvEffect::RequestDependency(dep) => {
+                // Dependencies are requested from the peer that cited them, in
+                // `process_peer_av_effects`.
+                warn!(?dep, "no peer to request dependency from");
+                vec![]
+            }
         }
     }
 
+    /// Processes the effects of a message from `sender`: Missing dependencies are requested from
+    /// `sender`, since it cited them.
+    fn process_peer_av_effects<E>(&mut self, sender: &I, av_effects: E) -> Vec<CpResult<I, C>>
+    where
+        E: IntoIterator<Item = AvEffect<C>>,
+    {
+        av_effects
+            .into_iter()
+            .flat_map(|effect| match effect {
+                AvEffect::RequestDependency(dep) => {
+                    vec![request_dependency(dep, sender.clone())]
+                }
+                effect => self.process_av_effect(effect),
+            })
+            .collect()
+    }
+
     fn process_new_vertex(&mut self, v: Vertex<C>) -> Vec<CpResult<I, C>> {
         let msg = HighwayMessage::NewVertex(v);
         let serialized_msg = rmp_serde::to_vec(&msg).expect("should serialize message");
@@ -127,6 +159,28 @@ impl<I: NodeIdT, C: Context> HighwayProtocol<I, C> {
             .collect()
     }
 
+    /// Returns a message requesting `peer`'s latest panorama, unless we are already waiting for
+    /// it.
+    ///
+    /// Requests that weren't answered within `PANORAMA_REQUEST_TIMEOUT_MILLIS` are dropped, so
+    /// that a lost reply doesn't keep us from asking that peer again.
+    fn request_latest_panorama(&mut self, peer: I) -> Option<CpResult<I, C>> {
+        let now = Timestamp::now();
+        let timeout = TimeDiff::from(PANORAMA_REQUEST_TIMEOUT_MILLIS);
+        self.panorama_requests
+            .retain(|_, request_time| *request_time + timeout > now);
+        if self.panorama_requests.contains_key(&peer) {
+            return None;
+        }
+        self.panorama_requests.insert(peer.clone(), now);
+        let msg = HighwayMessage::RequestLatestPanorama;
+        let serialized_msg = rmp_serde::to_vec(&msg).expect("should serialize message");
+        Some(ConsensusProtocolResult::CreatedTargetedMessage(
+            serialized_msg,
+            peer,
+        ))
+    }
+
     fn detect_finality(&mut self) -> impl Iterator<Item = CpResult<I, C>> + '_ {
         self.finality_detector
             .run(&self.highway)
@@ -143,11 +197,23 @@ impl<I: NodeIdT, C: Context> HighwayProtocol<I, C> {
 enum HighwayMessage<C: Context> {
     NewVertex(Vertex<C>),
     RequestDependency(Dependency<C>),
+    /// A request for the recipient's latest panorama, so that a node catching up can request only
+    /// the vertices it is missing instead of replaying all of them.
+    RequestLatestPanorama,
+    /// The sender's latest panorama, in response to `RequestLatestPanorama`.
+    LatestPanorama(Panorama<C>),
 }
 
 type CpResult<I, C> =
     ConsensusProtocolResult<I, <C as Context>::ConsensusValue, <C as Context>::ValidatorId>;
 
+/// Returns a message requesting the vertex satisfying `dep` from `peer`.
+fn request_dependency<I, C: Context>(dep: Dependency<C>, peer: I) -> CpResult<I, C> {
+    let msg = HighwayMessage::RequestDependency(dep);
+    let serialized_msg = rmp_serde::to_vec(&msg).expect("should serialize message");
+    ConsensusProtocolResult::CreatedTargetedMessage(serialized_msg, peer)
+}
+
 struct SynchronizerQueue<'a, I, C: Context> {
     vertex_queue: Vec<(I, PreValidatedVertex<C>)>,
     synchronizer_effects_queue: Vec<SynchronizerEffect<I, PreValidatedVertex<C>>>,
@@ -208,13 +274,11 @@ where
     ) {
         match effect {
             SynchronizerEffect::RequestVertex(sender, missing_vid) => {
-                let msg = HighwayMessage::RequestDependency(missing_vid);
-                let serialized_msg = rmp_serde::to_vec(&msg).expect("should serialize message");
                 self.results
-                    .push(ConsensusProtocolResult::CreatedTargetedMessage(
-                        serialized_msg,
-                        sender,
-                    ));
+                    .push(request_dependency(missing_vid, sender.clone()));
+                // The sender is ahead of us, so we catch up with its whole panorama at once.
+                self.results
+                    .extend(self.hw_proto.request_latest_panorama(sender));
             }
             SynchronizerEffect::Ready(pvv) => {
                 let vv = match self.hw_proto.highway.validate_vertex(pvv) {
@@ -310,6 +374,33 @@ where
                     Ok(vec![])
                 }
             }
+            Ok(HighwayMessage::RequestLatestPanorama) => {
+                let msg = HighwayMessage::LatestPanorama(self.highway.panorama().clone());
+                let serialized_msg = rmp_serde::to_vec(&msg).expect("should serialize message");
+                Ok(vec![ConsensusProtocolResult::CreatedTargetedMessage(
+                    serialized_msg,
+                    sender,
+                )])
+            }
+            Ok(HighwayMessage::LatestPanorama(_))
+                if self.panorama_requests.remove(&sender).is_none() =>
+            {
+                info!(?sender, "ignoring unsolicited panorama");
+                Ok(vec![])
+            }
+            Ok(HighwayMessage::LatestPanorama(panorama)) => {
+                let av_effects = match self.highway.request_missing_dependencies(&panorama) {
+                    Some(av_effects) => av_effects,
+                    None => {
+                        return Ok(vec![ConsensusProtocolResult::InvalidIncomingMessage(
+                            msg,
+                            sender,
+                            Error::msg("panorama has the wrong number of validators"),
+                        )]);
+                    }
+                };
+                Ok(self.process_peer_av_effects(&sender, av_effects))
+            }
         }
     }
 
@@ -358,6 +449,37 @@ where
     fn deactivate_validator(&mut self) {
         self.highway.deactivate_validator()
     }
+
+    fn leader_counts(
+        &self,
+        start: Timestamp,
+        end: Timestamp,
+    ) -> Vec<(C::ValidatorId, LeaderCount)> {
+        self.highway
+            .validators()
+            .iter()
+            .map(|validator| validator.id().clone())
+            .zip(self.highway.leader_counts(start, end))
+            .collect()
+    }
+
+    fn evidence(&self, validator: &C::ValidatorId) -> Option<Vec<u8>> {
+        let vidx = self.highway.validators().get_index(validator)?;
+        let vv = self.highway.get_dependency(&Dependency::Evidence(vidx))?;
+        // Serialized as a gossip message, so that it can be passed on to peers as it is.
+        let msg = HighwayMessage::NewVertex(vv.into());
+        Some(rmp_serde::to_vec(&msg).expect("should serialize message"))
+    }
+
+    fn inactive_validators(&self, now: Timestamp, rounds: u64) -> Vec<C::ValidatorId> {
+        let validators = self.highway.validators();
+        self.highway
+            .inactive_validators(now, rounds)
+            .into_iter()
+            .filter_map(|vidx| validators.get_by_index(vidx))
+            .map(|validator| validator.id().clone())
+            .collect()
+    }
 }
 
 pub(crate) struct HighwaySecret {
@@ -406,3 +528,204 @@ impl Context for HighwayContext {
         true
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{components::consensus::highway_core::highway::WireVote, testing::TestRng};
+
+    type TestProtocol = HighwayProtocol<u32, HighwayContext>;
+
+    const NODE: u32 = 0;
+    const PEER: u32 = 1;
+
+    /// Returns Alice's secret, and the validators: Alice with a small stake, and Bob.
+    fn new_test_validators(rng: &mut TestRng) -> (HighwaySecret, Validators<PublicKey>) {
+        let alice_secret_key = SecretKey::random(rng);
+        let alice_public_key = PublicKey::from(&alice_secret_key);
+        let alice = HighwaySecret::new(Rc::new(alice_secret_key), alice_public_key);
+        let bob_public_key = PublicKey::random(rng);
+        let validators = vec![(alice_public_key, 1u64), (bob_public_key, 10u64)]
+            .into_iter()
+            .collect();
+        (alice, validators)
+    }
+
+    /// Returns a `HighwayProtocol` instance with the given validators, and no active validator.
+    fn new_test_protocol(validators: Validators<PublicKey>) -> TestProtocol {
+        let params = Params::new(0, 10, 2, 8, 4, u64::MAX, Timestamp::from(u64::MAX));
+        HighwayProtocol::new(hash::hash(b"test era"), validators, params, Weight(3))
+    }
+
+    /// Returns the hash of a new vote by Alice citing everything `protocol` has seen, and the
+    /// serialized message containing it.
+    fn alice_vote(
+        protocol: &TestProtocol,
+        alice: &HighwaySecret,
+        seq_number: u64,
+        timestamp: u64,
+        rng: &mut TestRng,
+    ) -> (Digest, Vec<u8>) {
+        let creator = protocol
+            .highway
+            .validators()
+            .get_index(&alice.public_key)
+            .expect("Alice should be a validator");
+        let wvote = WireVote {
+            panorama: protocol.highway.panorama().clone(),
+            creator,
+            value: None,
+            seq_number,
+            timestamp: timestamp.into(),
+            round_exp: 4,
+        };
+        let swvote = SignedWireVote::new(wvote, alice, rng);
+        let msg = HighwayMessage::NewVertex(Vertex::Vote(swvote.clone()));
+        let serialized_msg = rmp_serde::to_vec(&msg).expect("should serialize message");
+        (swvote.hash(), serialized_msg)
+    }
+
+    /// Returns the deserialized targeted messages among the results, with their recipients.
+    fn targeted_messages(
+        results: Vec<CpResult<u32, HighwayContext>>,
+    ) -> Vec<(HighwayMessage<HighwayContext>, u32)> {
+        results
+            .into_iter()
+            .filter_map(|result| match result {
+                ConsensusProtocolResult::CreatedTargetedMessage(msg, to) => Some((
+                    rmp_serde::from_read_ref(msg.as_slice()).expect("should deserialize"),
+                    to,
+                )),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Returns the serialized evidence against `validator` that `protocol` knows of.
+    fn evidence(protocol: &TestProtocol, validator: &PublicKey) -> Option<Vec<u8>> {
+        ConsensusProtocol::<_, _, _, TestRng>::evidence(protocol, validator)
+    }
+
+    #[test]
+    fn should_catch_up_with_requested_panorama_only() {
+        let mut rng = TestRng::new();
+        let (alice, validators) = new_test_validators(&mut rng);
+        let mut node = new_test_protocol(validators.clone());
+        let mut peer = new_test_protocol(validators);
+
+        // The peer has two votes by Alice.
+        let (a0, a0_msg) = alice_vote(&peer, &alice, 0, 1, &mut rng);
+        peer.handle_message(NODE, a0_msg, &mut rng).unwrap();
+        let (a1, a1_msg) = alice_vote(&peer, &alice, 1, 2, &mut rng);
+        peer.handle_message(NODE, a1_msg.clone(), &mut rng).unwrap();
+
+        // A panorama we didn't ask for is ignored.
+        let msg = HighwayMessage::LatestPanorama(peer.highway.panorama().clone());
+        let serialized_msg = rmp_serde::to_vec(&msg).expect("should serialize message");
+        let results = node.handle_message(PEER, serialized_msg, &mut rng).unwrap();
+        assert!(results.is_empty());
+
+        // When the peer sends us a vote we can't add yet, we request its missing dependency, and
+        // the peer's latest panorama.
+        let results = node.handle_message(PEER, a1_msg, &mut rng).unwrap();
+        let mut messages = targeted_messages(results).into_iter();
+        match messages.next() {
+            Some((HighwayMessage::RequestDependency(Dependency::Vote(vhash)), PEER)) => {
+                assert_eq!(a0, vhash)
+            }
+            other => panic!("unexpected message: {:?}", other),
+        }
+        let request_msg = match messages.next() {
+            Some((msg @ HighwayMessage::RequestLatestPanorama, PEER)) => {
+                rmp_serde::to_vec(&msg).expect("should serialize message")
+            }
+            other => panic!("unexpected message: {:?}", other),
+        };
+        assert!(messages.next().is_none());
+
+        // The peer responds with its panorama, citing Alice's latest vote.
+        let results = peer.handle_message(NODE, request_msg, &mut rng).unwrap();
+        let response_msg = match targeted_messages(results).pop() {
+            Some((msg @ HighwayMessage::LatestPanorama(_), NODE)) => {
+                rmp_serde::to_vec(&msg).expect("should serialize message")
+            }
+            other => panic!("unexpected message: {:?}", other),
+        };
+
+        // We request the latest vote we are missing from the peer that cited it, but only in
+        // response to our own request.
+        let results = node
+            .handle_message(PEER, response_msg.clone(), &mut rng)
+            .unwrap();
+        assert!(results.iter().all(|result| matches!(
+            result,
+            ConsensusProtocolResult::CreatedTargetedMessage(_, PEER)
+        )));
+        let mut messages = targeted_messages(results).into_iter();
+        match messages.next() {
+            Some((HighwayMessage::RequestDependency(Dependency::Vote(vhash)), PEER)) => {
+                assert_eq!(a1, vhash)
+            }
+            other => panic!("unexpected message: {:?}", other),
+        }
+        assert!(messages.next().is_none());
+        let results = node.handle_message(PEER, response_msg, &mut rng).unwrap();
+        assert!(results.is_empty());
+    }
+
+    #[test]
+    fn should_request_panorama_again_after_lost_reply() {
+        let mut rng = TestRng::new();
+        let (alice, validators) = new_test_validators(&mut rng);
+        let mut node = new_test_protocol(validators.clone());
+        let mut peer = new_test_protocol(validators);
+
+        // The peer has two votes by Alice, and sends us the second one.
+        let (_, a0_msg) = alice_vote(&peer, &alice, 0, 1, &mut rng);
+        peer.handle_message(NODE, a0_msg, &mut rng).unwrap();
+        let (_, a1_msg) = alice_vote(&peer, &alice, 1, 2, &mut rng);
+        peer.handle_message(NODE, a1_msg.clone(), &mut rng).unwrap();
+        let panorama_requests = |results| {
+            targeted_messages(results)
+                .into_iter()
+                .filter(|msg| matches!(msg, (HighwayMessage::RequestLatestPanorama, PEER)))
+                .count()
+        };
+
+        // We request the peer's panorama only once while waiting for the reply.
+        let results = node.handle_message(PEER, a1_msg.clone(), &mut rng).unwrap();
+        assert_eq!(panorama_requests(results), 1);
+        let results = node.handle_message(PEER, a1_msg.clone(), &mut rng).unwrap();
+        assert_eq!(panorama_requests(results), 0);
+
+        // Once the request timed out, the reply is considered lost, and we ask again.
+        for request_time in node.panorama_requests.values_mut() {
+            *request_time = Timestamp::zero();
+        }
+        let results = node.handle_message(PEER, a1_msg, &mut rng).unwrap();
+        assert_eq!(panorama_requests(results), 1);
+        assert_eq!(node.panorama_requests.len(), 1);
+    }
+
+    #[test]
+    fn should_return_evidence_of_equivocation() {
+        let mut rng = TestRng::new();
+        let (alice, validators) = new_test_validators(&mut rng);
+        let mut node = new_test_protocol(validators.clone());
+
+        // Alice signs two different votes with the same sequence number.
+        let (_, a0_msg) = alice_vote(&node, &alice, 0, 1, &mut rng);
+        let (_, a0_prime_msg) = alice_vote(&node, &alice, 0, 2, &mut rng);
+        node.handle_message(PEER, a0_msg, &mut rng).unwrap();
+        assert!(evidence(&node, &alice.public_key).is_none());
+        node.handle_message(PEER, a0_prime_msg, &mut rng).unwrap();
+
+        // The evidence can be passed on as it is, and convinces other nodes, too.
+        let alice_evidence = evidence(&node, &alice.public_key).expect("should have evidence");
+        let mut other_node = new_test_protocol(validators);
+        other_node
+            .handle_message(NODE, alice_evidence, &mut rng)
+            .unwrap();
+        assert!(evidence(&other_node, &alice.public_key).is_some());
+    }
+}
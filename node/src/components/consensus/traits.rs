@@ -34,6 +34,10 @@ pub(crate) trait ValidatorSecret {
 
     type Signature: Eq + PartialEq + Clone + Debug + Hash + Serialize + DeserializeOwned;
 
+    /// Signs `hash`.
+    ///
+    /// Any randomness must be drawn from `rng` only, so that signatures are reproducible: given a
+    /// seeded RNG, e.g. `TestRng::from_seed`, the same inputs always yield the same signature.
     fn sign<R: Rng + CryptoRng + ?Sized>(&self, hash: &Self::Hash, rng: &mut R) -> Self::Signature;
 }
 
@@ -4,33 +4,53 @@ mod config;
 pub use config::Config;
 
 use std::{
+    collections::BTreeMap,
     fmt::{self, Debug, Display, Formatter},
     sync::Arc,
-    time::Instant,
+    time::{Duration, Instant},
 };
 
 use derive_more::From;
+use futures::{
+    future::{self, Either},
+    pin_mut,
+};
 use lmdb::DatabaseFlags;
-use prometheus::{self, Histogram, HistogramOpts, Registry};
+use prometheus::{self, Histogram, HistogramOpts, IntCounter, Registry};
 use rand::{CryptoRng, Rng};
 use thiserror::Error;
-use tokio::task;
-use tracing::trace;
+use tokio::{sync::watch, task, time};
+use tracing::{trace, warn};
 
 use casper_execution_engine::{
-    core::engine_state::{genesis::GenesisResult, EngineConfig, EngineState, Error},
-    shared::newtypes::CorrelationId,
+    core::{
+        engine_state::{
+            execute_request::ExecuteRequest, execution_result::ExecutionResult,
+            genesis::GenesisResult, BalanceResult, EngineConfig, EngineState, Error, QueryRequest,
+            QueryResult, RootNotFound,
+        },
+        execution,
+    },
+    shared::{
+        additive_map::AdditiveMap, newtypes::CorrelationId, stored_value::StoredValue,
+        transform::Transform, TypeMismatch,
+    },
     storage::{
-        error::lmdb::Error as StorageLmdbError, global_state::lmdb::LmdbGlobalState,
+        error::lmdb::Error as StorageLmdbError,
+        global_state::{lmdb::LmdbGlobalState, CommitResult},
         protocol_data_store::lmdb::LmdbProtocolDataStore,
-        transaction_source::lmdb::LmdbEnvironment, trie_store::lmdb::LmdbTrieStore,
+        transaction_source::lmdb::LmdbEnvironment,
+        trie_store::lmdb::LmdbTrieStore,
     },
 };
-use casper_types::ProtocolVersion;
+use casper_types::{contracts::NamedKeys, ContractHash, Key, ProtocolVersion, URef, U512};
 
 use crate::{
     components::Component,
-    crypto::hash,
+    crypto::{
+        asymmetric_key::PublicKey,
+        hash::{self, Digest},
+    },
     effect::{requests::ContractRuntimeRequest, EffectBuilder, EffectExt, Effects},
     Chainspec, StorageConfig,
 };
@@ -39,6 +59,11 @@ use crate::{
 pub(crate) struct ContractRuntime {
     engine_state: Arc<EngineState<LmdbGlobalState>>,
     metrics: Arc<ContractRuntimeMetrics>,
+    /// The maximum time to wait for a dry run, if limited.
+    max_execution_time: Option<Duration>,
+    /// Signals cancellation to pending execute and commit tasks when dropped, i.e. on shutdown.
+    _cancellation_sender: watch::Sender<bool>,
+    cancellation: watch::Receiver<bool>,
 }
 
 impl Debug for ContractRuntime {
@@ -70,6 +95,12 @@ pub struct ContractRuntimeMetrics {
     apply_effect: Histogram,
     commit_upgrade: Histogram,
     run_query: Histogram,
+    get_balance: Histogram,
+    warm_cache: Histogram,
+    commit_transform_count: Histogram,
+    commits_attempted: IntCounter,
+    commits_succeeded: IntCounter,
+    commits_failed: IntCounter,
 }
 
 /// Value of upper bound of histogram.
@@ -79,14 +110,33 @@ const EXPONENTIAL_BUCKET_FACTOR: f64 = 2.0;
 /// Bucket count, with last going to +Inf.
 const EXPONENTIAL_BUCKET_COUNT: usize = 6;
 
+/// Value of upper bound of the first transform count bucket.
+const TRANSFORM_COUNT_BUCKET_START: f64 = 1.0;
+/// Multiplier of previous upper bound for next transform count bound.
+const TRANSFORM_COUNT_BUCKET_FACTOR: f64 = 4.0;
+/// Transform count bucket count, with last going to +Inf.
+const TRANSFORM_COUNT_BUCKET_COUNT: usize = 8;
+
 const RUN_EXECUTE_NAME: &str = "contract_runtime_run_execute";
 const RUN_EXECUTE_HELP: &str = "tracking run of engine_state.run_execute.";
 const APPLY_EFFECT_NAME: &str = "contract_runtime_apply_commit";
 const APPLY_EFFECT_HELP: &str = "tracking run of engine_state.apply_effect.";
 const RUN_QUERY_NAME: &str = "contract_runtime_run_query";
 const RUN_QUERY_HELP: &str = "tracking run of engine_state.run_query.";
+const GET_BALANCE_NAME: &str = "contract_runtime_get_balance";
+const GET_BALANCE_HELP: &str = "tracking duration of purse balance queries.";
 const COMMIT_UPGRADE_NAME: &str = "contract_runtime_commit_upgrade";
 const COMMIT_UPGRADE_HELP: &str = "tracking run of engine_state.commit_upgrade";
+const WARM_CACHE_NAME: &str = "contract_runtime_warm_cache";
+const WARM_CACHE_HELP: &str = "tracking duration of global state cache warm-up.";
+const COMMIT_TRANSFORM_COUNT_NAME: &str = "contract_runtime_commit_transform_count";
+const COMMIT_TRANSFORM_COUNT_HELP: &str = "tracking number of transforms per commit.";
+const COMMITS_ATTEMPTED_NAME: &str = "contract_runtime_commits_attempted";
+const COMMITS_ATTEMPTED_HELP: &str = "number of attempted commits of execution effects.";
+const COMMITS_SUCCEEDED_NAME: &str = "contract_runtime_commits_succeeded";
+const COMMITS_SUCCEEDED_HELP: &str = "number of successful commits of execution effects.";
+const COMMITS_FAILED_NAME: &str = "contract_runtime_commits_failed";
+const COMMITS_FAILED_HELP: &str = "number of failed commits of execution effects.";
 
 /// Create prometheus Histogram and register.
 fn register_histogram_metric(
@@ -99,12 +149,33 @@ fn register_histogram_metric(
         EXPONENTIAL_BUCKET_FACTOR,
         EXPONENTIAL_BUCKET_COUNT,
     )?;
-    let histogram_opts = HistogramOpts::new(metric_name, metric_help).buckets(common_buckets);
+    register_histogram_metric_with_buckets(registry, metric_name, metric_help, common_buckets)
+}
+
+/// Create prometheus Histogram with the given buckets and register.
+fn register_histogram_metric_with_buckets(
+    registry: &Registry,
+    metric_name: &str,
+    metric_help: &str,
+    buckets: Vec<f64>,
+) -> Result<Histogram, prometheus::Error> {
+    let histogram_opts = HistogramOpts::new(metric_name, metric_help).buckets(buckets);
     let histogram = Histogram::with_opts(histogram_opts)?;
     registry.register(Box::new(histogram.clone()))?;
     Ok(histogram)
 }
 
+/// Create prometheus IntCounter and register.
+fn register_int_counter_metric(
+    registry: &Registry,
+    metric_name: &str,
+    metric_help: &str,
+) -> Result<IntCounter, prometheus::Error> {
+    let counter = IntCounter::new(metric_name, metric_help)?;
+    registry.register(Box::new(counter.clone()))?;
+    Ok(counter)
+}
+
 impl ContractRuntimeMetrics {
     /// Constructor of metrics which creates and registers metrics objects for use.
     fn new(registry: &Registry) -> Result<Self, prometheus::Error> {
@@ -116,11 +187,38 @@ impl ContractRuntimeMetrics {
                 APPLY_EFFECT_HELP,
             )?,
             run_query: register_histogram_metric(registry, RUN_QUERY_NAME, RUN_QUERY_HELP)?,
+            get_balance: register_histogram_metric(registry, GET_BALANCE_NAME, GET_BALANCE_HELP)?,
             commit_upgrade: register_histogram_metric(
                 registry,
                 COMMIT_UPGRADE_NAME,
                 COMMIT_UPGRADE_HELP,
             )?,
+            warm_cache: register_histogram_metric(registry, WARM_CACHE_NAME, WARM_CACHE_HELP)?,
+            commit_transform_count: register_histogram_metric_with_buckets(
+                registry,
+                COMMIT_TRANSFORM_COUNT_NAME,
+                COMMIT_TRANSFORM_COUNT_HELP,
+                prometheus::exponential_buckets(
+                    TRANSFORM_COUNT_BUCKET_START,
+                    TRANSFORM_COUNT_BUCKET_FACTOR,
+                    TRANSFORM_COUNT_BUCKET_COUNT,
+                )?,
+            )?,
+            commits_attempted: register_int_counter_metric(
+                registry,
+                COMMITS_ATTEMPTED_NAME,
+                COMMITS_ATTEMPTED_HELP,
+            )?,
+            commits_succeeded: register_int_counter_metric(
+                registry,
+                COMMITS_SUCCEEDED_NAME,
+                COMMITS_SUCCEEDED_HELP,
+            )?,
+            commits_failed: register_int_counter_metric(
+                registry,
+                COMMITS_FAILED_NAME,
+                COMMITS_FAILED_HELP,
+            )?,
         })
     }
 }
@@ -153,9 +251,12 @@ where
                 trace!(?execute_request, "execute");
                 let engine_state = Arc::clone(&self.engine_state);
                 let metrics = Arc::clone(&self.metrics);
+                let cancellation = self.cancellation.clone();
                 async move {
                     let correlation_id = CorrelationId::new();
-                    let result = task::spawn_blocking(move || {
+                    // Finalized blocks must be executed however long it takes: giving up on a
+                    // local timer would make the outcome depend on this node's speed.
+                    let result = run_blocking_with_timeout(None, Some(cancellation), move || {
                         let start = Instant::now();
                         let execution_result =
                             engine_state.run_execute(correlation_id, execute_request);
@@ -163,12 +264,34 @@ where
                         execution_result
                     })
                     .await
-                    .expect("should run");
+                    .and_then(|result| result.map_err(ExecuteError::RootNotFound));
                     trace!(?result, "execute result");
                     responder.respond(result).await
                 }
                 .ignore()
             }
+            Event::Request(ContractRuntimeRequest::ExecuteDryRun {
+                execute_request,
+                responder,
+            }) => {
+                trace!(?execute_request, "execute dry run");
+                let engine_state = Arc::clone(&self.engine_state);
+                let metrics = Arc::clone(&self.metrics);
+                let max_execution_time = self.max_execution_time;
+                let cancellation = self.cancellation.clone();
+                async move {
+                    let result = run_blocking_with_timeout(
+                        max_execution_time,
+                        Some(cancellation),
+                        move || execute_dry_run(&engine_state, &metrics, execute_request),
+                    )
+                    .await
+                    .and_then(|result| result.map_err(ExecuteError::RootNotFound));
+                    trace!(?result, "execute dry run result");
+                    responder.respond(result).await
+                }
+                .ignore()
+            }
             Event::Request(ContractRuntimeRequest::Commit {
                 pre_state_hash,
                 effects,
@@ -177,20 +300,20 @@ where
                 trace!(?pre_state_hash, ?effects, "commit");
                 let engine_state = Arc::clone(&self.engine_state);
                 let metrics = Arc::clone(&self.metrics);
+                let cancellation = self.cancellation.clone();
                 async move {
                     let correlation_id = CorrelationId::new();
-                    let result = task::spawn_blocking(move || {
-                        let start = Instant::now();
-                        let apply_result = engine_state.apply_effect(
+                    let result = run_blocking_with_timeout(None, Some(cancellation), move || {
+                        commit(
+                            &engine_state,
+                            &metrics,
                             correlation_id,
-                            pre_state_hash.into(),
+                            pre_state_hash,
                             effects,
-                        );
-                        metrics.apply_effect.observe(start.elapsed().as_secs_f64());
-                        apply_result
+                        )
                     })
                     .await
-                    .expect("should run");
+                    .and_then(|result| result.map_err(ExecuteError::Engine));
                     trace!(?result, "commit result");
                     responder.respond(result).await
                 }
@@ -242,10 +365,406 @@ where
                 }
                 .ignore()
             }
+            Event::Request(ContractRuntimeRequest::GetBalance {
+                state_root_hash,
+                purse,
+                responder,
+            }) => {
+                trace!(%state_root_hash, %purse, "get balance");
+                let engine_state = Arc::clone(&self.engine_state);
+                let metrics = Arc::clone(&self.metrics);
+                async move {
+                    let result = task::spawn_blocking(move || {
+                        let start = Instant::now();
+                        let result = get_balance(&engine_state, state_root_hash, purse);
+                        metrics.get_balance.observe(start.elapsed().as_secs_f64());
+                        result
+                    })
+                    .await
+                    .expect("should run");
+                    trace!(?result, "get balance result");
+                    responder.respond(result).await
+                }
+                .ignore()
+            }
+            Event::Request(ContractRuntimeRequest::NamedKeysDiff {
+                root_a,
+                root_b,
+                contract_hash,
+                responder,
+            }) => {
+                trace!(%root_a, %root_b, "named keys diff");
+                let engine_state = Arc::clone(&self.engine_state);
+                let metrics = Arc::clone(&self.metrics);
+                async move {
+                    let result = task::spawn_blocking(move || {
+                        named_keys_diff(&engine_state, &metrics, root_a, root_b, contract_hash)
+                    })
+                    .await
+                    .expect("should run");
+                    trace!(?result, "named keys diff result");
+                    responder.respond(result).await
+                }
+                .ignore()
+            }
+            Event::Request(ContractRuntimeRequest::WarmCache {
+                state_root,
+                protocol_version,
+                key_hints,
+                responder,
+            }) => {
+                trace!(%state_root, %protocol_version, count = key_hints.len(), "warm cache");
+                let protocol_version = ProtocolVersion::from_parts(
+                    protocol_version.major as u32,
+                    protocol_version.minor as u32,
+                    protocol_version.patch as u32,
+                );
+                let engine_state = Arc::clone(&self.engine_state);
+                let metrics = Arc::clone(&self.metrics);
+                async move {
+                    let result = task::spawn_blocking(move || {
+                        let start = Instant::now();
+                        let result =
+                            warm_cache(&engine_state, state_root, protocol_version, &key_hints);
+                        metrics.warm_cache.observe(start.elapsed().as_secs_f64());
+                        result
+                    })
+                    .await
+                    .expect("should run");
+                    if let Err(error) = &result {
+                        warn!(%error, "failed to warm global state cache");
+                    }
+                    responder.respond(result).await
+                }
+                .ignore()
+            }
+        }
+    }
+}
+
+/// Commits `effects` on top of the global state identified by `pre_state_hash`.
+fn commit(
+    engine_state: &EngineState<LmdbGlobalState>,
+    metrics: &ContractRuntimeMetrics,
+    correlation_id: CorrelationId,
+    pre_state_hash: Digest,
+    effects: AdditiveMap<Key, Transform>,
+) -> Result<CommitResult, Error> {
+    metrics.commit_transform_count.observe(effects.len() as f64);
+    metrics.commits_attempted.inc();
+    let start = Instant::now();
+    let apply_result = engine_state.apply_effect(correlation_id, pre_state_hash.into(), effects);
+    metrics.apply_effect.observe(start.elapsed().as_secs_f64());
+    match apply_result {
+        Ok(CommitResult::Success { .. }) => metrics.commits_succeeded.inc(),
+        Ok(_) | Err(_) => metrics.commits_failed.inc(),
+    }
+    apply_result
+}
+
+/// Error executing a set of deploys, or committing their execution effects.
+#[derive(Debug, Error)]
+pub enum ExecuteError {
+    /// The pre-state hash of the execution is not in the global state.
+    #[error("root not found: {0:?}")]
+    RootNotFound(RootNotFound),
+    /// A dry run did not finish in time.  The execution of finalized blocks never times out.
+    #[error("execution timed out after {0:?}")]
+    TimedOut(Duration),
+    /// The node shut down before execution finished.
+    #[error("cancelled due to shutdown")]
+    Cancelled,
+    /// The engine state failed to commit the execution effects.
+    #[error("engine state error: {0}")]
+    Engine(Error),
+}
+
+impl ExecuteError {
+    /// Returns whether executing the same deploys again may succeed.
+    ///
+    /// A failure of the global state database, e.g. running out of reader slots or an I/O error,
+    /// may clear up. A missing pre-state, a shutdown or any other engine error will not resolve
+    /// itself by retrying.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            ExecuteError::TimedOut(_) | ExecuteError::Engine(Error::Storage(_)) => true,
+            ExecuteError::RootNotFound(_) | ExecuteError::Cancelled | ExecuteError::Engine(_) => {
+                false
+            }
+        }
+    }
+}
+
+/// The result of executing deploys without committing their effects: each deploy's execution
+/// result, together with the transforms it would have applied to the global state.
+pub type DryRunResult = Result<Vec<(ExecutionResult, AdditiveMap<Key, Transform>)>, ExecuteError>;
+
+/// Executes the deploys of `execute_request`, but never commits their effects.
+fn execute_dry_run(
+    engine_state: &EngineState<LmdbGlobalState>,
+    metrics: &ContractRuntimeMetrics,
+    execute_request: ExecuteRequest,
+) -> Result<Vec<(ExecutionResult, AdditiveMap<Key, Transform>)>, RootNotFound> {
+    let start = Instant::now();
+    let execution_results = engine_state.run_execute(CorrelationId::new(), execute_request);
+    metrics.run_execute.observe(start.elapsed().as_secs_f64());
+    Ok(execution_results?
+        .into_iter()
+        .map(|result| {
+            let transforms = result.effect().transforms.clone();
+            (result, transforms)
+        })
+        .collect())
+}
+
+/// Runs `f` on a blocking task, giving up on it once `timeout` has elapsed, if given, or once
+/// `cancellation` signals that the node is shutting down.
+///
+/// On timeout or cancellation, the blocking task is left to finish, but its result is discarded.
+async fn run_blocking_with_timeout<F, T>(
+    timeout: Option<Duration>,
+    cancellation: Option<watch::Receiver<bool>>,
+    f: F,
+) -> Result<T, ExecuteError>
+where
+    F: FnOnce() -> T + Send + 'static,
+    T: Send + 'static,
+{
+    let join_handle = task::spawn_blocking(f);
+    let run = async move {
+        match timeout {
+            Some(timeout) => time::timeout(timeout, join_handle)
+                .await
+                .map_err(|_| ExecuteError::TimedOut(timeout)),
+            None => Ok(join_handle.await),
+        }
+    };
+    let result = match cancellation {
+        Some(cancellation) => {
+            let cancelled = cancelled(cancellation);
+            pin_mut!(run, cancelled);
+            match future::select(run, cancelled).await {
+                Either::Left((result, _)) => result?,
+                Either::Right(_) => return Err(ExecuteError::Cancelled),
+            }
+        }
+        None => run.await?,
+    };
+    Ok(result.expect("should run"))
+}
+
+/// Resolves once `true` is sent on `cancellation`, or its sender is dropped.
+async fn cancelled(mut cancellation: watch::Receiver<bool>) {
+    while let Some(cancel) = cancellation.recv().await {
+        if cancel {
+            return;
+        }
+    }
+}
+
+/// The differences between a contract's named keys under two state roots.
+///
+/// A name whose key changed, other than in the access rights of a `URef`, appears both as removed
+/// (with its old key) and as added (with its new key).
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct NamedKeysDiff {
+    /// Named keys only present under the second state root.
+    pub added: NamedKeys,
+    /// Named keys only present under the first state root.
+    pub removed: NamedKeys,
+    /// Named `URef`s with the same address but different access rights, as `(old, new)`.
+    pub rights_changed: BTreeMap<String, (URef, URef)>,
+}
+
+impl NamedKeysDiff {
+    /// Compares the named keys `before` with the named keys `after`.
+    fn new(before: &NamedKeys, after: &NamedKeys) -> Self {
+        let mut diff = NamedKeysDiff::default();
+        for (name, old_key) in before {
+            match (old_key, after.get(name)) {
+                (_, Some(new_key)) if new_key == old_key => (),
+                (Key::URef(old_uref), Some(Key::URef(new_uref)))
+                    if old_uref.addr() == new_uref.addr() =>
+                {
+                    let _ = diff
+                        .rights_changed
+                        .insert(name.clone(), (*old_uref, *new_uref));
+                }
+                (_, new_key) => {
+                    let _ = diff.removed.insert(name.clone(), *old_key);
+                    if let Some(new_key) = new_key {
+                        let _ = diff.added.insert(name.clone(), *new_key);
+                    }
+                }
+            }
+        }
+        for (name, new_key) in after {
+            if !before.contains_key(name) {
+                let _ = diff.added.insert(name.clone(), *new_key);
+            }
+        }
+        diff
+    }
+
+    /// Returns `true` if the named keys are identical.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.rights_changed.is_empty()
+    }
+}
+
+/// Reads the named keys of the contract stored under `contract_hash` at `state_root`.
+///
+/// A contract which is missing under `state_root` is treated as having no named keys.  Returns
+/// `None` if `state_root` is unknown.
+fn contract_named_keys(
+    engine_state: &EngineState<LmdbGlobalState>,
+    metrics: &ContractRuntimeMetrics,
+    state_root: Digest,
+    contract_hash: ContractHash,
+) -> Result<Option<NamedKeys>, Error> {
+    let query_request = QueryRequest::new(state_root.into(), Key::Hash(contract_hash), vec![]);
+    let start = Instant::now();
+    let result = engine_state.run_query(CorrelationId::new(), query_request);
+    metrics.run_query.observe(start.elapsed().as_secs_f64());
+    match result? {
+        QueryResult::Success(StoredValue::Contract(contract)) => {
+            Ok(Some(contract.named_keys().clone()))
+        }
+        QueryResult::Success(stored_value) => Err(Error::Exec(execution::Error::TypeMismatch(
+            TypeMismatch::new("Contract".to_string(), stored_value.type_name()),
+        ))),
+        // With an empty path, there are no references to follow.
+        QueryResult::ValueNotFound(_) | QueryResult::CircularReference(_) => {
+            Ok(Some(NamedKeys::new()))
+        }
+        QueryResult::RootNotFound => Ok(None),
+    }
+}
+
+/// Compares the named keys of the contract stored under `contract_hash` at `root_a` with those at
+/// `root_b`.
+///
+/// A contract which is missing under either state root is treated as having no named keys.  Returns
+/// `None` if either state root is unknown.
+fn named_keys_diff(
+    engine_state: &EngineState<LmdbGlobalState>,
+    metrics: &ContractRuntimeMetrics,
+    root_a: Digest,
+    root_b: Digest,
+    contract_hash: ContractHash,
+) -> Result<Option<NamedKeysDiff>, Error> {
+    let before = contract_named_keys(engine_state, metrics, root_a, contract_hash)?;
+    let after = contract_named_keys(engine_state, metrics, root_b, contract_hash)?;
+    match (before, after) {
+        (Some(before), Some(after)) => Ok(Some(NamedKeysDiff::new(&before, &after))),
+        _ => Ok(None),
+    }
+}
+
+/// Pre-reads the global state at `state_root`, so that the trie nodes read by the first blocks are
+/// in the LMDB page cache by the time they're needed.
+///
+/// The trie root is checked out once, and the system contracts of `protocol_version` followed by
+/// each of the `key_hints` are read through that single checkout.  Keys which are not present
+/// under `state_root` are skipped.  The global state is never written.
+fn warm_cache(
+    engine_state: &EngineState<LmdbGlobalState>,
+    state_root: Digest,
+    protocol_version: ProtocolVersion,
+    key_hints: &[Key],
+) -> Result<(), Error> {
+    let mut tracking_copy = match engine_state.tracking_copy(state_root.into())? {
+        Some(tracking_copy) => tracking_copy,
+        None => {
+            warn!(%state_root, "cannot warm global state cache: state root not found");
+            return Ok(());
+        }
+    };
+
+    let system_contracts = engine_state
+        .get_protocol_data(protocol_version)?
+        .map(|protocol_data| protocol_data.system_contracts())
+        .unwrap_or_default();
+    let correlation_id = CorrelationId::new();
+    for key in system_contracts
+        .into_iter()
+        .map(Key::Hash)
+        .chain(key_hints.iter().copied())
+    {
+        let _ = tracking_copy.read(correlation_id, &key)?;
+    }
+    Ok(())
+}
+
+/// Identifies the purse whose balance to query.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BalanceIdentifier {
+    /// The main purse of the account with this public key.
+    PublicKey(PublicKey),
+    /// The purse with this `URef`.
+    Purse(URef),
+}
+
+impl Display for BalanceIdentifier {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            BalanceIdentifier::PublicKey(public_key) => {
+                write!(formatter, "main purse of {}", public_key)
+            }
+            BalanceIdentifier::Purse(purse) => write!(formatter, "purse {}", purse),
         }
     }
 }
 
+/// Error querying the balance of a purse.
+#[derive(Debug, Error)]
+pub enum BalanceError {
+    /// The state root hash is not in the global state.
+    #[error("state root hash not found")]
+    RootNotFound,
+    /// There is no account for the public key.
+    #[error("no account found for public key {0}")]
+    UnknownAccount(PublicKey),
+    /// The engine state failed to read the balance.
+    #[error(transparent)]
+    Engine(#[from] Error),
+}
+
+/// Returns the balance of the purse identified by `purse` at `state_root`.
+fn get_balance(
+    engine_state: &EngineState<LmdbGlobalState>,
+    state_root: Digest,
+    purse: BalanceIdentifier,
+) -> Result<U512, BalanceError> {
+    let correlation_id = CorrelationId::new();
+    let purse_uref = match purse {
+        BalanceIdentifier::Purse(purse_uref) => purse_uref,
+        BalanceIdentifier::PublicKey(public_key) => {
+            let account_key = Key::Account(public_key.to_account_hash());
+            let query_request = QueryRequest::new(state_root.into(), account_key, vec![]);
+            match engine_state.run_query(correlation_id, query_request)? {
+                QueryResult::Success(StoredValue::Account(account)) => account.main_purse(),
+                QueryResult::Success(stored_value) => {
+                    return Err(BalanceError::Engine(Error::Exec(
+                        execution::Error::TypeMismatch(TypeMismatch::new(
+                            "Account".to_string(),
+                            stored_value.type_name(),
+                        )),
+                    )))
+                }
+                QueryResult::ValueNotFound(_) | QueryResult::CircularReference(_) => {
+                    return Err(BalanceError::UnknownAccount(public_key))
+                }
+                QueryResult::RootNotFound => return Err(BalanceError::RootNotFound),
+            }
+        }
+    };
+    match engine_state.get_purse_balance(correlation_id, state_root.into(), purse_uref)? {
+        BalanceResult::Success(balance) => Ok(balance),
+        BalanceResult::RootNotFound => Err(BalanceError::RootNotFound),
+    }
+}
+
 /// Error returned from mis-configuring the contract runtime component.
 #[derive(Debug, Error)]
 pub enum ConfigError {
@@ -288,9 +807,13 @@ impl ContractRuntime {
         let engine_state = Arc::new(EngineState::new(global_state, engine_config));
 
         let metrics = Arc::new(ContractRuntimeMetrics::new(registry)?);
+        let (cancellation_sender, cancellation) = watch::channel(false);
         Ok(ContractRuntime {
             engine_state,
             metrics,
+            max_execution_time: contract_runtime_config.max_execution_time(),
+            _cancellation_sender: cancellation_sender,
+            cancellation,
         })
     }
 
@@ -314,3 +837,413 @@ impl ContractRuntime {
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use casper_execution_engine::{
+        core::engine_state::execution_effect::ExecutionEffect,
+        shared::gas::Gas,
+        storage::{global_state::StateProvider, protocol_data::ProtocolData},
+    };
+    use casper_types::{account::AccountHash, AccessRights, CLValue, Contract, EntryPoints};
+    use tempfile::TempDir;
+
+    use super::*;
+    use crate::testing::TestRng;
+
+    const TEST_MAX_GLOBAL_STATE_SIZE: usize = 52_428_800;
+
+    /// Creates an engine state backed by an empty global state in `tempdir`, with default protocol
+    /// data for `ProtocolVersion::V1_0_0`, together with the empty state root hash.
+    fn new_engine_state(tempdir: &TempDir) -> (EngineState<LmdbGlobalState>, Digest) {
+        let environment =
+            Arc::new(LmdbEnvironment::new(tempdir.path(), TEST_MAX_GLOBAL_STATE_SIZE).unwrap());
+        let trie_store =
+            Arc::new(LmdbTrieStore::new(&environment, None, DatabaseFlags::empty()).unwrap());
+        let protocol_data_store = Arc::new(
+            LmdbProtocolDataStore::new(&environment, None, DatabaseFlags::empty()).unwrap(),
+        );
+        let global_state =
+            LmdbGlobalState::empty(environment, trie_store, protocol_data_store).unwrap();
+        global_state
+            .put_protocol_data(ProtocolVersion::V1_0_0, &ProtocolData::default())
+            .unwrap();
+        let empty_root_hash = Digest::from(global_state.empty_root_hash);
+        (
+            EngineState::new(global_state, EngineConfig::new()),
+            empty_root_hash,
+        )
+    }
+
+    /// Writes `value` under `key` on top of `state_root`, returning the new state root hash.
+    fn write(
+        engine_state: &EngineState<LmdbGlobalState>,
+        state_root: Digest,
+        key: Key,
+        value: StoredValue,
+    ) -> Digest {
+        let mut effects = AdditiveMap::new();
+        effects.insert(key, Transform::Write(value));
+        match engine_state
+            .apply_effect(CorrelationId::new(), state_root.into(), effects)
+            .unwrap()
+        {
+            CommitResult::Success { state_root } => state_root.into(),
+            commit_result => panic!("unexpected commit result: {:?}", commit_result),
+        }
+    }
+
+    #[test]
+    fn should_warm_cache_without_mutating_global_state() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let (engine_state, empty_root_hash) = new_engine_state(&tempdir);
+
+        let key = Key::Account(AccountHash::new([1; 32]));
+        let value = StoredValue::CLValue(CLValue::from_t(1u64).unwrap());
+        let state_root = write(&engine_state, empty_root_hash, key, value.clone());
+
+        // A key which isn't present shouldn't stop the warm-up.
+        let missing_key = Key::Account(AccountHash::new([2; 32]));
+        warm_cache(
+            &engine_state,
+            state_root,
+            ProtocolVersion::V1_0_0,
+            &[missing_key, key],
+        )
+        .unwrap();
+
+        // Writing the same value again on top of the warmed state root should yield the same
+        // state root, i.e. the warm-up left the global state untouched.
+        let rewritten_root = write(&engine_state, state_root, key, value.clone());
+        assert_eq!(rewritten_root, state_root);
+        let query_request = QueryRequest::new(state_root.into(), key, vec![]);
+        match engine_state
+            .run_query(CorrelationId::new(), query_request)
+            .unwrap()
+        {
+            QueryResult::Success(stored_value) => assert_eq!(stored_value, value),
+            query_result => panic!("unexpected query result: {:?}", query_result),
+        }
+
+        // An unknown state root is skipped rather than treated as an error.
+        let unknown_root = hash::hash(b"unknown state root");
+        warm_cache(&engine_state, unknown_root, ProtocolVersion::V1_0_0, &[key]).unwrap();
+    }
+
+    #[test]
+    fn should_get_balance_of_known_purse() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let (engine_state, empty_root_hash) = new_engine_state(&tempdir);
+
+        let purse = URef::new([1; 32], AccessRights::READ_ADD_WRITE);
+        let balance_key = Key::URef(URef::new([2; 32], AccessRights::READ_ADD_WRITE));
+        let state_root = write(
+            &engine_state,
+            empty_root_hash,
+            Key::Hash(purse.addr()),
+            StoredValue::CLValue(CLValue::from_t(balance_key).unwrap()),
+        );
+        let state_root = write(
+            &engine_state,
+            state_root,
+            balance_key,
+            StoredValue::CLValue(CLValue::from_t(U512::from(100)).unwrap()),
+        );
+
+        let balance =
+            get_balance(&engine_state, state_root, BalanceIdentifier::Purse(purse)).unwrap();
+        assert_eq!(balance, U512::from(100));
+    }
+
+    #[test]
+    fn should_report_unknown_account_when_getting_balance() {
+        let mut rng = TestRng::new();
+        let tempdir = tempfile::tempdir().unwrap();
+        let (engine_state, empty_root_hash) = new_engine_state(&tempdir);
+
+        let public_key = PublicKey::random(&mut rng);
+        match get_balance(
+            &engine_state,
+            empty_root_hash,
+            BalanceIdentifier::PublicKey(public_key),
+        ) {
+            Err(BalanceError::UnknownAccount(unknown)) => assert_eq!(unknown, public_key),
+            result => panic!("unexpected result: {:?}", result),
+        }
+    }
+
+    #[tokio::test]
+    async fn should_time_out_slow_execution() {
+        let timeout = Duration::from_millis(10);
+        let result = run_blocking_with_timeout(Some(timeout), None, || {
+            std::thread::sleep(Duration::from_millis(500));
+        })
+        .await;
+        match result {
+            Err(ExecuteError::TimedOut(elapsed)) => assert_eq!(elapsed, timeout),
+            _ => panic!("unexpected result: {:?}", result),
+        }
+    }
+
+    #[tokio::test]
+    async fn should_return_result_of_timely_execution() {
+        let with_timeout =
+            run_blocking_with_timeout(Some(Duration::from_secs(5)), None, || 1).await;
+        assert_eq!(with_timeout.unwrap(), 1);
+
+        let without_timeout = run_blocking_with_timeout(None, None, || 2).await;
+        assert_eq!(without_timeout.unwrap(), 2);
+
+        let (_sender, cancellation) = watch::channel(false);
+        let not_cancelled = run_blocking_with_timeout(None, Some(cancellation), || 3).await;
+        assert_eq!(not_cancelled.unwrap(), 3);
+    }
+
+    #[tokio::test]
+    async fn should_cancel_execution_on_shutdown() {
+        let (sender, cancellation) = watch::channel(false);
+        let execution = run_blocking_with_timeout(None, Some(cancellation), || {
+            std::thread::sleep(Duration::from_millis(500));
+        });
+        // Dropping the sender, as happens when the contract runtime is dropped, cancels the
+        // execution.
+        drop(sender);
+        match execution.await {
+            Err(ExecuteError::Cancelled) => (),
+            result => panic!("unexpected result: {:?}", result),
+        }
+    }
+
+    #[test]
+    fn should_only_retry_transient_execute_errors() {
+        let storage_error = StorageLmdbError::Lmdb(lmdb::Error::ReadersFull);
+        assert!(ExecuteError::Engine(Error::Storage(storage_error)).is_transient());
+        assert!(ExecuteError::TimedOut(Duration::from_secs(1)).is_transient());
+
+        let root = RootNotFound::new(Digest::default().into());
+        assert!(!ExecuteError::RootNotFound(root).is_transient());
+        assert!(!ExecuteError::Engine(Error::InsufficientPayment).is_transient());
+        assert!(!ExecuteError::Cancelled.is_transient());
+    }
+
+    #[test]
+    fn should_categorize_named_keys_changes_of_upgraded_contract() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let (engine_state, empty_root_hash) = new_engine_state(&tempdir);
+        let metrics = ContractRuntimeMetrics::new(&Registry::new()).unwrap();
+
+        let unchanged = URef::new([1; 32], AccessRights::READ);
+        let restricted = URef::new([2; 32], AccessRights::READ_ADD_WRITE);
+        let replaced = URef::new([3; 32], AccessRights::READ);
+        let removed = Key::Hash([4; 32]);
+        let added = URef::new([5; 32], AccessRights::READ);
+        let replacement = URef::new([6; 32], AccessRights::READ);
+
+        let contract = |named_keys: Vec<(&str, Key)>| {
+            let named_keys = named_keys
+                .into_iter()
+                .map(|(name, key)| (name.to_string(), key))
+                .collect();
+            StoredValue::Contract(Contract::new(
+                [0; 32],
+                [0; 32],
+                named_keys,
+                EntryPoints::default(),
+                ProtocolVersion::V1_0_0,
+            ))
+        };
+        let contract_hash = [7; 32];
+        let root_a = write(
+            &engine_state,
+            empty_root_hash,
+            Key::Hash(contract_hash),
+            contract(vec![
+                ("unchanged", Key::URef(unchanged)),
+                ("restricted", Key::URef(restricted)),
+                ("replaced", Key::URef(replaced)),
+                ("removed", removed),
+            ]),
+        );
+        let root_b = write(
+            &engine_state,
+            root_a,
+            Key::Hash(contract_hash),
+            contract(vec![
+                ("unchanged", Key::URef(unchanged)),
+                ("restricted", Key::URef(restricted.into_read())),
+                ("replaced", Key::URef(replacement)),
+                ("added", Key::URef(added)),
+            ]),
+        );
+
+        let diff = named_keys_diff(&engine_state, &metrics, root_a, root_b, contract_hash)
+            .unwrap()
+            .expect("both state roots should be known");
+        let expected_added: NamedKeys = vec![
+            ("added".to_string(), Key::URef(added)),
+            ("replaced".to_string(), Key::URef(replacement)),
+        ]
+        .into_iter()
+        .collect();
+        let expected_removed: NamedKeys = vec![
+            ("removed".to_string(), removed),
+            ("replaced".to_string(), Key::URef(replaced)),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(diff.added, expected_added);
+        assert_eq!(diff.removed, expected_removed);
+        assert_eq!(diff.rights_changed.len(), 1);
+        assert_eq!(
+            diff.rights_changed["restricted"],
+            (restricted, restricted.into_read())
+        );
+
+        // Comparing a state root with itself shows no differences.
+        let no_diff = named_keys_diff(&engine_state, &metrics, root_b, root_b, contract_hash)
+            .unwrap()
+            .unwrap();
+        assert!(no_diff.is_empty());
+
+        // A contract which didn't exist yet has no named keys.
+        let install_diff = named_keys_diff(
+            &engine_state,
+            &metrics,
+            empty_root_hash,
+            root_a,
+            contract_hash,
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(install_diff.added.len(), 4);
+        assert!(install_diff.removed.is_empty());
+    }
+
+    #[test]
+    fn should_report_transforms_of_dry_run_without_committing() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let (engine_state, empty_root_hash) = new_engine_state(&tempdir);
+        let metrics = ContractRuntimeMetrics::new(&Registry::new()).unwrap();
+
+        let key = Key::Account(AccountHash::new([1; 32]));
+        let old_value = StoredValue::CLValue(CLValue::from_t(1u64).unwrap());
+        let state_root = write(&engine_state, empty_root_hash, key, old_value.clone());
+
+        // A deploy which would overwrite the value.
+        let new_value = StoredValue::CLValue(CLValue::from_t(2u64).unwrap());
+        let mut transforms = AdditiveMap::new();
+        transforms.insert(key, Transform::Write(new_value));
+        let result = ExecutionResult::Success {
+            effect: ExecutionEffect::new(AdditiveMap::new(), transforms.clone()),
+            cost: Gas::default(),
+        };
+        let execute_request = ExecuteRequest::new(
+            state_root.into(),
+            0,
+            vec![Err(result)],
+            ProtocolVersion::V1_0_0,
+        );
+
+        let dry_run_results = execute_dry_run(&engine_state, &metrics, execute_request).unwrap();
+        match dry_run_results.as_slice() {
+            [(ExecutionResult::Success { effect, .. }, reported_transforms)] => {
+                assert_eq!(*reported_transforms, transforms);
+                assert_eq!(effect.transforms, transforms);
+            }
+            _ => panic!("unexpected dry run results: {:?}", dry_run_results),
+        }
+
+        // The global state under the state root is unchanged.
+        let query_request = QueryRequest::new(state_root.into(), key, vec![]);
+        match engine_state
+            .run_query(CorrelationId::new(), query_request)
+            .unwrap()
+        {
+            QueryResult::Success(stored_value) => assert_eq!(stored_value, old_value),
+            query_result => panic!("unexpected query result: {:?}", query_result),
+        }
+    }
+
+    #[test]
+    fn should_record_transform_count_of_commit() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let (engine_state, empty_root_hash) = new_engine_state(&tempdir);
+        let registry = Registry::new();
+        let metrics = ContractRuntimeMetrics::new(&registry).unwrap();
+
+        let mut effects = AdditiveMap::new();
+        for byte in 1..=3 {
+            let value = StoredValue::CLValue(CLValue::from_t(u64::from(byte)).unwrap());
+            effects.insert(
+                Key::Account(AccountHash::new([byte; 32])),
+                Transform::Write(value),
+            );
+        }
+        match commit(
+            &engine_state,
+            &metrics,
+            CorrelationId::new(),
+            empty_root_hash,
+            effects,
+        )
+        .unwrap()
+        {
+            CommitResult::Success { .. } => (),
+            commit_result => panic!("unexpected commit result: {:?}", commit_result),
+        }
+
+        let metric_families = registry.gather();
+        let histogram = metric_families
+            .iter()
+            .find(|family| family.get_name() == COMMIT_TRANSFORM_COUNT_NAME)
+            .expect("should have transform count metric")
+            .get_metric()[0]
+            .get_histogram();
+        assert_eq!(histogram.get_sample_count(), 1);
+        assert_eq!(histogram.get_sample_sum() as u64, 3);
+    }
+
+    #[test]
+    fn should_count_successful_and_failed_commits() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let (engine_state, empty_root_hash) = new_engine_state(&tempdir);
+        let metrics = ContractRuntimeMetrics::new(&Registry::new()).unwrap();
+
+        let mut effects = AdditiveMap::new();
+        effects.insert(
+            Key::Account(AccountHash::new([1; 32])),
+            Transform::Write(StoredValue::CLValue(CLValue::from_t(1u64).unwrap())),
+        );
+        let commit_result = commit(
+            &engine_state,
+            &metrics,
+            CorrelationId::new(),
+            empty_root_hash,
+            effects.clone(),
+        );
+        match commit_result.unwrap() {
+            CommitResult::Success { .. } => (),
+            commit_result => panic!("unexpected commit result: {:?}", commit_result),
+        }
+        assert_eq!(metrics.commits_attempted.get(), 1);
+        assert_eq!(metrics.commits_succeeded.get(), 1);
+        assert_eq!(metrics.commits_failed.get(), 0);
+
+        // Committing on top of an unknown state root fails.
+        let unknown_root_hash = Digest::from([0xff; Digest::LENGTH]);
+        let commit_result = commit(
+            &engine_state,
+            &metrics,
+            CorrelationId::new(),
+            unknown_root_hash,
+            effects,
+        );
+        match commit_result.unwrap() {
+            CommitResult::RootNotFound => (),
+            commit_result => panic!("unexpected commit result: {:?}", commit_result),
+        }
+        assert_eq!(metrics.commits_attempted.get(), 2);
+        assert_eq!(metrics.commits_succeeded.get(), 1);
+        assert_eq!(metrics.commits_failed.get(), 1);
+    }
+}
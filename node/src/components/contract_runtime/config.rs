@@ -1,9 +1,12 @@
+use std::time::Duration;
+
 use serde::{Deserialize, Serialize};
 
 use casper_execution_engine::shared::utils;
 
 const DEFAULT_MAX_GLOBAL_STATE_SIZE: usize = 805_306_368_000; // 750 GiB
 const DEFAULT_USE_SYSTEM_CONTRACTS: bool = false;
+const DEFAULT_WARM_UP_ON_STARTUP: bool = true;
 
 /// Contract runtime configuration.
 #[derive(Clone, Copy, Debug, Deserialize, Serialize)]
@@ -18,6 +21,14 @@ pub struct Config {
     ///
     /// The size should be a multiple of the OS page size.
     max_global_state_size: Option<usize>,
+    /// The maximum time in milliseconds to wait for a dry run of a set of deploys.  The execution
+    /// of finalized blocks is never limited.
+    ///
+    /// Defaults to no limit.
+    max_execution_time: Option<u64>,
+    /// Whether to pre-read the global state on startup, so that the first blocks don't execute
+    /// against a cold LMDB page cache.  Defaults to true.
+    warm_up_on_startup: Option<bool>,
 }
 
 impl Config {
@@ -33,6 +44,15 @@ impl Config {
         utils::check_multiple_of_page_size(value);
         value
     }
+
+    pub(crate) fn max_execution_time(&self) -> Option<Duration> {
+        self.max_execution_time.map(Duration::from_millis)
+    }
+
+    pub(crate) fn warm_up_on_startup(&self) -> bool {
+        self.warm_up_on_startup
+            .unwrap_or(DEFAULT_WARM_UP_ON_STARTUP)
+    }
 }
 
 impl Default for Config {
@@ -40,6 +60,8 @@ impl Default for Config {
         Config {
             use_system_contracts: Some(DEFAULT_USE_SYSTEM_CONTRACTS),
             max_global_state_size: Some(DEFAULT_MAX_GLOBAL_STATE_SIZE),
+            max_execution_time: None,
+            warm_up_on_startup: Some(DEFAULT_WARM_UP_ON_STARTUP),
         }
     }
 }
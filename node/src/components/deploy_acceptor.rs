@@ -5,6 +5,7 @@ use std::fmt::Debug;
 
 use rand::{CryptoRng, Rng};
 use semver::Version;
+use thiserror::Error;
 use tracing::{debug, error, warn};
 
 use crate::{
@@ -14,7 +15,7 @@ use crate::{
         EffectExt, Effects,
     },
     small_network::NodeId,
-    types::{Deploy, Timestamp},
+    types::{Deploy, DeployError, TimeDiff, Timestamp},
     utils::Source,
 };
 
@@ -34,6 +35,50 @@ impl<REv> ReactorEventT for REv where
 {
 }
 
+/// The reason for rejecting a `Deploy`.
+#[derive(Debug, Error)]
+pub enum Error {
+    /// The deploy is for a different chain.
+    #[error("invalid chain name {deploy_chain_name}, expected {chain_name}")]
+    InvalidChainName {
+        /// The chain name in the deploy's header.
+        deploy_chain_name: String,
+        /// The name of our chain.
+        chain_name: String,
+    },
+
+    /// The deploy has too many dependencies.
+    #[error("{dependencies} dependencies exceed the maximum of {max_dependencies}")]
+    ExcessiveDependencies {
+        /// The number of dependencies of the deploy.
+        dependencies: usize,
+        /// The maximum permitted number of dependencies.
+        max_dependencies: u8,
+    },
+
+    /// The deploy's time-to-live is too long.
+    #[error("time-to-live of {ttl} exceeds the maximum of {max_ttl}")]
+    ExcessiveTimeToLive {
+        /// The deploy's time-to-live.
+        ttl: TimeDiff,
+        /// The maximum permitted time-to-live.
+        max_ttl: TimeDiff,
+    },
+
+    /// The deploy has expired.
+    #[error("expired at {expired_at}, current time is {now}")]
+    Expired {
+        /// The time at which the deploy expired.
+        expired_at: Timestamp,
+        /// The time at which the deploy was validated.
+        now: Timestamp,
+    },
+
+    /// One of the deploy's approvals is invalid.
+    #[error("{0}")]
+    InvalidApproval(DeployError),
+}
+
 /// The `DeployAcceptor` is the component which handles all new `Deploy`s immediately after they're
 /// received by this node, regardless of whether they were provided by a peer or a client.
 ///
@@ -73,19 +118,20 @@ impl DeployAcceptor {
         source: Source<NodeId>,
         chainspec: Chainspec,
     ) -> Effects<Event> {
-        if is_valid(&*deploy, chainspec) {
-            let cloned_deploy = deploy.clone();
-            effect_builder
-                .put_deploy_to_storage(cloned_deploy)
-                .event(move |is_new| Event::PutToStorageResult {
-                    deploy,
-                    source,
-                    is_new,
-                })
-        } else {
-            effect_builder
-                .announce_invalid_deploy(deploy, source)
-                .ignore()
+        match validate_deploy(&*deploy, chainspec) {
+            Ok(()) => {
+                let cloned_deploy = deploy.clone();
+                effect_builder
+                    .put_deploy_to_storage(cloned_deploy)
+                    .event(move |is_new| Event::PutToStorageResult {
+                        deploy,
+                        source,
+                        is_new,
+                    })
+            }
+            Err(error) => effect_builder
+                .announce_invalid_deploy(deploy, source, error)
+                .ignore(),
         }
     }
 
@@ -145,7 +191,7 @@ impl<REv: ReactorEventT, R: Rng + CryptoRng + ?Sized> Component<REv, R> for Depl
     }
 }
 
-fn is_valid(deploy: &Deploy, chainspec: Chainspec) -> bool {
+fn validate_deploy(deploy: &Deploy, chainspec: Chainspec) -> Result<(), Error> {
     if deploy.header().chain_name() != chainspec.genesis.name {
         warn!(
             deploy_hash = %deploy.id(),
@@ -153,7 +199,10 @@ fn is_valid(deploy: &Deploy, chainspec: Chainspec) -> bool {
             chain_name = %chainspec.genesis.name,
             "deploy ttl excessive"
         );
-        return false;
+        return Err(Error::InvalidChainName {
+            deploy_chain_name: deploy.header().chain_name().to_string(),
+            chain_name: chainspec.genesis.name,
+        });
     }
 
     if deploy.header().dependencies().len()
@@ -165,7 +214,10 @@ fn is_valid(deploy: &Deploy, chainspec: Chainspec) -> bool {
             max_dependencies = %chainspec.genesis.deploy_config.max_dependencies,
             "deploy ttl excessive"
         );
-        return false;
+        return Err(Error::ExcessiveDependencies {
+            dependencies: deploy.header().dependencies().len(),
+            max_dependencies: chainspec.genesis.deploy_config.max_dependencies,
+        });
     }
 
     if deploy.header().ttl() > chainspec.genesis.deploy_config.max_ttl {
@@ -175,7 +227,10 @@ fn is_valid(deploy: &Deploy, chainspec: Chainspec) -> bool {
             max_ttl = %chainspec.genesis.deploy_config.max_ttl,
             "deploy ttl excessive"
         );
-        return false;
+        return Err(Error::ExcessiveTimeToLive {
+            ttl: deploy.header().ttl(),
+            max_ttl: chainspec.genesis.deploy_config.max_ttl,
+        });
     }
 
     let now = Timestamp::now();
@@ -186,10 +241,22 @@ fn is_valid(deploy: &Deploy, chainspec: Chainspec) -> bool {
             %now,
             "deploy expired"
         );
-        return false;
+        return Err(Error::Expired {
+            expired_at: deploy.header().expires(),
+            now,
+        });
+    }
+
+    if let Err(error) = deploy.verify_approvals() {
+        warn!(
+            deploy_hash = %deploy.id(),
+            %error,
+            "deploy has invalid approval"
+        );
+        return Err(Error::InvalidApproval(error));
     }
 
     // TODO - check if there is more that can be validated here.
 
-    true
+    Ok(())
 }
@@ -4,7 +4,7 @@
 //! a new block. Upon request, it returns a list of candidates that can be included.
 
 use std::{
-    collections::{HashMap, HashSet},
+    collections::{BTreeSet, HashMap, HashSet},
     fmt::{self, Display, Formatter},
 };
 
@@ -16,6 +16,7 @@ use tracing::{error, info};
 use crate::{
     components::{chainspec_loader::DeployConfig, storage::Storage, Component},
     effect::{
+        announcements::DeployBufferAnnouncement,
         requests::{DeployBufferRequest, StorageRequest},
         EffectBuilder, EffectExt, Effects, Responder,
     },
@@ -75,21 +76,53 @@ impl Display for Event {
     }
 }
 
+/// The reason for dropping a deploy from the deploy buffer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DropReason {
+    /// The buffer was full, and the deploy had the lowest priority of all buffered deploys.
+    BufferFull,
+}
+
+impl Display for DropReason {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            DropReason::BufferFull => write!(f, "buffer full"),
+        }
+    }
+}
+
+/// The priority of a buffered deploy: its gas price, expiry and hash.
+///
+/// When the buffer is full, the deploy paying the least is dropped first, and among those the one
+/// closest to expiry.
+type Priority = (u64, Timestamp, DeployHash);
+
+fn priority(hash: DeployHash, header: &DeployHeader) -> Priority {
+    (header.gas_price(), header.timestamp() + header.ttl(), hash)
+}
+
 /// Deploy buffer.
 #[derive(Debug, Clone)]
 pub(crate) struct DeployBuffer {
     block_max_deploy_count: usize,
+    /// The maximum number of deploys held in `collected_deploys` before the lowest-priority ones
+    /// are dropped.
+    max_collected_deploys: usize,
     collected_deploys: HashMap<DeployHash, DeployHeader>,
+    /// The priorities of the deploys in `collected_deploys`, lowest first.
+    collected_priorities: BTreeSet<Priority>,
     processed: HashMap<ProtoBlockHash, HashMap<DeployHash, DeployHeader>>,
     finalized: HashMap<ProtoBlockHash, HashMap<DeployHash, DeployHeader>>,
 }
 
 impl DeployBuffer {
     /// Creates a new, empty deploy buffer instance.
-    pub(crate) fn new(block_max_deploy_count: usize) -> Self {
+    pub(crate) fn new(block_max_deploy_count: usize, max_collected_deploys: usize) -> Self {
         DeployBuffer {
             block_max_deploy_count,
+            max_collected_deploys,
             collected_deploys: HashMap::new(),
+            collected_priorities: BTreeSet::new(),
             processed: HashMap::new(),
             finalized: HashMap::new(),
         }
@@ -97,18 +130,41 @@ impl DeployBuffer {
 
     /// Adds a deploy to the deploy buffer.
     ///
-    /// Returns `false` if the deploy has been rejected.
-    fn add_deploy(&mut self, hash: DeployHash, header: DeployHeader) {
+    /// If the buffer is full, the deploy with the lowest priority is dropped and its hash is
+    /// returned.  This may be the given deploy itself.
+    fn add_deploy(&mut self, hash: DeployHash, header: DeployHeader) -> Option<DeployHash> {
         // only add the deploy if it isn't contained in a finalized block
-        if !self
+        if self
             .finalized
             .values()
             .any(|block| block.contains_key(&hash))
         {
-            self.collected_deploys.insert(hash, header);
-            info!("added deploy {} to the buffer", hash);
-        } else {
             info!("deploy {} rejected from the buffer", hash);
+            return None;
+        }
+        self.insert_collected(hash, header);
+        info!("added deploy {} to the buffer", hash);
+
+        if self.collected_deploys.len() <= self.max_collected_deploys {
+            return None;
+        }
+        let &(_, _, dropped) = self.collected_priorities.iter().next()?;
+        self.remove_collected(&dropped);
+        info!("dropped deploy {} from the full buffer", dropped);
+        Some(dropped)
+    }
+
+    /// Inserts a deploy into `collected_deploys`, keeping `collected_priorities` in sync.
+    fn insert_collected(&mut self, hash: DeployHash, header: DeployHeader) {
+        self.remove_collected(&hash);
+        self.collected_priorities.insert(priority(hash, &header));
+        self.collected_deploys.insert(hash, header);
+    }
+
+    /// Removes a deploy from `collected_deploys`, keeping `collected_priorities` in sync.
+    fn remove_collected(&mut self, hash: &DeployHash) {
+        if let Some(header) = self.collected_deploys.remove(hash) {
+            self.collected_priorities.remove(&priority(*hash, &header));
         }
     }
 
@@ -162,6 +218,11 @@ impl DeployBuffer {
         // TODO: check gas and block size limits
     }
 
+    /// Returns the hashes of all buffered deploys that have not been proposed in a block yet.
+    fn pending_deploys(&self) -> Vec<DeployHash> {
+        self.collected_deploys.keys().copied().collect()
+    }
+
     /// Checks if a deploy is valid (for inclusion into the next block).
     fn is_deploy_valid(
         &self,
@@ -199,16 +260,18 @@ impl DeployBuffer {
                     .map(|deploy| (deploy_hash, deploy.clone()))
             })
             .collect();
-        self.collected_deploys
-            .retain(|deploy_hash, _| !deploy_map.contains_key(deploy_hash));
+        for deploy_hash in deploy_map.keys() {
+            self.remove_collected(deploy_hash);
+        }
         self.processed.insert(block, deploy_map);
     }
 
     /// Notifies the deploy buffer that a block has been finalized.
     fn finalized_block(&mut self, block: ProtoBlockHash) {
         if let Some(deploys) = self.processed.remove(&block) {
-            self.collected_deploys
-                .retain(|deploy_hash, _| !deploys.contains_key(deploy_hash));
+            for deploy_hash in deploys.keys() {
+                self.remove_collected(deploy_hash);
+            }
             self.finalized.insert(block, deploys);
         } else if !block.is_empty() {
             // TODO: Events are not guaranteed to be handled in order, so this could happen!
@@ -219,7 +282,9 @@ impl DeployBuffer {
     /// Notifies the deploy buffer that a block has been orphaned.
     fn orphaned_block(&mut self, block: ProtoBlockHash) {
         if let Some(deploys) = self.processed.remove(&block) {
-            self.collected_deploys.extend(deploys);
+            for (deploy_hash, header) in deploys {
+                self.insert_collected(deploy_hash, header);
+            }
         } else {
             // TODO: Events are not guaranteed to be handled in order, so this could happen!
             error!("orphaned block that hasn't been processed!");
@@ -229,7 +294,7 @@ impl DeployBuffer {
 
 impl<REv, R> Component<REv, R> for DeployBuffer
 where
-    REv: From<StorageRequest<Storage>> + Send,
+    REv: From<StorageRequest<Storage>> + From<DeployBufferAnnouncement> + Send,
     R: Rng + CryptoRng + ?Sized,
 {
     type Event = Event;
@@ -253,7 +318,16 @@ where
                     responder,
                 );
             }
-            Event::Buffer { hash, header } => self.add_deploy(hash, *header),
+            Event::Request(DeployBufferRequest::ListPending { responder }) => {
+                return responder.respond(self.pending_deploys()).ignore();
+            }
+            Event::Buffer { hash, header } => {
+                if let Some(dropped) = self.add_deploy(hash, *header) {
+                    return effect_builder
+                        .announce_deploy_dropped(dropped, DropReason::BufferFull)
+                        .ignore();
+                }
+            }
             Event::ProposedProtoBlock(block) => {
                 let (hash, deploys, _) = block.destructure();
                 self.added_block(hash, deploys)
@@ -289,8 +363,10 @@ mod tests {
     use super::*;
     use crate::{
         crypto::{asymmetric_key::SecretKey, hash::hash},
+        reactor::{validator, EventQueueHandle, QueueKind, Scheduler},
         testing::TestRng,
         types::{Deploy, DeployHash, DeployHeader, NodeConfig, ProtoBlockHash, TimeDiff},
+        utils,
     };
 
     fn generate_deploy(
@@ -298,9 +374,27 @@ mod tests {
         timestamp: Timestamp,
         ttl: TimeDiff,
         dependencies: Vec<DeployHash>,
+    ) -> (DeployHash, DeployHeader) {
+        generate_deploy_with(rng, timestamp, ttl, 10, dependencies)
+    }
+
+    fn generate_deploy_with_gas_price(
+        rng: &mut TestRng,
+        timestamp: Timestamp,
+        ttl: TimeDiff,
+        gas_price: u64,
+    ) -> (DeployHash, DeployHeader) {
+        generate_deploy_with(rng, timestamp, ttl, gas_price, vec![])
+    }
+
+    fn generate_deploy_with(
+        rng: &mut TestRng,
+        timestamp: Timestamp,
+        ttl: TimeDiff,
+        gas_price: u64,
+        dependencies: Vec<DeployHash>,
     ) -> (DeployHash, DeployHeader) {
         let secret_key = SecretKey::random(rng);
-        let gas_price = 10;
         let chain_name = "chain".to_string();
         let payment = ExecutableDeployItem::ModuleBytes {
             module_bytes: vec![],
@@ -326,6 +420,38 @@ mod tests {
         (*deploy.id(), deploy.take_header())
     }
 
+    /// Buffers the given deploy, and returns the hash of the deploy announced as dropped because of
+    /// it, if any.
+    async fn buffer_deploy(
+        buffer: &mut DeployBuffer,
+        scheduler: &'static Scheduler<validator::Event>,
+        rng: &mut TestRng,
+        hash: DeployHash,
+        header: DeployHeader,
+    ) -> Option<DeployHash> {
+        let effect_builder = EffectBuilder::new(EventQueueHandle::new(scheduler));
+        let header = Box::new(header);
+        let effects = buffer.handle_event(effect_builder, rng, Event::Buffer { hash, header });
+        if effects.is_empty() {
+            return None;
+        }
+        for effect in effects {
+            assert!(effect.await.is_empty());
+        }
+        match scheduler.pop().await {
+            (
+                validator::Event::DeployBufferAnnouncement(
+                    DeployBufferAnnouncement::DeployDropped { hash, reason },
+                ),
+                _,
+            ) => {
+                assert_eq!(reason, DropReason::BufferFull);
+                Some(hash)
+            }
+            (event, _) => panic!("unexpected event: {}", event),
+        }
+    }
+
     #[test]
     fn add_and_take_deploys() {
         let creation_time = Timestamp::from(100);
@@ -335,7 +461,10 @@ mod tests {
         let block_time3 = Timestamp::from(220);
 
         let no_blocks = HashSet::new();
-        let mut buffer = DeployBuffer::new(NodeConfig::default().block_max_deploy_count as usize);
+        let mut buffer = DeployBuffer::new(
+            NodeConfig::default().block_max_deploy_count as usize,
+            NodeConfig::default().deploy_buffer_max_deploy_count as usize,
+        );
         let mut rng = TestRng::new();
         let (hash1, deploy1) = generate_deploy(&mut rng, creation_time, ttl, vec![]);
         let (hash2, deploy2) = generate_deploy(&mut rng, creation_time, ttl, vec![]);
@@ -436,7 +565,10 @@ mod tests {
         let (hash2, deploy2) = generate_deploy(&mut rng, creation_time, ttl, vec![hash1]);
 
         let mut blocks = HashSet::new();
-        let mut buffer = DeployBuffer::new(NodeConfig::default().block_max_deploy_count as usize);
+        let mut buffer = DeployBuffer::new(
+            NodeConfig::default().block_max_deploy_count as usize,
+            NodeConfig::default().deploy_buffer_max_deploy_count as usize,
+        );
 
         // add deploy2
         buffer.add_deploy(hash2, deploy2);
@@ -464,4 +596,71 @@ mod tests {
         assert_eq!(deploys2.len(), 1);
         assert!(deploys2.contains(&hash2));
     }
+
+    #[tokio::test]
+    async fn should_drop_lowest_priority_deploy_when_full() {
+        let mut rng = TestRng::new();
+        let creation_time = Timestamp::from(100);
+        let mut buffer = DeployBuffer::new(10, 2);
+        let scheduler = utils::leak(Scheduler::<validator::Event>::new(QueueKind::weights()));
+
+        let (hash1, deploy1) =
+            generate_deploy_with_gas_price(&mut rng, creation_time, TimeDiff::from(100), 10);
+        let (hash2, deploy2) =
+            generate_deploy_with_gas_price(&mut rng, creation_time, TimeDiff::from(200), 5);
+        let (hash3, deploy3) =
+            generate_deploy_with_gas_price(&mut rng, creation_time, TimeDiff::from(100), 5);
+        let (hash4, deploy4) =
+            generate_deploy_with_gas_price(&mut rng, creation_time, TimeDiff::from(100), 1);
+
+        assert_eq!(
+            buffer_deploy(&mut buffer, scheduler, &mut rng, hash1, deploy1).await,
+            None
+        );
+        assert_eq!(
+            buffer_deploy(&mut buffer, scheduler, &mut rng, hash2, deploy2).await,
+            None
+        );
+
+        // Deploys 2 and 3 pay the least, and deploy 3 expires first.
+        assert_eq!(
+            buffer_deploy(&mut buffer, scheduler, &mut rng, hash3, deploy3).await,
+            Some(hash3)
+        );
+
+        // A deploy paying less than every buffered one is dropped itself.
+        assert_eq!(
+            buffer_deploy(&mut buffer, scheduler, &mut rng, hash4, deploy4).await,
+            Some(hash4)
+        );
+
+        let buffered: HashSet<_> = buffer.collected_deploys.keys().copied().collect();
+        assert_eq!(buffered, vec![hash1, hash2].into_iter().collect());
+        assert_eq!(buffer.collected_priorities.len(), 2);
+    }
+
+    #[test]
+    fn should_list_pending_deploys() {
+        let mut rng = TestRng::new();
+        let creation_time = Timestamp::from(100);
+        let ttl = TimeDiff::from(100);
+        let mut buffer = DeployBuffer::new(
+            NodeConfig::default().block_max_deploy_count as usize,
+            NodeConfig::default().deploy_buffer_max_deploy_count as usize,
+        );
+        assert!(buffer.pending_deploys().is_empty());
+
+        let (hash1, deploy1) = generate_deploy(&mut rng, creation_time, ttl, vec![]);
+        let (hash2, deploy2) = generate_deploy(&mut rng, creation_time, ttl, vec![]);
+        buffer.add_deploy(hash1, deploy1);
+        buffer.add_deploy(hash2, deploy2);
+
+        let pending: HashSet<_> = buffer.pending_deploys().into_iter().collect();
+        assert_eq!(pending, vec![hash1, hash2].into_iter().collect());
+
+        // Once proposed in a block, a deploy is no longer pending.
+        let block_hash = ProtoBlockHash::new(hash(random::<[u8; 16]>()));
+        buffer.added_block(block_hash, vec![hash1]);
+        assert_eq!(buffer.pending_deploys(), vec![hash2]);
+    }
 }
@@ -238,6 +238,7 @@ impl reactor::Reactor<TestRng> for Reactor {
             Event::DeployAcceptorAnnouncement(DeployAcceptorAnnouncement::InvalidDeploy {
                 deploy: _,
                 source: _,
+                error: _,
             }) => Effects::new(),
         }
     }
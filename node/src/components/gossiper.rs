@@ -195,16 +195,19 @@ impl<T: Item + 'static, REv: ReactorEventT<T>> Gossiper<T, REv> {
             return Effects::new();
         }
 
+        let peer_count = self.table.gossiped_to(&item_id, peers.len());
+        let mut effects = effect_builder
+            .announce_item_gossiped(item_id, peer_count)
+            .ignore();
+
         // Set timeouts to check later that the specified peers all responded.
-        peers
-            .into_iter()
-            .map(|peer| {
-                effect_builder
-                    .set_timeout(self.gossip_timeout)
-                    .map(move |_| smallvec![Event::CheckGossipTimeout { item_id, peer }])
-                    .boxed()
-            })
-            .collect()
+        effects.extend(peers.into_iter().map(|peer| {
+            effect_builder
+                .set_timeout(self.gossip_timeout)
+                .map(move |_| smallvec![Event::CheckGossipTimeout { item_id, peer }])
+                .boxed()
+        }));
+        effects
     }
 
     /// Checks that the given peer has responded to a previous gossip request we sent it.
@@ -353,6 +356,7 @@ impl<T: Item + 'static, REv: ReactorEventT<T>> Gossiper<T, REv> {
         sender: NodeId,
     ) -> Effects<Event<T>> {
         let mut effects: Effects<_> = Effects::new();
+        let was_finished = self.table.has_finished(&item_id);
         let action = if is_already_held {
             self.table.already_infected(&item_id, sender)
         } else {
@@ -364,6 +368,11 @@ impl<T: Item + 'static, REv: ReactorEventT<T>> Gossiper<T, REv> {
             self.table.we_infected(&item_id, sender)
         };
 
+        // This response may have saturated the item.
+        if !was_finished && self.table.has_finished(&item_id) {
+            effects.extend(effect_builder.announce_finished_gossiping(item_id).ignore());
+        }
+
         match action {
             GossipAction::ShouldGossip(should_gossip) => effects.extend(self.gossip(
                 effect_builder,
@@ -53,6 +53,8 @@ struct State {
     infected_by_us: HashSet<NodeId>,
     /// The count of in-flight gossip messages sent by us for this data.
     in_flight_count: usize,
+    /// The total number of peers we have forwarded this data to.
+    gossiped_to_count: usize,
 }
 
 impl State {
@@ -357,6 +359,26 @@ impl<T: Copy + Eq + Hash + Display> GossipTable<T> {
         GossipAction::Noop
     }
 
+    /// We have forwarded the data to `peer_count` further peers.
+    ///
+    /// Returns the total number of peers we have forwarded the data to so far, or just `peer_count`
+    /// if gossiping the data is no longer in progress.
+    pub(crate) fn gossiped_to(&mut self, data_id: &T, peer_count: usize) -> usize {
+        match self.current.get_mut(data_id) {
+            Some(state) => {
+                state.gossiped_to_count += peer_count;
+                state.gossiped_to_count
+            }
+            None => peer_count,
+        }
+    }
+
+    /// Returns whether gossiping the data has finished, i.e. it reached its infection target or
+    /// saturation limit.  Paused data has not finished.
+    pub(crate) fn has_finished(&self, data_id: &T) -> bool {
+        self.finished.contains_key(data_id)
+    }
+
     /// We have deemed the data not suitable for gossiping further.  If left in paused state, the
     /// entry will eventually be purged, as for finished entries.
     pub(crate) fn pause(&mut self, data_id: &T) {
@@ -799,6 +821,32 @@ mod tests {
         check_holders(&node_ids[3..4], &gossip_table, &data_id);
     }
 
+    #[test]
+    fn should_count_peers_gossiped_to() {
+        let mut rng = TestRng::new();
+        let data_id: u64 = rng.gen();
+
+        let mut gossip_table = GossipTable::new(Config::default());
+
+        // Unknown data only reports the peers of the current forward.
+        assert_eq!(2, gossip_table.gossiped_to(&data_id, 2));
+
+        // Successive forwards should accumulate the peer count.
+        let _ = gossip_table.new_complete_data(&data_id, None);
+        assert_eq!(
+            EXPECTED_DEFAULT_INFECTION_TARGET,
+            gossip_table.gossiped_to(&data_id, EXPECTED_DEFAULT_INFECTION_TARGET)
+        );
+        assert_eq!(
+            EXPECTED_DEFAULT_INFECTION_TARGET + 1,
+            gossip_table.gossiped_to(&data_id, 1)
+        );
+        assert_eq!(
+            EXPECTED_DEFAULT_INFECTION_TARGET + 3,
+            gossip_table.gossiped_to(&data_id, 2)
+        );
+    }
+
     #[test]
     fn should_not_remove_holder_if_responsive() {
         let mut rng = TestRng::new();
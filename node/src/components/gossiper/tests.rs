@@ -1,6 +1,6 @@
 #![cfg(test)]
 use std::{
-    collections::{BTreeSet, HashMap},
+    collections::{BTreeSet, HashMap, HashSet},
     fmt::{self, Debug, Display, Formatter},
     iter,
 };
@@ -26,13 +26,13 @@ use crate::{
         NetworkAnnouncement,
     },
     protocol::Message as NodeMessage,
-    reactor::{self, EventQueueHandle, Runner},
+    reactor::{self, EventQueueHandle, QueueKind, Runner, Scheduler},
     testing::{
         network::{Network, NetworkedReactor},
         ConditionCheckReactor, TestRng,
     },
     types::{Deploy, Tag},
-    utils::Loadable,
+    utils::{self, Loadable},
 };
 
 /// Top-level event for the reactor.
@@ -224,7 +224,7 @@ impl reactor::Reactor<TestRng> for Reactor {
             Event::NetworkAnnouncement(NetworkAnnouncement::GossipOurAddress(_)) => {
                 unreachable!("should not receive announcements of type GossipOurAddress");
             }
-            Event::NetworkAnnouncement(NetworkAnnouncement::NewPeer(_)) => {
+            Event::NetworkAnnouncement(NetworkAnnouncement::NewPeer { .. }) => {
                 // We do not care about new peers in the gossiper test.
                 Effects::new()
             }
@@ -248,9 +248,16 @@ impl reactor::Reactor<TestRng> for Reactor {
             Event::DeployAcceptorAnnouncement(DeployAcceptorAnnouncement::InvalidDeploy {
                 deploy: _,
                 source: _,
+                error: _,
             }) => Effects::new(),
-            Event::DeployGossiperAnnouncement(_ann) => {
-                unreachable!("the deploy gossiper should never make an announcement")
+            Event::DeployGossiperAnnouncement(GossiperAnnouncement::NewCompleteItem(_)) => {
+                unreachable!("the deploy gossiper should never announce a new complete item")
+            }
+            Event::DeployGossiperAnnouncement(GossiperAnnouncement::ItemGossiped { .. }) => {
+                Effects::new()
+            }
+            Event::DeployGossiperAnnouncement(GossiperAnnouncement::FinishedGossiping(_)) => {
+                Effects::new()
             }
         }
     }
@@ -492,3 +499,51 @@ async fn should_timeout_gossip_response() {
 
     NetworkController::<NodeMessage>::remove_active();
 }
+
+#[tokio::test]
+async fn should_announce_finished_gossiping_only_once_saturated() {
+    let mut rng = TestRng::new();
+    let scheduler = utils::leak(Scheduler::<Event>::new(QueueKind::weights()));
+    let effect_builder = EffectBuilder::new(EventQueueHandle::new(scheduler));
+
+    // With an infection target of 1 and a saturation limit of 50%, gossiping finishes as soon as
+    // two peers hold the item.
+    let default_config = Config::default();
+    let config = Config::new(
+        1,
+        50,
+        default_config.finished_entry_duration_secs(),
+        default_config.gossip_request_timeout_secs(),
+        default_config.get_remainder_timeout_secs(),
+    )
+    .unwrap();
+    let mut gossiper =
+        Gossiper::<Deploy, Event>::new_for_partial_items(config, get_deploy_from_storage);
+    let deploy_id = *Deploy::random(&mut rng).id();
+
+    // The effects of the first two steps only send gossip messages, so they are dropped.
+    let _ = gossiper.handle_item_received(effect_builder, deploy_id, Source::Client);
+    let _ = gossiper.handle_gossip_response(effect_builder, deploy_id, true, rng.gen());
+
+    // The second holder saturates the gossip, which is announced.
+    let effects = gossiper.handle_gossip_response(effect_builder, deploy_id, true, rng.gen());
+    for effect in effects {
+        assert!(effect.await.is_empty());
+    }
+    match scheduler.pop().await {
+        (Event::DeployGossiperAnnouncement(GossiperAnnouncement::FinishedGossiping(id)), _) => {
+            assert_eq!(id, deploy_id)
+        }
+        (event, _) => panic!("unexpected event: {}", event),
+    }
+
+    // Late responses don't announce it again.
+    let effects = gossiper.handle_gossip_response(effect_builder, deploy_id, true, rng.gen());
+    assert!(effects.is_empty());
+
+    // Running out of peers to gossip to only pauses gossiping, which isn't announced.
+    let paused_deploy_id = *Deploy::random(&mut rng).id();
+    let _ = gossiper.handle_item_received(effect_builder, paused_deploy_id, Source::Client);
+    let effects = gossiper.gossiped_to(effect_builder, paused_deploy_id, HashSet::new());
+    assert!(effects.is_empty());
+}
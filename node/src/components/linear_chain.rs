@@ -1,22 +1,53 @@
+#[cfg(test)]
+mod tests;
+
 use super::{storage::Storage, Component};
 use crate::{
-    components::storage::Value,
-    crypto::asymmetric_key::Signature,
+    components::{
+        consensus::EraId,
+        storage::{self, Value},
+    },
+    crypto::asymmetric_key::{self, PublicKey, Signature},
     effect::{
         self,
+        announcements::LinearChainAnnouncement,
         requests::{LinearChainRequest, StorageRequest},
-        EffectExt, Effects,
+        EffectBuilder, EffectExt, Effects, Responder,
     },
+    fatal,
     protocol::Message,
-    types::{Block, BlockHash},
+    types::{Block, BlockHash, BlockIdentifier},
 };
+use casper_execution_engine::shared::motes::Motes;
+use casper_types::U512;
 use derive_more::From;
 use effect::requests::{ConsensusRequest, NetworkRequest};
 use futures::FutureExt;
+use linked_hash_map::LinkedHashMap;
 use rand::{CryptoRng, Rng};
-use std::fmt::Display;
+use std::{collections::BTreeMap, fmt::Display, time::Duration};
 use tracing::{debug, error, warn};
 
+/// The number of attempts to put a block to storage before giving up with a fatal error.
+const MAX_PUT_BLOCK_ATTEMPTS: u32 = 5;
+/// The delay before the first retry of a failed block write.  It doubles with every further retry.
+const PUT_BLOCK_RETRY_DELAY: Duration = Duration::from_millis(100);
+/// The maximum number of eras whose validators are cached.
+const MAX_CACHED_ERAS: usize = 10;
+/// The maximum number of unknown blocks for which finality signatures are buffered.
+const MAX_PENDING_SIGNATURE_BLOCKS: usize = 100;
+/// The maximum number of blocks with an unknown parent which are buffered.
+const MAX_ORPHAN_BLOCKS: usize = 10;
+
+/// Why a block is put to storage.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PutBlockKind {
+    /// The block is new to the linear chain.
+    New,
+    /// The block is already in the linear chain and has collected further finality signatures.
+    NewSignatures,
+}
+
 #[derive(Debug, From)]
 pub enum Event<I> {
     /// A linear chain request issued by another node in the network.
@@ -26,10 +57,32 @@ pub enum Event<I> {
     LinearChainBlock(Block),
     /// A continuation for `GetBlock` scenario.
     GetBlockResult(BlockHash, Option<Block>, I),
-    /// New finality signature.
-    NewFinalitySignature(BlockHash, Signature),
-    /// The result of putting a block to storage.
-    PutBlockResult(Block),
+    /// A continuation for `BlockAtHeight` scenario.
+    GetBlockAtHeightResult(u64, Option<Block>, I),
+    /// New finality signature by the given signer.
+    NewFinalitySignature(BlockHash, PublicKey, Signature),
+    /// The result of getting the block for a new finality signature from storage.
+    GetBlockForFinalitySignatureResult(BlockHash, PublicKey, Signature, Option<Block>),
+    /// The result of getting the parent of a new linear chain block.
+    GetParentBlockResult(Block, Option<Block>),
+    /// The result of getting the validators of a new linear chain block's era from consensus.
+    GetValidatorsForBlockResult(Block, Option<BTreeMap<PublicKey, Motes>>),
+    /// The result of getting the validators of the era of the block a new finality signature is
+    /// for from consensus.
+    GetValidatorsForFinalitySignatureResult(
+        Block,
+        PublicKey,
+        Signature,
+        Option<BTreeMap<PublicKey, Motes>>,
+    ),
+    /// The result of getting the block for a finality signature weight request from storage.
+    GetBlockForSignatureWeightResult(Option<Block>, Responder<Option<Motes>>),
+    /// The result of putting a new block to storage: whether it wasn't stored before.
+    PutBlockResult(Block, bool),
+    /// A block with further finality signatures has been put to storage.
+    BlockSignaturesStored(BlockHash),
+    /// The given attempt to put a block to storage failed.
+    PutBlockFailed(Block, PutBlockKind, u32, storage::Error),
 }
 
 impl<I: Display> Display for Event<I> {
@@ -44,10 +97,66 @@ impl<I: Display> Display for Event<I> {
                 peer,
                 res.is_some()
             ),
-            Event::NewFinalitySignature(bh, _) => {
-                write!(f, "linear-chain new finality signature for block: {}", bh)
+            Event::GetBlockAtHeightResult(height, res, peer) => write!(
+                f,
+                "linear-chain get-block at height {} from {} found: {}",
+                height,
+                peer,
+                res.is_some()
+            ),
+            Event::NewFinalitySignature(bh, public_key, _) => write!(
+                f,
+                "linear-chain new finality signature for block: {} by {}",
+                bh, public_key
+            ),
+            Event::GetBlockForFinalitySignatureResult(bh, public_key, _, res) => write!(
+                f,
+                "linear-chain get-block for finality signature for {} by {} found: {}",
+                bh,
+                public_key,
+                res.is_some()
+            ),
+            Event::GetParentBlockResult(block, maybe_parent) => write!(
+                f,
+                "linear-chain get-parent-block for {} found: {}",
+                block.hash(),
+                maybe_parent.is_some()
+            ),
+            Event::GetValidatorsForBlockResult(block, maybe_validators) => write!(
+                f,
+                "linear-chain get-validators for {} found: {}",
+                block.hash(),
+                maybe_validators.is_some()
+            ),
+            Event::GetValidatorsForFinalitySignatureResult(block, _, _, maybe_validators) => {
+                write!(
+                    f,
+                    "linear-chain get-validators for finality signature for {} found: {}",
+                    block.hash(),
+                    maybe_validators.is_some()
+                )
+            }
+            Event::GetBlockForSignatureWeightResult(maybe_block, _) => write!(
+                f,
+                "linear-chain get-block for signature weight found: {}",
+                maybe_block.is_some()
+            ),
+            Event::PutBlockResult(block, newly_stored) => write!(
+                f,
+                "linear-chain put-block result for {} newly stored: {}",
+                block.hash(),
+                newly_stored
+            ),
+            Event::BlockSignaturesStored(bh) => {
+                write!(f, "linear-chain stored finality signatures for {}", bh)
             }
-            Event::PutBlockResult(_) => write!(f, "linear-chain put-block result"),
+            Event::PutBlockFailed(block, _, attempt, error) => write!(
+                f,
+                "linear-chain put-block attempt {} for {} failed: {}",
+                attempt,
+                block.hash(),
+                error
+            ),
         }
     }
 }
@@ -57,14 +166,276 @@ pub(crate) struct LinearChain<I> {
     _marker: std::marker::PhantomData<I>,
     /// The last block this component put to storage which is presumably the last block in the
     /// linear chain.
+    ///
+    /// This is `None` until we put the first block to storage after starting up, in which case the
+    /// highest block in storage is used instead.
     last_block: Option<Block>,
+    /// The highest block known to have collected signatures from validators holding at least
+    /// `finality_threshold_percent` of its era's total stake.
+    ///
+    /// This is `None` until a block reaches the threshold after starting up.
+    highest_finalized_block: Option<Block>,
+    /// Finality signatures and their signers received for blocks which were not yet in storage,
+    /// least recently buffered block first.  Only signers which are validators of a cached era are
+    /// kept.  Their signatures are added to the block once it is put to storage, if they are
+    /// validators of its era.
+    pending_signatures: LinkedHashMap<BlockHash, Vec<(PublicKey, Signature)>>,
+    /// Blocks whose parent is not in storage yet, keyed by the parent's hash, least recently
+    /// buffered first.  They are handled again once their parent is put to storage.
+    orphan_blocks: LinkedHashMap<BlockHash, Block>,
+    /// Recently added or requested blocks, least recently used first.
+    block_cache: LinkedHashMap<BlockHash, Block>,
+    /// The maximum number of blocks held in `block_cache`.
+    block_cache_size: usize,
+    /// The validators of recently seen eras with their stakes, least recently added first.
+    era_validators: LinkedHashMap<EraId, BTreeMap<PublicKey, Motes>>,
+    /// The percentage of its era's total stake whose validators need to sign a block before it is
+    /// announced as finalized and no further signatures for it are accepted.
+    finality_threshold_percent: u8,
+}
+
+/// Returns the total stake of the validators among the signers of `block`.
+fn signature_weight(block: &Block, validators: &BTreeMap<PublicKey, Motes>) -> Motes {
+    block
+        .proofs()
+        .iter()
+        .filter_map(|(public_key, _)| validators.get(public_key))
+        .copied()
+        .sum()
 }
 
 impl<I> LinearChain<I> {
-    pub fn new() -> Self {
+    pub fn new(block_cache_size: usize, finality_threshold_percent: u8) -> Self {
         LinearChain {
             _marker: std::marker::PhantomData,
             last_block: None,
+            highest_finalized_block: None,
+            pending_signatures: LinkedHashMap::new(),
+            orphan_blocks: LinkedHashMap::new(),
+            block_cache: LinkedHashMap::new(),
+            block_cache_size,
+            era_validators: LinkedHashMap::new(),
+            finality_threshold_percent,
+        }
+    }
+
+    /// Returns the cached block with the given hash, marking it as the most recently used.
+    fn get_cached_block(&mut self, block_hash: &BlockHash) -> Option<Block> {
+        self.block_cache.get_refresh(block_hash).cloned()
+    }
+
+    /// Puts `block` to storage.  If it is already stored with further finality signatures, they are
+    /// merged with those of `block`, so that none are lost.  If this is a retry, it first waits for
+    /// a delay that doubles with every attempt.
+    fn put_block<REv>(
+        effect_builder: EffectBuilder<REv>,
+        block: Block,
+        kind: PutBlockKind,
+        attempt: u32,
+    ) -> Effects<Event<I>>
+    where
+        REv: From<StorageRequest<Storage>> + Send,
+        I: Send + 'static,
+    {
+        let to_store = Box::new(block.clone());
+        async move {
+            if attempt > 1 {
+                let delay = PUT_BLOCK_RETRY_DELAY * 2u32.pow(attempt - 2);
+                let _ = effect_builder.set_timeout(delay).await;
+            }
+            match kind {
+                PutBlockKind::New => effect_builder.put_block_to_storage(to_store).await,
+                PutBlockKind::NewSignatures => {
+                    effect_builder.put_block_proofs_to_storage(to_store).await
+                }
+            }
+        }
+        .event(move |result| match (result, kind) {
+            (Ok(newly_stored), PutBlockKind::New) => Event::PutBlockResult(block, newly_stored),
+            (Ok(_), PutBlockKind::NewSignatures) => Event::BlockSignaturesStored(*block.hash()),
+            (Err(error), _) => Event::PutBlockFailed(block, kind, attempt, error),
+        })
+    }
+
+    /// Returns whether the signatures of `block` have reached the finality threshold.  This is
+    /// never the case if the validators of its era are unknown.
+    fn has_sufficient_finality(&self, block: &Block) -> bool {
+        let validators = match self.era_validators.get(&block.header().era_id()) {
+            Some(validators) => validators,
+            None => return false,
+        };
+        let total_stake: Motes = validators.values().copied().sum();
+        signature_weight(block, validators).value() * U512::from(100)
+            >= total_stake.value() * U512::from(self.finality_threshold_percent)
+    }
+
+    /// Caches the validators of `era_id`, evicting the oldest era if the cache is full.
+    fn cache_validators(&mut self, era_id: EraId, validators: BTreeMap<PublicKey, Motes>) {
+        let _ = self.era_validators.insert(era_id, validators);
+        while self.era_validators.len() > MAX_CACHED_ERAS {
+            let _ = self.era_validators.pop_front();
+        }
+    }
+
+    /// Returns whether `public_key` belongs to a validator of any cached era.
+    fn is_known_validator(&self, public_key: &PublicKey) -> bool {
+        self.era_validators
+            .values()
+            .any(|validators| validators.contains_key(public_key))
+    }
+
+    /// Buffers the finality signature of `public_key` for the unknown block `block_hash`, evicting
+    /// the signatures of the least recently buffered block if there are too many.
+    fn buffer_signature(
+        &mut self,
+        block_hash: BlockHash,
+        public_key: PublicKey,
+        signature: Signature,
+    ) {
+        // Only validators' signatures are kept, so the number of pending signatures per block is
+        // bounded by the number of known validators.
+        if !self.is_known_validator(&public_key) {
+            debug!(
+                "ignoring finality signature for unknown {} by unknown validator {}",
+                block_hash, public_key
+            );
+            return;
+        }
+        let pending = self
+            .pending_signatures
+            .entry(block_hash)
+            .or_insert_with(Vec::new);
+        if pending.iter().all(|(signer, _)| *signer != public_key) {
+            pending.push((public_key, signature));
+        }
+        while self.pending_signatures.len() > MAX_PENDING_SIGNATURE_BLOCKS {
+            let _ = self.pending_signatures.pop_front();
+        }
+    }
+
+    /// Buffers `block` until its parent is put to storage, dropping the least recently buffered
+    /// block if there are too many.
+    fn buffer_orphan_block(&mut self, block: Block) {
+        warn!(
+            "buffering {} until its parent {} is known",
+            block.hash(),
+            block.header().parent_hash()
+        );
+        let _ = self
+            .orphan_blocks
+            .insert(*block.header().parent_hash(), block);
+        while self.orphan_blocks.len() > MAX_ORPHAN_BLOCKS {
+            let _ = self.orphan_blocks.pop_front();
+        }
+    }
+
+    /// Appends the pending signatures of `block`'s era validators to it and puts it to storage.
+    fn put_new_block<REv>(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        mut block: Block,
+    ) -> Effects<Event<I>>
+    where
+        REv: From<StorageRequest<Storage>> + Send,
+        I: Send + 'static,
+    {
+        if let Some(signatures) = self.pending_signatures.remove(block.hash()) {
+            if let Some(validators) = self.era_validators.get(&block.header().era_id()) {
+                for (public_key, signature) in signatures {
+                    if validators.contains_key(&public_key) {
+                        let _ = block.append_proof(public_key, signature);
+                    }
+                }
+            }
+        }
+        Self::put_block(effect_builder, block, PutBlockKind::New, 1)
+    }
+
+    /// Adds the finality signature of `public_key` to `block`, whose era's validators must be
+    /// cached.  Signatures of non-validators and signatures for already finalized blocks are
+    /// ignored.
+    fn add_finality_signature<REv>(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        block: Block,
+        public_key: PublicKey,
+        signature: Signature,
+    ) -> Effects<Event<I>>
+    where
+        REv: From<StorageRequest<Storage>> + From<LinearChainAnnouncement> + Send,
+        I: Send + 'static,
+    {
+        // Prefer the cached block, as it includes any signatures added since `block` was
+        // retrieved.
+        let bh = *block.hash();
+        let mut block = self.get_cached_block(&bh).unwrap_or(block);
+        let is_validator = self
+            .era_validators
+            .get(&block.header().era_id())
+            .map_or(false, |validators| validators.contains_key(&public_key));
+        if !is_validator {
+            debug!(
+                "ignoring finality signature for {} by non-validator {}",
+                bh, public_key
+            );
+            return Effects::new();
+        }
+        // A signer counts once, even if it signs the block again.
+        if block.has_proof_from(&public_key) {
+            debug!("ignoring duplicate finality signature for {}", bh);
+            return Effects::new();
+        }
+        if self.has_sufficient_finality(&block) {
+            // Further signatures add nothing: dropping them caps their propagation.
+            debug!("ignoring finality signature for already finalized {}", bh);
+            return Effects::new();
+        }
+        let _ = block.append_proof(public_key, signature);
+        self.cache_block(block.clone());
+        self.update_highest_finalized_block(&block);
+        let mut effects = Effects::new();
+        // Announce only once, when the threshold is first reached.
+        if self.has_sufficient_finality(&block) {
+            effects.extend(
+                effect_builder
+                    .announce_block_finalized(block.clone())
+                    .ignore(),
+            );
+        }
+        effects.extend(Self::put_block(
+            effect_builder,
+            block,
+            PutBlockKind::NewSignatures,
+            1,
+        ));
+        effects
+    }
+
+    /// Records `block` as the highest finalized block if it has enough finality signatures and is
+    /// at least as high as the current one.
+    fn update_highest_finalized_block(&mut self, block: &Block) {
+        if !self.has_sufficient_finality(block) {
+            return;
+        }
+        let is_highest = self
+            .highest_finalized_block
+            .as_ref()
+            .map_or(true, |highest| {
+                block.header().height() >= highest.header().height()
+            });
+        if is_highest {
+            self.highest_finalized_block = Some(block.clone());
+        }
+    }
+
+    /// Caches `block`, evicting the least recently used block if the cache is full.
+    fn cache_block(&mut self, block: Block) {
+        if self.block_cache_size == 0 {
+            return;
+        }
+        let _ = self.block_cache.insert(*block.hash(), block);
+        while self.block_cache.len() > self.block_cache_size {
+            let _ = self.block_cache.pop_front();
         }
     }
 }
@@ -74,6 +445,7 @@ where
     REv: From<StorageRequest<Storage>>
         + From<ConsensusRequest>
         + From<NetworkRequest<I, Message>>
+        + From<LinearChainAnnouncement>
         + Send,
     R: Rng + CryptoRng + ?Sized,
     I: Display + Send + 'static,
@@ -87,54 +459,339 @@ where
         event: Self::Event,
     ) -> Effects<Self::Event> {
         match event {
-            Event::Request(LinearChainRequest::BlockRequest(bh, sender)) => effect_builder
-                .get_block_from_storage(bh)
-                .event(move |maybe_block| Event::GetBlockResult(bh, maybe_block, sender)),
+            Event::Request(LinearChainRequest::BlockRequest(bh, sender)) => {
+                match self.get_cached_block(&bh) {
+                    Some(block) => effect_builder
+                        .immediately()
+                        .event(move |_| Event::GetBlockResult(bh, Some(block), sender)),
+                    None => effect_builder
+                        .get_block_from_storage(bh)
+                        .event(move |maybe_block| Event::GetBlockResult(bh, maybe_block, sender)),
+                }
+            }
+            Event::Request(LinearChainRequest::BlockAtHeight(height, sender)) => effect_builder
+                .get_block_at_height_from_storage(height)
+                .event(move |maybe_block| {
+                    Event::GetBlockAtHeightResult(height, maybe_block, sender)
+                }),
             Event::Request(LinearChainRequest::LastFinalizedBlock(responder)) => {
-                responder.respond(self.last_block.clone()).ignore()
+                match &self.last_block {
+                    Some(block) => responder.respond(Some(block.clone())).ignore(),
+                    None => effect_builder
+                        .get_highest_block_from_storage()
+                        .then(move |maybe_block| responder.respond(maybe_block))
+                        .ignore(),
+                }
             }
-            Event::GetBlockResult(block_hash, maybe_block, sender) => {
-                match maybe_block {
-                    None => {
-                        debug!("failed to get {} for {}", block_hash, sender);
-                        Effects::new()
-                    },
-                    Some(block) => match Message::new_get_response(&block) {
+            Event::Request(LinearChainRequest::HighestBlockWithSufficientFinality(responder)) => {
+                responder
+                    .respond(self.highest_finalized_block.clone())
+                    .ignore()
+            }
+            Event::Request(LinearChainRequest::FinalitySignatures(bh, responder)) => {
+                match self.get_cached_block(&bh) {
+                    Some(block) => responder.respond(Some(block.proofs().to_vec())).ignore(),
+                    None => effect_builder
+                        .get_block_from_storage(bh)
+                        .then(move |maybe_block| {
+                            responder.respond(maybe_block.map(|block| block.proofs().to_vec()))
+                        })
+                        .ignore(),
+                }
+            }
+            Event::Request(LinearChainRequest::FinalitySignatureWeight(bh, responder)) => {
+                match self.get_cached_block(&bh) {
+                    Some(block) => effect_builder.immediately().event(move |_| {
+                        Event::GetBlockForSignatureWeightResult(Some(block), responder)
+                    }),
+                    None => effect_builder
+                        .get_block_from_storage(bh)
+                        .event(move |maybe_block| {
+                            Event::GetBlockForSignatureWeightResult(maybe_block, responder)
+                        }),
+                }
+            }
+            Event::GetBlockForSignatureWeightResult(maybe_block, responder) => {
+                let block = match maybe_block {
+                    Some(block) => block,
+                    None => return responder.respond(None).ignore(),
+                };
+                let era_id = block.header().era_id();
+                match self.era_validators.get(&era_id) {
+                    Some(validators) => responder
+                        .respond(Some(signature_weight(&block, validators)))
+                        .ignore(),
+                    None => effect_builder
+                        .get_validator_stakes(era_id)
+                        .then(move |maybe_validators| {
+                            let maybe_weight = maybe_validators
+                                .map(|validators| signature_weight(&block, &validators));
+                            responder.respond(maybe_weight)
+                        })
+                        .ignore(),
+                }
+            }
+            Event::Request(LinearChainRequest::GetBlock(
+                BlockIdentifier::Hash(block_hash),
+                responder,
+            )) => match self.get_cached_block(&block_hash) {
+                Some(block) => responder.respond(Some(block)).ignore(),
+                None => effect_builder
+                    .get_block_from_storage(block_hash)
+                    .then(move |maybe_block| responder.respond(maybe_block))
+                    .ignore(),
+            },
+            Event::Request(LinearChainRequest::GetBlock(
+                BlockIdentifier::Height(height),
+                responder,
+            )) => effect_builder
+                .get_block_at_height_from_storage(height)
+                .then(move |maybe_block| responder.respond(maybe_block))
+                .ignore(),
+            Event::GetBlockResult(block_hash, maybe_block, sender) => match maybe_block {
+                None => {
+                    debug!("failed to get {} for {}", block_hash, sender);
+                    Effects::new()
+                }
+                Some(block) => {
+                    let effects = match Message::new_get_response(&block) {
                         Ok(message) => effect_builder.send_message(sender, message).ignore(),
                         Err(error) => {
                             error!("failed to create get-response {}", error);
                             Effects::new()
                         }
+                    };
+                    self.cache_block(block);
+                    effects
+                }
+            },
+            Event::GetBlockAtHeightResult(height, maybe_block, sender) => match maybe_block {
+                None => {
+                    debug!("failed to get block at height {} for {}", height, sender);
+                    Effects::new()
+                }
+                Some(block) => match Message::new_get_response(&block) {
+                    Ok(message) => effect_builder.send_message(sender, message).ignore(),
+                    Err(error) => {
+                        error!("failed to create get-response {}", error);
+                        Effects::new()
+                    }
+                },
+            },
+            Event::LinearChainBlock(block) => {
+                let parent_hash = *block.header().parent_hash();
+                let maybe_parent = match &self.last_block {
+                    Some(last_block) if *last_block.hash() == parent_hash => {
+                        Some(last_block.clone())
                     }
+                    _ => self.get_cached_block(&parent_hash),
+                };
+                match maybe_parent {
+                    Some(parent) => effect_builder
+                        .immediately()
+                        .event(move |_| Event::GetParentBlockResult(block, Some(parent))),
+                    None => effect_builder.get_block_from_storage(parent_hash).event(
+                        move |maybe_parent| Event::GetParentBlockResult(block, maybe_parent),
+                    ),
                 }
             }
-            Event::LinearChainBlock(block) => {
+            Event::GetParentBlockResult(block, maybe_parent) => {
+                match maybe_parent {
+                    Some(parent) if block.header().timestamp() < parent.header().timestamp() => {
+                        error!(
+                            "rejecting {} with timestamp {} earlier than its parent's {}",
+                            block.hash(),
+                            block.header().timestamp(),
+                            parent.header().timestamp()
+                        );
+                        return Effects::new();
+                    }
+                    // Genesis' child is the only block without a parent in storage.
+                    None if !block.is_genesis_child() => {
+                        self.buffer_orphan_block(block);
+                        return Effects::new();
+                    }
+                    _ => (),
+                }
+                let era_id = block.header().era_id();
+                if self.era_validators.contains_key(&era_id) {
+                    return self.put_new_block(effect_builder, block);
+                }
                 effect_builder
-                .put_block_to_storage(Box::new(block.clone()))
-                .event(move |_| Event::PutBlockResult(block))
-            },
-            Event::PutBlockResult(block) => {
+                    .get_validator_stakes(era_id)
+                    .event(move |maybe_validators| {
+                        Event::GetValidatorsForBlockResult(block, maybe_validators)
+                    })
+            }
+            Event::GetValidatorsForBlockResult(block, maybe_validators) => {
+                match maybe_validators {
+                    Some(validators) => self.cache_validators(block.header().era_id(), validators),
+                    // The block is stored anyway, but its finality can't be assessed.
+                    None => warn!(
+                        "validators of {:?} unknown for {}",
+                        block.header().era_id(),
+                        block.hash()
+                    ),
+                }
+                self.put_new_block(effect_builder, block)
+            }
+            Event::PutBlockResult(block, newly_stored) => {
                 let block_hash = *block.hash();
                 debug!("LinearChainBlock --block_hash: {}", block_hash);
                 self.last_block = Some(block.clone());
-                effect_builder.handle_linear_chain_block(block.header().clone())
-                    .event(move |signature| Event::NewFinalitySignature(block_hash, signature))
-            },
-            Event::NewFinalitySignature(bh, signature) => {
-                effect_builder
-                .clone()
-                    .get_block_from_storage(bh)
-                    .then(move |maybe_block| match maybe_block {
-                        Some(mut block) => {
-                            block.append_proof(signature);
-                            effect_builder.put_block_to_storage(Box::new(block))
-                        }
-                        None => {
-                            warn!("Received a signature for {} but block was not found in the Linear chain storage", bh);
-                            panic!("Unhandled")
+                self.cache_block(block.clone());
+                self.update_highest_finalized_block(&block);
+                let mut effects = Effects::new();
+                if newly_stored {
+                    effects.extend(effect_builder.announce_block_added(block.clone()).ignore());
+                }
+                // The block may already carry enough signatures which arrived before it.
+                if self.has_sufficient_finality(&block) {
+                    effects.extend(
+                        effect_builder
+                            .announce_block_finalized(block.clone())
+                            .ignore(),
+                    );
+                }
+                // A child which arrived before the block can be handled now.
+                if let Some(child) = self.orphan_blocks.remove(&block_hash) {
+                    effects.extend(
+                        effect_builder
+                            .immediately()
+                            .event(move |_| Event::LinearChainBlock(child)),
+                    );
+                }
+                // Signatures which arrived while the block was being put to storage were buffered.
+                if let Some(signatures) = self.pending_signatures.remove(&block_hash) {
+                    for (public_key, signature) in signatures {
+                        effects.extend(self.add_finality_signature(
+                            effect_builder,
+                            block.clone(),
+                            public_key,
+                            signature,
+                        ));
+                    }
+                }
+                effects.extend(
+                    effect_builder
+                        .handle_linear_chain_block(block.header().clone())
+                        .event(move |(public_key, signature)| {
+                            Event::NewFinalitySignature(block_hash, public_key, signature)
+                        }),
+                );
+                effects
+            }
+            Event::BlockSignaturesStored(bh) => {
+                debug!("stored finality signatures for {}", bh);
+                Effects::new()
+            }
+            Event::PutBlockFailed(block, kind, attempt, error) => {
+                // Losing a block silently is dangerous: rather stop the node if storage keeps
+                // failing.
+                if attempt >= MAX_PUT_BLOCK_ATTEMPTS {
+                    return fatal!(
+                        effect_builder,
+                        format!(
+                            "failed to put {} to storage after {} attempts: {}",
+                            block.hash(),
+                            attempt,
+                            error
+                        )
+                    );
+                }
+                warn!(
+                    %error,
+                    "failed to put {} to storage on attempt {}; retrying",
+                    block.hash(),
+                    attempt
+                );
+                Self::put_block(effect_builder, block, kind, attempt + 1)
+            }
+            Event::NewFinalitySignature(bh, public_key, signature) => {
+                if let Err(error) = asymmetric_key::verify(bh.inner(), &signature, &public_key) {
+                    warn!(
+                        %error,
+                        "ignoring invalid finality signature for {} by {}",
+                        bh,
+                        public_key
+                    );
+                    return Effects::new();
+                }
+                match self.get_cached_block(&bh) {
+                    Some(block) => effect_builder.immediately().event(move |_| {
+                        Event::GetBlockForFinalitySignatureResult(
+                            bh,
+                            public_key,
+                            signature,
+                            Some(block),
+                        )
+                    }),
+                    None => effect_builder
+                        .get_block_from_storage(bh)
+                        .event(move |maybe_block| {
+                            Event::GetBlockForFinalitySignatureResult(
+                                bh,
+                                public_key,
+                                signature,
+                                maybe_block,
+                            )
+                        }),
+                }
+            }
+            Event::GetBlockForFinalitySignatureResult(bh, public_key, signature, maybe_block) => {
+                match self.get_cached_block(&bh).or(maybe_block) {
+                    Some(block) => {
+                        let era_id = block.header().era_id();
+                        if self.era_validators.contains_key(&era_id) {
+                            return self.add_finality_signature(
+                                effect_builder,
+                                block,
+                                public_key,
+                                signature,
+                            );
                         }
-                    })
-                    .ignore()
+                        effect_builder
+                            .get_validator_stakes(era_id)
+                            .event(move |maybe_validators| {
+                                Event::GetValidatorsForFinalitySignatureResult(
+                                    block,
+                                    public_key,
+                                    signature,
+                                    maybe_validators,
+                                )
+                            })
+                    }
+                    None => {
+                        // The signature may have arrived before the block was stored: keep it
+                        // until the block is put to storage.
+                        debug!(
+                            "received a signature for {} but block was not found in the linear \
+                            chain storage; buffering it",
+                            bh
+                        );
+                        self.buffer_signature(bh, public_key, signature);
+                        Effects::new()
+                    }
+                }
+            }
+            Event::GetValidatorsForFinalitySignatureResult(
+                block,
+                public_key,
+                signature,
+                maybe_validators,
+            ) => match maybe_validators {
+                Some(validators) => {
+                    self.cache_validators(block.header().era_id(), validators);
+                    self.add_finality_signature(effect_builder, block, public_key, signature)
+                }
+                None => {
+                    debug!(
+                        "ignoring finality signature for {}: validators of {:?} unknown",
+                        block.hash(),
+                        block.header().era_id()
+                    );
+                    Effects::new()
+                }
             },
         }
     }
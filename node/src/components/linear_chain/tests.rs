@@ -0,0 +1,1370 @@
+use std::{
+    fmt::{self, Display, Formatter},
+    iter, mem,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use derive_more::From;
+use prometheus::Registry;
+use rand::Rng;
+use smallvec::smallvec;
+use tempfile::TempDir;
+use thiserror::Error;
+use tokio::time;
+
+use super::*;
+use crate::{
+    components::{
+        consensus::EraId,
+        in_memory_network::NodeId,
+        storage::{self, StorageType},
+    },
+    crypto::{
+        asymmetric_key::{self, PublicKey, SecretKey},
+        hash::Digest,
+    },
+    effect::{requests::NetworkRequest, EffectBuilder},
+    reactor::{self, EventQueueHandle, Runner},
+    testing::TestRng,
+    types::{BlockHeader, FinalizedBlock, ProtoBlock, Tag, TimeDiff},
+};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+const TIMEOUT: Duration = Duration::from_secs(5);
+const BLOCK_CACHE_SIZE: usize = 10;
+/// The number of validators in every era besides the one signing on behalf of consensus.
+const OTHER_VALIDATOR_COUNT: usize = 3;
+/// The stake of every validator.
+const VALIDATOR_STAKE: u64 = 100;
+/// Two of the four equally staked validators need to sign a block to finalize it.
+const FINALITY_THRESHOLD_PERCENT: u8 = 50;
+
+/// Top-level event for the test reactor.
+#[derive(Debug, From)]
+#[must_use]
+enum ReactorEvent {
+    #[from]
+    Storage(storage::Event<Storage>),
+    #[from]
+    LinearChain(Event<NodeId>),
+    #[from]
+    ConsensusRequest(ConsensusRequest),
+    #[from]
+    NetworkRequest(NetworkRequest<NodeId, Message>),
+    #[from]
+    LinearChainAnnouncement(LinearChainAnnouncement),
+}
+
+impl From<StorageRequest<Storage>> for ReactorEvent {
+    fn from(request: StorageRequest<Storage>) -> Self {
+        ReactorEvent::Storage(storage::Event::Request(request))
+    }
+}
+
+impl From<LinearChainRequest<NodeId>> for ReactorEvent {
+    fn from(request: LinearChainRequest<NodeId>) -> Self {
+        ReactorEvent::LinearChain(Event::Request(request))
+    }
+}
+
+impl Display for ReactorEvent {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ReactorEvent::Storage(event) => write!(formatter, "storage: {}", event),
+            ReactorEvent::LinearChain(event) => write!(formatter, "linear chain: {}", event),
+            ReactorEvent::ConsensusRequest(request) => {
+                write!(formatter, "consensus request: {:?}", request)
+            }
+            ReactorEvent::NetworkRequest(request) => {
+                write!(formatter, "network request: {}", request)
+            }
+            ReactorEvent::LinearChainAnnouncement(announcement) => {
+                write!(formatter, "linear chain announcement: {}", announcement)
+            }
+        }
+    }
+}
+
+/// Error type returned by the test reactor.
+#[derive(Debug, Error)]
+enum ReactorError {
+    #[error("prometheus (metrics) error: {0}")]
+    Metrics(#[from] prometheus::Error),
+}
+
+/// A reactor running only the linear chain and storage components, signing every block it is
+/// asked to handle on behalf of consensus.
+struct Reactor {
+    storage: Storage,
+    linear_chain: LinearChain<NodeId>,
+    secret_key: SecretKey,
+    /// The keys of the other validators of every era.
+    other_validator_keys: Vec<SecretKey>,
+    /// Messages sent via the network, by destination.
+    sent_messages: Vec<(NodeId, Message)>,
+    /// The number of requests to get a block by its hash from storage.
+    get_block_requests: usize,
+    /// The number of upcoming requests to put a block to storage which should fail.
+    failing_block_puts: usize,
+    /// The kind and attempt number of every failed request to put a block to storage.
+    failed_block_puts: Vec<(PutBlockKind, u32)>,
+    /// Hashes of the blocks announced as added.
+    added_blocks: Vec<BlockHash>,
+    /// Hashes of the blocks announced as finalized.
+    finalized_blocks: Vec<BlockHash>,
+    /// Whether to hold back consensus' acknowledgment of linear chain blocks.
+    defer_consensus_responses: bool,
+    /// The consensus requests held back while `defer_consensus_responses` is set.
+    deferred_consensus_requests: Vec<ConsensusRequest>,
+    _storage_tempdir: TempDir,
+}
+
+impl reactor::Reactor<TestRng> for Reactor {
+    type Event = ReactorEvent;
+    type Config = ();
+    type Error = ReactorError;
+
+    fn new(
+        _config: Self::Config,
+        _registry: &Registry,
+        _event_queue: EventQueueHandle<Self::Event>,
+        rng: &mut TestRng,
+    ) -> Result<(Self, Effects<Self::Event>), Self::Error> {
+        let (storage_config, _storage_tempdir) = storage::Config::default_for_tests();
+        let storage = Storage::new(&storage_config).unwrap();
+
+        let reactor = Reactor {
+            storage,
+            linear_chain: LinearChain::new(BLOCK_CACHE_SIZE, FINALITY_THRESHOLD_PERCENT),
+            secret_key: SecretKey::random(rng),
+            other_validator_keys: (0..OTHER_VALIDATOR_COUNT)
+                .map(|_| SecretKey::random(rng))
+                .collect(),
+            sent_messages: Vec::new(),
+            get_block_requests: 0,
+            failing_block_puts: 0,
+            failed_block_puts: Vec::new(),
+            added_blocks: Vec::new(),
+            finalized_blocks: Vec::new(),
+            defer_consensus_responses: false,
+            deferred_consensus_requests: Vec::new(),
+            _storage_tempdir,
+        };
+
+        Ok((reactor, Effects::new()))
+    }
+
+    fn dispatch_event(
+        &mut self,
+        effect_builder: EffectBuilder<Self::Event>,
+        rng: &mut TestRng,
+        event: ReactorEvent,
+    ) -> Effects<Self::Event> {
+        match event {
+            ReactorEvent::Storage(storage::Event::Request(StorageRequest::PutBlock {
+                responder,
+                ..
+            }))
+            | ReactorEvent::Storage(storage::Event::Request(StorageRequest::PutBlockProofs {
+                responder,
+                ..
+            })) if self.failing_block_puts > 0 => {
+                self.failing_block_puts -= 1;
+                let error = storage::Error::Internal("injected failure".into());
+                responder.respond(Err(error)).ignore()
+            }
+            ReactorEvent::Storage(event) => {
+                if let storage::Event::Request(StorageRequest::GetBlock { .. }) = event {
+                    self.get_block_requests += 1;
+                }
+                reactor::wrap_effects(
+                    ReactorEvent::Storage,
+                    self.storage.handle_event(effect_builder, rng, event),
+                )
+            }
+            ReactorEvent::LinearChain(event) => {
+                if let Event::PutBlockFailed(_, kind, attempt, _) = event {
+                    self.failed_block_puts.push((kind, attempt));
+                }
+                reactor::wrap_effects(
+                    ReactorEvent::LinearChain,
+                    self.linear_chain.handle_event(effect_builder, rng, event),
+                )
+            }
+            ReactorEvent::ConsensusRequest(request @ ConsensusRequest::HandleLinearBlock(..))
+                if self.defer_consensus_responses =>
+            {
+                self.deferred_consensus_requests.push(request);
+                Effects::new()
+            }
+            ReactorEvent::ConsensusRequest(ConsensusRequest::HandleLinearBlock(
+                block_header,
+                responder,
+            )) => {
+                let signer_and_signature = self.sign(&block_header, rng);
+                responder.respond(signer_and_signature).ignore()
+            }
+            ReactorEvent::ConsensusRequest(ConsensusRequest::LeaderStats(_, responder)) => {
+                responder.respond(None).ignore()
+            }
+            ReactorEvent::ConsensusRequest(ConsensusRequest::ValidatorStakes(_, responder)) => {
+                responder.respond(Some(self.validator_stakes())).ignore()
+            }
+            ReactorEvent::ConsensusRequest(ConsensusRequest::ForceNextEra {
+                responder, ..
+            }) => responder.respond(None).ignore(),
+            ReactorEvent::ConsensusRequest(ConsensusRequest::CurrentEra(responder)) => {
+                responder.respond(EraId(0)).ignore()
+            }
+            ReactorEvent::ConsensusRequest(ConsensusRequest::GetEvidence { responder, .. }) => {
+                responder.respond(None).ignore()
+            }
+            ReactorEvent::NetworkRequest(NetworkRequest::SendMessage {
+                dest,
+                payload,
+                responder,
+            }) => {
+                self.sent_messages.push((dest, payload));
+                responder.respond(()).ignore()
+            }
+            ReactorEvent::NetworkRequest(_) => Effects::new(),
+            ReactorEvent::LinearChainAnnouncement(LinearChainAnnouncement::BlockAdded(block)) => {
+                self.added_blocks.push(*block.hash());
+                Effects::new()
+            }
+            ReactorEvent::LinearChainAnnouncement(LinearChainAnnouncement::BlockFinalized(
+                block,
+            )) => {
+                self.finalized_blocks.push(*block.hash());
+                Effects::new()
+            }
+        }
+    }
+}
+
+impl Reactor {
+    /// Signs the given block header on behalf of consensus.
+    fn sign(&self, block_header: &BlockHeader, rng: &mut TestRng) -> (PublicKey, Signature) {
+        let public_key = PublicKey::from(&self.secret_key);
+        let signature = asymmetric_key::sign(
+            block_header.hash().inner(),
+            &self.secret_key,
+            &public_key,
+            rng,
+        );
+        (public_key, signature)
+    }
+
+    /// Signs the given block hash on behalf of the other validator with the given index.
+    fn sign_as_validator(
+        &self,
+        index: usize,
+        block_hash: &BlockHash,
+        rng: &mut TestRng,
+    ) -> (PublicKey, Signature) {
+        let secret_key = &self.other_validator_keys[index];
+        let public_key = PublicKey::from(secret_key);
+        let signature = asymmetric_key::sign(block_hash.inner(), secret_key, &public_key, rng);
+        (public_key, signature)
+    }
+
+    /// Returns the validators of every era with their stakes.
+    fn validator_stakes(&self) -> BTreeMap<PublicKey, Motes> {
+        iter::once(&self.secret_key)
+            .chain(&self.other_validator_keys)
+            .map(|secret_key| {
+                let stake = Motes::new(U512::from(VALIDATOR_STAKE));
+                (PublicKey::from(secret_key), stake)
+            })
+            .collect()
+    }
+}
+
+/// Creates a random block without any finality signatures.
+fn unsigned_block(rng: &mut TestRng) -> Block {
+    Block::new(
+        BlockHash::new(Digest::random(rng)),
+        Digest::random(rng),
+        FinalizedBlock::random(rng),
+    )
+}
+
+/// Signs the given block hash with a random key, returning the signer and its signature.
+fn random_signature(rng: &mut TestRng, block_hash: &BlockHash) -> (PublicKey, Signature) {
+    let secret_key = SecretKey::random(rng);
+    let public_key = PublicKey::from(&secret_key);
+    let signature = asymmetric_key::sign(block_hash.inner(), &secret_key, &public_key, rng);
+    (public_key, signature)
+}
+
+/// Cranks `runner` until `condition` is met.
+///
+/// # Panics
+///
+/// Panics if the condition is not met within `TIMEOUT`.
+async fn crank_until<F>(runner: &mut Runner<Reactor, TestRng>, rng: &mut TestRng, condition: F)
+where
+    F: Fn(&Runner<Reactor, TestRng>) -> bool,
+{
+    let crank_until_met = async {
+        while !condition(runner) {
+            if runner.try_crank(rng).await.is_none() {
+                time::delay_for(POLL_INTERVAL).await;
+            }
+        }
+    };
+    time::timeout(TIMEOUT, crank_until_met)
+        .await
+        .expect("condition not met in time")
+}
+
+/// Cranks `runner` until no event has been queued for ten consecutive polling intervals.
+async fn crank_until_idle(runner: &mut Runner<Reactor, TestRng>, rng: &mut TestRng) {
+    let mut idle_polls = 0;
+    while idle_polls < 10 {
+        if runner.try_crank(rng).await.is_some() {
+            idle_polls = 0;
+        } else {
+            idle_polls += 1;
+            time::delay_for(POLL_INTERVAL).await;
+        }
+    }
+}
+
+/// Schedules `event` to be handled by the linear chain component.
+async fn inject_event(runner: &mut Runner<Reactor, TestRng>, event: Event<NodeId>) {
+    runner
+        .process_injected_effects(move |effect_builder| {
+            effect_builder
+                .immediately()
+                .event(move |_| ReactorEvent::LinearChain(event))
+        })
+        .await;
+}
+
+/// Hands a block to the linear chain component and waits until it is added, so that the component
+/// knows the validators.
+async fn learn_validators(runner: &mut Runner<Reactor, TestRng>, rng: &mut TestRng) {
+    let block = unsigned_child_block(runner, rng).await;
+    let block_hash = *block.hash();
+    inject_event(runner, Event::LinearChainBlock(block)).await;
+    crank_until(runner, rng, |runner| {
+        runner.reactor().added_blocks.contains(&block_hash)
+    })
+    .await;
+}
+
+/// Requests the last finalized block from the linear chain component.
+async fn last_finalized_block(
+    runner: &mut Runner<Reactor, TestRng>,
+    rng: &mut TestRng,
+) -> Option<Block> {
+    let result = Arc::new(Mutex::new(None));
+    let sink = Arc::clone(&result);
+    runner
+        .process_injected_effects(move |effect_builder| {
+            async move {
+                let maybe_block = effect_builder.get_last_finalized_block::<NodeId>().await;
+                *sink.lock().unwrap() = Some(maybe_block);
+            }
+            .ignore()
+        })
+        .await;
+    crank_until(runner, rng, |_| result.lock().unwrap().is_some()).await;
+    let maybe_block = result.lock().unwrap().take().unwrap();
+    maybe_block
+}
+
+/// Requests the highest block with sufficient finality signatures from the linear chain
+/// component.
+async fn highest_block_with_sufficient_finality(
+    runner: &mut Runner<Reactor, TestRng>,
+    rng: &mut TestRng,
+) -> Option<Block> {
+    let result = Arc::new(Mutex::new(None));
+    let sink = Arc::clone(&result);
+    runner
+        .process_injected_effects(move |effect_builder| {
+            async move {
+                let maybe_block = effect_builder
+                    .get_highest_block_with_sufficient_finality::<NodeId>()
+                    .await;
+                *sink.lock().unwrap() = Some(maybe_block);
+            }
+            .ignore()
+        })
+        .await;
+    crank_until(runner, rng, |_| result.lock().unwrap().is_some()).await;
+    let maybe_block = result.lock().unwrap().take().unwrap();
+    maybe_block
+}
+
+/// Requests the finality signatures of the given block from the linear chain component.
+async fn finality_signatures(
+    runner: &mut Runner<Reactor, TestRng>,
+    rng: &mut TestRng,
+    block_hash: BlockHash,
+) -> Option<Vec<(PublicKey, Signature)>> {
+    let result = Arc::new(Mutex::new(None));
+    let sink = Arc::clone(&result);
+    runner
+        .process_injected_effects(move |effect_builder| {
+            async move {
+                let maybe_signatures = effect_builder
+                    .get_finality_signatures::<NodeId>(block_hash)
+                    .await;
+                *sink.lock().unwrap() = Some(maybe_signatures);
+            }
+            .ignore()
+        })
+        .await;
+    crank_until(runner, rng, |_| result.lock().unwrap().is_some()).await;
+    let maybe_signatures = result.lock().unwrap().take().unwrap();
+    maybe_signatures
+}
+
+/// Requests the finality signature weight of the given block from the linear chain component.
+async fn finality_signature_weight(
+    runner: &mut Runner<Reactor, TestRng>,
+    rng: &mut TestRng,
+    block_hash: BlockHash,
+) -> Option<Motes> {
+    let result = Arc::new(Mutex::new(None));
+    let sink = Arc::clone(&result);
+    runner
+        .process_injected_effects(move |effect_builder| {
+            async move {
+                let maybe_weight = effect_builder
+                    .get_finality_signature_weight::<NodeId>(block_hash)
+                    .await;
+                *sink.lock().unwrap() = Some(maybe_weight);
+            }
+            .ignore()
+        })
+        .await;
+    crank_until(runner, rng, |_| result.lock().unwrap().is_some()).await;
+    let maybe_weight = result.lock().unwrap().take().unwrap();
+    maybe_weight
+}
+
+/// Requests the block with the given hash or at the given height from the linear chain component.
+async fn get_block(
+    runner: &mut Runner<Reactor, TestRng>,
+    rng: &mut TestRng,
+    block_identifier: BlockIdentifier,
+) -> Option<Block> {
+    let result = Arc::new(Mutex::new(None));
+    let sink = Arc::clone(&result);
+    runner
+        .process_injected_effects(move |effect_builder| {
+            async move {
+                let maybe_block = effect_builder.get_block::<NodeId>(block_identifier).await;
+                *sink.lock().unwrap() = Some(maybe_block);
+            }
+            .ignore()
+        })
+        .await;
+    crank_until(runner, rng, |_| result.lock().unwrap().is_some()).await;
+    let maybe_block = result.lock().unwrap().take().unwrap();
+    maybe_block
+}
+
+/// Puts `block` to storage directly, bypassing the linear chain component.
+async fn put_to_storage(runner: &mut Runner<Reactor, TestRng>, rng: &mut TestRng, block: Block) {
+    let block_hash = *block.hash();
+    runner
+        .process_injected_effects(move |effect_builder| {
+            effect_builder
+                .put_block_to_storage::<Storage>(Box::new(block))
+                .ignore()
+        })
+        .await;
+    crank_until(runner, rng, |runner| {
+        runner
+            .reactor()
+            .storage
+            .block_store()
+            .ids()
+            .unwrap()
+            .contains(&block_hash)
+    })
+    .await;
+}
+
+/// Puts a random block to storage directly, and returns a random child of it without any finality
+/// signatures.
+async fn unsigned_child_block(runner: &mut Runner<Reactor, TestRng>, rng: &mut TestRng) -> Block {
+    let parent = Block::random(rng);
+    let parent_hash = *parent.hash();
+    put_to_storage(runner, rng, parent).await;
+    Block::new(
+        parent_hash,
+        Digest::random(rng),
+        FinalizedBlock::random(rng),
+    )
+}
+
+#[tokio::test]
+async fn should_fall_back_to_storage_for_last_finalized_block() {
+    let mut rng = TestRng::new();
+    let mut runner = Runner::<Reactor, TestRng>::new((), &mut rng).await.unwrap();
+
+    // Nothing has been stored yet.
+    assert!(last_finalized_block(&mut runner, &mut rng).await.is_none());
+
+    // Store blocks without going via the linear chain component, as if they had been stored
+    // before a restart.
+    let blocks: Vec<Block> = (0..3).map(|_| Block::random(&mut rng)).collect();
+    for block in blocks.clone() {
+        put_to_storage(&mut runner, &mut rng, block).await;
+    }
+    let highest = blocks
+        .iter()
+        .map(|block| block.header().height())
+        .max()
+        .unwrap();
+
+    let tip = last_finalized_block(&mut runner, &mut rng)
+        .await
+        .expect("should fall back to storage");
+    assert_eq!(tip.header().height(), highest);
+}
+
+#[tokio::test]
+async fn should_buffer_signature_for_unknown_block() {
+    let mut rng = TestRng::new();
+    let mut runner = Runner::<Reactor, TestRng>::new((), &mut rng).await.unwrap();
+
+    learn_validators(&mut runner, &mut rng).await;
+    let block = unsigned_child_block(&mut runner, &mut rng).await;
+    let block_hash = *block.hash();
+    let (public_key, signature) = runner.reactor().sign_as_validator(0, &block_hash, &mut rng);
+
+    // Deliver the signature before the block is stored.
+    inject_event(
+        &mut runner,
+        Event::NewFinalitySignature(block_hash, public_key, signature),
+    )
+    .await;
+    crank_until(&mut runner, &mut rng, |runner| {
+        runner
+            .reactor()
+            .linear_chain
+            .pending_signatures
+            .contains_key(&block_hash)
+    })
+    .await;
+
+    // Once the block arrives, the buffered signature should be attached to it in storage.
+    inject_event(&mut runner, Event::LinearChainBlock(block)).await;
+    crank_until(&mut runner, &mut rng, |runner| {
+        runner
+            .reactor()
+            .storage
+            .block_store()
+            .get(smallvec![block_hash])
+            .into_iter()
+            .next()
+            .and_then(|result| result.unwrap())
+            .map_or(false, |stored_block| {
+                stored_block.has_proof_from(&public_key)
+            })
+    })
+    .await;
+    assert!(runner.reactor().linear_chain.pending_signatures.is_empty());
+}
+
+#[tokio::test]
+async fn should_drain_signatures_buffered_while_storing_block() {
+    let mut rng = TestRng::new();
+    let mut runner = Runner::<Reactor, TestRng>::new((), &mut rng).await.unwrap();
+    learn_validators(&mut runner, &mut rng).await;
+    let block = unsigned_child_block(&mut runner, &mut rng).await;
+    let block_hash = *block.hash();
+    runner.reactor_mut().failing_block_puts = 1;
+    let (public_key, signature) = runner.reactor().sign_as_validator(0, &block_hash, &mut rng);
+
+    // The first write fails, so the block is neither stored nor cached until it is retried.
+    inject_event(&mut runner, Event::LinearChainBlock(block)).await;
+    crank_until(&mut runner, &mut rng, |runner| {
+        !runner.reactor().failed_block_puts.is_empty()
+    })
+    .await;
+    inject_event(
+        &mut runner,
+        Event::NewFinalitySignature(block_hash, public_key, signature),
+    )
+    .await;
+    crank_until(&mut runner, &mut rng, |runner| {
+        runner
+            .reactor()
+            .linear_chain
+            .pending_signatures
+            .contains_key(&block_hash)
+    })
+    .await;
+
+    // Once the retry succeeds, the buffered signature is added to the block.
+    crank_until(&mut runner, &mut rng, |runner| {
+        runner
+            .reactor()
+            .linear_chain
+            .block_cache
+            .get(&block_hash)
+            .map_or(false, |block| block.has_proof_from(&public_key))
+    })
+    .await;
+    assert!(runner.reactor().linear_chain.pending_signatures.is_empty());
+}
+
+#[tokio::test]
+async fn should_ignore_invalid_signature() {
+    let mut rng = TestRng::new();
+    let mut runner = Runner::<Reactor, TestRng>::new((), &mut rng).await.unwrap();
+
+    let block_hash = *Block::random(&mut rng).hash();
+    let (_, signature) = random_signature(&mut rng, &block_hash);
+    let (impostor, _) = random_signature(&mut rng, &block_hash);
+
+    // A signature claimed by someone other than its signer is dropped rather than buffered.
+    inject_event(
+        &mut runner,
+        Event::NewFinalitySignature(block_hash, impostor, signature),
+    )
+    .await;
+    crank_until_idle(&mut runner, &mut rng).await;
+    assert!(runner.reactor().linear_chain.pending_signatures.is_empty());
+}
+
+#[tokio::test]
+async fn should_ignore_signatures_of_non_validators() {
+    let mut rng = TestRng::new();
+    let mut runner = Runner::<Reactor, TestRng>::new((), &mut rng).await.unwrap();
+    learn_validators(&mut runner, &mut rng).await;
+
+    // A non-validator's signature for an unknown block isn't buffered.
+    let unknown_hash = *unsigned_block(&mut rng).hash();
+    let (public_key, signature) = random_signature(&mut rng, &unknown_hash);
+    inject_event(
+        &mut runner,
+        Event::NewFinalitySignature(unknown_hash, public_key, signature),
+    )
+    .await;
+    crank_until_idle(&mut runner, &mut rng).await;
+    assert!(runner.reactor().linear_chain.pending_signatures.is_empty());
+
+    // Nor does it count towards the finality of a known block, however many there are.
+    let block = unsigned_child_block(&mut runner, &mut rng).await;
+    let block_hash = *block.hash();
+    inject_event(&mut runner, Event::LinearChainBlock(block)).await;
+    crank_until_idle(&mut runner, &mut rng).await;
+    for _ in 0..OTHER_VALIDATOR_COUNT {
+        let (public_key, signature) = random_signature(&mut rng, &block_hash);
+        inject_event(
+            &mut runner,
+            Event::NewFinalitySignature(block_hash, public_key, signature),
+        )
+        .await;
+    }
+    crank_until_idle(&mut runner, &mut rng).await;
+
+    let signatures = finality_signatures(&mut runner, &mut rng, block_hash)
+        .await
+        .expect("block should be known");
+    let consensus_key = PublicKey::from(&runner.reactor().secret_key);
+    assert_eq!(signatures.len(), 1);
+    assert_eq!(signatures[0].0, consensus_key);
+    assert!(runner.reactor().finalized_blocks.is_empty());
+}
+
+#[tokio::test]
+async fn should_not_store_duplicate_signatures() {
+    let mut rng = TestRng::new();
+    let mut runner = Runner::<Reactor, TestRng>::new((), &mut rng).await.unwrap();
+
+    learn_validators(&mut runner, &mut rng).await;
+    let block = unsigned_child_block(&mut runner, &mut rng).await;
+    let block_hash = *block.hash();
+
+    // Deliver two signatures by the same signer.
+    let mut signatures = vec![];
+    for _ in 0..2 {
+        let (public_key, signature) = runner.reactor().sign_as_validator(0, &block_hash, &mut rng);
+        signatures.push((public_key, signature));
+        inject_event(
+            &mut runner,
+            Event::NewFinalitySignature(block_hash, public_key, signature),
+        )
+        .await;
+    }
+    crank_until(&mut runner, &mut rng, |runner| {
+        runner
+            .reactor()
+            .linear_chain
+            .pending_signatures
+            .get(&block_hash)
+            .map_or(false, |signatures| !signatures.is_empty())
+    })
+    .await;
+
+    inject_event(&mut runner, Event::LinearChainBlock(block)).await;
+    crank_until(&mut runner, &mut rng, |runner| {
+        runner
+            .reactor()
+            .storage
+            .block_store()
+            .ids()
+            .unwrap()
+            .contains(&block_hash)
+    })
+    .await;
+
+    let stored_block = runner
+        .reactor()
+        .storage
+        .block_store()
+        .get(smallvec![block_hash])
+        .into_iter()
+        .next()
+        .unwrap()
+        .unwrap()
+        .unwrap();
+    let (public_key, first_signature) = signatures[0];
+    let proofs_by_signer: Vec<_> = stored_block
+        .proofs()
+        .iter()
+        .filter(|(signer, _)| *signer == public_key)
+        .collect();
+    assert_eq!(proofs_by_signer, vec![&(public_key, first_signature)]);
+}
+
+#[tokio::test]
+async fn should_store_late_signature_for_evicted_block() {
+    let mut rng = TestRng::new();
+    let mut runner = Runner::<Reactor, TestRng>::new((), &mut rng).await.unwrap();
+
+    // The test reactor signs the block on behalf of consensus.
+    let block = unsigned_child_block(&mut runner, &mut rng).await;
+    let block_hash = *block.hash();
+    inject_event(&mut runner, Event::LinearChainBlock(block)).await;
+    crank_until_idle(&mut runner, &mut rng).await;
+
+    // A late signature arrives once the block is only in storage any more.
+    runner.reactor_mut().linear_chain.block_cache.clear();
+    let (public_key, signature) = runner.reactor().sign_as_validator(0, &block_hash, &mut rng);
+    inject_event(
+        &mut runner,
+        Event::NewFinalitySignature(block_hash, public_key, signature),
+    )
+    .await;
+    crank_until_idle(&mut runner, &mut rng).await;
+
+    // Both signatures were written to storage.
+    let stored_block = runner
+        .reactor()
+        .storage
+        .block_store()
+        .get(smallvec![block_hash])
+        .into_iter()
+        .next()
+        .unwrap()
+        .unwrap()
+        .unwrap();
+    let consensus_key = PublicKey::from(&runner.reactor().secret_key);
+    assert!(stored_block.has_proof_from(&consensus_key));
+    assert!(stored_block.has_proof_from(&public_key));
+}
+
+#[tokio::test]
+async fn should_respond_to_block_at_height_request() {
+    let mut rng = TestRng::new();
+    let mut runner = Runner::<Reactor, TestRng>::new((), &mut rng).await.unwrap();
+
+    let block = Block::random(&mut rng);
+    let height = block.header().height();
+    put_to_storage(&mut runner, &mut rng, block.clone()).await;
+
+    // Request a non-existent height first, then the stored one: only the latter should be
+    // answered.
+    let peer: NodeId = rng.gen();
+    inject_event(
+        &mut runner,
+        Event::Request(LinearChainRequest::BlockAtHeight(height + 1, peer)),
+    )
+    .await;
+    inject_event(
+        &mut runner,
+        Event::Request(LinearChainRequest::BlockAtHeight(height, peer)),
+    )
+    .await;
+    crank_until(&mut runner, &mut rng, |runner| {
+        !runner.reactor().sent_messages.is_empty()
+    })
+    .await;
+
+    let sent_messages = &runner.reactor().sent_messages;
+    assert_eq!(sent_messages.len(), 1);
+    let (dest, message) = &sent_messages[0];
+    assert_eq!(*dest, peer);
+    match message {
+        Message::GetResponse {
+            tag: Tag::Block,
+            serialized_item,
+        } => {
+            let received: Block = rmp_serde::from_read_ref(serialized_item).unwrap();
+            assert_eq!(received, block);
+        }
+        _ => panic!("unexpected message: {}", message),
+    }
+}
+
+#[tokio::test]
+async fn should_serve_repeated_block_requests_from_cache() {
+    let mut rng = TestRng::new();
+    let mut runner = Runner::<Reactor, TestRng>::new((), &mut rng).await.unwrap();
+
+    let block = Block::random(&mut rng);
+    let block_hash = *block.hash();
+    put_to_storage(&mut runner, &mut rng, block).await;
+
+    let peer: NodeId = rng.gen();
+    for expected_responses in 1..=2 {
+        inject_event(
+            &mut runner,
+            Event::Request(LinearChainRequest::BlockRequest(block_hash, peer)),
+        )
+        .await;
+        crank_until(&mut runner, &mut rng, |runner| {
+            runner.reactor().sent_messages.len() == expected_responses
+        })
+        .await;
+    }
+
+    // Only the first request should have reached storage.
+    assert_eq!(runner.reactor().get_block_requests, 1);
+}
+
+#[test]
+fn should_evict_least_recently_used_block_from_cache() {
+    let mut rng = TestRng::new();
+    let mut linear_chain = LinearChain::<NodeId>::new(2, FINALITY_THRESHOLD_PERCENT);
+
+    let blocks: Vec<Block> = (0..3).map(|_| Block::random(&mut rng)).collect();
+    linear_chain.cache_block(blocks[0].clone());
+    linear_chain.cache_block(blocks[1].clone());
+    // Using the first block makes the second one the least recently used.
+    assert!(linear_chain.get_cached_block(blocks[0].hash()).is_some());
+    linear_chain.cache_block(blocks[2].clone());
+
+    assert!(linear_chain.get_cached_block(blocks[0].hash()).is_some());
+    assert!(linear_chain.get_cached_block(blocks[1].hash()).is_none());
+    assert!(linear_chain.get_cached_block(blocks[2].hash()).is_some());
+}
+
+#[test]
+fn should_evict_pending_signatures_of_least_recently_buffered_block() {
+    let mut rng = TestRng::new();
+    let mut linear_chain = LinearChain::<NodeId>::new(BLOCK_CACHE_SIZE, FINALITY_THRESHOLD_PERCENT);
+
+    let secret_key = SecretKey::random(&mut rng);
+    let public_key = PublicKey::from(&secret_key);
+    let validators = iter::once((public_key, Motes::new(U512::from(VALIDATOR_STAKE)))).collect();
+    linear_chain.cache_validators(EraId(0), validators);
+
+    let block_hashes: Vec<BlockHash> = (0..MAX_PENDING_SIGNATURE_BLOCKS + 1)
+        .map(|_| BlockHash::new(Digest::random(&mut rng)))
+        .collect();
+    for block_hash in &block_hashes {
+        let signature =
+            asymmetric_key::sign(block_hash.inner(), &secret_key, &public_key, &mut rng);
+        linear_chain.buffer_signature(*block_hash, public_key, signature);
+    }
+
+    assert_eq!(
+        linear_chain.pending_signatures.len(),
+        MAX_PENDING_SIGNATURE_BLOCKS
+    );
+    assert!(!linear_chain
+        .pending_signatures
+        .contains_key(&block_hashes[0]));
+    assert!(linear_chain
+        .pending_signatures
+        .contains_key(&block_hashes[MAX_PENDING_SIGNATURE_BLOCKS]));
+}
+
+#[test]
+fn should_reach_finality_by_stake_rather_than_signature_count() {
+    let mut rng = TestRng::new();
+    let mut linear_chain = LinearChain::<NodeId>::new(BLOCK_CACHE_SIZE, FINALITY_THRESHOLD_PERCENT);
+
+    let keys: Vec<SecretKey> = (0..3).map(|_| SecretKey::random(&mut rng)).collect();
+    let public_keys: Vec<PublicKey> = keys.iter().map(PublicKey::from).collect();
+    let sign = |block: &mut Block, index: usize, rng: &mut TestRng| {
+        let signature =
+            asymmetric_key::sign(block.hash().inner(), &keys[index], &public_keys[index], rng);
+        assert!(block.append_proof(public_keys[index], signature));
+    };
+
+    // One validator holds 60% of the stake, the other two 20% each.
+    let block = unsigned_block(&mut rng);
+    let stakes = vec![300u64, 100, 100];
+    let validators = public_keys
+        .iter()
+        .zip(stakes)
+        .map(|(public_key, stake)| (*public_key, Motes::new(U512::from(stake))))
+        .collect();
+    linear_chain.cache_validators(block.header().era_id(), validators);
+
+    // The two minor validators together don't reach the threshold...
+    let mut minor_signed = block.clone();
+    sign(&mut minor_signed, 1, &mut rng);
+    sign(&mut minor_signed, 2, &mut rng);
+    assert!(!linear_chain.has_sufficient_finality(&minor_signed));
+
+    // ...but the major validator alone does.
+    let mut major_signed = block.clone();
+    sign(&mut major_signed, 0, &mut rng);
+    assert!(linear_chain.has_sufficient_finality(&major_signed));
+}
+
+#[tokio::test]
+async fn should_announce_added_block_once() {
+    let mut rng = TestRng::new();
+    let mut runner = Runner::<Reactor, TestRng>::new((), &mut rng).await.unwrap();
+
+    let block = unsigned_child_block(&mut runner, &mut rng).await;
+    let block_hash = *block.hash();
+
+    inject_event(&mut runner, Event::LinearChainBlock(block.clone())).await;
+    crank_until(&mut runner, &mut rng, |runner| {
+        !runner.reactor().added_blocks.is_empty()
+    })
+    .await;
+
+    // Handing the same block to the linear chain again doesn't add it a second time.
+    inject_event(&mut runner, Event::LinearChainBlock(block)).await;
+    crank_until_idle(&mut runner, &mut rng).await;
+
+    assert_eq!(runner.reactor().added_blocks, vec![block_hash]);
+}
+
+#[tokio::test]
+async fn should_retry_failed_block_writes() {
+    let mut rng = TestRng::new();
+    let mut runner = Runner::<Reactor, TestRng>::new((), &mut rng).await.unwrap();
+    let block = unsigned_child_block(&mut runner, &mut rng).await;
+    let block_hash = *block.hash();
+    runner.reactor_mut().failing_block_puts = 3;
+
+    // The first two writes fail: the block is only stored on the third attempt.  Putting it again
+    // with the test reactor's signature then fails once more.
+    inject_event(&mut runner, Event::LinearChainBlock(block)).await;
+    crank_until(&mut runner, &mut rng, |runner| {
+        runner
+            .reactor()
+            .storage
+            .block_store()
+            .ids()
+            .unwrap()
+            .contains(&block_hash)
+    })
+    .await;
+    crank_until(&mut runner, &mut rng, |runner| {
+        runner.reactor().added_blocks == vec![block_hash]
+    })
+    .await;
+    crank_until_idle(&mut runner, &mut rng).await;
+
+    // Every failure was retried, and none of the writes got anywhere near the fatal error.
+    assert_eq!(runner.reactor().failing_block_puts, 0);
+    assert_eq!(
+        runner.reactor().failed_block_puts,
+        vec![
+            (PutBlockKind::New, 1),
+            (PutBlockKind::New, 2),
+            (PutBlockKind::NewSignatures, 1)
+        ]
+    );
+    assert!(runner
+        .reactor()
+        .failed_block_puts
+        .iter()
+        .all(|(_, attempt)| *attempt < MAX_PUT_BLOCK_ATTEMPTS));
+}
+
+#[tokio::test]
+async fn should_announce_finalized_block_once() {
+    let mut rng = TestRng::new();
+    let mut runner = Runner::<Reactor, TestRng>::new((), &mut rng).await.unwrap();
+
+    let block = unsigned_child_block(&mut runner, &mut rng).await;
+    let block_hash = *block.hash();
+    let proof_count = |runner: &Runner<Reactor, TestRng>| {
+        runner
+            .reactor()
+            .linear_chain
+            .block_cache
+            .get(&block_hash)
+            .map_or(0, |block| block.proofs().len())
+    };
+
+    // The test reactor signs the block on behalf of consensus: one signature isn't enough.
+    inject_event(&mut runner, Event::LinearChainBlock(block)).await;
+    crank_until(&mut runner, &mut rng, |runner| proof_count(runner) == 1).await;
+    assert!(runner.reactor().finalized_blocks.is_empty());
+
+    // Two more signatures cross the threshold, but the block should only be announced once and the
+    // surplus signature should be dropped.
+    for index in 0..2 {
+        let (public_key, signature) =
+            runner
+                .reactor()
+                .sign_as_validator(index, &block_hash, &mut rng);
+        inject_event(
+            &mut runner,
+            Event::NewFinalitySignature(block_hash, public_key, signature),
+        )
+        .await;
+    }
+    crank_until(&mut runner, &mut rng, |runner| proof_count(runner) == 2).await;
+    while runner.try_crank(&mut rng).await.is_some() {}
+
+    assert_eq!(proof_count(&runner), 2);
+    assert_eq!(runner.reactor().finalized_blocks, vec![block_hash]);
+}
+
+#[tokio::test]
+async fn should_stop_accepting_signatures_once_finalized() {
+    let mut rng = TestRng::new();
+    let mut runner = Runner::<Reactor, TestRng>::new((), &mut rng).await.unwrap();
+
+    let block = unsigned_child_block(&mut runner, &mut rng).await;
+    let block_hash = *block.hash();
+
+    // An unknown block has no weight at all.
+    assert!(finality_signature_weight(&mut runner, &mut rng, block_hash)
+        .await
+        .is_none());
+
+    // The test reactor signs the block on behalf of consensus.
+    inject_event(&mut runner, Event::LinearChainBlock(block)).await;
+    crank_until_idle(&mut runner, &mut rng).await;
+    let stake = |validator_count: u64| Motes::new(U512::from(validator_count * VALIDATOR_STAKE));
+    assert_eq!(
+        finality_signature_weight(&mut runner, &mut rng, block_hash).await,
+        Some(stake(1))
+    );
+
+    // Only the first additional signature is needed to reach the threshold.
+    for index in 0..OTHER_VALIDATOR_COUNT {
+        let (public_key, signature) =
+            runner
+                .reactor()
+                .sign_as_validator(index, &block_hash, &mut rng);
+        inject_event(
+            &mut runner,
+            Event::NewFinalitySignature(block_hash, public_key, signature),
+        )
+        .await;
+    }
+    crank_until_idle(&mut runner, &mut rng).await;
+
+    assert_eq!(
+        finality_signature_weight(&mut runner, &mut rng, block_hash).await,
+        Some(stake(2))
+    );
+    let signatures = finality_signatures(&mut runner, &mut rng, block_hash)
+        .await
+        .expect("block should be known");
+    assert_eq!(signatures.len(), 2);
+
+    // After a restart, the weight is computed from the signatures in storage.
+    runner.reactor_mut().linear_chain =
+        LinearChain::new(BLOCK_CACHE_SIZE, FINALITY_THRESHOLD_PERCENT);
+    assert_eq!(
+        finality_signature_weight(&mut runner, &mut rng, block_hash).await,
+        Some(stake(2))
+    );
+}
+
+#[tokio::test]
+async fn should_respond_to_finality_signatures_request() {
+    let mut rng = TestRng::new();
+    let mut runner = Runner::<Reactor, TestRng>::new((), &mut rng).await.unwrap();
+
+    // An unknown block has no signatures at all.
+    let unknown_hash = *Block::random(&mut rng).hash();
+    assert!(finality_signatures(&mut runner, &mut rng, unknown_hash)
+        .await
+        .is_none());
+
+    // A known block without signatures has an empty set of them.
+    let unsigned_block = unsigned_block(&mut rng);
+    let unsigned_hash = *unsigned_block.hash();
+    put_to_storage(&mut runner, &mut rng, unsigned_block).await;
+    assert_eq!(
+        finality_signatures(&mut runner, &mut rng, unsigned_hash).await,
+        Some(vec![])
+    );
+
+    // Otherwise, all of the block's signatures are returned.
+    let mut signed_block = Block::random(&mut rng);
+    let (public_key, signature) = random_signature(&mut rng, signed_block.hash());
+    assert!(signed_block.append_proof(public_key, signature));
+    let signed_hash = *signed_block.hash();
+    let expected_signatures = signed_block.proofs().to_vec();
+    put_to_storage(&mut runner, &mut rng, signed_block).await;
+    assert_eq!(
+        finality_signatures(&mut runner, &mut rng, signed_hash).await,
+        Some(expected_signatures)
+    );
+}
+
+#[tokio::test]
+async fn should_respond_with_late_signatures_of_evicted_block() {
+    let mut rng = TestRng::new();
+    let mut runner = Runner::<Reactor, TestRng>::new((), &mut rng).await.unwrap();
+
+    // The test reactor signs the block on behalf of consensus.
+    let block = unsigned_child_block(&mut runner, &mut rng).await;
+    let block_hash = *block.hash();
+    inject_event(&mut runner, Event::LinearChainBlock(block)).await;
+    crank_until_idle(&mut runner, &mut rng).await;
+
+    // A late signature arrives after the block was evicted from the cache.
+    runner.reactor_mut().linear_chain.block_cache.clear();
+    let (public_key, signature) = runner.reactor().sign_as_validator(0, &block_hash, &mut rng);
+    inject_event(
+        &mut runner,
+        Event::NewFinalitySignature(block_hash, public_key, signature),
+    )
+    .await;
+    crank_until_idle(&mut runner, &mut rng).await;
+
+    // The signatures are read back from storage, the late one included.
+    runner.reactor_mut().linear_chain.block_cache.clear();
+    let signatures = finality_signatures(&mut runner, &mut rng, block_hash)
+        .await
+        .expect("block should be known");
+    let consensus_key = PublicKey::from(&runner.reactor().secret_key);
+    assert_eq!(signatures.len(), 2);
+    assert!(signatures
+        .iter()
+        .any(|(signer, _)| *signer == consensus_key));
+    assert!(signatures.contains(&(public_key, signature)));
+}
+
+#[tokio::test]
+async fn should_await_consensus_acknowledgment_of_block() {
+    let mut rng = TestRng::new();
+    let mut runner = Runner::<Reactor, TestRng>::new((), &mut rng).await.unwrap();
+    runner.reactor_mut().defer_consensus_responses = true;
+
+    let block = unsigned_child_block(&mut runner, &mut rng).await;
+    let block_hash = *block.hash();
+    let proof_count = |runner: &Runner<Reactor, TestRng>| {
+        runner
+            .reactor()
+            .linear_chain
+            .block_cache
+            .get(&block_hash)
+            .map_or(0, |block| block.proofs().len())
+    };
+
+    // The block is stored and handed to consensus, but not signed until consensus acknowledges it.
+    inject_event(&mut runner, Event::LinearChainBlock(block)).await;
+    crank_until(&mut runner, &mut rng, |runner| {
+        !runner.reactor().deferred_consensus_requests.is_empty()
+    })
+    .await;
+    while runner.try_crank(&mut rng).await.is_some() {}
+    assert_eq!(proof_count(&runner), 0);
+
+    let requests = mem::take(&mut runner.reactor_mut().deferred_consensus_requests);
+    assert_eq!(requests.len(), 1);
+    for request in requests {
+        match request {
+            ConsensusRequest::HandleLinearBlock(block_header, responder) => {
+                assert_eq!(*block_header.hash(), block_hash);
+                let signer_and_signature = runner.reactor().sign(&block_header, &mut rng);
+                runner
+                    .process_injected_effects(move |_| {
+                        responder.respond(signer_and_signature).ignore()
+                    })
+                    .await;
+            }
+            _ => panic!("unexpected consensus request: {:?}", request),
+        }
+    }
+    crank_until(&mut runner, &mut rng, |runner| proof_count(runner) == 1).await;
+}
+
+#[tokio::test]
+async fn should_respond_to_get_block_request() {
+    let mut rng = TestRng::new();
+    let mut runner = Runner::<Reactor, TestRng>::new((), &mut rng).await.unwrap();
+
+    let block = Block::random(&mut rng);
+    let block_hash = *block.hash();
+    let height = block.header().height();
+    put_to_storage(&mut runner, &mut rng, block.clone()).await;
+
+    let by_hash = get_block(&mut runner, &mut rng, BlockIdentifier::Hash(block_hash)).await;
+    assert_eq!(by_hash, Some(block.clone()));
+
+    let by_height = get_block(&mut runner, &mut rng, BlockIdentifier::Height(height)).await;
+    assert_eq!(by_height, Some(block));
+
+    let missing_hash = *Block::random(&mut rng).hash();
+    assert!(
+        get_block(&mut runner, &mut rng, BlockIdentifier::Hash(missing_hash))
+            .await
+            .is_none()
+    );
+    assert!(
+        get_block(&mut runner, &mut rng, BlockIdentifier::Height(height + 1))
+            .await
+            .is_none()
+    );
+}
+
+#[tokio::test]
+async fn should_reject_block_with_timestamp_before_parents() {
+    let mut rng = TestRng::new();
+    let mut runner = Runner::<Reactor, TestRng>::new((), &mut rng).await.unwrap();
+
+    let parent = Block::random(&mut rng);
+    let parent_header = parent.header().clone();
+    put_to_storage(&mut runner, &mut rng, parent).await;
+
+    let proposer = PublicKey::from(&runner.reactor().secret_key);
+    let mut new_block = |parent_hash, height, timestamp| {
+        let finalized_block = FinalizedBlock::new(
+            ProtoBlock::new(vec![], rng.gen()),
+            timestamp,
+            vec![],
+            false,
+            parent_header.era_id(),
+            height,
+            proposer,
+        );
+        Block::new(parent_hash, Digest::random(&mut rng), finalized_block)
+    };
+    let one_second = TimeDiff::from(1_000);
+    let child_height = parent_header.height() + 1;
+    let early_child = new_block(
+        parent_header.hash(),
+        child_height,
+        parent_header.timestamp() - one_second,
+    );
+    let early_child_hash = *early_child.hash();
+    let late_child = new_block(
+        parent_header.hash(),
+        child_height,
+        parent_header.timestamp() + one_second,
+    );
+    let late_child_hash = *late_child.hash();
+    let grandchild = new_block(
+        late_child_hash,
+        child_height + 1,
+        late_child.header().timestamp() + one_second,
+    );
+    let grandchild_hash = *grandchild.hash();
+    let is_stored = |runner: &Runner<Reactor, TestRng>, block_hash| {
+        runner
+            .reactor()
+            .storage
+            .block_store()
+            .ids()
+            .unwrap()
+            .contains(block_hash)
+    };
+
+    // A block with a timestamp earlier than its parent's is neither stored nor signed.
+    inject_event(&mut runner, Event::LinearChainBlock(early_child)).await;
+    crank_until_idle(&mut runner, &mut rng).await;
+    assert!(!is_stored(&runner, &early_child_hash));
+
+    // A block whose parent is unknown is buffered rather than stored.
+    inject_event(&mut runner, Event::LinearChainBlock(grandchild)).await;
+    crank_until_idle(&mut runner, &mut rng).await;
+    assert!(!is_stored(&runner, &grandchild_hash));
+
+    // A block with a later timestamp is accepted, and then so is its buffered child.
+    inject_event(&mut runner, Event::LinearChainBlock(late_child)).await;
+    crank_until(&mut runner, &mut rng, |runner| {
+        is_stored(runner, &late_child_hash) && is_stored(runner, &grandchild_hash)
+    })
+    .await;
+    assert!(runner.reactor().linear_chain.orphan_blocks.is_empty());
+}
+
+#[tokio::test]
+async fn should_return_highest_block_with_sufficient_finality() {
+    let mut rng = TestRng::new();
+    let mut runner = Runner::<Reactor, TestRng>::new((), &mut rng).await.unwrap();
+
+    let mut blocks = vec![
+        unsigned_child_block(&mut runner, &mut rng).await,
+        unsigned_child_block(&mut runner, &mut rng).await,
+    ];
+    blocks.sort_by_key(|block| block.header().height());
+    let lower_hash = *blocks[0].hash();
+    let higher_hash = *blocks[1].hash();
+    let proof_count = |runner: &Runner<Reactor, TestRng>, block_hash: &BlockHash| {
+        runner
+            .reactor()
+            .linear_chain
+            .block_cache
+            .get(block_hash)
+            .map_or(0, |block| block.proofs().len())
+    };
+
+    assert!(
+        highest_block_with_sufficient_finality(&mut runner, &mut rng)
+            .await
+            .is_none()
+    );
+
+    // The test reactor signs each block on behalf of consensus: the lower block gets a second
+    // signature, crossing the threshold, while the higher one stays below it.
+    for block in blocks {
+        inject_event(&mut runner, Event::LinearChainBlock(block)).await;
+    }
+    let (public_key, signature) = runner.reactor().sign_as_validator(0, &lower_hash, &mut rng);
+    inject_event(
+        &mut runner,
+        Event::NewFinalitySignature(lower_hash, public_key, signature),
+    )
+    .await;
+    crank_until(&mut runner, &mut rng, |runner| {
+        proof_count(runner, &lower_hash) == 2 && proof_count(runner, &higher_hash) == 1
+    })
+    .await;
+
+    let highest = highest_block_with_sufficient_finality(&mut runner, &mut rng)
+        .await
+        .expect("should have a finalized block");
+    assert_eq!(*highest.hash(), lower_hash);
+    assert_eq!(highest.proofs().len(), 2);
+
+    // Once the higher block crosses the threshold, too, it is returned instead.
+    let (public_key, signature) = runner
+        .reactor()
+        .sign_as_validator(0, &higher_hash, &mut rng);
+    inject_event(
+        &mut runner,
+        Event::NewFinalitySignature(higher_hash, public_key, signature),
+    )
+    .await;
+    crank_until(&mut runner, &mut rng, |runner| {
+        proof_count(runner, &higher_hash) == 2
+    })
+    .await;
+
+    let highest = highest_block_with_sufficient_finality(&mut runner, &mut rng)
+        .await
+        .expect("should have a finalized block");
+    assert_eq!(*highest.hash(), higher_hash);
+}
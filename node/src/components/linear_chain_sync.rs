@@ -2,7 +2,9 @@ mod event;
 
 use super::{fetcher::FetchResult, storage::Storage, Component};
 use crate::{
-    effect::{self, EffectBuilder, EffectExt, EffectOptionExt, Effects},
+    components::storage,
+    effect::{self, EffectBuilder, EffectExt, EffectOptionExt, EffectResultExt, Effects},
+    fatal,
     types::{Block, BlockHash},
 };
 use effect::requests::{FetcherRequest, StorageRequest};
@@ -147,25 +149,48 @@ where
                         );
                     }
                     self.linear_chain.push(*block.clone());
-                    if block.is_genesis_child() {
-                        info!("Linear chain downloaded. Starting downloading deploys.");
-                        effect_builder
-                            .put_block_to_storage(block)
-                            .event(move |_| Event::LinearChainBlocksDownloaded())
-                    } else {
-                        self.reset_peers();
-                        let parent_hash = *block.parent_hash();
-                        let peer = self.random_peer(rng).expect("At least 1 peer available.");
-                        let mut effects = effect_builder.put_block_to_storage(block).ignore();
-                        let fetch_parent = effect_builder.fetch_block(parent_hash, peer).option(
-                            move |value| Event::GetBlockResult(block_hash, Some(value)),
-                            move || Event::GetBlockResult(block_hash, None),
-                        );
-                        effects.extend(fetch_parent);
-                        effects
-                    }
+                    effect_builder.put_block_to_storage(block.clone()).result(
+                        move |_| Event::BlockStored(block),
+                        move |error| Event::PutBlockFailed(block_hash, error),
+                    )
                 }
             },
+            Event::BlockStored(block) => {
+                if block.is_genesis_child() {
+                    // Check that the whole chain up to the block we started from is in storage.
+                    let up_to = self
+                        .linear_chain
+                        .first()
+                        .map_or(0, |block| block.header().height());
+                    return effect_builder
+                        .get_missing_block_heights_from_storage::<Storage>(up_to)
+                        .event(Event::MissingBlockHeights);
+                }
+                self.reset_peers();
+                let parent_hash = *block.parent_hash();
+                let peer = self.random_peer(rng).expect("At least 1 peer available.");
+                effect_builder.fetch_block(parent_hash, peer).option(
+                    move |value| Event::GetBlockResult(parent_hash, Some(value)),
+                    move || Event::GetBlockResult(parent_hash, None),
+                )
+            }
+            Event::MissingBlockHeights(missing) => {
+                if !missing.is_empty() {
+                    // Every block between the genesis child and the starting block was stored,
+                    // so the peers must have sent blocks with inconsistent heights.
+                    error!(?missing, "synced linear chain has gaps");
+                }
+                info!("Linear chain downloaded. Starting downloading deploys.");
+                self.handle_event(effect_builder, rng, Event::LinearChainBlocksDownloaded())
+            }
+            Event::PutBlockFailed(block_hash, error) => {
+                // Without the block in storage, the downloaded chain has a gap: stop rather than
+                // carry on syncing.
+                fatal!(
+                    effect_builder,
+                    format!("failed to put {} to storage: {}", block_hash, error)
+                )
+            }
             Event::DeployFound(_) => unimplemented!(),
             Event::DeployNotFound(_) => unimplemented!(),
             Event::LinearChainBlocksDownloaded() => {
@@ -1,13 +1,16 @@
 use crate::{
-    components::fetcher::FetchResult,
+    components::{fetcher::FetchResult, storage},
     types::{Block, BlockHash, DeployHash},
 };
-use std::fmt::Display;
+use std::{fmt::Display, ops::RangeInclusive};
 
 #[derive(Debug)]
 pub enum Event<I> {
     Start(BlockHash),
     GetBlockResult(BlockHash, Option<FetchResult<Block>>),
+    BlockStored(Box<Block>),
+    MissingBlockHeights(Vec<RangeInclusive<u64>>),
+    PutBlockFailed(BlockHash, storage::Error),
     DeployFound(DeployHash),
     DeployNotFound(DeployHash),
     LinearChainBlocksDownloaded(),
@@ -22,6 +25,13 @@ where
         match self {
             Event::Start(block_hash) => write!(f, "Start syncing from {}.", block_hash),
             Event::GetBlockResult(bh, r) => write!(f, "Get block result for {}: {:?}", bh, r),
+            Event::BlockStored(block) => write!(f, "Block stored: {}", block.hash()),
+            Event::MissingBlockHeights(missing) => {
+                write!(f, "Missing block heights: {:?}", missing)
+            }
+            Event::PutBlockFailed(bh, error) => {
+                write!(f, "Failed to put block {} to storage: {}", bh, error)
+            }
             Event::DeployFound(dh) => write!(f, "Deploy found: {}", dh),
             Event::DeployNotFound(dh) => write!(f, "Deploy not found: {}", dh),
             Event::LinearChainBlocksDownloaded() => write!(f, "Linear chain blocks downloaded"),
@@ -311,7 +311,8 @@ where
                 let _ = self.incoming.insert(peer_id, address);
 
                 // If the connection is now complete, announce the new peer before starting reader.
-                let mut effects = self.check_connection_complete(effect_builder, peer_id);
+                let mut effects =
+                    self.check_connection_complete(effect_builder, peer_id, true, address);
 
                 effects.extend(
                     message_reader(self.event_queue, stream, self.our_id, peer_id).event(
@@ -370,7 +371,8 @@ where
             error!(%peer_id, "{}: did not expect leftover channel in outgoing map", self.our_id);
         }
 
-        let mut effects = self.check_connection_complete(effect_builder, peer_id);
+        let mut effects =
+            self.check_connection_complete(effect_builder, peer_id, false, peer_address);
 
         effects.extend(
             message_sender(receiver, sink).event(move |result| Event::OutgoingFailed {
@@ -477,15 +479,21 @@ where
     /// Checks whether a connection has been established fully, i.e. with an incoming and outgoing
     /// connection.
     ///
+    /// `inbound` and `address` describe the connection which was just established.
+    ///
     /// Returns either no effect or an announcement that a new peer has connected.
     fn check_connection_complete(
         &self,
         effect_builder: EffectBuilder<REv>,
         peer_id: NodeId,
+        inbound: bool,
+        address: SocketAddr,
     ) -> Effects<Event<P>> {
         if self.outgoing.contains_key(&peer_id) && self.incoming.contains_key(&peer_id) {
-            debug!(%peer_id, "connection to peer is now complete");
-            effect_builder.announce_new_peer(peer_id).ignore()
+            debug!(%peer_id, inbound, %address, "connection to peer is now complete");
+            effect_builder
+                .announce_new_peer(peer_id, inbound, Some(address))
+                .ignore()
         } else {
             Effects::new()
         }
@@ -160,16 +160,23 @@ impl Reactor<TestRng> for TestReactor {
                 };
                 self.dispatch_event(effect_builder, rng, Event::AddressGossiper(event))
             }
-            Event::NetworkAnnouncement(NetworkAnnouncement::NewPeer(_)) => {
+            Event::NetworkAnnouncement(NetworkAnnouncement::NewPeer { .. }) => {
                 // We do not care about the announcement of new peers in this test.
                 Effects::new()
             }
-            Event::AddressGossiperAnnouncement(ann) => {
-                let GossiperAnnouncement::NewCompleteItem(gossiped_address) = ann;
+            Event::AddressGossiperAnnouncement(GossiperAnnouncement::NewCompleteItem(
+                gossiped_address,
+            )) => {
                 let reactor_event =
                     Event::SmallNet(small_network::Event::PeerAddressReceived(gossiped_address));
                 self.dispatch_event(effect_builder, rng, reactor_event)
             }
+            Event::AddressGossiperAnnouncement(GossiperAnnouncement::ItemGossiped { .. }) => {
+                Effects::new()
+            }
+            Event::AddressGossiperAnnouncement(GossiperAnnouncement::FinishedGossiping(_)) => {
+                Effects::new()
+            }
         }
     }
 }
@@ -1,10 +1,16 @@
+mod block_height_store;
 mod chainspec_store;
 mod config;
 mod error;
 mod event;
+mod execution_results_store;
+mod in_mem_block_height_store;
 mod in_mem_chainspec_store;
+mod in_mem_execution_results_store;
 mod in_mem_store;
+mod lmdb_block_height_store;
 mod lmdb_chainspec_store;
+mod lmdb_execution_results_store;
 mod lmdb_store;
 mod store;
 
@@ -12,6 +18,7 @@ use std::{
     fmt::{Debug, Display},
     fs,
     hash::Hash,
+    ops::RangeInclusive,
     sync::Arc,
 };
 
@@ -30,16 +37,22 @@ use crate::{
         EffectBuilder, EffectExt, Effects, Responder,
     },
     protocol::Message,
-    types::{Block, Deploy, Item},
+    types::{Block, BlockExecutionResults, BlockHash, Deploy, Item},
 };
+use block_height_store::BlockHeightStore;
 use chainspec_store::ChainspecStore;
 pub use config::Config;
 pub use error::Error;
 pub(crate) use error::Result;
 pub use event::Event;
+use execution_results_store::ExecutionResultsStore;
+use in_mem_block_height_store::InMemBlockHeightStore;
 use in_mem_chainspec_store::InMemChainspecStore;
+use in_mem_execution_results_store::InMemExecutionResultsStore;
 use in_mem_store::InMemStore;
+use lmdb_block_height_store::LmdbBlockHeightStore;
 use lmdb_chainspec_store::LmdbChainspecStore;
+use lmdb_execution_results_store::LmdbExecutionResultsStore;
 use lmdb_store::LmdbStore;
 use store::{Multiple, Store};
 
@@ -53,6 +66,8 @@ pub(crate) type DeployHeaderResults<S> =
 const BLOCK_STORE_FILENAME: &str = "block_store.db";
 const DEPLOY_STORE_FILENAME: &str = "deploy_store.db";
 const CHAINSPEC_STORE_FILENAME: &str = "chainspec_store.db";
+const EXECUTION_RESULTS_STORE_FILENAME: &str = "execution_results_store.db";
+const BLOCK_HEIGHT_STORE_FILENAME: &str = "block_height_store.db";
 
 /// Trait defining the API for a value able to be held within the storage component.
 pub trait Value: Clone + Serialize + DeserializeOwned + Send + Sync + Debug + Display {
@@ -88,17 +103,29 @@ pub trait Value: Clone + Serialize + DeserializeOwned + Send + Sync + Debug + Di
     fn take_header(self) -> Self::Header;
 }
 
+/// Trait defining the API for a block able to be held within the storage component.
+pub trait BlockValue: Value {
+    /// Returns the height of the block in the linear chain.
+    fn height(&self) -> u64;
+
+    /// Adds the finality signatures of `other`, another copy of this block, which this one doesn't
+    /// hold yet.
+    fn merge_proofs(&mut self, other: Self);
+}
+
 /// Trait which will handle management of the various storage sub-components.
 ///
 /// If this trait is ultimately only used for testing scenarios, we shouldn't need to expose it to
 /// the reactor - it can simply use a concrete type which implements this trait.
 pub trait StorageType {
-    type Block: Value;
+    type Block: BlockValue;
     type Deploy: Value + Item;
 
     fn block_store(&self) -> Arc<dyn Store<Value = Self::Block>>;
     fn deploy_store(&self) -> Arc<dyn Store<Value = Self::Deploy>>;
     fn chainspec_store(&self) -> Arc<dyn ChainspecStore>;
+    fn execution_results_store(&self) -> Arc<dyn ExecutionResultsStore>;
+    fn block_height_store(&self) -> Arc<dyn BlockHeightStore<<Self::Block as Value>::Id>>;
     fn new(config: &Config) -> Result<Self>
     where
         Self: Sized;
@@ -136,17 +163,43 @@ pub trait StorageType {
         .ignore()
     }
 
-    fn put_block(&self, block: Box<Self::Block>, responder: Responder<bool>) -> Effects<Event<Self>>
+    fn put_block(
+        &self,
+        block: Box<Self::Block>,
+        responder: Responder<Result<bool>>,
+    ) -> Effects<Event<Self>>
     where
         Self: Sized,
     {
         let block_store = self.block_store();
-        let block_hash = *block.id();
+        let block_height_store = self.block_height_store();
         async move {
-            let result = task::spawn_blocking(move || block_store.put(*block))
-                .await
-                .expect("should run")
-                .unwrap_or_else(|error| panic!("failed to put {}: {}", block_hash, error));
+            let result = task::spawn_blocking(move || {
+                store::put_block(&*block_store, &*block_height_store, *block)
+            })
+            .await
+            .expect("should run");
+            responder.respond(result).await
+        }
+        .ignore()
+    }
+
+    fn put_block_proofs(
+        &self,
+        block: Box<Self::Block>,
+        responder: Responder<Result<bool>>,
+    ) -> Effects<Event<Self>>
+    where
+        Self: Sized,
+    {
+        let block_store = self.block_store();
+        let block_height_store = self.block_height_store();
+        async move {
+            let result = task::spawn_blocking(move || {
+                store::put_block_proofs(&*block_store, &*block_height_store, *block)
+            })
+            .await
+            .expect("should run");
             responder.respond(result).await
         }
         .ignore()
@@ -197,6 +250,65 @@ pub trait StorageType {
         .ignore()
     }
 
+    fn get_highest_block(&self, responder: Responder<Option<Self::Block>>) -> Effects<Event<Self>>
+    where
+        Self: Sized,
+    {
+        let block_store = self.block_store();
+        let block_height_store = self.block_height_store();
+        async move {
+            let result = task::spawn_blocking(move || {
+                store::highest_block(&*block_store, &*block_height_store)
+            })
+            .await
+            .expect("should run")
+            .unwrap_or_else(|error| panic!("failed to get highest block: {}", error));
+            responder.respond(result).await
+        }
+        .ignore()
+    }
+
+    fn get_block_at_height(
+        &self,
+        height: u64,
+        responder: Responder<Option<Self::Block>>,
+    ) -> Effects<Event<Self>>
+    where
+        Self: Sized,
+    {
+        let block_store = self.block_store();
+        let block_height_store = self.block_height_store();
+        async move {
+            let result = task::spawn_blocking(move || {
+                store::block_at_height(&*block_store, &*block_height_store, height)
+            })
+            .await
+            .expect("should run")
+            .unwrap_or_else(|error| panic!("failed to get block at height {}: {}", height, error));
+            responder.respond(result).await
+        }
+        .ignore()
+    }
+
+    fn get_missing_block_heights(
+        &self,
+        up_to: u64,
+        responder: Responder<Vec<RangeInclusive<u64>>>,
+    ) -> Effects<Event<Self>>
+    where
+        Self: Sized,
+    {
+        let block_height_store = self.block_height_store();
+        async move {
+            let result = task::spawn_blocking(move || block_height_store.missing_heights(up_to))
+                .await
+                .expect("should run")
+                .unwrap_or_else(|error| panic!("failed to get missing block heights: {}", error));
+            responder.respond(result).await
+        }
+        .ignore()
+    }
+
     fn put_deploy(
         &self,
         deploy: Box<Self::Deploy>,
@@ -318,6 +430,57 @@ pub trait StorageType {
         }
         .ignore()
     }
+
+    fn put_execution_results(
+        &self,
+        block_hash: BlockHash,
+        execution_results: BlockExecutionResults,
+        responder: Responder<()>,
+    ) -> Effects<Event<Self>>
+    where
+        Self: Sized,
+    {
+        let execution_results_store = self.execution_results_store();
+        async move {
+            task::spawn_blocking(move || {
+                execution_results_store.put(block_hash, execution_results)
+            })
+            .await
+            .expect("should run")
+            .unwrap_or_else(|error| {
+                panic!(
+                    "failed to put execution results for {}: {}",
+                    block_hash, error
+                )
+            });
+            responder.respond(()).await
+        }
+        .ignore()
+    }
+
+    fn get_execution_results(
+        &self,
+        block_hash: BlockHash,
+        responder: Responder<Option<BlockExecutionResults>>,
+    ) -> Effects<Event<Self>>
+    where
+        Self: Sized,
+    {
+        let execution_results_store = self.execution_results_store();
+        async move {
+            let result = task::spawn_blocking(move || execution_results_store.get(block_hash))
+                .await
+                .expect("should run")
+                .unwrap_or_else(|error| {
+                    panic!(
+                        "failed to get execution results for {}: {}",
+                        block_hash, error
+                    )
+                });
+            responder.respond(result).await
+        }
+        .ignore()
+    }
 }
 
 impl<REv, R, S> Component<REv, R> for S
@@ -342,6 +505,9 @@ where
             Event::Request(StorageRequest::PutBlock { block, responder }) => {
                 self.put_block(block, responder)
             }
+            Event::Request(StorageRequest::PutBlockProofs { block, responder }) => {
+                self.put_block_proofs(block, responder)
+            }
             Event::Request(StorageRequest::GetBlock {
                 block_hash,
                 responder,
@@ -350,6 +516,15 @@ where
                 block_hash,
                 responder,
             }) => self.get_block_header(block_hash, responder),
+            Event::Request(StorageRequest::GetHighestBlock { responder }) => {
+                self.get_highest_block(responder)
+            }
+            Event::Request(StorageRequest::GetBlockAtHeight { height, responder }) => {
+                self.get_block_at_height(height, responder)
+            }
+            Event::Request(StorageRequest::GetMissingBlockHeights { up_to, responder }) => {
+                self.get_missing_block_heights(up_to, responder)
+            }
             Event::Request(StorageRequest::PutDeploy { deploy, responder }) => {
                 self.put_deploy(deploy, responder)
             }
@@ -371,6 +546,15 @@ where
             Event::Request(StorageRequest::GetChainspec { version, responder }) => {
                 self.get_chainspec(version, responder)
             }
+            Event::Request(StorageRequest::PutExecutionResults {
+                block_hash,
+                execution_results,
+                responder,
+            }) => self.put_execution_results(block_hash, execution_results, responder),
+            Event::Request(StorageRequest::GetExecutionResults {
+                block_hash,
+                responder,
+            }) => self.get_execution_results(block_hash, responder),
         }
     }
 }
@@ -381,10 +565,12 @@ pub(crate) struct InMemStorage<B: Value, D: Value> {
     block_store: Arc<InMemStore<B>>,
     deploy_store: Arc<InMemStore<D>>,
     chainspec_store: Arc<InMemChainspecStore>,
+    execution_results_store: Arc<InMemExecutionResultsStore>,
+    block_height_store: Arc<InMemBlockHeightStore<B::Id>>,
 }
 
 #[allow(trivial_casts)]
-impl<B: Value + 'static, D: Value + Item + 'static> StorageType for InMemStorage<B, D> {
+impl<B: BlockValue + 'static, D: Value + Item + 'static> StorageType for InMemStorage<B, D> {
     type Block = B;
     type Deploy = D;
 
@@ -400,11 +586,21 @@ impl<B: Value + 'static, D: Value + Item + 'static> StorageType for InMemStorage
         Arc::clone(&self.chainspec_store) as Arc<dyn ChainspecStore>
     }
 
+    fn execution_results_store(&self) -> Arc<dyn ExecutionResultsStore> {
+        Arc::clone(&self.execution_results_store) as Arc<dyn ExecutionResultsStore>
+    }
+
+    fn block_height_store(&self) -> Arc<dyn BlockHeightStore<B::Id>> {
+        Arc::clone(&self.block_height_store) as Arc<dyn BlockHeightStore<B::Id>>
+    }
+
     fn new(_config: &Config) -> Result<Self> {
         Ok(InMemStorage {
             block_store: Arc::new(InMemStore::new()),
             deploy_store: Arc::new(InMemStore::new()),
             chainspec_store: Arc::new(InMemChainspecStore::new()),
+            execution_results_store: Arc::new(InMemExecutionResultsStore::new()),
+            block_height_store: Arc::new(InMemBlockHeightStore::new()),
         })
     }
 }
@@ -415,10 +611,12 @@ pub struct LmdbStorage<B: Value, D: Value> {
     block_store: Arc<LmdbStore<B>>,
     deploy_store: Arc<LmdbStore<D>>,
     chainspec_store: Arc<LmdbChainspecStore>,
+    execution_results_store: Arc<LmdbExecutionResultsStore>,
+    block_height_store: Arc<LmdbBlockHeightStore<B::Id>>,
 }
 
 #[allow(trivial_casts)]
-impl<B: Value + 'static, D: Value + Item + 'static> StorageType for LmdbStorage<B, D> {
+impl<B: BlockValue + 'static, D: Value + Item + 'static> StorageType for LmdbStorage<B, D> {
     type Block = B;
     type Deploy = D;
 
@@ -432,16 +630,32 @@ impl<B: Value + 'static, D: Value + Item + 'static> StorageType for LmdbStorage<
         let block_store_path = path.join(BLOCK_STORE_FILENAME);
         let deploy_store_path = path.join(DEPLOY_STORE_FILENAME);
         let chainspec_store_path = path.join(CHAINSPEC_STORE_FILENAME);
+        let execution_results_store_path = path.join(EXECUTION_RESULTS_STORE_FILENAME);
+        let block_height_store_path = path.join(BLOCK_HEIGHT_STORE_FILENAME);
 
         let block_store = LmdbStore::new(block_store_path, config.max_block_store_size())?;
         let deploy_store = LmdbStore::new(deploy_store_path, config.max_deploy_store_size())?;
         let chainspec_store =
             LmdbChainspecStore::new(chainspec_store_path, config.max_chainspec_store_size())?;
+        let execution_results_store = LmdbExecutionResultsStore::new(
+            execution_results_store_path,
+            config.max_execution_results_store_size(),
+        )?;
+        let block_height_store = LmdbBlockHeightStore::new(
+            block_height_store_path,
+            config.max_block_height_store_size(),
+        )?;
+        // Blocks stored before the index existed need to be indexed once.
+        if block_height_store.highest()?.is_none() {
+            store::index_blocks_by_height(&block_store, &block_height_store)?;
+        }
 
         Ok(LmdbStorage {
             block_store: Arc::new(block_store),
             deploy_store: Arc::new(deploy_store),
             chainspec_store: Arc::new(chainspec_store),
+            execution_results_store: Arc::new(execution_results_store),
+            block_height_store: Arc::new(block_height_store),
         })
     }
 
@@ -456,4 +670,12 @@ impl<B: Value + 'static, D: Value + Item + 'static> StorageType for LmdbStorage<
     fn chainspec_store(&self) -> Arc<dyn ChainspecStore> {
         Arc::clone(&self.chainspec_store) as Arc<dyn ChainspecStore>
     }
+
+    fn execution_results_store(&self) -> Arc<dyn ExecutionResultsStore> {
+        Arc::clone(&self.execution_results_store) as Arc<dyn ExecutionResultsStore>
+    }
+
+    fn block_height_store(&self) -> Arc<dyn BlockHeightStore<B::Id>> {
+        Arc::clone(&self.block_height_store) as Arc<dyn BlockHeightStore<B::Id>>
+    }
 }
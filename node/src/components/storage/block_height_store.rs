@@ -0,0 +1,169 @@
+use std::ops::RangeInclusive;
+
+use super::Result;
+
+/// Trait defining the API for an index of the blocks held by the block store by their height, as
+/// managed by the storage component.
+pub trait BlockHeightStore<Id>: Send + Sync {
+    /// Records `id` as the ID of the block at `height`.
+    fn put(&self, height: u64, id: Id) -> Result<()>;
+    /// Returns the ID of the block at `height`, if any.
+    fn get(&self, height: u64) -> Result<Option<Id>>;
+    /// Returns the greatest height held by the index along with the ID of the block at it, or
+    /// `None` if the index is empty.
+    fn highest(&self) -> Result<Option<(u64, Id)>>;
+    /// Returns the heights up to and including `up_to` which are not held by the index, as
+    /// ranges in ascending order.
+    fn missing_heights(&self, up_to: u64) -> Result<Vec<RangeInclusive<u64>>>;
+}
+
+/// Returns the heights up to and including `up_to` which are not in `heights`, as ranges in
+/// ascending order.
+///
+/// `heights` must be in ascending order.  It is only consumed up to the first height above `up_to`.
+pub(super) fn missing_height_ranges<I>(heights: I, up_to: u64) -> Vec<RangeInclusive<u64>>
+where
+    I: IntoIterator<Item = u64>,
+{
+    let mut missing = Vec::new();
+    let mut next = 0;
+    for height in heights.into_iter().take_while(|height| *height <= up_to) {
+        if height > next {
+            missing.push(next..=height - 1);
+        }
+        next = match height.checked_add(1) {
+            Some(next) => next,
+            None => return missing,
+        };
+    }
+    if next <= up_to {
+        missing.push(next..=up_to);
+    }
+    missing
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        super::{Config, InMemBlockHeightStore, LmdbBlockHeightStore},
+        *,
+    };
+    use crate::{crypto::hash::Digest, testing::TestRng, types::BlockHash};
+
+    fn should_put_then_get<T: BlockHeightStore<BlockHash>>(block_height_store: &mut T) {
+        let mut rng = TestRng::new();
+
+        assert!(block_height_store.highest().unwrap().is_none());
+
+        let block_hashes: Vec<BlockHash> = (0..3)
+            .map(|_| BlockHash::new(Digest::random(&mut rng)))
+            .collect();
+        for (height, block_hash) in [7, 300, 12].iter().zip(&block_hashes) {
+            block_height_store.put(*height, *block_hash).unwrap();
+        }
+
+        assert_eq!(block_height_store.get(7).unwrap(), Some(block_hashes[0]));
+        assert_eq!(block_height_store.get(300).unwrap(), Some(block_hashes[1]));
+        assert_eq!(block_height_store.get(12).unwrap(), Some(block_hashes[2]));
+        assert!(block_height_store.get(8).unwrap().is_none());
+        assert_eq!(
+            block_height_store.highest().unwrap(),
+            Some((300, block_hashes[1]))
+        );
+    }
+
+    #[test]
+    fn lmdb_block_height_store_should_put_then_get() {
+        let (config, _tempdir) = Config::default_for_tests();
+        let mut lmdb_block_height_store = LmdbBlockHeightStore::<BlockHash>::new(
+            config.path(),
+            config.max_block_height_store_size(),
+        )
+        .unwrap();
+        should_put_then_get(&mut lmdb_block_height_store);
+    }
+
+    #[test]
+    fn in_mem_block_height_store_should_put_then_get() {
+        let mut in_mem_block_height_store = InMemBlockHeightStore::<BlockHash>::new();
+        should_put_then_get(&mut in_mem_block_height_store);
+    }
+
+    fn should_report_missing_heights<T: BlockHeightStore<BlockHash>>(block_height_store: &mut T) {
+        let mut rng = TestRng::new();
+
+        assert_eq!(block_height_store.missing_heights(2).unwrap(), vec![0..=2]);
+
+        for height in &[0, 1, 2, 4, 6] {
+            block_height_store
+                .put(*height, BlockHash::new(Digest::random(&mut rng)))
+                .unwrap();
+        }
+
+        assert!(block_height_store.missing_heights(2).unwrap().is_empty());
+        assert_eq!(
+            block_height_store.missing_heights(6).unwrap(),
+            vec![3..=3, 5..=5]
+        );
+        assert_eq!(
+            block_height_store.missing_heights(9).unwrap(),
+            vec![3..=3, 5..=5, 7..=9]
+        );
+    }
+
+    #[test]
+    fn lmdb_block_height_store_should_report_missing_heights() {
+        let (config, _tempdir) = Config::default_for_tests();
+        let mut lmdb_block_height_store = LmdbBlockHeightStore::<BlockHash>::new(
+            config.path(),
+            config.max_block_height_store_size(),
+        )
+        .unwrap();
+        should_report_missing_heights(&mut lmdb_block_height_store);
+    }
+
+    #[test]
+    fn in_mem_block_height_store_should_report_missing_heights() {
+        let mut in_mem_block_height_store = InMemBlockHeightStore::<BlockHash>::new();
+        should_report_missing_heights(&mut in_mem_block_height_store);
+    }
+
+    #[test]
+    fn should_report_missing_height_ranges() {
+        assert_eq!(missing_height_ranges(vec![], 0), vec![0..=0]);
+        assert!(missing_height_ranges(vec![0], 0).is_empty());
+        assert_eq!(missing_height_ranges(vec![2, 3, 8], 5), vec![0..=1, 4..=5]);
+        assert_eq!(
+            missing_height_ranges(vec![0, 1, u64::MAX], u64::MAX),
+            vec![2..=u64::MAX - 1]
+        );
+    }
+
+    #[test]
+    fn lmdb_block_height_store_should_recover_highest_after_reopening() {
+        let mut rng = TestRng::new();
+        let (config, _tempdir) = Config::default_for_tests();
+        let block_hash = BlockHash::new(Digest::random(&mut rng));
+        {
+            let lmdb_block_height_store = LmdbBlockHeightStore::<BlockHash>::new(
+                config.path(),
+                config.max_block_height_store_size(),
+            )
+            .unwrap();
+            lmdb_block_height_store.put(3, block_hash).unwrap();
+            lmdb_block_height_store
+                .put(1, BlockHash::new(Digest::random(&mut rng)))
+                .unwrap();
+        }
+
+        let lmdb_block_height_store = LmdbBlockHeightStore::<BlockHash>::new(
+            config.path(),
+            config.max_block_height_store_size(),
+        )
+        .unwrap();
+        assert_eq!(
+            lmdb_block_height_store.highest().unwrap(),
+            Some((3, block_hash))
+        );
+    }
+}
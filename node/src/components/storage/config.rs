@@ -15,6 +15,8 @@ const APPLICATION: &str = "casper-node";
 const DEFAULT_MAX_BLOCK_STORE_SIZE: usize = 483_183_820_800; // 450 GiB
 const DEFAULT_MAX_DEPLOY_STORE_SIZE: usize = 322_122_547_200; // 300 GiB
 const DEFAULT_MAX_CHAINSPEC_STORE_SIZE: usize = 1_073_741_824; // 1 GiB
+const DEFAULT_MAX_EXECUTION_RESULTS_STORE_SIZE: usize = 322_122_547_200; // 300 GiB
+const DEFAULT_MAX_BLOCK_HEIGHT_STORE_SIZE: usize = 1_073_741_824; // 1 GiB
 
 #[cfg(test)]
 const DEFAULT_TEST_MAX_DB_SIZE: usize = 52_428_800; // 50 MiB
@@ -54,6 +56,18 @@ pub struct Config {
     ///
     /// The size should be a multiple of the OS page size.
     max_chainspec_store_size: Option<usize>,
+    /// The maximum size of the database to use for the execution results store.
+    ///
+    /// Defaults to 322,122,547,200 == 300 GiB.
+    ///
+    /// The size should be a multiple of the OS page size.
+    max_execution_results_store_size: Option<usize>,
+    /// The maximum size of the database to use for the index of blocks by height.
+    ///
+    /// Defaults to 1,073,741,824 == 1 GiB.
+    ///
+    /// The size should be a multiple of the OS page size.
+    max_block_height_store_size: Option<usize>,
 }
 
 impl Config {
@@ -69,6 +83,8 @@ impl Config {
             max_block_store_size: Some(DEFAULT_TEST_MAX_DB_SIZE),
             max_deploy_store_size: Some(DEFAULT_TEST_MAX_DB_SIZE),
             max_chainspec_store_size: Some(DEFAULT_TEST_MAX_DB_SIZE),
+            max_execution_results_store_size: Some(DEFAULT_TEST_MAX_DB_SIZE),
+            max_block_height_store_size: Some(DEFAULT_TEST_MAX_DB_SIZE),
         };
         (config, tempdir)
     }
@@ -104,6 +120,22 @@ impl Config {
         value
     }
 
+    pub(crate) fn max_execution_results_store_size(&self) -> usize {
+        let value = self
+            .max_execution_results_store_size
+            .unwrap_or(DEFAULT_MAX_EXECUTION_RESULTS_STORE_SIZE);
+        utils::check_multiple_of_page_size(value);
+        value
+    }
+
+    pub(crate) fn max_block_height_store_size(&self) -> usize {
+        let value = self
+            .max_block_height_store_size
+            .unwrap_or(DEFAULT_MAX_BLOCK_HEIGHT_STORE_SIZE);
+        utils::check_multiple_of_page_size(value);
+        value
+    }
+
     fn default_path() -> PathBuf {
         ProjectDirs::from(QUALIFIER, ORGANIZATION, APPLICATION)
             .map(|project_dirs| project_dirs.data_dir().to_path_buf())
@@ -122,6 +154,8 @@ impl Default for Config {
             max_block_store_size: Some(DEFAULT_MAX_BLOCK_STORE_SIZE),
             max_deploy_store_size: Some(DEFAULT_MAX_DEPLOY_STORE_SIZE),
             max_chainspec_store_size: Some(DEFAULT_MAX_CHAINSPEC_STORE_SIZE),
+            max_execution_results_store_size: Some(DEFAULT_MAX_EXECUTION_RESULTS_STORE_SIZE),
+            max_block_height_store_size: Some(DEFAULT_MAX_BLOCK_HEIGHT_STORE_SIZE),
         }
     }
 }
@@ -0,0 +1,98 @@
+use super::Result;
+use crate::types::{BlockExecutionResults, BlockHash};
+
+/// Trait defining the API for an execution results store managed by the storage component.
+pub trait ExecutionResultsStore: Send + Sync {
+    fn put(&self, block_hash: BlockHash, execution_results: BlockExecutionResults) -> Result<()>;
+    fn get(&self, block_hash: BlockHash) -> Result<Option<BlockExecutionResults>>;
+}
+
+#[cfg(test)]
+mod tests {
+    use std::iter;
+
+    use super::{
+        super::{Config, InMemExecutionResultsStore, LmdbExecutionResultsStore},
+        *,
+    };
+
+    use crate::{
+        crypto::hash::Digest,
+        testing::TestRng,
+        types::{Deploy, ExecutionResult},
+    };
+
+    fn random_execution_results(rng: &mut TestRng) -> BlockExecutionResults {
+        iter::repeat_with(|| {
+            let deploy = Deploy::random(rng);
+            let execution_result = ExecutionResult::random(rng);
+            (*deploy.id(), (deploy.header().clone(), execution_result))
+        })
+        .take(3)
+        .collect()
+    }
+
+    fn should_put_then_get<T: ExecutionResultsStore>(execution_results_store: &mut T) {
+        let mut rng = TestRng::new();
+
+        let block_hash = BlockHash::new(Digest::random(&mut rng));
+        let execution_results = random_execution_results(&mut rng);
+
+        execution_results_store
+            .put(block_hash, execution_results.clone())
+            .unwrap();
+        let maybe_execution_results = execution_results_store.get(block_hash).unwrap();
+        let recovered_execution_results = maybe_execution_results.unwrap();
+
+        assert_eq!(recovered_execution_results, execution_results);
+    }
+
+    #[test]
+    fn lmdb_execution_results_store_should_put_then_get() {
+        let (config, _tempdir) = Config::default_for_tests();
+        let mut lmdb_execution_results_store = LmdbExecutionResultsStore::new(
+            config.path(),
+            config.max_execution_results_store_size(),
+        )
+        .unwrap();
+        should_put_then_get(&mut lmdb_execution_results_store);
+    }
+
+    #[test]
+    fn in_mem_execution_results_store_should_put_then_get() {
+        let mut in_mem_execution_results_store = InMemExecutionResultsStore::new();
+        should_put_then_get(&mut in_mem_execution_results_store);
+    }
+
+    fn should_fail_get<T: ExecutionResultsStore>(execution_results_store: &mut T) {
+        let mut rng = TestRng::new();
+
+        let block_hash = BlockHash::new(Digest::random(&mut rng));
+        let unknown_block_hash = BlockHash::new(Digest::random(&mut rng));
+
+        execution_results_store
+            .put(block_hash, random_execution_results(&mut rng))
+            .unwrap();
+        assert!(execution_results_store
+            .get(unknown_block_hash)
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn lmdb_execution_results_store_should_fail_to_get_unknown_block() {
+        let (config, _tempdir) = Config::default_for_tests();
+        let mut lmdb_execution_results_store = LmdbExecutionResultsStore::new(
+            config.path(),
+            config.max_execution_results_store_size(),
+        )
+        .unwrap();
+        should_fail_get(&mut lmdb_execution_results_store);
+    }
+
+    #[test]
+    fn in_mem_execution_results_store_should_fail_to_get_unknown_block() {
+        let mut in_mem_execution_results_store = InMemExecutionResultsStore::new();
+        should_fail_get(&mut in_mem_execution_results_store);
+    }
+}
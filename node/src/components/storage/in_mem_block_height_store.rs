@@ -0,0 +1,51 @@
+use std::{collections::BTreeMap, fmt::Debug, ops::RangeInclusive, sync::RwLock};
+
+use super::{block_height_store, BlockHeightStore, Result};
+
+/// In-memory version of a store.
+#[derive(Debug)]
+pub(super) struct InMemBlockHeightStore<Id> {
+    inner: RwLock<BTreeMap<u64, Id>>,
+}
+
+impl<Id> InMemBlockHeightStore<Id> {
+    pub(crate) fn new() -> Self {
+        InMemBlockHeightStore {
+            inner: RwLock::new(BTreeMap::new()),
+        }
+    }
+}
+
+impl<Id: Copy + Send + Sync> BlockHeightStore<Id> for InMemBlockHeightStore<Id> {
+    fn put(&self, height: u64, id: Id) -> Result<()> {
+        let _ = self.inner.write().expect("should lock").insert(height, id);
+        Ok(())
+    }
+
+    fn get(&self, height: u64) -> Result<Option<Id>> {
+        Ok(self
+            .inner
+            .read()
+            .expect("should lock")
+            .get(&height)
+            .copied())
+    }
+
+    fn highest(&self) -> Result<Option<(u64, Id)>> {
+        Ok(self
+            .inner
+            .read()
+            .expect("should lock")
+            .iter()
+            .next_back()
+            .map(|(height, id)| (*height, *id)))
+    }
+
+    fn missing_heights(&self, up_to: u64) -> Result<Vec<RangeInclusive<u64>>> {
+        let inner = self.inner.read().expect("should lock");
+        Ok(block_height_store::missing_height_ranges(
+            inner.range(..=up_to).map(|(height, _id)| *height),
+            up_to,
+        ))
+    }
+}
@@ -0,0 +1,38 @@
+use std::{collections::HashMap, fmt::Debug, sync::RwLock};
+
+use super::{ExecutionResultsStore, Result};
+use crate::types::{BlockExecutionResults, BlockHash};
+
+/// In-memory version of a store.
+#[derive(Debug)]
+pub(super) struct InMemExecutionResultsStore {
+    inner: RwLock<HashMap<BlockHash, BlockExecutionResults>>,
+}
+
+impl InMemExecutionResultsStore {
+    pub(crate) fn new() -> Self {
+        InMemExecutionResultsStore {
+            inner: RwLock::new(HashMap::new()),
+        }
+    }
+}
+
+impl ExecutionResultsStore for InMemExecutionResultsStore {
+    fn put(&self, block_hash: BlockHash, execution_results: BlockExecutionResults) -> Result<()> {
+        let _ = self
+            .inner
+            .write()
+            .expect("should lock")
+            .insert(block_hash, execution_results);
+        Ok(())
+    }
+
+    fn get(&self, block_hash: BlockHash) -> Result<Option<BlockExecutionResults>> {
+        Ok(self
+            .inner
+            .read()
+            .expect("should lock")
+            .get(&block_hash)
+            .cloned())
+    }
+}
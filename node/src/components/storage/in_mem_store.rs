@@ -31,6 +31,19 @@ impl<V: Value> Store for InMemStore<V> {
         Ok(false)
     }
 
+    fn put_or_merge(&self, value: V, merge: fn(&mut V, V)) -> Result<bool> {
+        match self.inner.write().expect("should lock").entry(*value.id()) {
+            Entry::Vacant(entry) => {
+                entry.insert(value);
+                Ok(true)
+            }
+            Entry::Occupied(mut entry) => {
+                merge(entry.get_mut(), value);
+                Ok(false)
+            }
+        }
+    }
+
     fn get(&self, ids: Multiple<V::Id>) -> Multiple<Result<Option<V>>> {
         let inner = self.inner.read().expect("should lock");
         ids.iter().map(|id| Ok(inner.get(id).cloned())).collect()
@@ -0,0 +1,113 @@
+use std::{
+    convert::TryInto, fmt::Debug, marker::PhantomData, ops::RangeInclusive, path::Path,
+    sync::RwLock,
+};
+
+use lmdb::{
+    self, Cursor, Database, DatabaseFlags, Environment, EnvironmentFlags, Transaction, WriteFlags,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use tracing::info;
+
+use super::{block_height_store, BlockHeightStore, Result};
+
+/// LMDB version of a store.
+///
+/// Heights are stored as big-endian keys, so that the database holds them in ascending order.
+#[derive(Debug)]
+pub(super) struct LmdbBlockHeightStore<Id> {
+    env: Environment,
+    db: Database,
+    /// The greatest height in the database, read once on opening it.
+    highest: RwLock<Option<u64>>,
+    _phantom: PhantomData<Id>,
+}
+
+impl<Id> LmdbBlockHeightStore<Id> {
+    pub(crate) fn new<P: AsRef<Path>>(db_path: P, max_size: usize) -> Result<Self> {
+        let env = Environment::new()
+            .set_flags(EnvironmentFlags::NO_SUB_DIR)
+            .set_map_size(max_size)
+            .open(db_path.as_ref())?;
+        let db = env.create_db(None, DatabaseFlags::empty())?;
+        info!("opened DB at {}", db_path.as_ref().display());
+
+        let highest = {
+            let txn = env.begin_ro_txn().expect("should create ro txn");
+            let highest = {
+                let mut cursor = txn.open_ro_cursor(db).expect("should create ro cursor");
+                cursor.iter().map(|(key, _value)| decode_height(key)).last()
+            };
+            txn.commit().expect("should commit txn");
+            highest
+        };
+
+        Ok(LmdbBlockHeightStore {
+            env,
+            db,
+            highest: RwLock::new(highest),
+            _phantom: PhantomData,
+        })
+    }
+}
+
+/// Decodes a height from a database key.
+fn decode_height(key: &[u8]) -> u64 {
+    u64::from_be_bytes(key.try_into().expect("height key should have 8 bytes"))
+}
+
+impl<Id> BlockHeightStore<Id> for LmdbBlockHeightStore<Id>
+where
+    Id: Serialize + DeserializeOwned + Send + Sync,
+{
+    fn put(&self, height: u64, id: Id) -> Result<()> {
+        let serialized_id = rmp_serde::to_vec(&id)?;
+        let mut txn = self.env.begin_rw_txn().expect("should create rw txn");
+        txn.put(
+            self.db,
+            &height.to_be_bytes(),
+            &serialized_id,
+            WriteFlags::empty(),
+        )
+        .expect("should put");
+        txn.commit().expect("should commit txn");
+        let mut highest = self.highest.write().expect("should lock");
+        if highest.map_or(true, |highest| height > highest) {
+            *highest = Some(height);
+        }
+        Ok(())
+    }
+
+    fn get(&self, height: u64) -> Result<Option<Id>> {
+        let txn = self.env.begin_ro_txn().expect("should create ro txn");
+        let serialized_id = match txn.get(self.db, &height.to_be_bytes()) {
+            Ok(value) => value,
+            Err(lmdb::Error::NotFound) => return Ok(None),
+            Err(error) => panic!("should get: {:?}", error),
+        };
+        let id = rmp_serde::from_read_ref(serialized_id)?;
+        txn.commit().expect("should commit txn");
+        Ok(Some(id))
+    }
+
+    fn highest(&self) -> Result<Option<(u64, Id)>> {
+        let maybe_height = *self.highest.read().expect("should lock");
+        match maybe_height {
+            Some(height) => Ok(self.get(height)?.map(|id| (height, id))),
+            None => Ok(None),
+        }
+    }
+
+    fn missing_heights(&self, up_to: u64) -> Result<Vec<RangeInclusive<u64>>> {
+        let txn = self.env.begin_ro_txn().expect("should create ro txn");
+        let missing = {
+            let mut cursor = txn
+                .open_ro_cursor(self.db)
+                .expect("should create ro cursor");
+            let heights = cursor.iter().map(|(key, _value)| decode_height(key));
+            block_height_store::missing_height_ranges(heights, up_to)
+        };
+        txn.commit().expect("should commit txn");
+        Ok(missing)
+    }
+}
@@ -0,0 +1,52 @@
+use std::{fmt::Debug, path::Path};
+
+use lmdb::{self, Database, DatabaseFlags, Environment, EnvironmentFlags, Transaction, WriteFlags};
+use tracing::info;
+
+use super::{ExecutionResultsStore, Result};
+use crate::types::{BlockExecutionResults, BlockHash};
+
+/// LMDB version of a store.
+#[derive(Debug)]
+pub(super) struct LmdbExecutionResultsStore {
+    env: Environment,
+    db: Database,
+}
+
+impl LmdbExecutionResultsStore {
+    pub(crate) fn new<P: AsRef<Path>>(db_path: P, max_size: usize) -> Result<Self> {
+        let env = Environment::new()
+            .set_flags(EnvironmentFlags::NO_SUB_DIR)
+            .set_map_size(max_size)
+            .open(db_path.as_ref())?;
+        let db = env.create_db(None, DatabaseFlags::empty())?;
+        info!("opened DB at {}", db_path.as_ref().display());
+
+        Ok(LmdbExecutionResultsStore { env, db })
+    }
+}
+
+impl ExecutionResultsStore for LmdbExecutionResultsStore {
+    fn put(&self, block_hash: BlockHash, execution_results: BlockExecutionResults) -> Result<()> {
+        let id = rmp_serde::to_vec(&block_hash)?;
+        let serialized_value = rmp_serde::to_vec(&execution_results)?;
+        let mut txn = self.env.begin_rw_txn().expect("should create rw txn");
+        txn.put(self.db, &id, &serialized_value, WriteFlags::empty())
+            .expect("should put");
+        txn.commit().expect("should commit txn");
+        Ok(())
+    }
+
+    fn get(&self, block_hash: BlockHash) -> Result<Option<BlockExecutionResults>> {
+        let id = rmp_serde::to_vec(&block_hash)?;
+        let txn = self.env.begin_ro_txn().expect("should create ro txn");
+        let serialized_value = match txn.get(self.db, &id) {
+            Ok(value) => value,
+            Err(lmdb::Error::NotFound) => return Ok(None),
+            Err(error) => panic!("should get: {:?}", error),
+        };
+        let value = rmp_serde::from_read_ref(serialized_value)?;
+        txn.commit().expect("should commit txn");
+        Ok(Some(value))
+    }
+}
@@ -82,6 +82,27 @@ impl<V: Value> Store for LmdbStore<V> {
         Ok(result)
     }
 
+    fn put_or_merge(&self, value: V, merge: fn(&mut V, V)) -> Result<bool> {
+        let id = rmp_serde::to_vec(value.id())?;
+        // Reading and writing within the same read-write transaction ensures no other write can
+        // happen in between.
+        let mut txn = self.env.begin_rw_txn().expect("should create rw txn");
+        let (merged_value, newly_stored) = match txn.get(self.db, &id) {
+            Ok(serialized_value) => {
+                let mut stored_value: V = rmp_serde::from_read_ref(serialized_value)?;
+                merge(&mut stored_value, value);
+                (stored_value, false)
+            }
+            Err(lmdb::Error::NotFound) => (value, true),
+            Err(error) => panic!("should get: {:?}", error),
+        };
+        let serialized_value = rmp_serde::to_vec(&merged_value)?;
+        txn.put(self.db, &id, &serialized_value, WriteFlags::empty())
+            .unwrap_or_else(|error| panic!("should put: {:?}", error));
+        txn.commit().expect("should commit txn");
+        Ok(newly_stored)
+    }
+
     fn get(&self, ids: Multiple<V::Id>) -> Multiple<Result<Option<V>>> {
         self.get_values(ids)
     }
@@ -1,6 +1,6 @@
-use smallvec::SmallVec;
+use smallvec::{smallvec, SmallVec};
 
-use super::{Result, Value};
+use super::{BlockHeightStore, BlockValue, Result, Value};
 
 pub(super) type Multiple<T> = SmallVec<[T; 3]>;
 
@@ -11,6 +11,13 @@ pub trait Store: Send + Sync {
     /// If the store did not have this value present, true is returned.  If the store did have this
     /// value present, false is returned.
     fn put(&self, block: Self::Value) -> Result<bool>;
+    /// Like `put`, but if the store did have this value present, `merge` is called to merge `value`
+    /// into it, and the result replaces it.
+    fn put_or_merge(
+        &self,
+        value: Self::Value,
+        merge: fn(&mut Self::Value, Self::Value),
+    ) -> Result<bool>;
     fn get(
         &self,
         ids: Multiple<<Self::Value as Value>::Id>,
@@ -23,15 +30,97 @@ pub trait Store: Send + Sync {
     fn ids(&self) -> Result<Vec<<Self::Value as Value>::Id>>;
 }
 
+/// Puts `block` to `store` and records it in `height_store`.
+///
+/// Returns whether `store` did not hold the block before.
+pub(super) fn put_block<B: BlockValue>(
+    store: &dyn Store<Value = B>,
+    height_store: &dyn BlockHeightStore<B::Id>,
+    block: B,
+) -> Result<bool> {
+    let height = block.height();
+    let id = *block.id();
+    let newly_stored = store.put(block)?;
+    height_store.put(height, id)?;
+    Ok(newly_stored)
+}
+
+/// Puts `block` to `store` and records it in `height_store`.  If `store` already holds the block,
+/// the proofs of `block` are merged into the stored ones instead.
+///
+/// Returns whether `store` did not hold the block before.
+pub(super) fn put_block_proofs<B: BlockValue>(
+    store: &dyn Store<Value = B>,
+    height_store: &dyn BlockHeightStore<B::Id>,
+    block: B,
+) -> Result<bool> {
+    let height = block.height();
+    let id = *block.id();
+    let newly_stored = store.put_or_merge(block, B::merge_proofs)?;
+    height_store.put(height, id)?;
+    Ok(newly_stored)
+}
+
+/// Returns the block with the greatest height held by `store`, or `None` if it is empty.
+pub(super) fn highest_block<B: BlockValue>(
+    store: &dyn Store<Value = B>,
+    height_store: &dyn BlockHeightStore<B::Id>,
+) -> Result<Option<B>> {
+    match height_store.highest()? {
+        Some((_, id)) => get_block(store, id),
+        None => Ok(None),
+    }
+}
+
+/// Returns the block at the given height held by `store`, or `None` if there is none.
+pub(super) fn block_at_height<B: BlockValue>(
+    store: &dyn Store<Value = B>,
+    height_store: &dyn BlockHeightStore<B::Id>,
+    height: u64,
+) -> Result<Option<B>> {
+    match height_store.get(height)? {
+        Some(id) => get_block(store, id),
+        None => Ok(None),
+    }
+}
+
+/// Records every block held by `store` in `height_store`.
+///
+/// This reads every block, and is only meant to build the index for a block store which predates
+/// it.
+pub(super) fn index_blocks_by_height<B: BlockValue>(
+    store: &dyn Store<Value = B>,
+    height_store: &dyn BlockHeightStore<B::Id>,
+) -> Result<()> {
+    for id in store.ids()? {
+        if let Some(block) = get_block(store, id)? {
+            height_store.put(block.height(), id)?;
+        }
+    }
+    Ok(())
+}
+
+/// Returns the block with the given ID held by `store`, if any.
+fn get_block<B: BlockValue>(store: &dyn Store<Value = B>, id: B::Id) -> Result<Option<B>> {
+    store
+        .get(smallvec![id])
+        .pop()
+        .expect("can only contain one result")
+}
+
 #[cfg(test)]
 mod tests {
     use smallvec::smallvec;
 
     use super::{
-        super::{Config, InMemStore, LmdbStore},
+        super::{Config, InMemBlockHeightStore, InMemStore, LmdbBlockHeightStore, LmdbStore},
         *,
     };
-    use crate::{testing::TestRng, types::Deploy};
+    use crate::{
+        crypto::asymmetric_key::{self, PublicKey, SecretKey},
+        testing::TestRng,
+        types::{Block, BlockHash, Deploy},
+    };
 
     fn should_put_then_get<T: Store<Value = Deploy>>(store: &mut T) {
         let mut rng = TestRng::new();
@@ -63,4 +152,157 @@ mod tests {
         let mut in_mem_deploy_store = InMemStore::<Deploy>::new();
         should_put_then_get(&mut in_mem_deploy_store);
     }
+
+    fn should_get_highest_block<T: Store<Value = Block>, H: BlockHeightStore<BlockHash>>(
+        store: &mut T,
+        height_store: &mut H,
+    ) {
+        let mut rng = TestRng::new();
+
+        assert!(highest_block(store, height_store).unwrap().is_none());
+
+        let blocks: Vec<Block> = (0..5).map(|_| Block::random(&mut rng)).collect();
+        for block in &blocks {
+            put_block(store, height_store, block.clone()).unwrap();
+        }
+        let expected_height = blocks.iter().map(BlockValue::height).max().unwrap();
+
+        let highest = highest_block(store, height_store).unwrap().unwrap();
+        assert_eq!(highest.height(), expected_height);
+    }
+
+    #[test]
+    fn lmdb_block_store_should_get_highest_block() {
+        let (config, _tempdir) = Config::default_for_tests();
+        let mut lmdb_block_store =
+            LmdbStore::<Block>::new(config.path(), config.max_block_store_size()).unwrap();
+        let mut lmdb_block_height_store = LmdbBlockHeightStore::<BlockHash>::new(
+            config.path().with_file_name("block_height_store.db"),
+            config.max_block_height_store_size(),
+        )
+        .unwrap();
+        should_get_highest_block(&mut lmdb_block_store, &mut lmdb_block_height_store);
+    }
+
+    #[test]
+    fn in_mem_block_store_should_get_highest_block() {
+        let mut in_mem_block_store = InMemStore::<Block>::new();
+        let mut in_mem_block_height_store = InMemBlockHeightStore::<BlockHash>::new();
+        should_get_highest_block(&mut in_mem_block_store, &mut in_mem_block_height_store);
+    }
+
+    fn should_get_block_at_height<T: Store<Value = Block>, H: BlockHeightStore<BlockHash>>(
+        store: &mut T,
+        height_store: &mut H,
+    ) {
+        let mut rng = TestRng::new();
+
+        let blocks: Vec<Block> = (0..5).map(|_| Block::random(&mut rng)).collect();
+        for block in &blocks {
+            put_block(store, height_store, block.clone()).unwrap();
+        }
+
+        for block in &blocks {
+            let found = block_at_height(store, height_store, block.height())
+                .unwrap()
+                .unwrap();
+            assert_eq!(found.height(), block.height());
+        }
+
+        let missing_height = blocks.iter().map(BlockValue::height).max().unwrap() + 1;
+        assert!(block_at_height(store, height_store, missing_height)
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn lmdb_block_store_should_get_block_at_height() {
+        let (config, _tempdir) = Config::default_for_tests();
+        let mut lmdb_block_store =
+            LmdbStore::<Block>::new(config.path(), config.max_block_store_size()).unwrap();
+        let mut lmdb_block_height_store = LmdbBlockHeightStore::<BlockHash>::new(
+            config.path().with_file_name("block_height_store.db"),
+            config.max_block_height_store_size(),
+        )
+        .unwrap();
+        should_get_block_at_height(&mut lmdb_block_store, &mut lmdb_block_height_store);
+    }
+
+    #[test]
+    fn in_mem_block_store_should_get_block_at_height() {
+        let mut in_mem_block_store = InMemStore::<Block>::new();
+        let mut in_mem_block_height_store = InMemBlockHeightStore::<BlockHash>::new();
+        should_get_block_at_height(&mut in_mem_block_store, &mut in_mem_block_height_store);
+    }
+
+    fn should_merge_proofs_of_stored_block<
+        T: Store<Value = Block>,
+        H: BlockHeightStore<BlockHash>,
+    >(
+        store: &mut T,
+        height_store: &mut H,
+    ) {
+        let mut rng = TestRng::new();
+
+        let block = Block::random(&mut rng);
+        assert!(put_block_proofs(store, height_store, block.clone()).unwrap());
+
+        let mut signed_block = block.clone();
+        let secret_key = SecretKey::random(&mut rng);
+        let public_key = PublicKey::from(&secret_key);
+        let signature =
+            asymmetric_key::sign(block.hash().inner(), &secret_key, &public_key, &mut rng);
+        assert!(signed_block.append_proof(public_key, signature));
+
+        // The further proof is added, and putting the outdated copy again doesn't remove it.
+        assert!(!put_block_proofs(store, height_store, signed_block.clone()).unwrap());
+        assert!(!put_block_proofs(store, height_store, block).unwrap());
+
+        let stored_block = get_block(store, *signed_block.hash()).unwrap().unwrap();
+        assert_eq!(stored_block, signed_block);
+    }
+
+    #[test]
+    fn lmdb_block_store_should_merge_proofs_of_stored_block() {
+        let (config, _tempdir) = Config::default_for_tests();
+        let mut lmdb_block_store =
+            LmdbStore::<Block>::new(config.path(), config.max_block_store_size()).unwrap();
+        let mut lmdb_block_height_store = LmdbBlockHeightStore::<BlockHash>::new(
+            config.path().with_file_name("block_height_store.db"),
+            config.max_block_height_store_size(),
+        )
+        .unwrap();
+        should_merge_proofs_of_stored_block(&mut lmdb_block_store, &mut lmdb_block_height_store);
+    }
+
+    #[test]
+    fn in_mem_block_store_should_merge_proofs_of_stored_block() {
+        let mut in_mem_block_store = InMemStore::<Block>::new();
+        let mut in_mem_block_height_store = InMemBlockHeightStore::<BlockHash>::new();
+        should_merge_proofs_of_stored_block(
+            &mut in_mem_block_store,
+            &mut in_mem_block_height_store,
+        );
+    }
+
+    #[test]
+    fn should_index_existing_blocks_by_height() {
+        let mut rng = TestRng::new();
+        let store = InMemStore::<Block>::new();
+        let height_store = InMemBlockHeightStore::<BlockHash>::new();
+
+        let blocks: Vec<Block> = (0..5).map(|_| Block::random(&mut rng)).collect();
+        for block in &blocks {
+            store.put(block.clone()).unwrap();
+        }
+        assert!(highest_block(&store, &height_store).unwrap().is_none());
+
+        index_blocks_by_height(&store, &height_store).unwrap();
+        for block in &blocks {
+            let found = block_at_height(&store, &height_store, block.height())
+                .unwrap()
+                .unwrap();
+            assert_eq!(found.height(), block.height());
+        }
+    }
 }
@@ -63,10 +63,11 @@ pub mod requests;
 
 use std::{
     any::type_name,
-    collections::{HashMap, HashSet},
+    collections::{BTreeMap, HashMap, HashSet},
     fmt::{self, Debug, Display, Formatter},
     future::Future,
     net::SocketAddr,
+    ops::RangeInclusive,
     time::{Duration, Instant},
 };
 
@@ -78,35 +79,50 @@ use tracing::error;
 use casper_execution_engine::{
     core::{
         engine_state::{
-            self, execute_request::ExecuteRequest, execution_result::ExecutionResults,
+            self,
+            execute_request::ExecuteRequest,
+            execution_result::ExecutionResults,
             genesis::GenesisResult,
+            query::{QueryRequest, QueryResult},
         },
         execution,
     },
-    shared::{additive_map::AdditiveMap, transform::Transform},
+    shared::{additive_map::AdditiveMap, motes::Motes, transform::Transform},
     storage::global_state::CommitResult,
 };
-use casper_types::Key;
+use casper_types::{Key, U512};
 
 use crate::{
     components::{
-        consensus::BlockContext,
+        block_executor::StepEffect,
+        chainspec_loader::NextUpgrade,
+        consensus::{BlockContext, EraId},
+        contract_runtime::{BalanceError, BalanceIdentifier, ExecuteError},
+        deploy_acceptor::Error as DeployAcceptorError,
+        deploy_buffer::DropReason,
         fetcher::FetchResult,
         small_network::GossipedAddress,
-        storage::{DeployHashes, DeployHeaderResults, DeployResults, StorageType, Value},
+        storage::{
+            DeployHashes, DeployHeaderResults, DeployResults, Error as StorageError, StorageType,
+            Value,
+        },
     },
     crypto::{
         asymmetric_key::{PublicKey, Signature},
         hash::Digest,
     },
     reactor::{EventQueueHandle, QueueKind},
-    types::{Block, BlockHash, BlockHeader, Deploy, DeployHash, FinalizedBlock, Item, ProtoBlock},
+    types::{
+        Block, BlockExecutionResults, BlockHash, BlockHeader, BlockIdentifier, Deploy, DeployHash,
+        FinalizedBlock, Item, ProtoBlock,
+    },
     utils::Source,
     Chainspec,
 };
 use announcements::{
-    ApiServerAnnouncement, BlockExecutorAnnouncement, ConsensusAnnouncement,
-    DeployAcceptorAnnouncement, GossiperAnnouncement, NetworkAnnouncement,
+    ApiServerAnnouncement, BlockExecutorAnnouncement, ChainspecLoaderAnnouncement,
+    ConsensusAnnouncement, ControlAnnouncement, DeployAcceptorAnnouncement,
+    DeployBufferAnnouncement, GossiperAnnouncement, LinearChainAnnouncement, NetworkAnnouncement,
 };
 use requests::{
     BlockExecutorRequest, BlockValidationRequest, ConsensusRequest, ContractRuntimeRequest,
@@ -353,8 +369,8 @@ impl<REv> EffectBuilder<REv> {
     /// Reports a fatal error.
     ///
     /// Usually causes the node to cease operations quickly and exit/crash.
-    pub async fn fatal<M: Display + ?Sized>(self, file: &str, line: u32, msg: &M) {
-        panic!("fatal error [{}:{}]: {}", file, line, msg);
+    pub async fn fatal(self, fatal_error: ControlAnnouncement) {
+        panic!("{}", fatal_error);
     }
 
     /// Sets a timeout.
@@ -389,6 +405,71 @@ impl<REv> EffectBuilder<REv> {
             .await
     }
 
+    /// Retrieves the total stake of the validators which signed the block with the given hash.
+    ///
+    /// Returns `None` if the block or the validators of its era are unknown.
+    #[cfg(test)]
+    pub(crate) async fn get_finality_signature_weight<I>(
+        self,
+        block_hash: BlockHash,
+    ) -> Option<Motes>
+    where
+        REv: From<LinearChainRequest<I>>,
+    {
+        self.make_request(
+            |responder| LinearChainRequest::FinalitySignatureWeight(block_hash, responder),
+            QueueKind::Api,
+        )
+        .await
+    }
+
+    /// Retrieves the highest block which has collected enough finality signatures.
+    ///
+    /// Returns `None` if no such block is known.
+    pub(crate) async fn get_highest_block_with_sufficient_finality<I>(self) -> Option<Block>
+    where
+        REv: From<LinearChainRequest<I>>,
+    {
+        self.make_request(
+            LinearChainRequest::HighestBlockWithSufficientFinality,
+            QueueKind::Api,
+        )
+        .await
+    }
+
+    /// Retrieves the block with the given hash or at the given height from the linear chain.
+    ///
+    /// Returns `None` if the block is unknown.
+    pub(crate) async fn get_block<I>(self, block_identifier: BlockIdentifier) -> Option<Block>
+    where
+        REv: From<LinearChainRequest<I>>,
+    {
+        self.make_request(
+            |responder| LinearChainRequest::GetBlock(block_identifier, responder),
+            QueueKind::Api,
+        )
+        .await
+    }
+
+    /// Retrieves the finality signatures collected for the block with the given hash, with their
+    /// signers.
+    ///
+    /// Returns `None` if the block is unknown.
+    #[cfg(test)]
+    pub(crate) async fn get_finality_signatures<I>(
+        self,
+        block_hash: BlockHash,
+    ) -> Option<Vec<(PublicKey, Signature)>>
+    where
+        REv: From<LinearChainRequest<I>>,
+    {
+        self.make_request(
+            |responder| LinearChainRequest::FinalitySignatures(block_hash, responder),
+            QueueKind::Api,
+        )
+        .await
+    }
+
     /// Sends a network message.
     ///
     /// The message is queued in "fire-and-forget" fashion, there is no guarantee that the peer
@@ -491,13 +572,24 @@ impl<REv> EffectBuilder<REv> {
     }
 
     /// Announces that a new peer has connected.
-    pub(crate) async fn announce_new_peer<I, P>(self, peer_id: I)
-    where
+    ///
+    /// `inbound` indicates whether the connection completing the link was accepted rather than
+    /// dialed by us.
+    pub(crate) async fn announce_new_peer<I, P>(
+        self,
+        peer: I,
+        inbound: bool,
+        addr: Option<SocketAddr>,
+    ) where
         REv: From<NetworkAnnouncement<I, P>>,
     {
         self.0
             .schedule(
-                NetworkAnnouncement::NewPeer(peer_id),
+                NetworkAnnouncement::NewPeer {
+                    peer,
+                    inbound,
+                    addr,
+                },
                 QueueKind::NetworkIncoming,
             )
             .await;
@@ -521,6 +613,33 @@ impl<REv> EffectBuilder<REv> {
             .await;
     }
 
+    /// Announces that a gossiper has forwarded an item, having sent it to `peer_count` peers in
+    /// total so far.
+    pub(crate) async fn announce_item_gossiped<T: Item>(self, id: T::Id, peer_count: usize)
+    where
+        REv: From<GossiperAnnouncement<T>>,
+    {
+        self.0
+            .schedule(
+                GossiperAnnouncement::ItemGossiped { id, peer_count },
+                QueueKind::Regular,
+            )
+            .await;
+    }
+
+    /// Announces that a gossiper has finished gossiping an item, since it has saturated the network.
+    pub(crate) async fn announce_finished_gossiping<T: Item>(self, item_id: T::Id)
+    where
+        REv: From<GossiperAnnouncement<T>>,
+    {
+        self.0
+            .schedule(
+                GossiperAnnouncement::FinishedGossiping(item_id),
+                QueueKind::Regular,
+            )
+            .await;
+    }
+
     /// Announces that the HTTP API server has received a deploy.
     pub(crate) async fn announce_deploy_received(self, deploy: Box<Deploy>)
     where
@@ -549,21 +668,39 @@ impl<REv> EffectBuilder<REv> {
         )
     }
 
-    /// Announces that an invalid deploy has been received.
+    /// Announces that an invalid deploy has been received, and why it was rejected.
     pub(crate) fn announce_invalid_deploy<I>(
         self,
         deploy: Box<Deploy>,
         source: Source<I>,
+        error: DeployAcceptorError,
     ) -> impl Future<Output = ()>
     where
         REv: From<DeployAcceptorAnnouncement<I>>,
     {
         self.0.schedule(
-            DeployAcceptorAnnouncement::InvalidDeploy { deploy, source },
+            DeployAcceptorAnnouncement::InvalidDeploy {
+                deploy,
+                source,
+                error,
+            },
             QueueKind::Regular,
         )
     }
 
+    /// Announce that a deploy has been dropped from the deploy buffer.
+    pub(crate) async fn announce_deploy_dropped(self, hash: DeployHash, reason: DropReason)
+    where
+        REv: From<DeployBufferAnnouncement>,
+    {
+        self.0
+            .schedule(
+                DeployBufferAnnouncement::DeployDropped { hash, reason },
+                QueueKind::Regular,
+            )
+            .await
+    }
+
     /// Announce new block has been created.
     pub(crate) async fn announce_linear_chain_block(self, block: Block)
     where
@@ -577,8 +714,90 @@ impl<REv> EffectBuilder<REv> {
             .await
     }
 
+    /// Announce that the step at the end of an era has been executed.
+    pub(crate) async fn announce_step_executed(self, era_id: EraId, effects_summary: StepEffect)
+    where
+        REv: From<BlockExecutorAnnouncement>,
+    {
+        self.0
+            .schedule(
+                BlockExecutorAnnouncement::StepExecuted {
+                    era_id,
+                    effects_summary,
+                },
+                QueueKind::Regular,
+            )
+            .await
+    }
+
+    /// Announce that executing a finalized block has failed.
+    pub(crate) async fn announce_block_execution_failed(
+        self,
+        finalized_block: FinalizedBlock,
+        error: ExecuteError,
+    ) where
+        REv: From<BlockExecutorAnnouncement>,
+    {
+        self.0
+            .schedule(
+                BlockExecutorAnnouncement::BlockExecutionFailed {
+                    finalized_block,
+                    error,
+                },
+                QueueKind::Regular,
+            )
+            .await
+    }
+
+    /// Announce that the node has reached the activation point of an upgrade it has not applied.
+    pub(crate) async fn announce_upgrade_overdue(self, current_era: EraId, upgrade: NextUpgrade)
+    where
+        REv: From<ChainspecLoaderAnnouncement>,
+    {
+        self.0
+            .schedule(
+                ChainspecLoaderAnnouncement::UpgradeOverdue {
+                    current_era,
+                    upgrade,
+                },
+                QueueKind::Regular,
+            )
+            .await
+    }
+
+    /// Announce that a block has been added to the linear chain and put to storage.
+    pub(crate) async fn announce_block_added(self, block: Block)
+    where
+        REv: From<LinearChainAnnouncement>,
+    {
+        self.0
+            .schedule(
+                LinearChainAnnouncement::BlockAdded(Box::new(block)),
+                QueueKind::Regular,
+            )
+            .await
+    }
+
+    /// Announce that a block has received enough finality signatures to be considered finalized.
+    pub(crate) async fn announce_block_finalized(self, block: Block)
+    where
+        REv: From<LinearChainAnnouncement>,
+    {
+        self.0
+            .schedule(
+                LinearChainAnnouncement::BlockFinalized(Box::new(block)),
+                QueueKind::Regular,
+            )
+            .await
+    }
+
     /// Puts the given block into the linear block store.
-    pub(crate) async fn put_block_to_storage<S>(self, block: Box<S::Block>) -> bool
+    ///
+    /// Returns whether the block was newly stored, or an error if writing it failed.
+    pub(crate) async fn put_block_to_storage<S>(
+        self,
+        block: Box<S::Block>,
+    ) -> Result<bool, StorageError>
     where
         S: StorageType + 'static,
         REv: From<StorageRequest<S>>,
@@ -590,6 +809,25 @@ impl<REv> EffectBuilder<REv> {
         .await
     }
 
+    /// Puts the given block into the linear block store, or if it is already stored, adds the
+    /// given block's finality signatures to the stored ones.
+    ///
+    /// Returns whether the block was newly stored, or an error if writing it failed.
+    pub(crate) async fn put_block_proofs_to_storage<S>(
+        self,
+        block: Box<S::Block>,
+    ) -> Result<bool, StorageError>
+    where
+        S: StorageType + 'static,
+        REv: From<StorageRequest<S>>,
+    {
+        self.make_request(
+            |responder| StorageRequest::PutBlockProofs { block, responder },
+            QueueKind::Regular,
+        )
+        .await
+    }
+
     /// Gets the requested block from the linear block store.
     pub(crate) async fn get_block_from_storage<S>(
         self,
@@ -629,6 +867,49 @@ impl<REv> EffectBuilder<REv> {
         .await
     }
 
+    /// Gets the block with the greatest height from the linear block store.
+    pub(crate) async fn get_highest_block_from_storage<S>(self) -> Option<S::Block>
+    where
+        S: StorageType + 'static,
+        REv: From<StorageRequest<S>>,
+    {
+        self.make_request(
+            |responder| StorageRequest::GetHighestBlock { responder },
+            QueueKind::Regular,
+        )
+        .await
+    }
+
+    /// Gets a block at the given height from the linear block store.
+    pub(crate) async fn get_block_at_height_from_storage<S>(self, height: u64) -> Option<S::Block>
+    where
+        S: StorageType + 'static,
+        REv: From<StorageRequest<S>>,
+    {
+        self.make_request(
+            |responder| StorageRequest::GetBlockAtHeight { height, responder },
+            QueueKind::Regular,
+        )
+        .await
+    }
+
+    /// Gets the heights up to and including `up_to` at which there is no block in the linear block
+    /// store, as ranges in ascending order.
+    pub(crate) async fn get_missing_block_heights_from_storage<S>(
+        self,
+        up_to: u64,
+    ) -> Vec<RangeInclusive<u64>>
+    where
+        S: StorageType + 'static,
+        REv: From<StorageRequest<S>>,
+    {
+        self.make_request(
+            |responder| StorageRequest::GetMissingBlockHeights { up_to, responder },
+            QueueKind::Regular,
+        )
+        .await
+    }
+
     /// Puts the given deploy into the deploy store.
     pub(crate) async fn put_deploy_to_storage<S>(self, deploy: Box<S::Deploy>) -> bool
     where
@@ -764,6 +1045,18 @@ impl<REv> EffectBuilder<REv> {
         (proto_block, block_context)
     }
 
+    /// Gets the hashes of the deploys buffered but not yet proposed in a block.
+    pub(crate) async fn get_pending_deploys(self) -> Vec<DeployHash>
+    where
+        REv: From<DeployBufferRequest>,
+    {
+        self.make_request(
+            |responder| DeployBufferRequest::ListPending { responder },
+            QueueKind::Api,
+        )
+        .await
+    }
+
     /// Passes a finalized proto-block to the block executor component to execute it.
     pub(crate) async fn execute_block(self, finalized_block: FinalizedBlock)
     where
@@ -811,14 +1104,14 @@ impl<REv> EffectBuilder<REv> {
             .await
     }
 
-    /// Announces that a proto block has been finalized.
-    pub(crate) async fn announce_finalized_proto_block(self, proto_block: ProtoBlock)
+    /// Announces that a block has been finalized.
+    pub(crate) async fn announce_finalized_block(self, finalized_block: FinalizedBlock)
     where
         REv: From<ConsensusAnnouncement>,
     {
         self.0
             .schedule(
-                ConsensusAnnouncement::Finalized(proto_block),
+                ConsensusAnnouncement::Finalized(Box::new(finalized_block)),
                 QueueKind::Regular,
             )
             .await
@@ -838,6 +1131,27 @@ impl<REv> EffectBuilder<REv> {
             .await
     }
 
+    /// Announces that consensus has moved on from `ended_era` to `new_era`.
+    pub(crate) async fn announce_era_transition(
+        self,
+        ended_era: EraId,
+        new_era: EraId,
+        new_validators: Vec<PublicKey>,
+    ) where
+        REv: From<ConsensusAnnouncement>,
+    {
+        self.0
+            .schedule(
+                ConsensusAnnouncement::EraTransition {
+                    ended_era,
+                    new_era,
+                    new_validators,
+                },
+                QueueKind::Regular,
+            )
+            .await
+    }
+
     /// Runs the genesis process on the contract runtime.
     pub(crate) async fn commit_genesis(
         self,
@@ -885,11 +1199,52 @@ impl<REv> EffectBuilder<REv> {
         .await
     }
 
+    /// Puts the execution results of the deploys of the given block into the execution results
+    /// store.
+    pub(crate) async fn put_execution_results_to_storage<S>(
+        self,
+        block_hash: BlockHash,
+        execution_results: BlockExecutionResults,
+    ) where
+        S: StorageType + 'static,
+        REv: From<StorageRequest<S>>,
+    {
+        self.make_request(
+            |responder| StorageRequest::PutExecutionResults {
+                block_hash,
+                execution_results,
+                responder,
+            },
+            QueueKind::Regular,
+        )
+        .await
+    }
+
+    /// Gets the execution results of the deploys of the given block from the execution results
+    /// store.
+    pub(crate) async fn get_execution_results_from_storage<S>(
+        self,
+        block_hash: BlockHash,
+    ) -> Option<BlockExecutionResults>
+    where
+        S: StorageType + 'static,
+        REv: From<StorageRequest<S>>,
+    {
+        self.make_request(
+            |responder| StorageRequest::GetExecutionResults {
+                block_hash,
+                responder,
+            },
+            QueueKind::Regular,
+        )
+        .await
+    }
+
     /// Requests an execution of deploys using Contract Runtime.
     pub(crate) async fn request_execute(
         self,
         execute_request: ExecuteRequest,
-    ) -> Result<ExecutionResults, engine_state::RootNotFound>
+    ) -> Result<ExecutionResults, ExecuteError>
     where
         REv: From<ContractRuntimeRequest>,
     {
@@ -903,12 +1258,50 @@ impl<REv> EffectBuilder<REv> {
         .await
     }
 
+    /// Requests a query of global state on the Contract Runtime component.
+    pub(crate) async fn query_global_state(
+        self,
+        query_request: QueryRequest,
+    ) -> Result<QueryResult, engine_state::Error>
+    where
+        REv: From<ContractRuntimeRequest>,
+    {
+        self.make_request(
+            |responder| ContractRuntimeRequest::Query {
+                query_request,
+                responder,
+            },
+            QueueKind::Regular,
+        )
+        .await
+    }
+
+    /// Requests the balance of a purse from the Contract Runtime component.
+    pub(crate) async fn get_balance(
+        self,
+        state_root_hash: Digest,
+        purse: BalanceIdentifier,
+    ) -> Result<U512, BalanceError>
+    where
+        REv: From<ContractRuntimeRequest>,
+    {
+        self.make_request(
+            |responder| ContractRuntimeRequest::GetBalance {
+                state_root_hash,
+                purse,
+                responder,
+            },
+            QueueKind::Regular,
+        )
+        .await
+    }
+
     /// Requests a commit of effects on the Contract Runtime component.
     pub(crate) async fn request_commit(
         self,
         pre_state_hash: Digest,
         effects: AdditiveMap<Key, Transform>,
-    ) -> Result<CommitResult, engine_state::Error>
+    ) -> Result<CommitResult, ExecuteError>
     where
         REv: From<ContractRuntimeRequest>,
     {
@@ -923,6 +1316,28 @@ impl<REv> EffectBuilder<REv> {
         .await
     }
 
+    /// Requests the Contract Runtime component to pre-read the global state along the given keys.
+    pub(crate) async fn warm_contract_runtime_cache(
+        self,
+        state_root: Digest,
+        protocol_version: Version,
+        key_hints: Vec<Key>,
+    ) -> Result<(), engine_state::Error>
+    where
+        REv: From<ContractRuntimeRequest>,
+    {
+        self.make_request(
+            |responder| ContractRuntimeRequest::WarmCache {
+                state_root,
+                protocol_version,
+                key_hints,
+                responder,
+            },
+            QueueKind::Regular,
+        )
+        .await
+    }
+
     /// Returns a map of validators for given `era` to their weights as known from `root_hash`.
     ///
     /// This operation is read only.
@@ -944,7 +1359,12 @@ impl<REv> EffectBuilder<REv> {
     }
 
     /// Request consensus to sign a block from the linear chain and possibly start a new era.
-    pub(crate) async fn handle_linear_chain_block(self, block_header: BlockHeader) -> Signature
+    ///
+    /// Returns our public key and signature only once consensus has fully incorporated the block.
+    pub(crate) async fn handle_linear_chain_block(
+        self,
+        block_header: BlockHeader,
+    ) -> (PublicKey, Signature)
     where
         REv: From<ConsensusRequest>,
     {
@@ -954,15 +1374,64 @@ impl<REv> EffectBuilder<REv> {
         )
         .await
     }
+
+    /// Requests the validators of the given era with their stakes from consensus.  Returns `None`
+    /// if the era is not active.
+    pub(crate) async fn get_validator_stakes(
+        self,
+        era_id: EraId,
+    ) -> Option<BTreeMap<PublicKey, Motes>>
+    where
+        REv: From<ConsensusRequest>,
+    {
+        self.make_request(
+            |responder| ConsensusRequest::ValidatorStakes(era_id, responder),
+            QueueKind::Regular,
+        )
+        .await
+    }
+
+    /// Requests the ID of the current era from consensus.
+    pub(crate) async fn get_current_era(self) -> EraId
+    where
+        REv: From<ConsensusRequest>,
+    {
+        self.make_request(ConsensusRequest::CurrentEra, QueueKind::Regular)
+            .await
+    }
 }
 
 /// Construct a fatal error effect.
 ///
 /// This macro is a convenient wrapper around `EffectBuilder::fatal` that inserts the `file!()` and
-/// `line!()` number automatically.
+/// `line!()` number and the `module_path!()` of the reporting component automatically.  An error
+/// code can optionally be given as `code = <CODE>` before the message.
 #[macro_export]
 macro_rules! fatal {
+    ($effect_builder:expr, code = $code:expr, $msg:expr) => {
+        $effect_builder
+            .fatal(
+                $crate::effect::announcements::ControlAnnouncement::FatalError {
+                    file: file!(),
+                    line: line!(),
+                    component: module_path!(),
+                    error_code: Some($code),
+                    msg: $msg.to_string(),
+                },
+            )
+            .ignore()
+    };
     ($effect_builder:expr, $msg:expr) => {
-        $effect_builder.fatal(file!(), line!(), &$msg).ignore()
+        $effect_builder
+            .fatal(
+                $crate::effect::announcements::ControlAnnouncement::FatalError {
+                    file: file!(),
+                    line: line!(),
+                    component: module_path!(),
+                    error_code: None,
+                    msg: $msg.to_string(),
+                },
+            )
+            .ignore()
     };
 }
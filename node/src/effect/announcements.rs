@@ -3,14 +3,106 @@
 //! Announcements indicate new incoming data or events from various sources. See the top-level
 //! module documentation for details.
 
-use std::fmt::{self, Display, Formatter};
+use std::{
+    fmt::{self, Display, Formatter},
+    net::SocketAddr,
+};
+
+use serde::Serialize;
 
 use crate::{
-    components::small_network::GossipedAddress,
-    types::{Block, Deploy, Item, ProtoBlock},
+    components::{
+        block_executor::StepEffect, chainspec_loader::NextUpgrade, consensus::EraId,
+        contract_runtime::ExecuteError, deploy_acceptor::Error as DeployAcceptorError,
+        deploy_buffer::DropReason, small_network::GossipedAddress,
+    },
+    crypto::asymmetric_key::PublicKey,
+    types::{Block, Deploy, DeployHash, FinalizedBlock, Item, ProtoBlock},
     utils::Source,
 };
 
+/// The severity of a control announcement.
+///
+/// Severities are ordered from lowest to highest; announcements of a higher severity take
+/// precedence.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum Severity {
+    /// Something unexpected happened, but the node can carry on.
+    Warning,
+    /// The node should stop.
+    Shutdown,
+    /// The node cannot carry on and must stop immediately.
+    Fatal,
+}
+
+/// A control announcement.
+///
+/// Control announcements concern the node as a whole rather than a single component.
+#[derive(Debug)]
+pub enum ControlAnnouncement {
+    /// A component has encountered a fatal error the node cannot recover from.
+    FatalError {
+        /// The source file in which the error was reported.
+        file: &'static str,
+        /// The line at which the error was reported.
+        line: u32,
+        /// The module path of the component which reported the error.
+        component: &'static str,
+        /// A code identifying the kind of error, if any.
+        error_code: Option<u32>,
+        /// A description of the error.
+        msg: String,
+    },
+    /// The node has been asked to shut down.
+    Shutdown {
+        /// Whether the node should finish its ongoing work before stopping.
+        graceful: bool,
+        /// Why the node is shutting down.
+        reason: String,
+    },
+    /// A component has encountered a problem the node can recover from.
+    Warning {
+        /// A description of the problem.
+        msg: String,
+    },
+}
+
+impl ControlAnnouncement {
+    /// Returns the severity of the announcement.
+    pub fn severity(&self) -> Severity {
+        match self {
+            ControlAnnouncement::FatalError { .. } => Severity::Fatal,
+            ControlAnnouncement::Shutdown { .. } => Severity::Shutdown,
+            ControlAnnouncement::Warning { .. } => Severity::Warning,
+        }
+    }
+}
+
+impl Display for ControlAnnouncement {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ControlAnnouncement::FatalError {
+                file,
+                line,
+                component,
+                error_code,
+                msg,
+            } => {
+                write!(f, "fatal error in {} [{}:{}]", component, file, line)?;
+                if let Some(code) = error_code {
+                    write!(f, " (code {})", code)?;
+                }
+                write!(f, ": {}", msg)
+            }
+            ControlAnnouncement::Shutdown { graceful, reason } => {
+                let kind = if *graceful { "graceful" } else { "immediate" };
+                write!(f, "{} shutdown requested: {}", kind, reason)
+            }
+            ControlAnnouncement::Warning { msg } => write!(f, "warning: {}", msg),
+        }
+    }
+}
+
 /// A networking layer announcement.
 #[derive(Debug)]
 #[must_use]
@@ -29,7 +121,15 @@ pub enum NetworkAnnouncement<I, P> {
     /// IMPORTANT NOTE: This announcement is a work-around for some short-term functionality. Do
     ///                 not rely on or use this for anything without asking anyone that has written
     ///                 this section of the code first!
-    NewPeer(I),
+    NewPeer {
+        /// The ID of the newly connected peer.
+        peer: I,
+        /// Whether the connection which completed the link to the peer was accepted by us (`true`)
+        /// or dialed by us (`false`).
+        inbound: bool,
+        /// The remote address of the connection, if known.
+        addr: Option<SocketAddr>,
+    },
 }
 
 impl<I, P> Display for NetworkAnnouncement<I, P>
@@ -43,8 +143,21 @@ where
                 write!(formatter, "received from {}: {}", sender, payload)
             }
             NetworkAnnouncement::GossipOurAddress(_) => write!(formatter, "gossip our address"),
-            NetworkAnnouncement::NewPeer(id) => {
-                write!(formatter, "new peer connection established to {}", id)
+            NetworkAnnouncement::NewPeer {
+                peer,
+                inbound,
+                addr,
+            } => {
+                let direction = if *inbound { "inbound" } else { "outbound" };
+                write!(
+                    formatter,
+                    "new {} peer connection established to {}",
+                    direction, peer
+                )?;
+                if let Some(addr) = addr {
+                    write!(formatter, " at {}", addr)?;
+                }
+                Ok(())
             }
         }
     }
@@ -88,6 +201,8 @@ pub enum DeployAcceptorAnnouncement<I> {
         deploy: Box<Deploy>,
         /// The source (peer or client) of the deploy.
         source: Source<I>,
+        /// The reason the deploy was rejected.
+        error: DeployAcceptorError,
     },
 }
 
@@ -100,23 +215,39 @@ impl<I: Display> Display for DeployAcceptorAnnouncement<I> {
                 deploy.id(),
                 source
             ),
-            DeployAcceptorAnnouncement::InvalidDeploy { deploy, source } => {
-                write!(formatter, "invalid deploy {} from {}", deploy.id(), source)
-            }
+            DeployAcceptorAnnouncement::InvalidDeploy {
+                deploy,
+                source,
+                error,
+            } => write!(
+                formatter,
+                "invalid deploy {} from {}: {}",
+                deploy.id(),
+                source,
+                error
+            ),
         }
     }
 }
 
 /// A consensus announcement.
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
 pub enum ConsensusAnnouncement {
     /// A block was proposed and will either be finalized or orphaned soon.
     Proposed(ProtoBlock),
     /// A block was finalized.
-    // TODO: Replace with `FinalizedBlock`.
-    Finalized(ProtoBlock),
+    Finalized(Box<FinalizedBlock>),
     /// A block was orphaned.
     Orphaned(ProtoBlock),
+    /// A switch block was finalized and consensus moved on to a new era.
+    EraTransition {
+        /// The era that has just ended.
+        ended_era: EraId,
+        /// The era that has just started.
+        new_era: EraId,
+        /// The validators of the new era.
+        new_validators: Vec<PublicKey>,
+    },
 }
 
 impl Display for ConsensusAnnouncement {
@@ -125,12 +256,21 @@ impl Display for ConsensusAnnouncement {
             ConsensusAnnouncement::Proposed(block) => {
                 write!(formatter, "proposed proto block {}", block)
             }
-            ConsensusAnnouncement::Finalized(block) => {
-                write!(formatter, "finalized proto block {}", block)
-            }
+            ConsensusAnnouncement::Finalized(block) => write!(formatter, "{}", block),
             ConsensusAnnouncement::Orphaned(block) => {
                 write!(formatter, "orphaned proto block {}", block)
             }
+            ConsensusAnnouncement::EraTransition {
+                ended_era,
+                new_era,
+                new_validators,
+            } => write!(
+                formatter,
+                "transitioned from {:?} to {:?} with {} validators",
+                ended_era,
+                new_era,
+                new_validators.len()
+            ),
         }
     }
 }
@@ -140,6 +280,20 @@ impl Display for ConsensusAnnouncement {
 pub enum BlockExecutorAnnouncement {
     /// A new block from the linear chain was produced.
     LinearChainBlock(Block),
+    /// The step at the end of an era was executed, along with the era's switch block.
+    StepExecuted {
+        /// The era that ended.
+        era_id: EraId,
+        /// A summary of the slashings and rewards.
+        effects_summary: StepEffect,
+    },
+    /// Executing a finalized block failed, and no block was created from it.
+    BlockExecutionFailed {
+        /// The block whose execution failed.
+        finalized_block: FinalizedBlock,
+        /// The error that caused the execution to fail.
+        error: ExecuteError,
+    },
 }
 
 impl Display for BlockExecutorAnnouncement {
@@ -148,6 +302,87 @@ impl Display for BlockExecutorAnnouncement {
             BlockExecutorAnnouncement::LinearChainBlock(block) => {
                 write!(f, "created linear chain block {}", block.hash())
             }
+            BlockExecutorAnnouncement::StepExecuted {
+                era_id,
+                effects_summary,
+            } => write!(
+                f,
+                "executed step at the end of era {}: {}",
+                era_id.0, effects_summary
+            ),
+            BlockExecutorAnnouncement::BlockExecutionFailed {
+                finalized_block,
+                error,
+            } => write!(f, "failed to execute {}: {}", finalized_block, error),
+        }
+    }
+}
+
+/// A DeployBuffer announcement.
+#[derive(Debug)]
+pub enum DeployBufferAnnouncement {
+    /// A deploy has been dropped from the deploy buffer, and will not be proposed.
+    DeployDropped {
+        /// The hash of the dropped deploy.
+        hash: DeployHash,
+        /// The reason for dropping the deploy.
+        reason: DropReason,
+    },
+}
+
+impl Display for DeployBufferAnnouncement {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            DeployBufferAnnouncement::DeployDropped { hash, reason } => write!(
+                f,
+                "dropped deploy {} from the deploy buffer: {}",
+                hash, reason
+            ),
+        }
+    }
+}
+
+/// A LinearChain announcement.
+#[derive(Debug)]
+pub enum LinearChainAnnouncement {
+    /// A block has been added to the linear chain and put to storage.
+    BlockAdded(Box<Block>),
+    /// A block has received enough finality signatures to be considered finalized.
+    BlockFinalized(Box<Block>),
+}
+
+impl Display for LinearChainAnnouncement {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            LinearChainAnnouncement::BlockAdded(block) => {
+                write!(f, "added linear chain block {}", block.hash())
+            }
+            LinearChainAnnouncement::BlockFinalized(block) => {
+                write!(f, "finalized linear chain block {}", block.hash())
+            }
+        }
+    }
+}
+
+/// A ChainspecLoader announcement.
+#[derive(Debug)]
+pub enum ChainspecLoaderAnnouncement {
+    /// The node has reached the activation point of an upgrade it has not applied.
+    UpgradeOverdue {
+        /// The era the node is in.
+        current_era: EraId,
+        /// The upgrade that should have been applied.
+        upgrade: NextUpgrade,
+    },
+}
+
+impl Display for ChainspecLoaderAnnouncement {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ChainspecLoaderAnnouncement::UpgradeOverdue {
+                current_era,
+                upgrade,
+            } => write!(f, "overdue in era {}: {}", current_era.0, upgrade),
         }
     }
 }
@@ -157,12 +392,208 @@ impl Display for BlockExecutorAnnouncement {
 pub enum GossiperAnnouncement<T: Item> {
     /// A new item has been received, where the item's ID is the complete item.
     NewCompleteItem(T::Id),
+    /// An item has been forwarded to further peers.
+    ItemGossiped {
+        /// The ID of the gossiped item.
+        id: T::Id,
+        /// The total number of peers the item has been forwarded to so far.
+        peer_count: usize,
+    },
+    /// Gossiping an item has finished, since it has saturated the network.
+    FinishedGossiping(T::Id),
 }
 
 impl<T: Item> Display for GossiperAnnouncement<T> {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         match self {
             GossiperAnnouncement::NewCompleteItem(item) => write!(f, "new complete item {}", item),
+            GossiperAnnouncement::ItemGossiped { id, peer_count } => {
+                write!(f, "gossiped {} to {} peers in total", id, peer_count)
+            }
+            GossiperAnnouncement::FinishedGossiping(item_id) => {
+                write!(f, "finished gossiping {}", item_id)
+            }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{cmp::Reverse, collections::BTreeMap};
+
+    use serde_json::{json, Value};
+
+    use super::*;
+    use crate::{testing::TestRng, utils::Loadable, Chainspec};
+
+    #[test]
+    fn should_serialize_consensus_announcements_to_json() {
+        let mut rng = TestRng::new();
+
+        let finalized_block = FinalizedBlock::random(&mut rng);
+        let finalized = ConsensusAnnouncement::Finalized(Box::new(finalized_block.clone()));
+        let json = serde_json::to_value(&finalized).unwrap();
+        assert_eq!(
+            json["Finalized"],
+            serde_json::to_value(&finalized_block).unwrap()
+        );
+
+        let public_key = PublicKey::random(&mut rng);
+        let era_transition = ConsensusAnnouncement::EraTransition {
+            ended_era: EraId(0),
+            new_era: EraId(1),
+            new_validators: vec![public_key],
+        };
+        let json = serde_json::to_value(&era_transition).unwrap();
+        assert_eq!(json["EraTransition"]["ended_era"], json!(0));
+        assert_eq!(json["EraTransition"]["new_era"], json!(1));
+        assert_eq!(
+            json["EraTransition"]["new_validators"],
+            Value::Array(vec![serde_json::to_value(&public_key).unwrap()])
+        );
+    }
+
+    #[test]
+    fn should_display_era_of_executed_step() {
+        let mut rng = TestRng::new();
+        let public_key = PublicKey::random(&mut rng);
+        let step_executed = BlockExecutorAnnouncement::StepExecuted {
+            era_id: EraId(7),
+            effects_summary: StepEffect {
+                slashed: vec![public_key],
+                rewards: BTreeMap::new(),
+            },
+        };
+        assert_eq!(
+            step_executed.to_string(),
+            "executed step at the end of era 7: 1 validators slashed, 0 validators rewarded"
+        );
+    }
+
+    #[test]
+    fn should_display_code_and_component_of_fatal_error() {
+        let fatal_error = ControlAnnouncement::FatalError {
+            file: "src/components/small_network.rs",
+            line: 42,
+            component: "casper_node::components::small_network",
+            error_code: Some(17),
+            msg: "isolated".to_string(),
+        };
+        assert_eq!(
+            fatal_error.to_string(),
+            "fatal error in casper_node::components::small_network \
+            [src/components/small_network.rs:42] (code 17): isolated"
+        );
+
+        let uncoded_fatal_error = ControlAnnouncement::FatalError {
+            file: "src/components/small_network.rs",
+            line: 42,
+            component: "casper_node::components::small_network",
+            error_code: None,
+            msg: "isolated".to_string(),
+        };
+        assert_eq!(
+            uncoded_fatal_error.to_string(),
+            "fatal error in casper_node::components::small_network \
+            [src/components/small_network.rs:42]: isolated"
+        );
+    }
+
+    #[test]
+    fn should_order_fatal_errors_above_shutdowns_above_warnings() {
+        let fatal_error = ControlAnnouncement::FatalError {
+            file: file!(),
+            line: line!(),
+            component: module_path!(),
+            error_code: None,
+            msg: "crashed".to_string(),
+        };
+        let shutdown = ControlAnnouncement::Shutdown {
+            graceful: true,
+            reason: "upgrading".to_string(),
+        };
+        let warning = ControlAnnouncement::Warning {
+            msg: "slow".to_string(),
+        };
+
+        assert!(fatal_error.severity() > shutdown.severity());
+        assert!(shutdown.severity() > warning.severity());
+
+        let mut announcements = vec![warning, fatal_error, shutdown];
+        announcements.sort_by_key(|announcement| Reverse(announcement.severity()));
+        let severities: Vec<_> = announcements
+            .iter()
+            .map(ControlAnnouncement::severity)
+            .collect();
+        assert_eq!(
+            severities,
+            vec![Severity::Fatal, Severity::Shutdown, Severity::Warning]
+        );
+    }
+
+    #[test]
+    fn should_display_overdue_upgrade() {
+        let chainspec = Chainspec::from_resources("test/valid/chainspec.toml");
+        let current_era = EraId(24);
+        let upgrade = chainspec
+            .overdue_upgrade(&chainspec.genesis.protocol_version, current_era)
+            .unwrap();
+
+        let upgrade_overdue = ChainspecLoaderAnnouncement::UpgradeOverdue {
+            current_era,
+            upgrade,
+        };
+        assert_eq!(
+            upgrade_overdue.to_string(),
+            "overdue in era 24: upgrade to 0.2.0 at era 23"
+        );
+    }
+
+    #[test]
+    fn should_display_reason_for_invalid_deploy() {
+        let mut rng = TestRng::new();
+        let deploy = Deploy::random(&mut rng);
+        let deploy_hash = *deploy.id();
+
+        let invalid_deploy = DeployAcceptorAnnouncement::InvalidDeploy {
+            deploy: Box::new(deploy),
+            source: Source::Peer(7u64),
+            error: DeployAcceptorError::ExcessiveDependencies {
+                dependencies: 11,
+                max_dependencies: 10,
+            },
+        };
+        assert_eq!(
+            invalid_deploy.to_string(),
+            format!(
+                "invalid deploy {} from 7: 11 dependencies exceed the maximum of 10",
+                deploy_hash
+            )
+        );
+    }
+
+    #[test]
+    fn should_display_new_peer_with_connection_direction() {
+        let addr: SocketAddr = "127.0.0.1:34553".parse().unwrap();
+
+        let inbound = NetworkAnnouncement::<u64, String>::NewPeer {
+            peer: 1,
+            inbound: true,
+            addr: Some(addr),
+        };
+        assert_eq!(
+            inbound.to_string(),
+            "new inbound peer connection established to 1 at 127.0.0.1:34553"
+        );
+
+        let outbound = NetworkAnnouncement::<u64, String>::NewPeer {
+            peer: 2,
+            inbound: false,
+            addr: None,
+        };
+        assert_eq!(
+            outbound.to_string(),
+            "new outbound peer connection established to 2"
+        );
+    }
+}
@@ -4,11 +4,14 @@
 //! top-level module documentation for details.
 
 use std::{
-    collections::{HashMap, HashSet},
+    collections::{BTreeMap, HashMap, HashSet},
     fmt::{self, Debug, Display, Formatter},
     net::SocketAddr,
+    ops::RangeInclusive,
 };
 
+use futures::channel::mpsc;
+use hex_fmt::HexFmt;
 use semver::Version;
 
 use casper_execution_engine::{
@@ -20,21 +23,32 @@ use casper_execution_engine::{
         query::{QueryRequest, QueryResult},
         upgrade::{UpgradeConfig, UpgradeResult},
     },
-    shared::{additive_map::AdditiveMap, transform::Transform},
+    shared::{additive_map::AdditiveMap, motes::Motes, transform::Transform},
     storage::global_state::CommitResult,
 };
-use casper_types::Key;
+use casper_types::{ContractHash, Key, U512};
 
 use super::Responder;
 use crate::{
     components::{
+        api_server::{MetricsFormat, ResponseFormat, SerializationError},
+        consensus::{EraId, LeaderCount},
+        contract_runtime::{
+            BalanceError, BalanceIdentifier, DryRunResult, ExecuteError, NamedKeysDiff,
+        },
         fetcher::FetchResult,
-        storage::{DeployHashes, DeployHeaderResults, DeployResults, StorageType, Value},
+        storage::{
+            DeployHashes, DeployHeaderResults, DeployResults, Error as StorageError, StorageType,
+            Value,
+        },
+    },
+    crypto::{
+        asymmetric_key::{PublicKey, Signature},
+        hash::Digest,
     },
-    crypto::{asymmetric_key::Signature, hash::Digest},
     types::{
-        Block as LinearBlock, BlockHash, BlockHeader, Deploy, DeployHash, FinalizedBlock, Item,
-        ProtoBlockHash, Timestamp,
+        Block as LinearBlock, BlockExecutionResults, BlockHash, BlockHeader, BlockIdentifier,
+        Deploy, DeployHash, FinalizedBlock, Item, ProtoBlockHash, Timestamp,
     },
     utils::DisplayIter,
     Chainspec,
@@ -179,8 +193,17 @@ pub enum StorageRequest<S: StorageType + 'static> {
         /// Block to be stored.
         block: Box<S::Block>,
         /// Responder to call with the result.  Returns true if the block was stored on this
-        /// attempt or false if it was previously stored.
-        responder: Responder<bool>,
+        /// attempt, false if it was previously stored, or an error if writing it failed.
+        responder: Responder<Result<bool, StorageError>>,
+    },
+    /// Store given block, or if it is already stored, add its finality signatures to the stored
+    /// block's.
+    PutBlockProofs {
+        /// Block whose finality signatures are to be stored.
+        block: Box<S::Block>,
+        /// Responder to call with the result.  Returns true if the block was stored on this
+        /// attempt, false if it was previously stored, or an error if writing it failed.
+        responder: Responder<Result<bool, StorageError>>,
     },
     /// Retrieve block with given hash.
     GetBlock {
@@ -198,6 +221,27 @@ pub enum StorageRequest<S: StorageType + 'static> {
         /// local storage.
         responder: Responder<Option<<S::Block as Value>::Header>>,
     },
+    /// Retrieve the block with the greatest height.
+    GetHighestBlock {
+        /// Responder to call with the result.  Returns `None` if there are no blocks in local
+        /// storage.
+        responder: Responder<Option<S::Block>>,
+    },
+    /// Retrieve a block at the given height.
+    GetBlockAtHeight {
+        /// Height of the block.
+        height: u64,
+        /// Responder to call with the result.  Returns `None` if there is no block at the given
+        /// height in local storage.
+        responder: Responder<Option<S::Block>>,
+    },
+    /// Retrieve the heights up to and including the given one at which there is no block.
+    GetMissingBlockHeights {
+        /// The greatest height to check.
+        up_to: u64,
+        /// Responder to call with the missing heights, as ranges in ascending order.
+        responder: Responder<Vec<RangeInclusive<u64>>>,
+    },
     /// Store given deploy.
     PutDeploy {
         /// Deploy to store.
@@ -239,16 +283,43 @@ pub enum StorageRequest<S: StorageType + 'static> {
         /// Responder to call with the result.
         responder: Responder<Option<Chainspec>>,
     },
+    /// Store the execution results of the deploys of the block with the given hash.
+    PutExecutionResults {
+        /// Hash of the block whose deploys were executed.
+        block_hash: BlockHash,
+        /// Execution results, keyed by deploy hash.
+        execution_results: BlockExecutionResults,
+        /// Responder to call with the result.
+        responder: Responder<()>,
+    },
+    /// Retrieve the execution results of the deploys of the block with the given hash.
+    GetExecutionResults {
+        /// Hash of the block whose execution results are to be retrieved.
+        block_hash: BlockHash,
+        /// Responder to call with the result.  Returns `None` if no execution results are stored
+        /// for the block.
+        responder: Responder<Option<BlockExecutionResults>>,
+    },
 }
 
 impl<S: StorageType> Display for StorageRequest<S> {
     fn fmt(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
         match self {
             StorageRequest::PutBlock { block, .. } => write!(formatter, "put {}", block),
+            StorageRequest::PutBlockProofs { block, .. } => {
+                write!(formatter, "put proofs of {}", block)
+            }
             StorageRequest::GetBlock { block_hash, .. } => write!(formatter, "get {}", block_hash),
             StorageRequest::GetBlockHeader { block_hash, .. } => {
                 write!(formatter, "get {}", block_hash)
             }
+            StorageRequest::GetHighestBlock { .. } => write!(formatter, "get highest block"),
+            StorageRequest::GetBlockAtHeight { height, .. } => {
+                write!(formatter, "get block at height {}", height)
+            }
+            StorageRequest::GetMissingBlockHeights { up_to, .. } => {
+                write!(formatter, "get missing block heights up to {}", up_to)
+            }
             StorageRequest::PutDeploy { deploy, .. } => write!(formatter, "put {}", deploy),
             StorageRequest::GetDeploys { deploy_hashes, .. } => {
                 write!(formatter, "get {}", DisplayIter::new(deploy_hashes.iter()))
@@ -267,6 +338,12 @@ impl<S: StorageType> Display for StorageRequest<S> {
             StorageRequest::GetChainspec { version, .. } => {
                 write!(formatter, "get chainspec {}", version)
             }
+            StorageRequest::PutExecutionResults { block_hash, .. } => {
+                write!(formatter, "put execution results for {}", block_hash)
+            }
+            StorageRequest::GetExecutionResults { block_hash, .. } => {
+                write!(formatter, "get execution results for {}", block_hash)
+            }
         }
     }
 }
@@ -284,6 +361,11 @@ pub enum DeployBufferRequest {
         /// Responder to call with the result.
         responder: Responder<HashSet<DeployHash>>,
     },
+    /// Request the hashes of all deploys buffered but not yet proposed in a block.
+    ListPending {
+        /// Responder to call with the result.
+        responder: Responder<Vec<DeployHash>>,
+    },
 }
 
 impl Display for DeployBufferRequest {
@@ -299,6 +381,9 @@ impl Display for DeployBufferRequest {
                 current_instant,
                 past_blocks.len()
             ),
+            DeployBufferRequest::ListPending { responder: _ } => {
+                write!(formatter, "list pending deploys")
+            }
         }
     }
 }
@@ -314,30 +399,125 @@ pub enum ApiRequest {
     SubmitDeploy {
         /// The deploy to be announced.
         deploy: Box<Deploy>,
-        /// Responder to call.
-        responder: Responder<()>,
+        /// Responder to call with the hash of the deploy.
+        responder: Responder<DeployHash>,
     },
-    /// Return the specified deploy if it exists, else `None`.
+    /// Return the specified deploy if it exists, else `None`, serialized in the given format.
     GetDeploy {
         /// The hash of the deploy to be retrieved.
         hash: DeployHash,
-        /// Responder to call with the result.
-        responder: Responder<Option<Deploy>>,
+        /// The format in which to serialize the result.
+        format: ResponseFormat,
+        /// Responder to call with the serialized result.
+        responder: Responder<Result<Vec<u8>, SerializationError>>,
     },
-    /// Return the list of all deploy hashes stored on this node.
+    /// Return a page of the deploy hashes stored on this node in ascending order, serialized in
+    /// the given format.
     ListDeploys {
-        /// Responder to call with the result.
-        responder: Responder<Vec<DeployHash>>,
+        /// The maximum number of deploy hashes to return, or `None` for all of them.
+        limit: Option<usize>,
+        /// The last deploy hash of the previous page, or `None` to start from the first one.
+        cursor: Option<DeployHash>,
+        /// The format in which to serialize the result.
+        format: ResponseFormat,
+        /// Responder to call with the serialized result.
+        responder: Responder<Result<Vec<u8>, SerializationError>>,
     },
-    /// Return string formatted, prometheus compatible metrics or `None` if an error occurred.
+    /// Return the metrics rendered in the given format or `None` if an error occurred.
     GetMetrics {
+        /// The format in which to render the metrics.
+        format: MetricsFormat,
         /// Responder to call with the result.
         responder: Responder<Option<String>>,
     },
-    /// Return string formatted status or `None` if an error occurred.
+    /// Return the node's status, serialized in the given format.
     GetStatus {
-        /// Responder to call with the result.
-        responder: Responder<Option<String>>,
+        /// The format in which to serialize the result.
+        format: ResponseFormat,
+        /// Responder to call with the serialized result.
+        responder: Responder<Result<Vec<u8>, SerializationError>>,
+    },
+    /// Return the client-relevant parameters of the active chainspec, serialized in the given
+    /// format.
+    GetChainspec {
+        /// The format in which to serialize the result.
+        format: ResponseFormat,
+        /// Responder to call with the serialized result.
+        responder: Responder<Result<Vec<u8>, SerializationError>>,
+    },
+    /// Return the next upgrade scheduled in the active chainspec, or `None` if there is none,
+    /// serialized in the given format.
+    GetNextUpgrade {
+        /// The format in which to serialize the result.
+        format: ResponseFormat,
+        /// Responder to call with the serialized result.
+        responder: Responder<Result<Vec<u8>, SerializationError>>,
+    },
+    /// Return the currently connected peers and their addresses, serialized in the given format.
+    GetPeers {
+        /// The format in which to serialize the result.
+        format: ResponseFormat,
+        /// Responder to call with the serialized result.
+        responder: Responder<Result<Vec<u8>, SerializationError>>,
+    },
+    /// Return the hashes of the deploys pending in the deploy buffer, serialized in the given
+    /// format.
+    GetPendingDeploys {
+        /// The format in which to serialize the result.
+        format: ResponseFormat,
+        /// Responder to call with the serialized result.
+        responder: Responder<Result<Vec<u8>, SerializationError>>,
+    },
+    /// Query the value under `base_key`, or under the named key reached by following `path` from
+    /// it, in the global state with the given root hash, serialized in the given format.
+    QueryGlobalState {
+        /// The global state root hash.
+        state_root_hash: Digest,
+        /// The key at which to start the query.
+        base_key: Key,
+        /// The names of the named keys to follow from `base_key`.
+        path: Vec<String>,
+        /// The format in which to serialize the result.
+        format: ResponseFormat,
+        /// Responder to call with the serialized result.
+        responder: Responder<Result<Vec<u8>, SerializationError>>,
+    },
+    /// Return the balance of the given purse in the global state with the given root hash,
+    /// serialized in the given format.
+    GetBalance {
+        /// The global state root hash.
+        state_root_hash: Digest,
+        /// The purse whose balance to return.
+        purse: BalanceIdentifier,
+        /// The format in which to serialize the result.
+        format: ResponseFormat,
+        /// Responder to call with the serialized result.
+        responder: Responder<Result<Vec<u8>, SerializationError>>,
+    },
+    /// Subscribe to the blocks finalized from now on.
+    SubscribeFinalizedBlocks {
+        /// Responder to call with the receiving end of the subscription, or `None` if there are
+        /// too many subscribers already.
+        responder: Responder<Option<mpsc::Receiver<FinalizedBlock>>>,
+    },
+    /// Return the specified block if it exists, else `None`, serialized in the given format.
+    GetBlock {
+        /// The hash or height of the block to be retrieved.
+        identifier: BlockIdentifier,
+        /// The format in which to serialize the result.
+        format: ResponseFormat,
+        /// Responder to call with the serialized result.
+        responder: Responder<Result<Vec<u8>, SerializationError>>,
+    },
+    /// Return the execution results of the deploys of the specified block if they exist, else
+    /// `None`, serialized in the given format.
+    GetBlockExecutionResults {
+        /// The hash of the block whose execution results are to be retrieved.
+        block_hash: BlockHash,
+        /// The format in which to serialize the result.
+        format: ResponseFormat,
+        /// Responder to call with the serialized result.
+        responder: Responder<Result<Vec<u8>, SerializationError>>,
     },
 }
 
@@ -347,8 +527,36 @@ impl Display for ApiRequest {
             ApiRequest::SubmitDeploy { deploy, .. } => write!(formatter, "submit {}", *deploy),
             ApiRequest::GetDeploy { hash, .. } => write!(formatter, "get {}", hash),
             ApiRequest::ListDeploys { .. } => write!(formatter, "list deploys"),
-            ApiRequest::GetMetrics { .. } => write!(formatter, "get metrics"),
+            ApiRequest::GetMetrics { format, .. } => write!(formatter, "get metrics as {}", format),
             ApiRequest::GetStatus { .. } => write!(formatter, "get status"),
+            ApiRequest::GetChainspec { .. } => write!(formatter, "get chainspec"),
+            ApiRequest::GetNextUpgrade { .. } => write!(formatter, "get next upgrade"),
+            ApiRequest::GetPeers { .. } => write!(formatter, "get peers"),
+            ApiRequest::GetPendingDeploys { .. } => write!(formatter, "get pending deploys"),
+            ApiRequest::GetBalance {
+                state_root_hash,
+                purse,
+                ..
+            } => write!(formatter, "get balance of {} at {}", purse, state_root_hash),
+            ApiRequest::SubscribeFinalizedBlocks { .. } => {
+                write!(formatter, "subscribe to finalized blocks")
+            }
+            ApiRequest::QueryGlobalState {
+                state_root_hash,
+                base_key,
+                path,
+                ..
+            } => write!(
+                formatter,
+                "query {} under {}/{}",
+                state_root_hash,
+                base_key,
+                path.join("/")
+            ),
+            ApiRequest::GetBlock { identifier, .. } => write!(formatter, "get {}", identifier),
+            ApiRequest::GetBlockExecutionResults { block_hash, .. } => {
+                write!(formatter, "get execution results for {}", block_hash)
+            }
         }
     }
 }
@@ -369,7 +577,14 @@ pub enum ContractRuntimeRequest {
         /// Execution request containing deploys.
         execute_request: ExecuteRequest,
         /// Responder to call with the execution result.
-        responder: Responder<Result<ExecutionResults, engine_state::RootNotFound>>,
+        responder: Responder<Result<ExecutionResults, ExecuteError>>,
+    },
+    /// An `ExecuteRequest` whose deploys will be executed without committing their effects.
+    ExecuteDryRun {
+        /// Execution request containing deploys.
+        execute_request: ExecuteRequest,
+        /// Responder to call with the execution results and their transforms.
+        responder: Responder<DryRunResult>,
     },
     /// A request to commit existing execution transforms.
     Commit {
@@ -378,7 +593,7 @@ pub enum ContractRuntimeRequest {
         /// Effects obtained through `ExecutionResult`
         effects: AdditiveMap<Key, Transform>,
         /// Responder to call with the commit result.
-        responder: Responder<Result<CommitResult, engine_state::Error>>,
+        responder: Responder<Result<CommitResult, ExecuteError>>,
     },
     /// A request to run upgrade.
     Upgrade {
@@ -394,6 +609,37 @@ pub enum ContractRuntimeRequest {
         /// Responder to call with the upgrade result.
         responder: Responder<Result<QueryResult, engine_state::Error>>,
     },
+    /// A request to compare a contract's named keys under two state roots.
+    NamedKeysDiff {
+        /// The state root hash to compare from.
+        root_a: Digest,
+        /// The state root hash to compare to.
+        root_b: Digest,
+        /// The hash of the contract whose named keys should be compared.
+        contract_hash: ContractHash,
+        /// Responder to call with the differences, or `None` if either state root is unknown.
+        responder: Responder<Result<Option<NamedKeysDiff>, engine_state::Error>>,
+    },
+    /// A request for the balance of a purse.
+    GetBalance {
+        /// The state root hash to read from.
+        state_root_hash: Digest,
+        /// The purse whose balance to return.
+        purse: BalanceIdentifier,
+        /// Responder to call with the balance.
+        responder: Responder<Result<U512, BalanceError>>,
+    },
+    /// A request to pre-read the global state along the given keys, warming up the LMDB cache.
+    WarmCache {
+        /// The state root hash to read from.
+        state_root: Digest,
+        /// The protocol version whose system contracts should be read.
+        protocol_version: Version,
+        /// The keys expected to be read frequently.
+        key_hints: Vec<Key>,
+        /// Responder to call once warm-up has finished.
+        responder: Responder<Result<(), engine_state::Error>>,
+    },
 }
 
 impl Display for ContractRuntimeRequest {
@@ -412,6 +658,14 @@ impl Display for ContractRuntimeRequest {
                 execute_request.parent_state_hash
             ),
 
+            ContractRuntimeRequest::ExecuteDryRun {
+                execute_request, ..
+            } => write!(
+                formatter,
+                "execute dry run request: {}",
+                execute_request.parent_state_hash
+            ),
+
             ContractRuntimeRequest::Commit {
                 pre_state_hash,
                 effects,
@@ -429,6 +683,40 @@ impl Display for ContractRuntimeRequest {
             ContractRuntimeRequest::Query { query_request, .. } => {
                 write!(formatter, "query request: {:?}", query_request)
             }
+
+            ContractRuntimeRequest::NamedKeysDiff {
+                root_a,
+                root_b,
+                contract_hash,
+                ..
+            } => write!(
+                formatter,
+                "named keys diff request: {} {} {}",
+                root_a,
+                root_b,
+                HexFmt(contract_hash)
+            ),
+
+            ContractRuntimeRequest::GetBalance {
+                state_root_hash,
+                purse,
+                ..
+            } => write!(
+                formatter,
+                "get balance request: {} {}",
+                state_root_hash, purse
+            ),
+
+            ContractRuntimeRequest::WarmCache {
+                state_root,
+                key_hints,
+                ..
+            } => write!(
+                formatter,
+                "warm cache request: {} [{}]",
+                state_root,
+                DisplayIter::new(key_hints.iter())
+            ),
         }
     }
 }
@@ -500,8 +788,22 @@ impl<T: Display, I: Display> Display for BlockValidationRequest<T, I> {
 pub enum LinearChainRequest<I> {
     /// Request whole block from the linear chain, by hash.
     BlockRequest(BlockHash, I),
+    /// Request whole block from the linear chain, by height.
+    BlockAtHeight(u64, I),
     /// Get last finalized block.
     LastFinalizedBlock(Responder<Option<LinearBlock>>),
+    /// Get the highest block which has collected enough finality signatures to be considered
+    /// finalized.  Responds with `None` if no such block is known.
+    HighestBlockWithSufficientFinality(Responder<Option<LinearBlock>>),
+    /// Get the finality signatures collected for the block with the given hash, with their
+    /// signers.  Responds with `None` if the block is unknown.
+    FinalitySignatures(BlockHash, Responder<Option<Vec<(PublicKey, Signature)>>>),
+    /// Get the total stake of the validators which signed the block with the given hash.
+    /// Responds with `None` if the block or the validators of its era are unknown.
+    FinalitySignatureWeight(BlockHash, Responder<Option<Motes>>),
+    /// Get the block with the given hash or at the given height.  Responds with `None` if the
+    /// block is unknown.
+    GetBlock(BlockIdentifier, Responder<Option<LinearBlock>>),
 }
 
 impl<I: Display> Display for LinearChainRequest<I> {
@@ -510,7 +812,22 @@ impl<I: Display> Display for LinearChainRequest<I> {
             LinearChainRequest::BlockRequest(bh, peer) => {
                 write!(f, "block request for hash {} from {}", bh, peer)
             }
+            LinearChainRequest::BlockAtHeight(height, peer) => {
+                write!(f, "block request for height {} from {}", height, peer)
+            }
             LinearChainRequest::LastFinalizedBlock(_) => write!(f, "last finalized block request"),
+            LinearChainRequest::HighestBlockWithSufficientFinality(_) => {
+                write!(f, "highest block with sufficient finality request")
+            }
+            LinearChainRequest::FinalitySignatures(bh, _) => {
+                write!(f, "finality signatures request for {}", bh)
+            }
+            LinearChainRequest::FinalitySignatureWeight(bh, _) => {
+                write!(f, "finality signature weight request for {}", bh)
+            }
+            LinearChainRequest::GetBlock(block_identifier, _) => {
+                write!(f, "get block request for {}", block_identifier)
+            }
         }
     }
 }
@@ -520,5 +837,37 @@ impl<I: Display> Display for LinearChainRequest<I> {
 /// Consensus component requests.
 pub enum ConsensusRequest {
     /// Request for consensus to sign a new linear chain block and possibly start a new era.
-    HandleLinearBlock(Box<BlockHeader>, Responder<Signature>),
+    ///
+    /// Responds with our public key and signature once consensus has fully incorporated the block,
+    /// including starting the new era if it is a switch block.
+    HandleLinearBlock(Box<BlockHeader>, Responder<(PublicKey, Signature)>),
+    /// Request for the number of rounds each validator has led so far in the given era, compared
+    /// to the number expected according to their weights.  Responds with `None` if the era is
+    /// not active.
+    LeaderStats(EraId, Responder<Option<Vec<(PublicKey, LeaderCount)>>>),
+    /// Request for the validators of the given era with their stakes.  Responds with `None` if
+    /// the era is not active.
+    ValidatorStakes(EraId, Responder<Option<BTreeMap<PublicKey, Motes>>>),
+    /// Request for consensus to start the next era with the given validators, without waiting
+    /// for the current era to end.  Responds with the new era's ID, or `None` if forced era
+    /// transitions are not enabled in the consensus config.
+    ForceNextEra {
+        /// The validators of the new era, with their stakes.
+        validators: BTreeMap<PublicKey, Motes>,
+        /// Responder to call with the new era's ID.
+        responder: Responder<Option<EraId>>,
+    },
+    /// Request for the ID of the current era.
+    CurrentEra(Responder<EraId>),
+    /// Request for the evidence against a validator in the given era, e.g. a proof of
+    /// equivocation.  Responds with the serialized evidence, or `None` if there is none or the era
+    /// is not active.
+    GetEvidence {
+        /// The era in which the validator may have misbehaved.
+        era_id: EraId,
+        /// The validator's public key.
+        validator: PublicKey,
+        /// Responder to call with the serialized evidence.
+        responder: Responder<Option<Vec<u8>>>,
+    },
 }
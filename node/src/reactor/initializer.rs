@@ -6,6 +6,7 @@ use derive_more::From;
 use prometheus::Registry;
 use rand::{CryptoRng, Rng};
 use thiserror::Error;
+use tracing::warn;
 
 use crate::{
     components::{
@@ -16,6 +17,7 @@ use crate::{
         Component,
     },
     effect::{
+        announcements::ChainspecLoaderAnnouncement,
         requests::{ContractRuntimeRequest, NetworkRequest, StorageRequest},
         EffectBuilder, Effects,
     },
@@ -39,6 +41,10 @@ pub enum Event {
     /// Contract runtime event.
     #[from]
     ContractRuntime(contract_runtime::Event),
+
+    /// Chainspec loader announcement.
+    #[from]
+    ChainspecLoaderAnnouncement(ChainspecLoaderAnnouncement),
 }
 
 impl From<StorageRequest<Storage>> for Event {
@@ -65,6 +71,9 @@ impl Display for Event {
             Event::Chainspec(event) => write!(formatter, "chainspec: {}", event),
             Event::Storage(event) => write!(formatter, "storage: {}", event),
             Event::ContractRuntime(event) => write!(formatter, "contract runtime: {}", event),
+            Event::ChainspecLoaderAnnouncement(ann) => {
+                write!(formatter, "chainspec loader announcement: {}", ann)
+            }
         }
     }
 }
@@ -171,6 +180,11 @@ impl<RNG: Rng + CryptoRng + ?Sized> reactor::Reactor<RNG> for Reactor {
                 self.contract_runtime
                     .handle_event(effect_builder, rng, event),
             ),
+            Event::ChainspecLoaderAnnouncement(ann) => {
+                // No eras are started during initialization.
+                warn!(%ann, "unexpected chainspec loader announcement");
+                Effects::new()
+            }
         }
     }
 
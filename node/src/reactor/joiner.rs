@@ -168,14 +168,16 @@ impl<R: Rng + CryptoRng + ?Sized> reactor::Reactor<R> for Reactor {
                 Event::Network,
                 self.net.handle_event(effect_builder, rng, event),
             ),
-            Event::NetworkAnnouncement(NetworkAnnouncement::NewPeer(id)) => reactor::wrap_effects(
-                Event::LinearChainSync,
-                self.linear_chain_sync.handle_event(
-                    effect_builder,
-                    rng,
-                    linear_chain_sync::Event::NewPeerConnected(id),
-                ),
-            ),
+            Event::NetworkAnnouncement(NetworkAnnouncement::NewPeer { peer: id, .. }) => {
+                reactor::wrap_effects(
+                    Event::LinearChainSync,
+                    self.linear_chain_sync.handle_event(
+                        effect_builder,
+                        rng,
+                        linear_chain_sync::Event::NewPeerConnected(id),
+                    ),
+                )
+            }
             Event::NetworkAnnouncement(_) => Default::default(),
             Event::Storage(event) => reactor::wrap_effects(
                 Event::Storage,
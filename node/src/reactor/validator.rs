@@ -16,7 +16,9 @@ use std::{
 use derive_more::From;
 use prometheus::Registry;
 use rand::{CryptoRng, Rng};
-use tracing::{debug, error, warn};
+use tracing::{debug, error, info, warn};
+
+use casper_types::Key;
 
 #[cfg(test)]
 use crate::testing::network::NetworkedReactor;
@@ -25,7 +27,7 @@ use crate::{
         api_server::{self, ApiServer},
         block_executor::{self, BlockExecutor},
         block_validator::{self, BlockValidator},
-        chainspec_loader::ChainspecLoader,
+        chainspec_loader::{self, ChainspecLoader},
         consensus::{self, EraSupervisor},
         contract_runtime::{self, ContractRuntime},
         deploy_acceptor::{self, DeployAcceptor},
@@ -40,15 +42,16 @@ use crate::{
     },
     effect::{
         announcements::{
-            ApiServerAnnouncement, BlockExecutorAnnouncement, ConsensusAnnouncement,
-            DeployAcceptorAnnouncement, GossiperAnnouncement, NetworkAnnouncement,
+            ApiServerAnnouncement, BlockExecutorAnnouncement, ChainspecLoaderAnnouncement,
+            ConsensusAnnouncement, DeployAcceptorAnnouncement, DeployBufferAnnouncement,
+            GossiperAnnouncement, LinearChainAnnouncement, NetworkAnnouncement,
         },
         requests::{
             ApiRequest, BlockExecutorRequest, BlockValidationRequest, ConsensusRequest,
             ContractRuntimeRequest, DeployBufferRequest, FetcherRequest, LinearChainRequest,
             MetricsRequest, NetworkInfoRequest, NetworkRequest, StorageRequest,
         },
-        EffectBuilder, Effects,
+        EffectBuilder, EffectExt, Effects,
     },
     protocol::Message,
     reactor::{self, EventQueueHandle},
@@ -102,6 +105,9 @@ pub enum Event {
     /// Linear chain event.
     #[from]
     LinearChain(linear_chain::Event<NodeId>),
+    /// Chainspec loader event.
+    #[from]
+    ChainspecLoader(chainspec_loader::Event),
 
     // Requests
     /// Network request.
@@ -142,12 +148,21 @@ pub enum Event {
     /// BlockExecutor announcement.
     #[from]
     BlockExecutorAnnouncement(BlockExecutorAnnouncement),
+    /// DeployBuffer announcement.
+    #[from]
+    DeployBufferAnnouncement(DeployBufferAnnouncement),
+    /// LinearChain announcement.
+    #[from]
+    LinearChainAnnouncement(LinearChainAnnouncement),
     /// Deploy Gossiper announcement.
     #[from]
     DeployGossiperAnnouncement(GossiperAnnouncement<Deploy>),
     /// Address Gossiper announcement.
     #[from]
     AddressGossiperAnnouncement(GossiperAnnouncement<GossipedAddress>),
+    /// Chainspec loader announcement.
+    #[from]
+    ChainspecLoaderAnnouncement(ChainspecLoaderAnnouncement),
 }
 
 impl From<StorageRequest<Storage>> for Event {
@@ -214,6 +229,7 @@ impl Display for Event {
             Event::BlockExecutor(event) => write!(f, "block executor: {}", event),
             Event::LinearChain(event) => write!(f, "linear-chain event {}", event),
             Event::ProtoBlockValidator(event) => write!(f, "block validator: {}", event),
+            Event::ChainspecLoader(event) => write!(f, "chainspec loader: {}", event),
             Event::NetworkRequest(req) => write!(f, "network request: {}", req),
             Event::NetworkInfoRequest(req) => write!(f, "network info request: {}", req),
             Event::DeployFetcherRequest(req) => write!(f, "deploy fetcher request: {}", req),
@@ -230,12 +246,19 @@ impl Display for Event {
             Event::BlockExecutorAnnouncement(ann) => {
                 write!(f, "block-executor announcement: {}", ann)
             }
+            Event::DeployBufferAnnouncement(ann) => {
+                write!(f, "deploy-buffer announcement: {}", ann)
+            }
+            Event::LinearChainAnnouncement(ann) => write!(f, "linear-chain announcement: {}", ann),
             Event::DeployGossiperAnnouncement(ann) => {
                 write!(f, "deploy gossiper announcement: {}", ann)
             }
             Event::AddressGossiperAnnouncement(ann) => {
                 write!(f, "address gossiper announcement: {}", ann)
             }
+            Event::ChainspecLoaderAnnouncement(ann) => {
+                write!(f, "chainspec loader announcement: {}", ann)
+            }
         }
     }
 }
@@ -267,6 +290,7 @@ pub struct Reactor<R: Rng + CryptoRng + ?Sized> {
     block_executor: BlockExecutor,
     proto_block_validator: BlockValidator<ProtoBlock, NodeId>,
     linear_chain: LinearChain<NodeId>,
+    chainspec_loader: ChainspecLoader,
 }
 
 #[cfg(test)]
@@ -306,7 +330,15 @@ impl<R: Rng + CryptoRng + ?Sized> reactor::Reactor<R> for Reactor<R> {
 
         let address_gossiper = Gossiper::new_for_complete_items(config.gossip);
 
-        let api_server = ApiServer::new(config.http_server, effect_builder);
+        let api_server = ApiServer::new(
+            config.http_server,
+            chainspec_loader
+                .chainspec()
+                .genesis
+                .protocol_version
+                .clone(),
+            effect_builder,
+        );
         let timestamp = Timestamp::now();
         let validator_stakes = chainspec_loader
             .chainspec()
@@ -335,6 +367,8 @@ impl<R: Rng + CryptoRng + ?Sized> reactor::Reactor<R> for Reactor<R> {
             effect_builder,
             validator_stakes,
             &chainspec_loader.chainspec().genesis.highway_config,
+            config.node.block_max_deploy_count as usize,
+            registry,
             rng,
         )?;
         let deploy_acceptor = DeployAcceptor::new();
@@ -343,17 +377,43 @@ impl<R: Rng + CryptoRng + ?Sized> reactor::Reactor<R> for Reactor<R> {
             config.gossip,
             gossiper::get_deploy_from_storage::<Deploy, Event>,
         );
-        let deploy_buffer = DeployBuffer::new(config.node.block_max_deploy_count as usize);
+        let deploy_buffer = DeployBuffer::new(
+            config.node.block_max_deploy_count as usize,
+            config.node.deploy_buffer_max_deploy_count as usize,
+        );
         // Post state hash is expected to be present.
         let genesis_post_state_hash = chainspec_loader
             .genesis_post_state_hash()
             .expect("should have post state hash");
         let block_executor = BlockExecutor::new(genesis_post_state_hash);
         let proto_block_validator = BlockValidator::new();
-        let linear_chain = LinearChain::new();
+        let linear_chain = LinearChain::new(
+            config.node.linear_chain_block_cache_size as usize,
+            config.node.finality_threshold_percent,
+        );
+
+        // Pre-read the genesis accounts, so the first blocks don't execute against a cold cache.
+        let genesis = &chainspec_loader.chainspec().genesis;
+        let warm_up_protocol_version = genesis.protocol_version.clone();
+        let key_hints = genesis
+            .accounts
+            .iter()
+            .map(|genesis_account| Key::Account(genesis_account.account_hash()))
+            .collect();
 
         let mut effects = reactor::wrap_effects(Event::Network, net_effects);
         effects.extend(reactor::wrap_effects(Event::Consensus, consensus_effects));
+        if config.contract_runtime.warm_up_on_startup() {
+            effects.extend(
+                effect_builder
+                    .warm_contract_runtime_cache(
+                        genesis_post_state_hash,
+                        warm_up_protocol_version,
+                        key_hints,
+                    )
+                    .ignore(),
+            );
+        }
 
         Ok((
             Reactor {
@@ -371,6 +431,7 @@ impl<R: Rng + CryptoRng + ?Sized> reactor::Reactor<R> for Reactor<R> {
                 block_executor,
                 proto_block_validator,
                 linear_chain,
+                chainspec_loader,
             },
             effects,
         ))
@@ -440,6 +501,11 @@ impl<R: Rng + CryptoRng + ?Sized> reactor::Reactor<R> for Reactor<R> {
                 Event::LinearChain,
                 self.linear_chain.handle_event(effect_builder, rng, event),
             ),
+            Event::ChainspecLoader(event) => reactor::wrap_effects(
+                Event::ChainspecLoader,
+                self.chainspec_loader
+                    .handle_event(effect_builder, rng, event),
+            ),
 
             // Requests:
             Event::NetworkRequest(req) => self.dispatch_event(
@@ -558,7 +624,7 @@ impl<R: Rng + CryptoRng + ?Sized> reactor::Reactor<R> for Reactor<R> {
                 };
                 self.dispatch_event(effect_builder, rng, Event::AddressGossiper(event))
             }
-            Event::NetworkAnnouncement(NetworkAnnouncement::NewPeer(peer_id)) => {
+            Event::NetworkAnnouncement(NetworkAnnouncement::NewPeer { peer: peer_id, .. }) => {
                 debug!(%peer_id, "new peer announcement event ignored (validator reactor does not care)");
                 Effects::new()
             }
@@ -605,6 +671,7 @@ impl<R: Rng + CryptoRng + ?Sized> reactor::Reactor<R> for Reactor<R> {
             Event::DeployAcceptorAnnouncement(DeployAcceptorAnnouncement::InvalidDeploy {
                 deploy: _,
                 source: _,
+                error: _,
             }) => Effects::new(),
             Event::ConsensusAnnouncement(consensus_announcement) => {
                 let reactor_event = Event::DeployBuffer(match consensus_announcement {
@@ -612,11 +679,32 @@ impl<R: Rng + CryptoRng + ?Sized> reactor::Reactor<R> for Reactor<R> {
                         deploy_buffer::Event::ProposedProtoBlock(block)
                     }
                     ConsensusAnnouncement::Finalized(block) => {
-                        deploy_buffer::Event::FinalizedProtoBlock(block)
+                        let proto_block = block.proto_block().clone();
+                        let mut effects = self.dispatch_event(
+                            effect_builder,
+                            rng,
+                            Event::ApiServer(api_server::Event::FinalizedBlock(block)),
+                        );
+                        effects.extend(self.dispatch_event(
+                            effect_builder,
+                            rng,
+                            Event::DeployBuffer(deploy_buffer::Event::FinalizedProtoBlock(
+                                proto_block,
+                            )),
+                        ));
+                        return effects;
                     }
                     ConsensusAnnouncement::Orphaned(block) => {
                         deploy_buffer::Event::OrphanedProtoBlock(block)
                     }
+                    ConsensusAnnouncement::EraTransition {
+                        ended_era, new_era, ..
+                    } => {
+                        debug!(?ended_era, ?new_era, "era transition");
+                        let reactor_event =
+                            Event::ChainspecLoader(chainspec_loader::Event::NewEra(new_era));
+                        return self.dispatch_event(effect_builder, rng, reactor_event);
+                    }
                 });
                 self.dispatch_event(effect_builder, rng, reactor_event)
             }
@@ -627,15 +715,72 @@ impl<R: Rng + CryptoRng + ?Sized> reactor::Reactor<R> for Reactor<R> {
                     Event::LinearChain(linear_chain::Event::LinearChainBlock(block));
                 self.dispatch_event(effect_builder, rng, reactor_event)
             }
-            Event::DeployGossiperAnnouncement(_ann) => {
-                unreachable!("the deploy gossiper should never make an announcement")
+            Event::BlockExecutorAnnouncement(BlockExecutorAnnouncement::StepExecuted {
+                era_id,
+                effects_summary,
+            }) => {
+                info!(era = era_id.0, %effects_summary, "step executed");
+                Effects::new()
             }
-            Event::AddressGossiperAnnouncement(ann) => {
-                let GossiperAnnouncement::NewCompleteItem(gossiped_address) = ann;
+            Event::BlockExecutorAnnouncement(BlockExecutorAnnouncement::BlockExecutionFailed {
+                finalized_block,
+                error,
+            }) => {
+                let reactor_event = Event::Consensus(consensus::Event::BlockExecutionFailed {
+                    finalized_block,
+                    error,
+                });
+                self.dispatch_event(effect_builder, rng, reactor_event)
+            }
+            Event::DeployBufferAnnouncement(DeployBufferAnnouncement::DeployDropped {
+                hash,
+                reason,
+            }) => {
+                debug!(%hash, %reason, "deploy dropped from the deploy buffer");
+                Effects::new()
+            }
+            Event::LinearChainAnnouncement(LinearChainAnnouncement::BlockAdded(block)) => {
+                debug!(block_hash = %block.hash(), "linear chain block added");
+                Effects::new()
+            }
+            Event::LinearChainAnnouncement(LinearChainAnnouncement::BlockFinalized(block)) => {
+                // TODO: Let the API server report finalized blocks.
+                debug!(block_hash = %block.hash(), "linear chain block finalized");
+                Effects::new()
+            }
+            Event::DeployGossiperAnnouncement(GossiperAnnouncement::NewCompleteItem(_)) => {
+                unreachable!("the deploy gossiper should never announce a new complete item")
+            }
+            Event::DeployGossiperAnnouncement(GossiperAnnouncement::ItemGossiped { .. }) => {
+                Effects::new()
+            }
+            Event::DeployGossiperAnnouncement(GossiperAnnouncement::FinishedGossiping(_)) => {
+                Effects::new()
+            }
+            Event::AddressGossiperAnnouncement(GossiperAnnouncement::NewCompleteItem(
+                gossiped_address,
+            )) => {
                 let reactor_event =
                     Event::Network(small_network::Event::PeerAddressReceived(gossiped_address));
                 self.dispatch_event(effect_builder, rng, reactor_event)
             }
+            Event::AddressGossiperAnnouncement(GossiperAnnouncement::ItemGossiped { .. }) => {
+                Effects::new()
+            }
+            Event::AddressGossiperAnnouncement(GossiperAnnouncement::FinishedGossiping(_)) => {
+                Effects::new()
+            }
+            Event::ChainspecLoaderAnnouncement(ChainspecLoaderAnnouncement::UpgradeOverdue {
+                current_era,
+                upgrade,
+            }) => {
+                error!(
+                    era = current_era.0,
+                    %upgrade,
+                    "upgrade is overdue: the node must be upgraded to stay in consensus"
+                );
+                Effects::new()
+            }
         }
     }
 }
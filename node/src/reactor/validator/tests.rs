@@ -1,4 +1,8 @@
-use std::{collections::HashSet, time::Duration};
+use std::{
+    collections::HashSet,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
 
 use anyhow::bail;
 use rand::Rng;
@@ -10,6 +14,7 @@ use casper_types::U512;
 use crate::{
     components::{consensus::EraId, small_network, storage},
     crypto::asymmetric_key::{PublicKey, SecretKey},
+    effect::announcements::ConsensusAnnouncement,
     reactor::{initializer, joiner, validator, Runner},
     testing::{self, network::Network, ConditionCheckReactor, TestRng},
     types::Timestamp,
@@ -185,3 +190,56 @@ async fn run_validator_network() {
     net.settle_on(&mut rng, is_in_era(2), Duration::from_secs(60))
         .await;
 }
+
+#[tokio::test]
+async fn announce_era_transition_once_per_boundary() {
+    testing::init_logging();
+
+    let mut rng = TestRng::new();
+
+    const NETWORK_SIZE: usize = 3;
+    let mut chain = TestChain::new(&mut rng, NETWORK_SIZE);
+
+    let mut net = chain
+        .create_initialized_network(&mut rng)
+        .await
+        .expect("network initialization failed");
+
+    // Record every era transition announced on the first node. The checker never returns `true`,
+    // so it stays installed for the whole test.
+    let transitions = Arc::new(Mutex::new(Vec::new()));
+    let recorded = Arc::clone(&transitions);
+    net.nodes_mut()
+        .values_mut()
+        .next()
+        .expect("need at least one node")
+        .reactor_mut()
+        .set_condition_checker(Box::new(move |event| {
+            if let validator::Event::ConsensusAnnouncement(ConsensusAnnouncement::EraTransition {
+                ended_era,
+                new_era,
+                new_validators,
+            }) = event
+            {
+                assert_eq!(new_validators.len(), NETWORK_SIZE);
+                recorded.lock().unwrap().push((*ended_era, *new_era));
+            }
+            false
+        }));
+
+    let reached_era = |era_num| {
+        let transitions = Arc::clone(&transitions);
+        move |_: &Nodes| transitions.lock().unwrap().len() >= era_num
+    };
+
+    net.settle_on(&mut rng, reached_era(2), Duration::from_secs(90))
+        .await;
+
+    let transitions = transitions.lock().unwrap();
+    assert_eq!(
+        transitions[..2],
+        [(EraId(0), EraId(1)), (EraId(1), EraId(2))]
+    );
+    let unique: HashSet<_> = transitions.iter().collect();
+    assert_eq!(unique.len(), transitions.len(), "duplicate era transition");
+}
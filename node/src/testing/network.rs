@@ -272,6 +272,13 @@ where
         &self.nodes
     }
 
+    /// Returns the internal map of nodes, mutable.
+    pub fn nodes_mut(
+        &mut self,
+    ) -> &mut HashMap<R::NodeId, Runner<ConditionCheckReactor<R>, TestRng>> {
+        &mut self.nodes
+    }
+
     /// Create effects and dispatch them on the given node.
     ///
     /// The effects are created via a call to `create_effects` which is itself passed an instance of
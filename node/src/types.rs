@@ -2,14 +2,16 @@
 
 mod block;
 mod deploy;
+mod execution_result;
 mod item;
 mod node_config;
 mod status_feed;
 mod timestamp;
 
-pub use block::{Block, BlockHash, BlockHeader};
+pub use block::{Block, BlockHash, BlockHeader, BlockIdentifier};
 pub(crate) use block::{BlockLike, FinalizedBlock, ProtoBlock, ProtoBlockHash, SystemTransaction};
 pub use deploy::{Approval, Deploy, DeployHash, DeployHeader, Error as DeployError};
+pub use execution_result::{BlockExecutionResults, ExecutionResult};
 pub use item::{Item, Tag};
 pub use node_config::NodeConfig;
 pub use status_feed::StatusFeed;
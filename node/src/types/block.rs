@@ -1,11 +1,13 @@
 #[cfg(test)]
 use std::iter;
 use std::{
-    collections::BTreeMap,
+    collections::{BTreeMap, BTreeSet},
     fmt::{self, Debug, Display, Formatter},
     hash::Hash,
 };
 
+#[cfg(feature = "bytesrepr-hashing")]
+use casper_types::bytesrepr::ToBytes;
 use hex_fmt::{HexFmt, HexList};
 #[cfg(test)]
 use rand::Rng;
@@ -13,7 +15,10 @@ use serde::{Deserialize, Serialize};
 
 use super::{Item, Tag, Timestamp};
 use crate::{
-    components::{consensus::EraId, storage::Value},
+    components::{
+        consensus::EraId,
+        storage::{BlockValue, Value},
+    },
     crypto::{
         asymmetric_key::{PublicKey, Signature},
         hash::{self, Digest},
@@ -27,8 +32,26 @@ use crate::{
     testing::TestRng,
 };
 
+/// The version of the `bytesrepr`-based `ProtoBlock` hashing scheme, prefixed to the hashed bytes.
+///
+/// The MessagePack-based scheme predates versioning and hashes its bytes without a prefix, so that
+/// existing proto block hashes stay valid. Any later scheme must use a new version, so that the
+/// hashes of different node versions never silently diverge.
+#[cfg(feature = "bytesrepr-hashing")]
+const BYTESREPR_PROTO_BLOCK_HASH_VERSION: u8 = 1;
+
 pub trait BlockLike: Eq + Hash {
     fn deploys(&self) -> &Vec<DeployHash>;
+
+    /// Returns the first deploy hash which occurs more than once in the block, if any.
+    ///
+    /// Executing such a block would apply the same deploy twice.
+    fn duplicate_deploy(&self) -> Option<&DeployHash> {
+        let mut seen = BTreeSet::new();
+        self.deploys()
+            .iter()
+            .find(|deploy_hash| !seen.insert(*deploy_hash))
+    }
 }
 
 /// A cryptographic hash identifying a `ProtoBlock`.
@@ -78,9 +101,7 @@ pub struct ProtoBlock {
 
 impl ProtoBlock {
     pub(crate) fn new(deploys: Vec<DeployHash>, random_bit: bool) -> Self {
-        let hash = ProtoBlockHash::new(hash::hash(
-            &rmp_serde::to_vec(&(&deploys, random_bit)).expect("serialize ProtoBlock"),
-        ));
+        let hash = Self::msgpack_hash(&deploys, random_bit);
 
         ProtoBlock {
             hash,
@@ -93,11 +114,66 @@ impl ProtoBlock {
         &self.hash
     }
 
+    /// Computes the hash of a proto block's contents, serialized using MessagePack.
+    ///
+    /// This is the original, unversioned hashing scheme.
+    fn msgpack_hash(deploys: &[DeployHash], random_bit: bool) -> ProtoBlockHash {
+        ProtoBlockHash::new(hash::hash(
+            &rmp_serde::to_vec(&(deploys, random_bit)).expect("serialize ProtoBlock"),
+        ))
+    }
+
+    /// Computes the hash of a proto block's contents, serialized using `bytesrepr` and prefixed
+    /// with `BYTESREPR_PROTO_BLOCK_HASH_VERSION`.
+    ///
+    /// This is the hashing scheme we are migrating to. While the migration is in progress, both
+    /// hashes are accepted by `has_valid_hash`.
+    #[cfg(feature = "bytesrepr-hashing")]
+    fn bytesrepr_hash(deploys: &[DeployHash], random_bit: bool) -> ProtoBlockHash {
+        let digests: Vec<Digest> = deploys
+            .iter()
+            .map(|deploy_hash| *deploy_hash.inner())
+            .collect();
+        let mut serialized = vec![BYTESREPR_PROTO_BLOCK_HASH_VERSION];
+        serialized.append(&mut digests.to_bytes().expect("serialize ProtoBlock deploys"));
+        serialized.append(
+            &mut random_bit
+                .to_bytes()
+                .expect("serialize ProtoBlock random bit"),
+        );
+        ProtoBlockHash::new(hash::hash(&serialized))
+    }
+
+    /// Returns `true` if the proto block's hash matches its contents.
+    ///
+    /// With the `bytesrepr-hashing` feature enabled, a hash computed with either codec is
+    /// accepted, so that nodes can be upgraded without forking the network.
+    pub(crate) fn has_valid_hash(&self) -> bool {
+        if self.hash == Self::msgpack_hash(&self.deploys, self.random_bit) {
+            return true;
+        }
+        #[cfg(feature = "bytesrepr-hashing")]
+        {
+            if self.hash == Self::bytesrepr_hash(&self.deploys, self.random_bit) {
+                return true;
+            }
+        }
+        false
+    }
+
     /// The list of deploy hashes included in the block.
     pub(crate) fn deploys(&self) -> &Vec<DeployHash> {
         &self.deploys
     }
 
+    /// Returns `true` if the proto block contains no more than `max_deploys` deploys.
+    ///
+    /// This is cheap to check, so oversized proposals can be rejected before validating their
+    /// deploys.
+    pub(crate) fn within_limits(&self, max_deploys: usize) -> bool {
+        self.deploys.len() <= max_deploys
+    }
+
     /// A random bit needed for initializing a future era.
     pub(crate) fn random_bit(&self) -> bool {
         self.random_bit
@@ -217,6 +293,46 @@ impl FinalizedBlock {
         }
     }
 
+    /// Creates a finalized block which is not the last block of its era.
+    pub(crate) fn new_regular(
+        proto_block: ProtoBlock,
+        timestamp: Timestamp,
+        system_transactions: Vec<SystemTransaction>,
+        era_id: EraId,
+        height: u64,
+        proposer: PublicKey,
+    ) -> Self {
+        Self::new(
+            proto_block,
+            timestamp,
+            system_transactions,
+            false,
+            era_id,
+            height,
+            proposer,
+        )
+    }
+
+    /// Creates a switch block, i.e. the last block of its era.
+    pub(crate) fn new_switch_block(
+        proto_block: ProtoBlock,
+        timestamp: Timestamp,
+        system_transactions: Vec<SystemTransaction>,
+        era_id: EraId,
+        height: u64,
+        proposer: PublicKey,
+    ) -> Self {
+        Self::new(
+            proto_block,
+            timestamp,
+            system_transactions,
+            true,
+            era_id,
+            height,
+            proposer,
+        )
+    }
+
     /// The finalized proto block.
     pub(crate) fn proto_block(&self) -> &ProtoBlock {
         &self.proto_block
@@ -242,11 +358,72 @@ impl FinalizedBlock {
         self.height
     }
 
+    /// Returns `true` if this is the last block of its era.
+    pub(crate) fn switch_block(&self) -> bool {
+        self.switch_block
+    }
+
+    /// Returns the validators slashed for equivocating by this block.
+    pub(crate) fn equivocators(&self) -> impl Iterator<Item = &PublicKey> {
+        self.system_transactions
+            .iter()
+            .filter_map(|system_transaction| match system_transaction {
+                SystemTransaction::Slash(public_key) => Some(public_key),
+                SystemTransaction::Rewards(_) => None,
+            })
+    }
+
+    /// Returns `true` if every validator slashed by this block is in `validators`, and none of them
+    /// is slashed more than once.
+    pub(crate) fn slashings_valid(&self, validators: &BTreeSet<PublicKey>) -> bool {
+        let mut slashed = BTreeSet::new();
+        self.system_transactions
+            .iter()
+            .all(|system_transaction| match system_transaction {
+                SystemTransaction::Slash(public_key) => {
+                    validators.contains(public_key) && slashed.insert(public_key)
+                }
+                SystemTransaction::Rewards(_) => true,
+            })
+    }
+
     /// Returns true if block is Genesis' child.
     /// Genesis child block is from era 0 and height 0.
     pub(crate) fn is_genesis_child(&self) -> bool {
         self.era_id() == EraId(0) && self.height() == 0
     }
+
+    /// Generates a random instance using a `TestRng`.
+    #[cfg(test)]
+    pub fn random(rng: &mut TestRng) -> Self {
+        let deploy_count = rng.gen_range(0, 11);
+        let deploy_hashes = iter::repeat_with(|| DeployHash::new(Digest::random(rng)))
+            .take(deploy_count)
+            .collect();
+        let random_bit = rng.gen();
+        let proto_block = ProtoBlock::new(deploy_hashes, random_bit);
+
+        // TODO - make Timestamp deterministic.
+        let timestamp = Timestamp::now();
+        let system_transactions_count = rng.gen_range(1, 11);
+        let system_transactions = iter::repeat_with(|| SystemTransaction::random(rng))
+            .take(system_transactions_count)
+            .collect();
+        let switch_block = rng.gen_bool(0.1);
+        let era = rng.gen_range(0, 5);
+        let secret_key: SecretKey = SecretKey::new_ed25519(rng.gen());
+        let public_key = PublicKey::from(&secret_key);
+
+        FinalizedBlock::new(
+            proto_block,
+            timestamp,
+            system_transactions,
+            switch_block,
+            EraId(era),
+            era * 10 + rng.gen_range(0, 10),
+            public_key,
+        )
+    }
 }
 
 impl From<Block> for FinalizedBlock {
@@ -314,6 +491,24 @@ impl Display for BlockHash {
     }
 }
 
+/// Identifies a [`Block`](struct.Block.html) either by its hash or by its height.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum BlockIdentifier {
+    /// The block's hash.
+    Hash(BlockHash),
+    /// The block's height.
+    Height(u64),
+}
+
+impl Display for BlockIdentifier {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        match self {
+            BlockIdentifier::Hash(block_hash) => write!(formatter, "{}", block_hash),
+            BlockIdentifier::Height(height) => write!(formatter, "block-height({})", height),
+        }
+    }
+}
+
 /// The header portion of a [`Block`](struct.Block.html).
 #[derive(Clone, Ord, PartialOrd, Eq, PartialEq, Hash, Serialize, Deserialize, Debug)]
 pub struct BlockHeader {
@@ -424,7 +619,14 @@ pub struct Block {
     hash: BlockHash,
     header: BlockHeader,
     body: (), // TODO: implement body of block
-    proofs: Vec<Signature>,
+    /// Finality signatures without their signers, as held by blocks written by earlier versions.
+    /// They are kept, but don't count towards the block's finality.
+    legacy_proofs: Vec<Signature>,
+    /// The finality signatures of this block with their signers, ordered by signer.
+    ///
+    /// Being the last field, it is empty when deserializing a block written by an earlier version.
+    #[serde(default)]
+    proofs: Vec<(PublicKey, Signature)>,
 }
 
 impl Block {
@@ -461,6 +663,7 @@ impl Block {
             hash,
             header,
             body,
+            legacy_proofs: vec![],
             proofs: vec![],
         }
     }
@@ -486,10 +689,33 @@ impl Block {
         self.header.era_id == EraId(0) && self.header.height == 0
     }
 
-    /// Appends the given signature to this block's proofs.  It should have been validated prior to
-    /// this via `BlockHash::verify()`.
-    pub(crate) fn append_proof(&mut self, proof: Signature) {
-        self.proofs.push(proof)
+    /// Adds the given signer's signature to this block's proofs.  It should have been validated
+    /// prior to this via `BlockHash::verify()`.
+    ///
+    /// Returns `false` if the signer already has a proof, in which case it is kept.
+    pub(crate) fn append_proof(&mut self, public_key: PublicKey, signature: Signature) -> bool {
+        match self
+            .proofs
+            .binary_search_by(|(signer, _)| signer.cmp(&public_key))
+        {
+            Ok(_) => false,
+            Err(index) => {
+                self.proofs.insert(index, (public_key, signature));
+                true
+            }
+        }
+    }
+
+    /// Returns whether the given signer has a proof on this block.
+    pub(crate) fn has_proof_from(&self, public_key: &PublicKey) -> bool {
+        self.proofs
+            .binary_search_by(|(signer, _)| signer.cmp(public_key))
+            .is_ok()
+    }
+
+    /// Returns this block's proofs with their signers, ordered by signer.
+    pub(crate) fn proofs(&self) -> &[(PublicKey, Signature)] {
+        &self.proofs
     }
 
     fn serialize_body(body: &()) -> Result<Vec<u8>, rmp_serde::encode::Error> {
@@ -499,33 +725,7 @@ impl Block {
     /// Generates a random instance using a `TestRng`.
     #[cfg(test)]
     pub fn random(rng: &mut TestRng) -> Self {
-        let deploy_count = rng.gen_range(0, 11);
-        let deploy_hashes = iter::repeat_with(|| DeployHash::new(Digest::random(rng)))
-            .take(deploy_count)
-            .collect();
-        let random_bit = rng.gen();
-        let proto_block = ProtoBlock::new(deploy_hashes, random_bit);
-
-        // TODO - make Timestamp deterministic.
-        let timestamp = Timestamp::now();
-        let system_transactions_count = rng.gen_range(1, 11);
-        let system_transactions = iter::repeat_with(|| SystemTransaction::random(rng))
-            .take(system_transactions_count)
-            .collect();
-        let switch_block = rng.gen_bool(0.1);
-        let era = rng.gen_range(0, 5);
-        let secret_key: SecretKey = SecretKey::new_ed25519(rng.gen());
-        let public_key = PublicKey::from(&secret_key);
-
-        let finalized_block = FinalizedBlock::new(
-            proto_block,
-            timestamp,
-            system_transactions,
-            switch_block,
-            EraId(era),
-            era * 10 + rng.gen_range(0, 10),
-            public_key,
-        );
+        let finalized_block = FinalizedBlock::random(rng);
 
         let parent_hash = BlockHash::new(Digest::random(rng));
         let post_state_hash = Digest::random(rng);
@@ -536,7 +736,7 @@ impl Block {
             let secret_key = SecretKey::random(rng);
             let public_key = PublicKey::from(&secret_key);
             let signature = asymmetric_key::sign(block.hash.inner(), &secret_key, &public_key, rng);
-            block.append_proof(signature);
+            let _ = block.append_proof(public_key, signature);
         }
 
         block
@@ -559,7 +759,7 @@ impl Display for Block {
             self.header.era_id.0,
             self.header.height,
             DisplayIter::new(self.header.system_transactions.iter()),
-            self.proofs.len()
+            self.legacy_proofs.len() + self.proofs.len()
         )
     }
 }
@@ -587,6 +787,18 @@ impl Value for Block {
     }
 }
 
+impl BlockValue for Block {
+    fn height(&self) -> u64 {
+        self.header.height
+    }
+
+    fn merge_proofs(&mut self, other: Self) {
+        for (public_key, signature) in other.proofs {
+            let _ = self.append_proof(public_key, signature);
+        }
+    }
+}
+
 impl Item for Block {
     type Id = BlockHash;
 
@@ -597,3 +809,252 @@ impl Item for Block {
         *self.hash()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_hash_known_proto_block_with_msgpack() {
+        let proto_block = ProtoBlock::new(vec![], false);
+        let expected =
+            Digest::from_hex("c448332f50f00fe56e678d1b1416143e9241408792d1840752c605221867ee41")
+                .unwrap();
+        assert_eq!(*proto_block.hash().inner(), expected);
+        assert!(proto_block.has_valid_hash());
+    }
+
+    #[test]
+    fn should_round_trip_finalized_block_through_block() {
+        let mut rng = TestRng::new();
+        for _ in 0..100 {
+            let finalized_block = FinalizedBlock::random(&mut rng);
+            let parent_hash = BlockHash::new(Digest::random(&mut rng));
+            let post_state_hash = Digest::random(&mut rng);
+
+            let block = Block::new(parent_hash, post_state_hash, finalized_block.clone());
+            assert_eq!(block.parent_hash(), &parent_hash);
+            assert_eq!(block.header().post_state_hash(), &post_state_hash);
+            assert_eq!(block.header().proposer(), &finalized_block.proposer);
+
+            assert_eq!(FinalizedBlock::from(block), finalized_block);
+        }
+    }
+
+    #[test]
+    fn should_deserialize_block_written_without_signers() {
+        // The layout of blocks written before their proofs were stored with their signers.
+        #[derive(Serialize)]
+        struct LegacyBlock {
+            hash: BlockHash,
+            header: BlockHeader,
+            body: (),
+            proofs: Vec<Signature>,
+        }
+
+        let mut rng = TestRng::new();
+        let block = Block::random(&mut rng);
+        let legacy_proofs: Vec<Signature> = block
+            .proofs()
+            .iter()
+            .map(|(_, signature)| *signature)
+            .collect();
+        let legacy_block = LegacyBlock {
+            hash: block.hash,
+            header: block.header.clone(),
+            body: (),
+            proofs: legacy_proofs.clone(),
+        };
+
+        let serialized = rmp_serde::to_vec(&legacy_block).unwrap();
+        let deserialized: Block = rmp_serde::from_read_ref(&serialized).unwrap();
+        assert_eq!(deserialized.hash(), block.hash());
+        assert_eq!(deserialized.legacy_proofs, legacy_proofs);
+        assert!(deserialized.proofs().is_empty());
+
+        // Blocks with signers round-trip as they are.
+        let serialized = rmp_serde::to_vec(&block).unwrap();
+        let deserialized: Block = rmp_serde::from_read_ref(&serialized).unwrap();
+        assert_eq!(deserialized, block);
+    }
+
+    #[test]
+    fn should_check_deploy_count_limit() {
+        let mut rng = TestRng::new();
+        let deploys = iter::repeat_with(|| DeployHash::new(Digest::random(&mut rng)))
+            .take(3)
+            .collect();
+        let proto_block = ProtoBlock::new(deploys, false);
+
+        assert!(proto_block.within_limits(4));
+        assert!(proto_block.within_limits(3));
+        assert!(!proto_block.within_limits(2));
+    }
+
+    /// Returns a finalized block slashing the given validators.
+    fn finalized_block_slashing(rng: &mut TestRng, slashed: &[PublicKey]) -> FinalizedBlock {
+        let system_transactions = slashed
+            .iter()
+            .map(|public_key| SystemTransaction::Slash(*public_key))
+            .collect();
+        FinalizedBlock::new(
+            ProtoBlock::new(vec![], false),
+            Timestamp::now(),
+            system_transactions,
+            false,
+            EraId(1),
+            10,
+            PublicKey::random(rng),
+        )
+    }
+
+    #[test]
+    fn should_construct_regular_and_switch_blocks() {
+        let mut rng = TestRng::new();
+        let proto_block = ProtoBlock::new(vec![DeployHash::new(Digest::random(&mut rng))], true);
+        let timestamp = Timestamp::now();
+        let system_transactions = vec![SystemTransaction::Slash(PublicKey::random(&mut rng))];
+        let proposer = PublicKey::random(&mut rng);
+        let manual = |switch_block| {
+            FinalizedBlock::new(
+                proto_block.clone(),
+                timestamp,
+                system_transactions.clone(),
+                switch_block,
+                EraId(2),
+                25,
+                proposer,
+            )
+        };
+
+        let regular = FinalizedBlock::new_regular(
+            proto_block.clone(),
+            timestamp,
+            system_transactions.clone(),
+            EraId(2),
+            25,
+            proposer,
+        );
+        assert!(!regular.switch_block());
+        assert_eq!(regular, manual(false));
+
+        let switch_block = FinalizedBlock::new_switch_block(
+            proto_block.clone(),
+            timestamp,
+            system_transactions.clone(),
+            EraId(2),
+            25,
+            proposer,
+        );
+        assert!(switch_block.switch_block());
+        assert_eq!(switch_block, manual(true));
+    }
+
+    #[test]
+    fn should_accept_slashings_of_validators() {
+        let mut rng = TestRng::new();
+        let validators: Vec<_> = iter::repeat_with(|| PublicKey::random(&mut rng))
+            .take(3)
+            .collect();
+        let block = finalized_block_slashing(&mut rng, &validators[..2]);
+        assert!(block.slashings_valid(&validators.into_iter().collect()));
+    }
+
+    #[test]
+    fn should_list_equivocators_of_regular_and_switch_blocks() {
+        let mut rng = TestRng::new();
+        let equivocators: Vec<_> = iter::repeat_with(|| PublicKey::random(&mut rng))
+            .take(2)
+            .collect();
+        let honest = PublicKey::random(&mut rng);
+        let mut rewards = BTreeMap::new();
+        let _ = rewards.insert(honest, 1_000);
+        let mut system_transactions: Vec<_> = equivocators
+            .iter()
+            .map(|public_key| SystemTransaction::Slash(*public_key))
+            .collect();
+        system_transactions.push(SystemTransaction::Rewards(rewards));
+
+        let regular = FinalizedBlock::new_regular(
+            ProtoBlock::new(vec![], false),
+            Timestamp::now(),
+            system_transactions.clone(),
+            EraId(1),
+            10,
+            PublicKey::random(&mut rng),
+        );
+        let switch_block = FinalizedBlock::new_switch_block(
+            ProtoBlock::new(vec![], true),
+            Timestamp::now(),
+            system_transactions,
+            EraId(1),
+            11,
+            PublicKey::random(&mut rng),
+        );
+        for block in &[regular, switch_block] {
+            assert_eq!(
+                block.equivocators().collect::<Vec<_>>(),
+                equivocators.iter().collect::<Vec<_>>()
+            );
+        }
+
+        let without_slashings = finalized_block_slashing(&mut rng, &[]);
+        assert_eq!(without_slashings.equivocators().count(), 0);
+    }
+
+    #[test]
+    fn should_reject_slashing_of_non_validator() {
+        let mut rng = TestRng::new();
+        let validator = PublicKey::random(&mut rng);
+        let non_validator = PublicKey::random(&mut rng);
+        let block = finalized_block_slashing(&mut rng, &[validator, non_validator]);
+        assert!(!block.slashings_valid(&iter::once(validator).collect()));
+    }
+
+    #[test]
+    fn should_reject_duplicate_slashings() {
+        let mut rng = TestRng::new();
+        let validator = PublicKey::random(&mut rng);
+        let block = finalized_block_slashing(&mut rng, &[validator, validator]);
+        assert!(!block.slashings_valid(&iter::once(validator).collect()));
+    }
+
+    #[test]
+    fn should_reject_mismatched_hash() {
+        let proto_block = ProtoBlock::new(vec![], false);
+        let (_, deploys, random_bit) = ProtoBlock::new(vec![], true).destructure();
+        let tampered = ProtoBlock {
+            hash: *proto_block.hash(),
+            deploys,
+            random_bit,
+        };
+        assert!(!tampered.has_valid_hash());
+    }
+
+    #[cfg(feature = "bytesrepr-hashing")]
+    #[test]
+    fn should_hash_known_proto_block_with_bytesrepr() {
+        let expected_empty =
+            Digest::from_hex("ef1f5ca620f4a9d2ffd5d9538b92b25baf05f2ba399aa9ca7c8eaf07c13e9cf0")
+                .unwrap();
+        assert_eq!(
+            *ProtoBlock::bytesrepr_hash(&[], false).inner(),
+            expected_empty
+        );
+
+        let deploys = vec![DeployHash::new(Digest::from([7; Digest::LENGTH]))];
+        let expected =
+            Digest::from_hex("8fe565cc609d6361291c5f0379b64cbf6180b00b1fdaadb9bfa6db382dc287a2")
+                .unwrap();
+        let hash = ProtoBlock::bytesrepr_hash(&deploys, true);
+        assert_eq!(*hash.inner(), expected);
+
+        // A proto block carrying the new-style hash is accepted during the migration.
+        let proto_block = ProtoBlock {
+            hash,
+            deploys,
+            random_bit: true,
+        };
+        assert!(proto_block.has_valid_hash());
+    }
+}
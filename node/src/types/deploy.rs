@@ -271,6 +271,17 @@ impl Deploy {
         self.approvals.push(approval);
     }
 
+    /// Verifies each of this deploy's approvals against its hash.
+    ///
+    /// Returns an error identifying the first approval which fails verification.
+    pub fn verify_approvals(&self) -> Result<(), Error> {
+        for (index, approval) in self.approvals.iter().enumerate() {
+            asymmetric_key::verify(&self.hash, &approval.signature, &approval.signer)
+                .map_err(|error| Error::FailedVerification { index, error })?;
+        }
+        Ok(())
+    }
+
     /// Returns the `DeployHash` identifying this `Deploy`.
     pub fn id(&self) -> &DeployHash {
         &self.hash
@@ -432,14 +443,9 @@ impl<'de> Deserialize<'de> for Deploy {
             approvals,
         };
 
-        for (index, approval) in deploy.approvals.iter().enumerate() {
-            if let Err(error) =
-                asymmetric_key::verify(&deploy.hash, &approval.signature, &approval.signer)
-            {
-                let error_msg = format!("failed to verify approval {}", index);
-                warn!("{}: {}: {}", DESER_ERROR_MSG_GENERAL, error_msg, error);
-                return Err(serde::de::Error::custom(error_msg));
-            }
+        if let Err(error) = deploy.verify_approvals() {
+            warn!("{}: {}", DESER_ERROR_MSG_GENERAL, error);
+            return Err(serde::de::Error::custom(error));
         }
 
         Ok(deploy)
@@ -843,4 +849,23 @@ mod tests {
         let deserialized = rmp_serde::from_read_ref(&serialized).unwrap();
         assert_eq!(deploy, deserialized);
     }
+
+    #[test]
+    fn should_identify_invalid_approval() {
+        let mut rng = TestRng::new();
+        let mut deploy = Deploy::random(&mut rng);
+        assert!(deploy.verify_approvals().is_ok());
+
+        // Add a second approval whose signature is over a different hash.
+        let secret_key = SecretKey::random(&mut rng);
+        let signer = PublicKey::from(&secret_key);
+        let other_hash = hash::hash(rng.next_u64().to_le_bytes());
+        let signature = asymmetric_key::sign(other_hash, &secret_key, &signer, &mut rng);
+        deploy.approvals.push(Approval { signer, signature });
+
+        match deploy.verify_approvals() {
+            Err(Error::FailedVerification { index, .. }) => assert_eq!(index, 1),
+            result => panic!("expected failed verification, got {:?}", result),
+        }
+    }
 }
@@ -0,0 +1,62 @@
+use std::collections::HashMap;
+
+#[cfg(test)]
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use casper_execution_engine::core::engine_state::execution_result::ExecutionResult as EngineExecutionResult;
+use casper_types::U512;
+
+#[cfg(test)]
+use crate::testing::TestRng;
+use crate::types::{DeployHash, DeployHeader};
+
+/// The results of executing the deploys of a block, keyed by deploy hash.
+pub type BlockExecutionResults = HashMap<DeployHash, (DeployHeader, ExecutionResult)>;
+
+/// The outcome of executing a single deploy, in a form which can be stored and served to clients.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ExecutionResult {
+    /// The deploy failed.
+    Failure {
+        /// The reason the deploy failed.
+        error_message: String,
+        /// The gas consumed by the deploy.
+        cost: U512,
+    },
+    /// The deploy succeeded.
+    Success {
+        /// The gas consumed by the deploy.
+        cost: U512,
+    },
+}
+
+impl ExecutionResult {
+    /// Generates a random instance using a `TestRng`.
+    #[cfg(test)]
+    pub fn random(rng: &mut TestRng) -> Self {
+        let cost = U512::from(rng.gen::<u64>());
+        if rng.gen() {
+            ExecutionResult::Success { cost }
+        } else {
+            ExecutionResult::Failure {
+                error_message: format!("error {}", rng.gen::<u32>()),
+                cost,
+            }
+        }
+    }
+}
+
+impl From<&EngineExecutionResult> for ExecutionResult {
+    fn from(execution_result: &EngineExecutionResult) -> Self {
+        match execution_result {
+            EngineExecutionResult::Failure { error, cost, .. } => ExecutionResult::Failure {
+                error_message: error.to_string(),
+                cost: cost.value(),
+            },
+            EngineExecutionResult::Success { cost, .. } => {
+                ExecutionResult::Success { cost: cost.value() }
+            }
+        }
+    }
+}
@@ -5,6 +5,9 @@ use crate::{utils::External, Chainspec};
 
 const DEFAULT_CHAINSPEC_CONFIG_PATH: &str = "chainspec.toml";
 const DEFAULT_BLOCK_MAX_DEPLOY_COUNT: u32 = 3;
+const DEFAULT_LINEAR_CHAIN_BLOCK_CACHE_SIZE: u32 = 100;
+const DEFAULT_DEPLOY_BUFFER_MAX_DEPLOY_COUNT: u32 = 10_000;
+const DEFAULT_FINALITY_THRESHOLD_PERCENT: u8 = 66;
 
 /// Node configuration.
 #[derive(Debug, Deserialize, Serialize)]
@@ -15,6 +18,15 @@ pub struct NodeConfig {
     pub chainspec_config_path: External<Chainspec>,
     /// The maximum number of deploys permitted in a single block.
     pub block_max_deploy_count: u32,
+    /// The maximum number of deploys held in the deploy buffer.  When exceeded, the deploys with
+    /// the lowest gas price are dropped.
+    pub deploy_buffer_max_deploy_count: u32,
+    /// The maximum number of recently added or requested blocks held in memory by the linear
+    /// chain component.
+    pub linear_chain_block_cache_size: u32,
+    /// The percentage of its era's total stake whose validators need to sign a block before it is
+    /// announced as finalized by the linear chain component.
+    pub finality_threshold_percent: u8,
     /// Hash used as a trust anchor when joining, if any.
     pub trusted_hash: Option<BlockHash>,
 }
@@ -24,6 +36,9 @@ impl Default for NodeConfig {
         NodeConfig {
             chainspec_config_path: External::path(DEFAULT_CHAINSPEC_CONFIG_PATH),
             block_max_deploy_count: DEFAULT_BLOCK_MAX_DEPLOY_COUNT,
+            deploy_buffer_max_deploy_count: DEFAULT_DEPLOY_BUFFER_MAX_DEPLOY_COUNT,
+            linear_chain_block_cache_size: DEFAULT_LINEAR_CHAIN_BLOCK_CACHE_SIZE,
+            finality_threshold_percent: DEFAULT_FINALITY_THRESHOLD_PERCENT,
             trusted_hash: None,
         }
     }
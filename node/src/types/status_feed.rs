@@ -1,33 +1,100 @@
 use std::{collections::HashMap, net::SocketAddr};
 
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
-use crate::{small_network::NodeId, types::Block};
+use crate::{
+    components::consensus::EraId,
+    small_network::NodeId,
+    types::{Block, TimeDiff},
+};
 
 /// Data feed for client status endpoint.
-#[derive(Debug, Serialize)]
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
 pub struct StatusFeed {
+    version: String,
+    era_id: Option<EraId>,
+    last_linear_block_height: Option<u64>,
     last_linear_block_hash: Option<String>,
     peers: Vec<String>,
+    uptime: TimeDiff,
 }
 
 impl StatusFeed {
     pub(crate) fn new(
         last_linear_block: Option<Block>,
         peers: HashMap<NodeId, SocketAddr>,
+        era_id: Option<EraId>,
+        uptime: TimeDiff,
     ) -> Self {
         StatusFeed {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            era_id,
+            last_linear_block_height: last_linear_block.as_ref().map(|b| b.header().height()),
             last_linear_block_hash: last_linear_block.map(|b| hex::encode(b.hash().inner())),
             peers: peers.values().map(ToString::to_string).collect(),
+            uptime,
         }
     }
+
+    /// Returns the current era, if known.
+    pub(crate) fn era_id(&self) -> Option<EraId> {
+        self.era_id
+    }
+
+    /// Returns the height of the last finalized block, if any.
+    pub(crate) fn last_linear_block_height(&self) -> Option<u64> {
+        self.last_linear_block_height
+    }
+
+    /// Returns the number of connected peers.
+    pub(crate) fn peer_count(&self) -> usize {
+        self.peers.len()
+    }
 }
 
 impl Default for StatusFeed {
     fn default() -> Self {
         StatusFeed {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            era_id: None,
+            last_linear_block_height: None,
             last_linear_block_hash: None,
             peers: vec![],
+            uptime: TimeDiff::from(0),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TestRng;
+
+    #[test]
+    fn should_round_trip_status_feed_through_json() {
+        let mut rng = TestRng::new();
+        let block = Block::random(&mut rng);
+        let mut peers = HashMap::new();
+        let _ = peers.insert(
+            NodeId::from([1; 64]),
+            "127.0.0.1:34553".parse::<SocketAddr>().unwrap(),
+        );
+        let status_feed = StatusFeed::new(
+            Some(block.clone()),
+            peers,
+            Some(EraId(3)),
+            TimeDiff::from(60_000),
+        );
+
+        assert_eq!(status_feed.era_id(), Some(EraId(3)));
+        assert_eq!(
+            status_feed.last_linear_block_height(),
+            Some(block.header().height())
+        );
+        assert_eq!(status_feed.peer_count(), 1);
+
+        let json = serde_json::to_string(&status_feed).unwrap();
+        let decoded: StatusFeed = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded, status_feed);
+    }
+}
@@ -73,6 +73,41 @@ pub fn call_contract<T: CLTyped + FromBytes>(
     deserialize_contract_result(bytes_written)
 }
 
+/// Calls the given stored contract, passing the given arguments to it, without aborting if it
+/// reverts.
+///
+/// If the stored contract calls [`ret`], then `Ok` of that value is returned.  If the stored
+/// contract calls [`revert`], then `Err` of the given error is returned and any changes to global
+/// state made by the stored contract are discarded.  Otherwise `Ok(())` is returned.
+pub fn try_call_contract<T: CLTyped + FromBytes>(
+    contract_hash: ContractHash,
+    entry_point_name: &str,
+    runtime_args: RuntimeArgs,
+) -> Result<T, ApiError> {
+    let (contract_hash_ptr, contract_hash_size, _bytes1) = contract_api::to_ptr(contract_hash);
+    let (entry_point_name_ptr, entry_point_name_size, _bytes2) =
+        contract_api::to_ptr(entry_point_name);
+    let (runtime_args_ptr, runtime_args_size, _bytes2) = contract_api::to_ptr(runtime_args);
+
+    let bytes_written = {
+        let mut bytes_written = MaybeUninit::uninit();
+        let ret = unsafe {
+            ext_ffi::try_call_contract(
+                contract_hash_ptr,
+                contract_hash_size,
+                entry_point_name_ptr,
+                entry_point_name_size,
+                runtime_args_ptr,
+                runtime_args_size,
+                bytes_written.as_mut_ptr(),
+            )
+        };
+        api_error::result_from(ret)?;
+        unsafe { bytes_written.assume_init() }
+    };
+    Ok(deserialize_contract_result(bytes_written))
+}
+
 /// Invokes the specified `entry_point_name` of stored logic at a specific `contract_package_hash`
 /// address, for the most current version of a contract package by default or a specific
 /// `contract_version` if one is provided, and passing the provided `runtime_args` to it
@@ -626,6 +626,28 @@ extern "C" {
         urefs_ptr: *const u8,
         urefs_size: usize,
     ) -> i32;
+    /// Calls a contract by its hash in the same way as [`call_contract`], except that a revert of
+    /// the called contract is reported by returning its standard error code rather than aborting
+    /// the caller.  Any changes to global state made by the reverted contract are discarded.
+    ///
+    /// # Arguments
+    /// * `contract_hash_ptr` - pointer to serialized contract hash.
+    /// * `contract_hash_size` - size of contract hash in serialized form.
+    /// * `entry_point_name_ptr` - pointer to serialized contract entry point name
+    /// * `entry_point_name_size` - size of serialized contract entry point name
+    /// * `runtime_args_ptr` - pointer to serialized runtime arguments
+    /// * `runtime_args_size` - size of serialized runtime arguments
+    /// * `result_size` - a pointer to a value which will be set to a size of bytes of called
+    ///   contract return value
+    pub fn try_call_contract(
+        contract_hash_ptr: *const u8,
+        contract_hash_size: usize,
+        entry_point_name_ptr: *const u8,
+        entry_point_name_size: usize,
+        runtime_args_ptr: *const u8,
+        runtime_args_size: usize,
+        result_size: *mut usize,
+    ) -> i32;
 
     /// Prints data directly to stanadard output on the host.
     ///
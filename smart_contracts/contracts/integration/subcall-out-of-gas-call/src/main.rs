@@ -0,0 +1,24 @@
+#![no_std]
+#![no_main]
+
+use casper_contract::{contract_api::runtime, unwrap_or_revert::UnwrapOrRevert};
+use casper_types::{runtime_args, ApiError, RuntimeArgs};
+
+const ENTRY_POINT_NAME: &str = "loop_ext";
+const ARG_ITERATIONS: &str = "iterations";
+const SUBCALL_OUT_OF_GAS_KEY: &str = "subcall_out_of_gas";
+
+#[no_mangle]
+pub extern "C" fn call() {
+    let contract_hash = runtime::get_key(SUBCALL_OUT_OF_GAS_KEY)
+        .unwrap_or_revert_with(ApiError::GetKey)
+        .into_hash()
+        .unwrap_or_revert();
+    let iterations: u64 = runtime::get_named_arg(ARG_ITERATIONS);
+
+    runtime::call_contract::<()>(
+        contract_hash,
+        ENTRY_POINT_NAME,
+        runtime_args! { ARG_ITERATIONS => iterations },
+    )
+}
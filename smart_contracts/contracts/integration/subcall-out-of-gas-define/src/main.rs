@@ -0,0 +1,45 @@
+#![no_std]
+#![no_main]
+
+extern crate alloc;
+
+use alloc::vec;
+
+use casper_contract::contract_api::{runtime, storage};
+use casper_types::{
+    CLType, EntryPoint, EntryPointAccess, EntryPointType, EntryPoints, Key, Parameter,
+};
+
+const ENTRY_POINT_NAME: &str = "loop_ext";
+const ARG_ITERATIONS: &str = "iterations";
+const SUBCALL_OUT_OF_GAS_KEY: &str = "subcall_out_of_gas";
+
+/// Makes a host call `iterations` times, so that a large enough bound runs out of gas.
+#[no_mangle]
+pub extern "C" fn loop_ext() {
+    let iterations: u64 = runtime::get_named_arg(ARG_ITERATIONS);
+    for _ in 0..iterations {
+        let _blocktime = runtime::get_blocktime();
+    }
+}
+
+fn entry_points() -> EntryPoints {
+    let mut entry_points = EntryPoints::new();
+
+    entry_points.add_entry_point(EntryPoint::new(
+        ENTRY_POINT_NAME,
+        vec![Parameter::new(ARG_ITERATIONS, CLType::U64)],
+        CLType::Unit,
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+
+    entry_points
+}
+
+#[no_mangle]
+pub extern "C" fn call() {
+    let (contract_hash, _contract_version) =
+        storage::new_contract(entry_points(), None, None, None);
+    runtime::put_key(SUBCALL_OUT_OF_GAS_KEY, Key::from(contract_hash));
+}
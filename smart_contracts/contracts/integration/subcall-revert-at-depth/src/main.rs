@@ -0,0 +1,65 @@
+#![no_std]
+#![no_main]
+
+extern crate alloc;
+
+use alloc::vec;
+
+use casper_contract::contract_api::{runtime, storage};
+use casper_types::{
+    runtime_args, ApiError, CLType, ContractHash, EntryPoint, EntryPointAccess, EntryPointType,
+    EntryPoints, Parameter, RuntimeArgs,
+};
+
+const ENTRY_POINT_RECURSE: &str = "recurse";
+const ARG_DEPTH: &str = "depth";
+const ARG_CONTRACT_HASH: &str = "contract_hash";
+const DEPTH_REACHED_ERROR: u16 = 4;
+
+/// Calls itself until it is `depth` contract calls deep, and only then reverts.
+#[no_mangle]
+pub extern "C" fn recurse() {
+    let depth: u32 = runtime::get_named_arg(ARG_DEPTH);
+    if depth <= 1 {
+        runtime::revert(ApiError::User(DEPTH_REACHED_ERROR));
+    }
+    let contract_hash: ContractHash = runtime::get_named_arg(ARG_CONTRACT_HASH);
+    runtime::call_contract::<()>(
+        contract_hash,
+        ENTRY_POINT_RECURSE,
+        runtime_args! { ARG_DEPTH => depth - 1, ARG_CONTRACT_HASH => contract_hash },
+    );
+}
+
+fn entry_points() -> EntryPoints {
+    let mut entry_points = EntryPoints::new();
+
+    entry_points.add_entry_point(EntryPoint::new(
+        ENTRY_POINT_RECURSE,
+        vec![
+            Parameter::new(ARG_DEPTH, CLType::U32),
+            Parameter::new(ARG_CONTRACT_HASH, CLType::ByteArray(32)),
+        ],
+        CLType::Unit,
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+
+    entry_points
+}
+
+/// Installs the recursing contract and calls it with the `depth` argument, which must be at least
+/// 1.
+#[no_mangle]
+pub extern "C" fn call() {
+    let depth: u32 = runtime::get_named_arg(ARG_DEPTH);
+
+    let (contract_hash, _contract_version) =
+        storage::new_contract(entry_points(), None, None, None);
+
+    runtime::call_contract::<()>(
+        contract_hash,
+        ENTRY_POINT_RECURSE,
+        runtime_args! { ARG_DEPTH => depth, ARG_CONTRACT_HASH => contract_hash },
+    );
+}
@@ -0,0 +1,51 @@
+#![no_std]
+#![no_main]
+
+extern crate alloc;
+
+use alloc::string::String;
+
+use casper_contract::{
+    contract_api::{runtime, storage},
+    unwrap_or_revert::UnwrapOrRevert,
+};
+use casper_types::{runtime_args, ApiError, RuntimeArgs};
+
+const ENTRY_POINT_INCREMENT: &str = "increment";
+const ARG_ENTRY_POINT: &str = "entry_point";
+const ARG_PROCEED: &str = "proceed";
+const TRY_SUBCALL_KEY: &str = "try_subcall";
+const PATH_KEY: &str = "path";
+const PATH_PROCEEDED: &str = "proceeded";
+const PATH_SKIPPED: &str = "skipped";
+const PATH_REVERTED: &str = "reverted";
+
+#[no_mangle]
+pub extern "C" fn call() {
+    let contract_hash = runtime::get_key(TRY_SUBCALL_KEY)
+        .unwrap_or_revert_with(ApiError::GetKey)
+        .into_hash()
+        .unwrap_or_revert();
+    let entry_point: String = runtime::get_named_arg(ARG_ENTRY_POINT);
+    let proceed: bool = runtime::get_named_arg(ARG_PROCEED);
+
+    // Only make the second subcall if the first one succeeded and asked for it.
+    let path = match runtime::try_call_contract::<bool>(
+        contract_hash,
+        &entry_point,
+        runtime_args! { ARG_PROCEED => proceed },
+    ) {
+        Ok(true) => {
+            runtime::call_contract::<()>(
+                contract_hash,
+                ENTRY_POINT_INCREMENT,
+                RuntimeArgs::default(),
+            );
+            PATH_PROCEEDED
+        }
+        Ok(false) => PATH_SKIPPED,
+        Err(_) => PATH_REVERTED,
+    };
+
+    runtime::put_key(PATH_KEY, storage::new_uref(String::from(path)).into());
+}
@@ -0,0 +1,89 @@
+#![no_std]
+#![no_main]
+
+extern crate alloc;
+
+use alloc::{string::String, vec};
+use core::convert::TryInto;
+
+use casper_contract::{
+    contract_api::{runtime, storage},
+    unwrap_or_revert::UnwrapOrRevert,
+};
+use casper_types::{
+    contracts::{NamedKeys, Parameters},
+    ApiError, CLType, CLValue, EntryPoint, EntryPointAccess, EntryPointType, EntryPoints, Key,
+    Parameter, URef,
+};
+
+const ENTRY_POINT_CHECK: &str = "check";
+const ENTRY_POINT_CHECK_AND_REVERT: &str = "check_and_revert";
+const ENTRY_POINT_INCREMENT: &str = "increment";
+const ARG_PROCEED: &str = "proceed";
+const COUNTER_KEY: &str = "counter";
+const TRY_SUBCALL_KEY: &str = "try_subcall";
+const CHECK_REVERTED_ERROR: u16 = 3;
+
+fn counter_uref() -> URef {
+    runtime::get_key(COUNTER_KEY)
+        .unwrap_or_revert_with(ApiError::GetKey)
+        .try_into()
+        .unwrap_or_revert()
+}
+
+/// Returns the `proceed` argument, telling the caller whether to go on to call `increment`.
+#[no_mangle]
+pub extern "C" fn check() {
+    let proceed: bool = runtime::get_named_arg(ARG_PROCEED);
+    runtime::ret(CLValue::from_t(proceed).unwrap_or_revert());
+}
+
+/// Increments the counter, then reverts, so that the increment should never become visible.
+#[no_mangle]
+pub extern "C" fn check_and_revert() {
+    storage::add(counter_uref(), 1u32);
+    runtime::revert(ApiError::User(CHECK_REVERTED_ERROR));
+}
+
+#[no_mangle]
+pub extern "C" fn increment() {
+    storage::add(counter_uref(), 1u32);
+}
+
+fn entry_points() -> EntryPoints {
+    let mut entry_points = EntryPoints::new();
+
+    entry_points.add_entry_point(EntryPoint::new(
+        ENTRY_POINT_CHECK,
+        vec![Parameter::new(ARG_PROCEED, CLType::Bool)],
+        CLType::Bool,
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        ENTRY_POINT_CHECK_AND_REVERT,
+        vec![Parameter::new(ARG_PROCEED, CLType::Bool)],
+        CLType::Unit,
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entry_points.add_entry_point(EntryPoint::new(
+        ENTRY_POINT_INCREMENT,
+        Parameters::default(),
+        CLType::Unit,
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+
+    entry_points
+}
+
+#[no_mangle]
+pub extern "C" fn call() {
+    let mut named_keys = NamedKeys::new();
+    named_keys.insert(String::from(COUNTER_KEY), storage::new_uref(0u32).into());
+
+    let (contract_hash, _contract_version) =
+        storage::new_contract(entry_points(), Some(named_keys), None, None);
+    runtime::put_key(TRY_SUBCALL_KEY, Key::from(contract_hash));
+}
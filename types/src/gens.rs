@@ -58,6 +58,18 @@ pub fn uref_arb() -> impl Strategy<Value = URef> {
         .prop_map(|(id, access_rights)| URef::new(id, access_rights))
 }
 
+/// Generates arbitrary `URef`s, biased towards the edge cases of having no access rights and
+/// having full access rights.
+pub fn uref_with_edge_cases_arb() -> impl Strategy<Value = URef> {
+    let access_rights = prop_oneof![
+        1 => Just(AccessRights::NONE),
+        1 => Just(AccessRights::READ_ADD_WRITE),
+        2 => access_rights_arb(),
+    ];
+    (array::uniform32(bits::u8::ANY), access_rights)
+        .prop_map(|(id, access_rights)| URef::new(id, access_rights))
+}
+
 pub fn key_arb() -> impl Strategy<Value = Key> {
     prop_oneof![
         account_hash_arb().prop_map(Key::Account),
@@ -28,7 +28,30 @@ pub enum FromStrError {
     InvalidAccessRights,
     Hex(base16::DecodeError),
     Int(ParseIntError),
+    #[deprecated(since = "0.6.0", note = "replaced by `InvalidAddressLength`")]
     Address(TryFromSliceError),
+    InvalidAddressLength(usize),
+}
+
+#[allow(deprecated)]
+impl Display for FromStrError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            FromStrError::InvalidPrefix => write!(f, "prefix is not '{}'", PREFIX),
+            FromStrError::MissingSuffix => write!(f, "no access rights as suffix"),
+            FromStrError::InvalidAccessRights => write!(f, "invalid access rights"),
+            FromStrError::Hex(error) => write!(f, "failed to decode address portion: {}", error),
+            FromStrError::Int(error) => write!(f, "failed to parse an int: {}", error),
+            FromStrError::Address(error) => {
+                write!(f, "address portion is the wrong length: {}", error)
+            }
+            FromStrError::InvalidAddressLength(length) => write!(
+                f,
+                "address portion is the wrong length: expected {} bytes, got {}",
+                UREF_ADDR_LENGTH, length
+            ),
+        }
+    }
 }
 
 impl From<base16::DecodeError> for FromStrError {
@@ -43,6 +66,7 @@ impl From<ParseIntError> for FromStrError {
     }
 }
 
+#[allow(deprecated)]
 impl From<TryFromSliceError> for FromStrError {
     fn from(error: TryFromSliceError) -> Self {
         FromStrError::Address(error)
@@ -58,7 +82,7 @@ pub struct URef(URefAddr, AccessRights);
 
 impl URef {
     /// Constructs a [`URef`] from an address and access rights.
-    pub fn new(address: URefAddr, access_rights: AccessRights) -> Self {
+    pub const fn new(address: URefAddr, access_rights: AccessRights) -> Self {
         URef(address, access_rights)
     }
 
@@ -99,6 +123,23 @@ impl URef {
         URef(self.0, AccessRights::READ_ADD_WRITE)
     }
 
+    /// Returns a [`Key::URef`] wrapping this [`URef`].
+    pub fn into_key(self) -> Key {
+        Key::URef(self)
+    }
+
+    /// Returns a new [`URef`] with the same address and the given `rights` added to the existing
+    /// access rights.
+    pub fn grant(self, rights: AccessRights) -> URef {
+        URef(self.0, self.1 | rights)
+    }
+
+    /// Returns a new [`URef`] with the same address and the given `rights` removed from the
+    /// existing access rights.
+    pub fn revoke(self, rights: AccessRights) -> URef {
+        URef(self.0, self.1 - rights)
+    }
+
     /// Returns `true` if the access rights are `Some` and
     /// [`is_writeable`](AccessRights::is_writeable) is `true` for them.
     pub fn is_writeable(self) -> bool {
@@ -126,6 +167,19 @@ impl URef {
         )
     }
 
+    /// Formats the [`URef`] in an abbreviated form for use in log lines, showing only the first
+    /// and last four bytes of the address, e.g. `uref-00010203..1c1d1e1f-007`.
+    pub fn to_short_string(&self) -> String {
+        let addr = self.addr();
+        format!(
+            "{}{}..{}-{:03o}",
+            PREFIX,
+            base16::encode_lower(&addr[..4]),
+            base16::encode_lower(&addr[UREF_ADDR_LENGTH - 4..]),
+            self.access_rights().bits()
+        )
+    }
+
     /// Parses a string formatted as per `Self::as_string()` into a `URef`.
     pub fn from_formatted_str(input: &str) -> Result<Self, FromStrError> {
         let remainder = input
@@ -135,7 +189,12 @@ impl URef {
         if parts.len() != 2 {
             return Err(FromStrError::MissingSuffix);
         }
-        let addr = URefAddr::try_from(base16::decode(parts[0])?.as_ref())?;
+        let addr_bytes = base16::decode(parts[0])?;
+        if addr_bytes.len() != UREF_ADDR_LENGTH {
+            return Err(FromStrError::InvalidAddressLength(addr_bytes.len()));
+        }
+        let mut addr = URefAddr::default();
+        addr.copy_from_slice(&addr_bytes);
         let access_rights_value = u8::from_str_radix(parts[1], 8)?;
         let access_rights = AccessRights::from_bits(access_rights_value)
             .ok_or_else(|| FromStrError::InvalidAccessRights)?;
@@ -174,7 +233,13 @@ impl bytesrepr::FromBytes for URef {
     fn from_bytes(bytes: &[u8]) -> Result<(Self, &[u8]), bytesrepr::Error> {
         let (id, rem): ([u8; 32], &[u8]) = bytesrepr::FromBytes::from_bytes(bytes)?;
         let (access_rights, rem): (AccessRights, &[u8]) = bytesrepr::FromBytes::from_bytes(rem)?;
-        Ok((URef(id, access_rights), rem))
+        let uref = URef(id, access_rights);
+        // Guard against the layout drifting away from `serialized_length`.
+        debug_assert_eq!(
+            bytes.len() - rem.len(),
+            bytesrepr::ToBytes::serialized_length(&uref)
+        );
+        Ok((uref, rem))
     }
 }
 
@@ -193,6 +258,7 @@ impl TryFrom<Key> for URef {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::bytesrepr::{FromBytes, ToBytes};
 
     #[test]
     fn uref_as_string() {
@@ -218,6 +284,73 @@ mod tests {
         );
     }
 
+    #[test]
+    fn uref_from_truncated_bytes() {
+        let uref = URef::new([9; UREF_ADDR_LENGTH], AccessRights::READ_WRITE);
+        let bytes = uref.to_bytes().unwrap();
+        assert_eq!(bytes.len(), UREF_SERIALIZED_LENGTH);
+
+        let (parsed, rem) = URef::from_bytes(&bytes).unwrap();
+        assert_eq!(parsed, uref);
+        assert!(rem.is_empty());
+
+        for len in 0..UREF_SERIALIZED_LENGTH {
+            assert_eq!(
+                URef::from_bytes(&bytes[..len]).unwrap_err(),
+                bytesrepr::Error::EarlyEndOfStream
+            );
+        }
+    }
+
+    #[test]
+    fn uref_in_const_context() {
+        const UREF: URef = URef::new([7; UREF_ADDR_LENGTH], AccessRights::READ_ADD);
+        assert_eq!(UREF.addr(), [7; UREF_ADDR_LENGTH]);
+        assert_eq!(UREF.access_rights(), AccessRights::READ_ADD);
+    }
+
+    #[test]
+    fn uref_grant_and_revoke() {
+        let addr_array = [1u8; 32];
+
+        let uref = URef::new(addr_array, AccessRights::READ_ADD_WRITE).revoke(AccessRights::WRITE);
+        assert_eq!(uref.addr(), addr_array);
+        assert_eq!(uref.access_rights(), AccessRights::READ_ADD);
+
+        let uref = URef::new(addr_array, AccessRights::NONE).grant(AccessRights::READ);
+        assert_eq!(uref.addr(), addr_array);
+        assert_eq!(uref.access_rights(), AccessRights::READ);
+
+        let uref = URef::new(addr_array, AccessRights::READ)
+            .grant(AccessRights::READ)
+            .revoke(AccessRights::ADD);
+        assert_eq!(uref.access_rights(), AccessRights::READ);
+    }
+
+    #[test]
+    fn uref_as_short_string() {
+        let mut addr_array = [0u8; 32];
+        for (index, byte) in addr_array.iter_mut().enumerate() {
+            *byte = index as u8;
+        }
+        let uref = URef::new(addr_array, AccessRights::READ_ADD_WRITE);
+        assert_eq!(uref.to_short_string(), "uref-00010203..1c1d1e1f-007");
+    }
+
+    #[test]
+    fn uref_key_round_trip() {
+        let uref = URef::new([3; UREF_ADDR_LENGTH], AccessRights::READ_ADD_WRITE);
+        let key = Key::from(uref);
+        assert_eq!(key, uref.into_key());
+        assert_eq!(URef::try_from(key), Ok(uref));
+
+        let hash_key = Key::Hash([3; 32]);
+        assert_eq!(
+            URef::try_from(hash_key),
+            Err(ApiError::UnexpectedKeyVariant)
+        );
+    }
+
     fn round_trip(uref: URef) {
         let string = uref.to_formatted_string();
         let parsed_uref = URef::from_formatted_str(&string).unwrap();
@@ -260,4 +393,38 @@ mod tests {
             "uref-0000000000000000000000000000000000000000000000000000000000000000-200";
         assert!(URef::from_formatted_str(invalid_access_rights).is_err());
     }
+
+    #[test]
+    fn uref_from_str_reports_address_length() {
+        let short_addr = "uref-00000000000000000000000000000000000000000000000000000000000000-000";
+        let error = URef::from_formatted_str(short_addr).unwrap_err();
+        assert_eq!(
+            format!("{}", error),
+            "address portion is the wrong length: expected 32 bytes, got 31"
+        );
+
+        let long_addr =
+            "uref-000000000000000000000000000000000000000000000000000000000000000000-000";
+        let error = URef::from_formatted_str(long_addr).unwrap_err();
+        assert_eq!(
+            format!("{}", error),
+            "address portion is the wrong length: expected 32 bytes, got 33"
+        );
+    }
+}
+
+#[cfg(test)]
+mod proptests {
+    use proptest::prelude::*;
+
+    use super::URef;
+    use crate::gens;
+
+    proptest! {
+        #[test]
+        fn test_formatted_string_round_trip(uref in gens::uref_with_edge_cases_arb()) {
+            let formatted = uref.to_formatted_string();
+            prop_assert_eq!(URef::from_formatted_str(&formatted).unwrap(), uref);
+        }
+    }
 }